@@ -0,0 +1,224 @@
+//! 本地 mock OIDC 提供方，用于联调 OIDC 登录：
+//! - 授权端点不展示登录页，直接以固定用户身份回跳
+//! - 支持 PKCE (S256) 校验
+//! - id_token 使用启动时生成的 Ed25519 密钥签名（EdDSA），JWKS 中公布对应公钥
+//!
+//! 用法：
+//! ```bash
+//! cargo run --example mock_oidc
+//! # 后端 .env
+//! OIDC_PROVIDERS=mock
+//! OIDC_MOCK_ISSUER=http://127.0.0.1:9000
+//! OIDC_MOCK_CLIENT_ID=blog
+//! OIDC_MOCK_REDIRECT_URI=http://127.0.0.1:5173/oidc/callback
+//! ```
+
+use axum::{
+    Form, Json, Router,
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use blog_shared::oidc::pkce_challenge;
+use blog_shared::random_string;
+use chrono::Utc;
+use ed25519_dalek::SigningKey;
+use ed25519_dalek::pkcs8::EncodePrivateKey;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+
+const KID: &str = "mock";
+
+struct MockState {
+    issuer: String,
+    /// 每次启动重新生成的签名密钥
+    signing_key: SigningKey,
+    subject: String,
+    username: String,
+    email: String,
+    codes: Mutex<HashMap<String, IssuedCode>>,
+}
+
+struct IssuedCode {
+    client_id: String,
+    redirect_uri: String,
+    nonce: Option<String>,
+    code_challenge: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeParams {
+    client_id: String,
+    redirect_uri: String,
+    state: Option<String>,
+    nonce: Option<String>,
+    code_challenge: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenForm {
+    grant_type: String,
+    code: String,
+    redirect_uri: String,
+    client_id: String,
+    code_verifier: Option<String>,
+}
+
+#[derive(Serialize)]
+struct IdToken<'a> {
+    iss: &'a str,
+    sub: &'a str,
+    aud: &'a str,
+    exp: i64,
+    iat: i64,
+    nonce: Option<String>,
+    email: &'a str,
+    preferred_username: &'a str,
+    name: &'a str,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let port: u16 = env::var("MOCK_OIDC_PORT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(9000);
+    let username = env::var("MOCK_OIDC_USERNAME").unwrap_or_else(|_| "alice".into());
+
+    let state = Arc::new(MockState {
+        issuer: format!("http://127.0.0.1:{port}"),
+        signing_key: SigningKey::from_bytes(&rand::random()),
+        subject: env::var("MOCK_OIDC_SUBJECT").unwrap_or_else(|_| format!("mock-{username}")),
+        email: format!("{username}@example.com"),
+        username,
+        codes: Mutex::new(HashMap::new()),
+    });
+
+    let app = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/authorize", get(authorize))
+        .route("/token", post(token))
+        .route("/jwks", get(jwks))
+        .with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+    println!("mock oidc provider listening on {}", state.issuer);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn discovery(State(state): State<Arc<MockState>>) -> Json<serde_json::Value> {
+    Json(json!({
+        "issuer": state.issuer,
+        "authorization_endpoint": format!("{}/authorize", state.issuer),
+        "token_endpoint": format!("{}/token", state.issuer),
+        "jwks_uri": format!("{}/jwks", state.issuer),
+        "response_types_supported": ["code"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": ["EdDSA"],
+        "code_challenge_methods_supported": ["S256"],
+    }))
+}
+
+/// 直接以固定用户身份授权并回跳
+async fn authorize(
+    State(state): State<Arc<MockState>>,
+    Query(params): Query<AuthorizeParams>,
+) -> Redirect {
+    let code = random_string(24);
+
+    state.codes.lock().unwrap().insert(
+        code.clone(),
+        IssuedCode {
+            client_id: params.client_id,
+            redirect_uri: params.redirect_uri.clone(),
+            nonce: params.nonce,
+            code_challenge: params.code_challenge,
+        },
+    );
+
+    let mut location = url::Url::parse(&params.redirect_uri).expect("invalid redirect_uri");
+    location.query_pairs_mut().append_pair("code", &code);
+    if let Some(s) = params.state {
+        location.query_pairs_mut().append_pair("state", &s);
+    }
+    Redirect::to(location.as_str())
+}
+
+async fn token(State(state): State<Arc<MockState>>, Form(form): Form<TokenForm>) -> Response {
+    let invalid = |msg: &str| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "invalid_grant", "error_description": msg })),
+        )
+            .into_response()
+    };
+
+    if form.grant_type != "authorization_code" {
+        return invalid("unsupported grant_type");
+    }
+    let Some(issued) = state.codes.lock().unwrap().remove(&form.code) else {
+        return invalid("unknown code");
+    };
+    if issued.client_id != form.client_id || issued.redirect_uri != form.redirect_uri {
+        return invalid("client_id or redirect_uri mismatch");
+    }
+    if let Some(challenge) = issued.code_challenge {
+        let verifier = form.code_verifier.unwrap_or_default();
+        if pkce_challenge(&verifier) != challenge {
+            return invalid("pkce verification failed");
+        }
+    }
+
+    let now = Utc::now().timestamp();
+    let claims = IdToken {
+        iss: &state.issuer,
+        sub: &state.subject,
+        aud: &form.client_id,
+        exp: now + 300,
+        iat: now,
+        nonce: issued.nonce,
+        email: &state.email,
+        preferred_username: &state.username,
+        name: &state.username,
+    };
+    let header = Header {
+        kid: Some(KID.into()),
+        ..Header::new(Algorithm::EdDSA)
+    };
+    let der = state
+        .signing_key
+        .to_pkcs8_der()
+        .expect("failed to encode signing key");
+    let id_token =
+        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_ed_der(der.as_bytes()))
+            .expect("failed to sign id_token");
+
+    Json(json!({
+        "access_token": "mock-access-token",
+        "token_type": "Bearer",
+        "expires_in": 300,
+        "id_token": id_token,
+    }))
+    .into_response()
+}
+
+async fn jwks(State(state): State<Arc<MockState>>) -> Json<serde_json::Value> {
+    Json(json!({
+        "keys": [{
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "use": "sig",
+            "kid": KID,
+            "alg": "EdDSA",
+            "x": URL_SAFE_NO_PAD.encode(state.signing_key.verifying_key().to_bytes()),
+        }]
+    }))
+}
@@ -0,0 +1,17 @@
+-- 0008_create_api_tokens.sql
+-- 个人访问令牌：供脚本 / CI 调用 API，仅保存令牌的哈希
+CREATE TABLE
+    IF NOT EXISTS api_tokens (
+        id TEXT PRIMARY KEY,
+        user_id TEXT NOT NULL,            -- 外键，指向用户表
+        name TEXT NOT NULL,               -- 令牌名称，便于区分用途
+        token_hash TEXT NOT NULL UNIQUE,  -- SHA-256(令牌)，明文只在创建时返回一次
+        token_prefix TEXT NOT NULL,       -- 令牌前几位，用于列表中辨认
+        scopes TEXT NOT NULL,             -- 空格分隔的权限范围，如 "articles:write comments:moderate"
+        expires_at TEXT,                  -- 为空表示永不过期
+        last_used_at TEXT,
+        revoked_at TEXT,
+        created_at TEXT DEFAULT (datetime ('now'))
+    );
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user_id ON api_tokens (user_id);
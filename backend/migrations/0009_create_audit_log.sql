@@ -0,0 +1,18 @@
+-- 0009_create_audit_log.sql
+-- 审计日志：记录所有特权操作的执行者、对象及前后快照
+CREATE TABLE
+    IF NOT EXISTS audit_log (
+        id TEXT PRIMARY KEY,
+        actor_id TEXT,                -- 执行者用户 id，首次设置等系统操作为空
+        actor_name TEXT,              -- 执行时的用户名，用户被删除后仍可追溯
+        action TEXT NOT NULL,         -- 操作，如 user.delete / article.update
+        target_type TEXT NOT NULL,    -- 对象类型：user / article / comment / token
+        target_id TEXT,
+        before TEXT,                  -- 操作前快照（JSON）
+        after TEXT,                   -- 操作后快照（JSON）
+        created_at TEXT NOT NULL DEFAULT (datetime ('now'))
+    );
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at ON audit_log (created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor_id ON audit_log (actor_id);
+CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log (target_type, target_id);
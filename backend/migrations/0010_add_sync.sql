@@ -0,0 +1,99 @@
+-- 0010_add_sync.sql
+-- 桌面端同步：
+-- - sync_counter 是全局变更序号，文章 / 评论每次写入、删除都取下一个序号，客户端按序号增量拉取
+-- - articles.clocks 记录每个字段最后修改的毫秒时间戳（JSON），用于按字段合并
+-- - 删除写入 sync_tombstones，客户端据此删除本地数据
+-- 以上均由触发器维护，普通接口无需关心
+CREATE TABLE
+    IF NOT EXISTS sync_counter (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        value INTEGER NOT NULL
+    );
+
+INSERT INTO sync_counter (id, value) VALUES (1, 1);
+
+CREATE TABLE
+    IF NOT EXISTS sync_tombstones (
+        entity TEXT NOT NULL,         -- article / comment
+        id TEXT NOT NULL,
+        seq INTEGER NOT NULL,
+        deleted_at TEXT NOT NULL DEFAULT (datetime ('now')),
+        PRIMARY KEY (entity, id)
+    );
+
+ALTER TABLE articles ADD COLUMN clocks TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE articles ADD COLUMN seq INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE comments ADD COLUMN seq INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX IF NOT EXISTS idx_articles_seq ON articles (seq);
+CREATE INDEX IF NOT EXISTS idx_comments_seq ON comments (seq);
+CREATE INDEX IF NOT EXISTS idx_sync_tombstones_seq ON sync_tombstones (seq);
+
+-- 新文章：取序号；同步写入时已带 clocks，其余情况所有字段记为当前时间
+CREATE TRIGGER IF NOT EXISTS articles_sync_insert AFTER INSERT ON articles
+BEGIN
+    UPDATE sync_counter SET value = value + 1 WHERE id = 1;
+    UPDATE articles SET
+        seq = (SELECT value FROM sync_counter WHERE id = 1),
+        clocks = CASE WHEN NEW.clocks = '{}' THEN json_object(
+            'title', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+            'content', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+            'summary', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+            'tags', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+            'status', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+            'update_at', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+        ) ELSE NEW.clocks END
+    WHERE id = NEW.id;
+    DELETE FROM sync_tombstones WHERE entity = 'article' AND id = NEW.id;
+END;
+
+-- 修改文章：取序号，并刷新实际变化字段的时钟（浏览数不参与同步）
+CREATE TRIGGER IF NOT EXISTS articles_sync_update
+AFTER UPDATE OF title, content, summary, tags, status, update_at ON articles
+BEGIN
+    UPDATE sync_counter SET value = value + 1 WHERE id = 1;
+    UPDATE articles SET
+        seq = (SELECT value FROM sync_counter WHERE id = 1),
+        clocks = json_set(clocks,
+            '$.title', CASE WHEN OLD.title IS NOT NEW.title
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.title'), 0) END,
+            '$.content', CASE WHEN OLD.content IS NOT NEW.content
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.content'), 0) END,
+            '$.summary', CASE WHEN OLD.summary IS NOT NEW.summary
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.summary'), 0) END,
+            '$.tags', CASE WHEN OLD.tags IS NOT NEW.tags
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.tags'), 0) END,
+            '$.status', CASE WHEN OLD.status IS NOT NEW.status
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.status'), 0) END,
+            '$.update_at', CASE WHEN OLD.update_at IS NOT NEW.update_at
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.update_at'), 0) END)
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS articles_sync_delete AFTER DELETE ON articles
+BEGIN
+    UPDATE sync_counter SET value = value + 1 WHERE id = 1;
+    INSERT OR REPLACE INTO sync_tombstones (entity, id, seq)
+    VALUES ('article', OLD.id, (SELECT value FROM sync_counter WHERE id = 1));
+END;
+
+CREATE TRIGGER IF NOT EXISTS comments_sync_insert AFTER INSERT ON comments
+BEGIN
+    UPDATE sync_counter SET value = value + 1 WHERE id = 1;
+    UPDATE comments SET seq = (SELECT value FROM sync_counter WHERE id = 1)
+    WHERE comment_id = NEW.comment_id;
+    DELETE FROM sync_tombstones WHERE entity = 'comment' AND id = NEW.comment_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS comments_sync_delete AFTER DELETE ON comments
+BEGIN
+    UPDATE sync_counter SET value = value + 1 WHERE id = 1;
+    INSERT OR REPLACE INTO sync_tombstones (entity, id, seq)
+    VALUES ('comment', OLD.comment_id, (SELECT value FROM sync_counter WHERE id = 1));
+END;
@@ -0,0 +1,3 @@
+-- 0011_unique_username.sql
+-- 用户名唯一：并发注册时由数据库兜底，冲突映射为 409 USERNAME_TAKEN
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username ON users (username);
@@ -0,0 +1,25 @@
+-- 0012_create_notifications.sql
+-- 站内通知：评论被回复、被点赞，以及管理员处理了用户的评论
+CREATE TABLE
+    IF NOT EXISTS notifications (
+        id TEXT PRIMARY KEY,
+        user_id TEXT NOT NULL,        -- 接收者
+        kind TEXT NOT NULL,           -- reply / like / moderation
+        actor_name TEXT,              -- 触发者用户名
+        article_id TEXT,
+        comment_id TEXT,              -- 相关评论：回复本身、被点赞或被删除的评论
+        message TEXT NOT NULL,        -- 摘要，如回复内容的开头
+        read_at TEXT,                 -- 为空表示未读
+        created_at TEXT NOT NULL DEFAULT (datetime ('now'))
+    );
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user ON notifications (user_id, created_at);
+
+-- 通知偏好：只保存用户改动过的类型，没有记录时视为开启
+CREATE TABLE
+    IF NOT EXISTS notification_preferences (
+        user_id TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        enabled INTEGER NOT NULL,
+        PRIMARY KEY (user_id, kind)
+    );
@@ -0,0 +1,32 @@
+-- 0013_create_webhooks.sql
+-- Webhook 订阅：由管理员维护，事件发生时向 url 投递签名后的 JSON
+CREATE TABLE
+    IF NOT EXISTS webhooks (
+        id TEXT PRIMARY KEY,
+        url TEXT NOT NULL,
+        secret TEXT NOT NULL,         -- HMAC-SHA256 签名密钥
+        events TEXT NOT NULL,         -- 订阅的事件，空格分隔
+        active INTEGER NOT NULL DEFAULT 1,
+        created_at TEXT NOT NULL DEFAULT (datetime ('now')),
+        updated_at TEXT NOT NULL DEFAULT (datetime ('now'))
+    );
+
+-- 投递队列与投递日志：失败后按指数退避重试，超过次数后标记为 dead
+CREATE TABLE
+    IF NOT EXISTS webhook_deliveries (
+        id TEXT PRIMARY KEY,
+        webhook_id TEXT NOT NULL,
+        event TEXT NOT NULL,
+        payload TEXT NOT NULL,        -- 请求体原文，签名基于它计算
+        status TEXT NOT NULL DEFAULT 'pending', -- pending / delivered / dead
+        attempts INTEGER NOT NULL DEFAULT 0,
+        next_attempt_at TEXT NOT NULL DEFAULT (datetime ('now')),
+        response_status INTEGER,      -- 最近一次请求的 HTTP 状态码
+        last_error TEXT,
+        created_at TEXT NOT NULL DEFAULT (datetime ('now')),
+        delivered_at TEXT
+    );
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON webhook_deliveries (status, next_attempt_at);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook ON webhook_deliveries (webhook_id, created_at);
@@ -0,0 +1,37 @@
+-- 0014_create_subscribers.sql
+-- 邮件订阅：提交后发送确认邮件，点击确认链接（双重确认）后才会收到邮件
+CREATE TABLE
+    IF NOT EXISTS subscribers (
+        id TEXT PRIMARY KEY,
+        email TEXT NOT NULL COLLATE NOCASE UNIQUE,
+        user_id TEXT,                 -- 登录后订阅时关联的用户，用于回复提醒
+        digest INTEGER NOT NULL DEFAULT 1,  -- 接收文章摘要
+        replies INTEGER NOT NULL DEFAULT 0, -- 评论被回复时接收邮件
+        status TEXT NOT NULL DEFAULT 'pending', -- pending / confirmed / unsubscribed
+        confirm_token TEXT UNIQUE,    -- 确认后清空
+        unsubscribe_token TEXT NOT NULL UNIQUE,
+        created_at TEXT NOT NULL DEFAULT (datetime ('now')),
+        confirmed_at TEXT
+    );
+
+CREATE INDEX IF NOT EXISTS idx_subscribers_user ON subscribers (user_id);
+
+-- 已发送的文章摘要
+CREATE TABLE
+    IF NOT EXISTS mail_digests (
+        id TEXT PRIMARY KEY,
+        article_count INTEGER NOT NULL,
+        recipient_count INTEGER NOT NULL,
+        sent_at TEXT NOT NULL DEFAULT (datetime ('now'))
+    );
+
+-- 已包含在摘要中的文章，下一期只收录其余已发布的文章
+CREATE TABLE
+    IF NOT EXISTS digest_articles (
+        article_id TEXT PRIMARY KEY,
+        digest_id TEXT NOT NULL
+    );
+
+-- 启用前已发布的文章不再进入摘要
+INSERT OR IGNORE INTO digest_articles (article_id, digest_id)
+SELECT id, 'initial' FROM articles WHERE status = 'published';
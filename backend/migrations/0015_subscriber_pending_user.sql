@@ -0,0 +1,6 @@
+-- 0015_subscriber_pending_user.sql
+-- 登录后订阅时先记录申请的用户，点击确认链接（证明邮箱属于申请人）后才关联到 user_id
+ALTER TABLE subscribers ADD COLUMN pending_user_id TEXT;
+
+-- 尚未确认的订阅此前已直接关联用户，改为等待确认
+UPDATE subscribers SET pending_user_id = user_id, user_id = NULL WHERE status = 'pending';
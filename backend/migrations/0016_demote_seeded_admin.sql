@@ -0,0 +1,7 @@
+-- 0016_demote_seeded_admin.sql
+-- 旧版本种子数据插入的管理员（id = '1'）密码公开在仓库中：仍在使用该密码时取消其全部权限，
+-- 启动时改走首次设置流程创建超管；已修改过密码的账号保持不变
+UPDATE users
+SET is_super_admin = 0, identity = 'visitor'
+WHERE id = '1'
+  AND password = '$argon2id$v=19$m=19456,t=2,p=1$xJUF+YJE+OglZNMlk0fQrQ$Hj6T+uOmomQ3vyDcqUvn7fUmf8QKXoOOMCZZU8H+5xA';
@@ -0,0 +1,111 @@
+//! 个人访问令牌（PAT）：
+//! - 明文形如 `pat_xxx`，只在创建时返回一次，数据库仅保存 SHA-256 哈希
+//! - 每个令牌带权限范围（scope），只能用于声明了对应 scope 的接口
+//! - 校验通过后转换为 `Claims`，与登录 JWT 共用后续的权限判断
+
+use crate::auth::Claims;
+use crate::db::AppState;
+use crate::error::{AppResult, ErrorCode};
+use crate::models::api_token::{find_active_token_by_hash, touch_api_token};
+use blog_shared::random_string;
+use chrono::{NaiveDateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// 令牌明文前缀，用于和 JWT 区分
+pub const TOKEN_PREFIX: &str = "pat_";
+
+pub const ARTICLES_WRITE: &str = "articles:write";
+pub const COMMENTS_WRITE: &str = "comments:write";
+pub const COMMENTS_MODERATE: &str = "comments:moderate";
+/// 桌面端同步，读写全部文章与评论
+pub const SYNC: &str = "sync";
+
+/// 全部可用的 scope
+pub const ALL_SCOPES: &[&str] = &[ARTICLES_WRITE, COMMENTS_WRITE, COMMENTS_MODERATE, SYNC];
+
+/// 生成新令牌，返回 (明文, 哈希, 展示用前缀)
+pub fn generate() -> (String, String, String) {
+    let token = format!("{TOKEN_PREFIX}{}", random_string(40));
+    let prefix = token.chars().take(TOKEN_PREFIX.len() + 6).collect();
+    let hashed = hash(&token);
+    (token, hashed, prefix)
+}
+
+/// 令牌本身是高熵随机串，直接使用 SHA-256 即可
+pub fn hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// 校验令牌并转换为 Claims，同时记录最近使用时间
+pub async fn authenticate(state: &AppState, token: &str) -> AppResult<Claims> {
+    let record = find_active_token_by_hash(&state.pool, &hash(token))
+        .await?
+        .ok_or(ErrorCode::InvalidApiToken)?;
+
+    touch_api_token(&state.pool, &record.id).await?;
+
+    let ts = |s: &Option<String>| {
+        s.as_deref()
+            .and_then(|v| NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S").ok())
+            .map(|dt| dt.and_utc().timestamp().max(0) as usize)
+    };
+
+    Ok(Claims {
+        user_id: record.user_id,
+        message: record.name,
+        exp: ts(&record.expires_at).unwrap_or(0),
+        iat: ts(&record.created_at).unwrap_or_else(|| Utc::now().timestamp().max(0) as usize),
+        iss: state.cfg.auth.jwt_issuer.clone(),
+        aud: state.cfg.auth.jwt_audience.clone(),
+        scopes: Some(record.scopes.split_whitespace().map(String::from).collect()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_tokens_are_prefixed_and_hashed() {
+        let (token, hashed, prefix) = generate();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_eq!(token.len(), TOKEN_PREFIX.len() + 40);
+        assert!(token.starts_with(&prefix));
+        assert_eq!(prefix.len(), TOKEN_PREFIX.len() + 6);
+        assert_eq!(hashed, hash(&token));
+        assert_ne!(generate().0, token);
+    }
+
+    #[test]
+    fn hash_is_hex_sha256() {
+        assert_eq!(
+            hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn scopes_only_restrict_api_tokens() {
+        let claims = |scopes: Option<&[&str]>| Claims {
+            user_id: "u1".into(),
+            message: String::new(),
+            exp: 0,
+            iat: 0,
+            iss: String::new(),
+            aud: String::new(),
+            scopes: scopes.map(|s| s.iter().map(|s| s.to_string()).collect()),
+        };
+
+        // 登录 JWT 不受 scope 限制
+        assert!(claims(None).require_scope(SYNC).is_ok());
+
+        let token = claims(Some(&[ARTICLES_WRITE, COMMENTS_WRITE]));
+        assert!(token.require_scope(ARTICLES_WRITE).is_ok());
+        assert!(token.require_scope(COMMENTS_WRITE).is_ok());
+        for scope in [COMMENTS_MODERATE, SYNC] {
+            let err = token.require_scope(scope).unwrap_err();
+            assert_eq!(err.report().code(), ErrorCode::InsufficientScope);
+        }
+        assert!(claims(Some(&[])).require_scope(SYNC).is_err());
+    }
+}
@@ -0,0 +1,410 @@
+//! JWT 签名密钥管理：
+//! - HS256：使用 `JWT_SECRET`（默认值仅允许在开发模式下使用）
+//! - EdDSA / RS256：从 PEM 文件加载私钥签名，公钥通过 JWKS 发布
+//! - 每个密钥带 `kid`，轮换期间旧公钥仍可用于校验，已签发的 token 不会失效
+
+use crate::config::{Config, DEFAULT_JWT_SECRET};
+use anyhow::{Context, bail};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// 用于校验的密钥
+struct VerifyingEntry {
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+pub struct JwtKeys {
+    /// 当前签名密钥
+    pub kid: String,
+    pub algorithm: Algorithm,
+    encoding: EncodingKey,
+    /// kid -> 校验密钥（当前密钥 + 轮换中的旧密钥）
+    verifying: HashMap<String, VerifyingEntry>,
+    /// 对外公布的公钥，HS256 时为空
+    jwks: JwkSet,
+}
+
+impl JwtKeys {
+    /// 按配置加载密钥，配置不安全或密钥无法解析时拒绝启动
+    pub fn from_config(cfg: &Config) -> anyhow::Result<Self> {
+        let algorithm = Algorithm::from_str(&cfg.auth.jwt_algorithm)
+            .with_context(|| format!("不支持的 JWT_ALGORITHM: {}", cfg.auth.jwt_algorithm))?;
+
+        let mut keys = match algorithm {
+            Algorithm::HS256 => Self::hmac(cfg)?,
+            Algorithm::EdDSA | Algorithm::RS256 => {
+                let path = cfg
+                    .auth
+                    .jwt_private_key_file
+                    .as_deref()
+                    .context("使用 EdDSA / RS256 时必须配置 JWT_PRIVATE_KEY_FILE")?;
+                Self::asymmetric(algorithm, &cfg.auth.jwt_key_id, path)?
+            }
+            other => bail!(
+                "不支持的 JWT_ALGORITHM: {:?}（可选 HS256 / EdDSA / RS256）",
+                other
+            ),
+        };
+
+        for (kid, path) in &cfg.auth.jwt_previous_keys {
+            if keys.verifying.contains_key(kid) {
+                bail!("JWT_PREVIOUS_KEYS 中的 kid 重复: {}", kid);
+            }
+            let jwk = load_public_jwk(kid, path)?;
+            keys.add_verifying(jwk)?;
+        }
+
+        tracing::info!(
+            "JWT 使用 {:?} 签名，kid = {}，可校验的密钥数 = {}",
+            keys.algorithm,
+            keys.kid,
+            keys.verifying.len()
+        );
+        Ok(keys)
+    }
+
+    fn hmac(cfg: &Config) -> anyhow::Result<Self> {
+        if cfg.auth.jwt_secret.is_empty() {
+            bail!("JWT_SECRET 不能为空");
+        }
+        if cfg.auth.jwt_secret == DEFAULT_JWT_SECRET {
+            if !cfg.auth.jwt_allow_default_secret {
+                bail!(
+                    "JWT_SECRET 仍为默认值，拒绝启动；请设置随机密钥，或在开发环境设置 JWT_ALLOW_DEFAULT_SECRET=true"
+                );
+            }
+            tracing::warn!("⚠️ 正在使用默认 JWT_SECRET，仅限开发环境");
+        }
+
+        let secret = cfg.auth.jwt_secret.as_bytes();
+        let mut verifying = HashMap::new();
+        verifying.insert(
+            cfg.auth.jwt_key_id.clone(),
+            VerifyingEntry {
+                algorithm: Algorithm::HS256,
+                key: DecodingKey::from_secret(secret),
+            },
+        );
+
+        Ok(Self {
+            kid: cfg.auth.jwt_key_id.clone(),
+            algorithm: Algorithm::HS256,
+            encoding: EncodingKey::from_secret(secret),
+            verifying,
+            jwks: JwkSet { keys: Vec::new() },
+        })
+    }
+
+    fn asymmetric(algorithm: Algorithm, kid: &str, path: &str) -> anyhow::Result<Self> {
+        let pem = std::fs::read_to_string(path)
+            .with_context(|| format!("无法读取 JWT 私钥文件: {}", path))?;
+
+        let (encoding, public) = match algorithm {
+            Algorithm::EdDSA => {
+                let key = SigningKey::from_pkcs8_pem(&pem)
+                    .with_context(|| format!("无法解析 Ed25519 私钥（需 PKCS#8 PEM）: {}", path))?;
+                (
+                    EncodingKey::from_ed_pem(pem.as_bytes())?,
+                    ed25519_jwk(kid, &key.verifying_key()),
+                )
+            }
+            _ => {
+                let key = RsaPrivateKey::from_pkcs8_pem(&pem)
+                    .or_else(|_| RsaPrivateKey::from_pkcs1_pem(&pem))
+                    .with_context(|| format!("无法解析 RSA 私钥: {}", path))?;
+                (
+                    EncodingKey::from_rsa_pem(pem.as_bytes())?,
+                    rsa_jwk(kid, &key.to_public_key()),
+                )
+            }
+        };
+
+        let mut keys = Self {
+            kid: kid.to_string(),
+            algorithm,
+            encoding,
+            verifying: HashMap::new(),
+            jwks: JwkSet { keys: Vec::new() },
+        };
+        keys.add_verifying(public)?;
+        Ok(keys)
+    }
+
+    /// 添加一个可用于校验的公钥，并在 JWKS 中公布
+    fn add_verifying(&mut self, jwk: Jwk) -> anyhow::Result<()> {
+        let kid = jwk.common.key_id.clone().unwrap_or_default();
+        let algorithm = match jwk.algorithm {
+            AlgorithmParameters::OctetKeyPair(_) => Algorithm::EdDSA,
+            _ => Algorithm::RS256,
+        };
+        let key = DecodingKey::from_jwk(&jwk)?;
+
+        self.verifying
+            .insert(kid, VerifyingEntry { algorithm, key });
+        self.jwks.keys.push(jwk);
+        Ok(())
+    }
+
+    pub fn encoding_key(&self) -> &EncodingKey {
+        &self.encoding
+    }
+
+    /// 按 kid 查找校验密钥；没有 kid 的旧 token 使用当前密钥
+    pub fn verifying_key(&self, kid: Option<&str>) -> Option<(Algorithm, &DecodingKey)> {
+        self.verifying
+            .get(kid.unwrap_or(&self.kid))
+            .map(|entry| (entry.algorithm, &entry.key))
+    }
+
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+}
+
+/// 读取轮换中的旧公钥（Ed25519 或 RSA 的 PEM 公钥）
+fn load_public_jwk(kid: &str, path: &str) -> anyhow::Result<Jwk> {
+    let pem = std::fs::read_to_string(path)
+        .with_context(|| format!("无法读取 JWT 公钥文件: {}", path))?;
+
+    if let Ok(key) = VerifyingKey::from_public_key_pem(&pem) {
+        return Ok(ed25519_jwk(kid, &key));
+    }
+    let key = RsaPublicKey::from_public_key_pem(&pem)
+        .or_else(|_| RsaPublicKey::from_pkcs1_pem(&pem))
+        .with_context(|| format!("无法解析 JWT 公钥（需 Ed25519 或 RSA PEM）: {}", path))?;
+    Ok(rsa_jwk(kid, &key))
+}
+
+fn common(kid: &str, algorithm: KeyAlgorithm) -> CommonParameters {
+    CommonParameters {
+        public_key_use: Some(PublicKeyUse::Signature),
+        key_algorithm: Some(algorithm),
+        key_id: Some(kid.to_string()),
+        ..Default::default()
+    }
+}
+
+fn ed25519_jwk(kid: &str, key: &VerifyingKey) -> Jwk {
+    Jwk {
+        common: common(kid, KeyAlgorithm::EdDSA),
+        algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(key.to_bytes()),
+        }),
+    }
+}
+
+fn rsa_jwk(kid: &str, key: &RsaPublicKey) -> Jwk {
+    Jwk {
+        common: common(kid, KeyAlgorithm::RS256),
+        algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+            key_type: RSAKeyType::RSA,
+            n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+            e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Claims;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use jsonwebtoken::{Header, Validation};
+    use std::path::{Path, PathBuf};
+
+    /// 每个测试独立的临时目录，测试结束时删除
+    fn key_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blog-keys-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// 把 PEM 写到 `dir`，返回路径
+    fn write_pem(dir: &Path, name: &str, pem: &str) -> String {
+        let path = dir.join(name);
+        std::fs::write(&path, pem).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn ed25519_pair(dir: &Path, seed: u8, name: &str) -> (String, String) {
+        let key = SigningKey::from_bytes(&[seed; 32]);
+        let private = key.to_pkcs8_pem(Default::default()).unwrap();
+        let public = key
+            .verifying_key()
+            .to_public_key_pem(Default::default())
+            .unwrap();
+        (
+            write_pem(dir, &format!("{}.pem", name), &private),
+            write_pem(dir, &format!("{}.pub.pem", name), &public),
+        )
+    }
+
+    fn config(algorithm: &str, kid: &str, private: &str, previous: &[(&str, &str)]) -> Config {
+        let mut cfg = Config::default();
+        cfg.auth.jwt_algorithm = algorithm.into();
+        cfg.auth.jwt_key_id = kid.into();
+        cfg.auth.jwt_private_key_file = Some(private.into());
+        cfg.auth.jwt_previous_keys = previous
+            .iter()
+            .map(|(kid, path)| (kid.to_string(), path.to_string()))
+            .collect();
+        cfg
+    }
+
+    fn claims() -> Claims {
+        let now = chrono::Utc::now().timestamp() as usize;
+        Claims {
+            user_id: "1".into(),
+            message: "test".into(),
+            exp: now + 600,
+            iat: now,
+            iss: "myblog".into(),
+            aud: "myblog".into(),
+            scopes: None,
+        }
+    }
+
+    /// 用 `keys` 按 token header 中的 kid 校验
+    fn verify(keys: &JwtKeys, token: &str) -> bool {
+        let header = jsonwebtoken::decode_header(token).unwrap();
+        let Some((algorithm, key)) = keys.verifying_key(header.kid.as_deref()) else {
+            return false;
+        };
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&["myblog"]);
+        validation.set_audience(&["myblog"]);
+        jsonwebtoken::decode::<Claims>(token, key, &validation).is_ok()
+    }
+
+    #[test]
+    fn retired_kid_still_verifies_while_listed() {
+        let dir = key_dir("rotation");
+        let (old_private, old_public) = ed25519_pair(&dir, 1, "2024");
+        let (new_private, _) = ed25519_pair(&dir, 2, "2025");
+
+        // 轮换前用旧密钥签发的 token
+        let old_keys = JwtKeys::from_config(&config("EdDSA", "2024", &old_private, &[])).unwrap();
+        let mut header = Header::new(old_keys.algorithm);
+        header.kid = Some(old_keys.kid.clone());
+        let old_token = jsonwebtoken::encode(&header, &claims(), old_keys.encoding_key()).unwrap();
+
+        // 宽限期内：旧公钥仍在 JWT_PREVIOUS_KEYS 中
+        let rotating = JwtKeys::from_config(&config(
+            "EdDSA",
+            "2025",
+            &new_private,
+            &[("2024", &old_public)],
+        ))
+        .unwrap();
+        assert!(verify(&rotating, &old_token), "旧 kid 在宽限期内应仍可校验");
+        let kids: Vec<_> = rotating
+            .jwks()
+            .keys
+            .iter()
+            .map(|jwk| jwk.common.key_id.clone().unwrap())
+            .collect();
+        assert_eq!(kids, ["2025", "2024"]);
+
+        // 新密钥签发的 token 同样可校验
+        let mut header = Header::new(rotating.algorithm);
+        header.kid = Some(rotating.kid.clone());
+        let new_token = jsonwebtoken::encode(&header, &claims(), rotating.encoding_key()).unwrap();
+        assert!(verify(&rotating, &new_token));
+
+        // 宽限期结束：移除旧公钥后不再接受
+        let retired = JwtKeys::from_config(&config("EdDSA", "2025", &new_private, &[])).unwrap();
+        assert!(!verify(&retired, &old_token), "旧 kid 移除后应拒绝");
+        assert!(verify(&retired, &new_token));
+
+        // 冒用新 kid 但用旧私钥签名的 token 签名不匹配
+        let mut forged = Header::new(Algorithm::EdDSA);
+        forged.kid = Some("2025".into());
+        let forged = jsonwebtoken::encode(&forged, &claims(), old_keys.encoding_key()).unwrap();
+        assert!(!verify(&rotating, &forged));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn jwks_publishes_ed25519_public_key() {
+        let dir = key_dir("ed25519");
+        let (private, _) = ed25519_pair(&dir, 3, "ed");
+        let keys = JwtKeys::from_config(&config("EdDSA", "ed", &private, &[])).unwrap();
+
+        let json = serde_json::to_value(keys.jwks()).unwrap();
+        let jwk = &json["keys"][0];
+        let expected_x =
+            URL_SAFE_NO_PAD.encode(SigningKey::from_bytes(&[3; 32]).verifying_key().to_bytes());
+        assert_eq!(json["keys"].as_array().unwrap().len(), 1);
+        assert_eq!(jwk["kty"], "OKP");
+        assert_eq!(jwk["crv"], "Ed25519");
+        assert_eq!(jwk["alg"], "EdDSA");
+        assert_eq!(jwk["use"], "sig");
+        assert_eq!(jwk["kid"], "ed");
+        assert_eq!(jwk["x"], expected_x.as_str());
+        assert!(jwk.get("d").is_none(), "JWKS 不能包含私钥");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn jwks_publishes_rsa_public_key() {
+        let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+        let private = key.to_pkcs8_pem(Default::default()).unwrap();
+        let public = key
+            .to_public_key()
+            .to_public_key_pem(Default::default())
+            .unwrap();
+        let dir = key_dir("rsa");
+        let private = write_pem(&dir, "rsa.pem", &private);
+        let public = write_pem(&dir, "rsa.pub.pem", &public);
+        let (ed_private, _) = ed25519_pair(&dir, 4, "ed-current");
+
+        // RSA 作为当前密钥
+        let keys = JwtKeys::from_config(&config("RS256", "rsa", &private, &[])).unwrap();
+        let json = serde_json::to_value(keys.jwks()).unwrap();
+        let jwk = &json["keys"][0];
+        assert_eq!(jwk["kty"], "RSA");
+        assert_eq!(jwk["alg"], "RS256");
+        assert_eq!(jwk["use"], "sig");
+        assert_eq!(jwk["kid"], "rsa");
+        assert_eq!(jwk["e"], "AQAB");
+        assert_eq!(
+            jwk["n"],
+            URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()).as_str()
+        );
+        assert!(jwk.get("d").is_none(), "JWKS 不能包含私钥");
+
+        // RSA 作为轮换中的旧公钥，与 Ed25519 当前密钥一起发布
+        let keys = JwtKeys::from_config(&config(
+            "EdDSA",
+            "ed-current",
+            &ed_private,
+            &[("rsa", &public)],
+        ))
+        .unwrap();
+        let json = serde_json::to_value(keys.jwks()).unwrap();
+        let kinds: Vec<_> = json["keys"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|jwk| (jwk["kid"].as_str().unwrap(), jwk["kty"].as_str().unwrap()))
+            .collect();
+        assert_eq!(kinds, [("ed-current", "OKP"), ("rsa", "RSA")]);
+        assert_eq!(json["keys"][1]["n"], jwk["n"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -2,14 +2,23 @@
 //! - JWT 生成/校验（基于 jsonwebtoken）
 //! - 密码哈希/校验（基于 argon2）
 //! - `JwtAuth` 提取器：从请求头解析 Bearer Token 并验证，向 handler 提供 Claims
+//! - `ApiAuth` 提取器：在 `JwtAuth` 基础上额外接受个人访问令牌，handler 需检查 scope
+//! - `api_token` 子模块：个人访问令牌与 scope
+//! - `keys` 子模块：JWT 签名/校验密钥与 JWKS
+//! - `oidc` 子模块：外部 OIDC 提供方登录
+
+pub mod api_token;
+pub mod keys;
+pub mod oidc;
 
 use crate::db::AppState;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, ErrorCode};
+use crate::models::user::{User, find_user_by_id};
 use axum::extract::{FromRef, FromRequestParts};
 use axum::http::header::AUTHORIZATION;
 use axum::http::request::Parts;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{Header, Validation};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -25,6 +34,33 @@ pub struct Claims {
     pub message: String, // 冗余字段，便于调试展示
     pub exp: usize,      // 过期时间（秒）
     pub iat: usize,      // 签发时间（秒）
+    pub iss: String,     // 签发方
+    pub aud: String,     // 受众
+    /// 个人访问令牌的权限范围；登录 JWT 为 None，不受 scope 限制
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+}
+
+impl Claims {
+    /// 个人访问令牌必须包含指定 scope，登录 JWT 直接放行（仍需各 handler 校验身份）
+    pub fn require_scope(&self, scope: &str) -> AppResult<()> {
+        match &self.scopes {
+            Some(scopes) if !scopes.iter().any(|s| s == scope) => Err(AppError::Reason(
+                ErrorCode::InsufficientScope,
+                format!("api token missing scope: {}", scope),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// 当前用户必须是管理员，返回其账号；账号已删除时返回 `AccountDeleted`
+    pub async fn require_admin(&self, state: &AppState) -> AppResult<User> {
+        match find_user_by_id(&state.pool, self.user_id.clone()).await? {
+            Some(user) if user.identity == "admin" => Ok(user),
+            Some(_) => Err(AppError::Forbidden),
+            None => Err(ErrorCode::AccountDeleted.into()),
+        }
+    }
 }
 
 fn now_ts() -> usize {
@@ -33,7 +69,7 @@ fn now_ts() -> usize {
 
 pub fn generate_token(state: &AppState, user_id: String, username: &str) -> AppResult<String> {
     let iat = now_ts();
-    let exp = (Utc::now() + Duration::seconds(state.cfg.jwt_ttl))
+    let exp = (Utc::now() + Duration::seconds(state.cfg.auth.jwt_ttl))
         .timestamp()
         .max(0) as usize;
 
@@ -42,12 +78,16 @@ pub fn generate_token(state: &AppState, user_id: String, username: &str) -> AppR
         message: username.to_string(),
         exp,
         iat,
+        iss: state.cfg.auth.jwt_issuer.clone(),
+        aud: state.cfg.auth.jwt_audience.clone(),
+        scopes: None,
     };
-    let token = jsonwebtoken::encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(state.cfg.jwt_secret.as_bytes()),
-    )?;
+    let header = Header {
+        alg: state.jwt.algorithm,
+        kid: Some(state.jwt.kid.clone()),
+        ..Default::default()
+    };
+    let token = jsonwebtoken::encode(&header, &claims, state.jwt.encoding_key())?;
     Ok(token)
 }
 
@@ -98,7 +138,7 @@ where
             .get(AUTHORIZATION)
             .and_then(|hv| hv.to_str().ok())
             .and_then(|v| v.strip_prefix("Bearer "))
-            .ok_or_else(|| AppError::Unauthorized("missing bearer token".into()))?;
+            .ok_or(ErrorCode::MissingToken)?;
 
         /* debug版；解析Authorization
                 for (key, value) in parts.headers.iter() {
@@ -115,6 +155,11 @@ where
 
         */
 
+        // 个人访问令牌只能用于声明了 scope 的接口（见 ApiAuth）
+        if token.starts_with(api_token::TOKEN_PREFIX) {
+            return Err(ErrorCode::ApiTokenNotAccepted.into());
+        }
+
         let claims = decode_token(&state, token)?;
         // println!("debug JwtAuth claims.user_id: {}", &claims.user_id);
 
@@ -122,20 +167,72 @@ where
     }
 }
 
-/// 用 state 里的密钥验证签名。
+/// 同时接受登录 JWT 与个人访问令牌的提取器
+/// 用法：`ApiAuth(claims): ApiAuth`，随后调用 `claims.require_scope(...)`
+pub struct ApiAuth(pub Claims);
+impl<S> FromRequestParts<S> for ApiAuth
+where
+    Arc<AppState>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = Arc::<AppState>::from_ref(state);
+
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|hv| hv.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or(ErrorCode::MissingToken)?;
+
+        let claims = if token.starts_with(api_token::TOKEN_PREFIX) {
+            api_token::authenticate(&state, token).await?
+        } else {
+            decode_token(&state, token)?
+        };
+
+        Ok(ApiAuth(claims))
+    }
+}
+
+/// 按 header 中的 kid 选择密钥验证签名，并校验 iss / aud。
 pub fn decode_token(state: &AppState, token: &str) -> AppResult<Claims> {
     // println!("debug decode_token:{}", token);
-    let data = jsonwebtoken::decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(state.cfg.jwt_secret.as_bytes()),
-        &Validation::default(),
-    )?;
+    decode_token_with_leeway(state, token, None)
+}
+
+/// 刷新 token 时使用：过期不超过 `auth.jwt_refresh_grace` 秒的 token 仍视为有效
+pub fn decode_token_for_refresh(state: &AppState, token: &str) -> AppResult<Claims> {
+    let grace = state.live_cfg.read().unwrap().auth.jwt_refresh_grace.max(0) as u64;
+    decode_token_with_leeway(state, token, Some(grace))
+}
+
+fn decode_token_with_leeway(
+    state: &AppState,
+    token: &str,
+    leeway: Option<u64>,
+) -> AppResult<Claims> {
+    let header = jsonwebtoken::decode_header(token)?;
+    let (algorithm, key) = state
+        .jwt
+        .verifying_key(header.kid.as_deref())
+        .ok_or(ErrorCode::InvalidToken)?;
+
+    let mut validation = Validation::new(algorithm);
+    validation.set_issuer(&[&state.cfg.auth.jwt_issuer]);
+    validation.set_audience(&[&state.cfg.auth.jwt_audience]);
+    if let Some(leeway) = leeway {
+        validation.leeway = leeway;
+    }
+
+    let data = jsonwebtoken::decode::<Claims>(token, key, &validation)?;
 
     Ok(data.claims)
 }
 
 // 可选登录器
-#[allow(dead_code)]
 pub struct MaybeJwtAuth(pub Option<Claims>);
 impl<S> FromRequestParts<S> for MaybeJwtAuth
 where
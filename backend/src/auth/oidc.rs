@@ -0,0 +1,234 @@
+//! OIDC 客户端（授权码 + PKCE）：
+//! - 通过 `/.well-known/openid-configuration` 发现提供方端点
+//! - 生成授权地址，并在内存中暂存 state / nonce / code_verifier 及发起请求的用户
+//! - 用授权码换取 id_token，并基于提供方的 JWKS 校验签名、iss、aud、nonce；
+//!   只接受非对称签名算法，且算法必须与 JWKS 中的密钥相符
+
+use crate::config::OidcProviderConfig;
+use crate::error::{AppError, AppResult, ErrorCode};
+use blog_shared::oidc::{allowed_algorithms, decoding_key, pkce_challenge};
+use blog_shared::random_string;
+use jsonwebtoken::Validation;
+use jsonwebtoken::jwk::JwkSet;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use url::Url;
+
+/// 授权请求的有效期，超时的 state 会被清理
+const PENDING_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// 提供方元数据（只取用到的字段）
+#[derive(Debug, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    #[serde(default)]
+    pub id_token_signing_alg_values_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// id_token 中用到的声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+    pub nonce: Option<String>,
+}
+
+/// 一次尚未完成的授权请求
+struct PendingAuth {
+    provider: String,
+    nonce: String,
+    code_verifier: String,
+    /// 发起授权时已登录的用户，回调时只允许同一用户使用该 state
+    initiator: Option<String>,
+    created_at: Instant,
+}
+
+/// 授权码换取的结果
+pub struct Exchanged {
+    pub claims: IdTokenClaims,
+    /// 发起授权的用户 id，未登录发起时为 None
+    pub initiator: Option<String>,
+}
+
+/// OIDC 运行期状态：HTTP 客户端与待完成的授权请求
+#[derive(Default)]
+pub struct OidcState {
+    http: reqwest::Client,
+    pending: Mutex<HashMap<String, PendingAuth>>,
+}
+
+impl OidcState {
+    /// 生成授权地址，返回 (authorization_url, state)；
+    /// `initiator` 为发起请求的已登录用户，state 与其绑定
+    pub async fn authorization_url(
+        &self,
+        provider: &OidcProviderConfig,
+        initiator: Option<String>,
+    ) -> AppResult<(String, String)> {
+        let metadata = self.discover(provider).await?;
+
+        let state = random_string(32);
+        let nonce = random_string(32);
+        let code_verifier = random_string(64);
+
+        let mut url = Url::parse(&metadata.authorization_endpoint).map_err(|e| {
+            AppError::InternalServerError(format!("invalid authorization endpoint: {e}"))
+        })?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &provider.client_id)
+            .append_pair("redirect_uri", &provider.redirect_uri)
+            .append_pair("scope", &provider.scopes)
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &pkce_challenge(&code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        let mut pending = self.pending.lock().await;
+        pending.retain(|_, p| p.created_at.elapsed() < PENDING_TTL);
+        pending.insert(
+            state.clone(),
+            PendingAuth {
+                provider: provider.name.clone(),
+                nonce,
+                code_verifier,
+                initiator,
+                created_at: Instant::now(),
+            },
+        );
+
+        Ok((url.to_string(), state))
+    }
+
+    /// 用授权码换取并校验 id_token
+    pub async fn exchange_code(
+        &self,
+        provider: &OidcProviderConfig,
+        code: &str,
+        state: &str,
+    ) -> AppResult<Exchanged> {
+        // state 只能使用一次
+        let pending = self
+            .pending
+            .lock()
+            .await
+            .remove(state)
+            .filter(|p| p.provider == provider.name && p.created_at.elapsed() < PENDING_TTL)
+            .ok_or_else(|| {
+                AppError::Reason(
+                    ErrorCode::OidcLoginFailed,
+                    "invalid or expired oidc state".into(),
+                )
+            })?;
+
+        let metadata = self.discover(provider).await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("code_verifier", pending.code_verifier.as_str()),
+        ];
+        if let Some(secret) = &provider.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let res = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::InternalServerError(format!("oidc token request failed: {e}"))
+            })?;
+        if !res.status().is_success() {
+            let status = res.status();
+            let text = res.text().await.unwrap_or_default();
+            tracing::warn!("OIDC token 端点返回错误 {}: {}", status, text);
+            return Err(AppError::Reason(
+                ErrorCode::OidcLoginFailed,
+                "oidc code exchange failed".into(),
+            ));
+        }
+        let token: TokenResponse = res.json().await.map_err(|e| {
+            AppError::InternalServerError(format!("invalid oidc token response: {e}"))
+        })?;
+
+        let claims = self
+            .verify_id_token(provider, &metadata, &token.id_token)
+            .await?;
+        if claims.nonce.as_deref() != Some(pending.nonce.as_str()) {
+            return Err(AppError::Reason(
+                ErrorCode::OidcLoginFailed,
+                "oidc nonce mismatch".into(),
+            ));
+        }
+
+        Ok(Exchanged {
+            claims,
+            initiator: pending.initiator,
+        })
+    }
+
+    /// 基于 JWKS 校验 id_token 的签名与 iss / aud
+    async fn verify_id_token(
+        &self,
+        provider: &OidcProviderConfig,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+    ) -> AppResult<IdTokenClaims> {
+        let header = jsonwebtoken::decode_header(id_token)?;
+
+        let jwks: JwkSet = self.get_json(&metadata.jwks_uri).await?;
+        let allowed = allowed_algorithms(&metadata.id_token_signing_alg_values_supported);
+        let (key, alg) = decoding_key(&header, &jwks, &allowed)
+            .map_err(|e| AppError::Reason(ErrorCode::OidcLoginFailed, e.to_string()))?;
+
+        let mut validation = Validation::new(alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&provider.client_id]);
+
+        let data = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)?;
+        Ok(data.claims)
+    }
+
+    /// 获取提供方元数据
+    async fn discover(&self, provider: &OidcProviderConfig) -> AppResult<ProviderMetadata> {
+        let url = format!("{}/.well-known/openid-configuration", provider.issuer);
+        let metadata: ProviderMetadata = self.get_json(&url).await?;
+
+        if metadata.issuer.trim_end_matches('/') != provider.issuer {
+            return Err(AppError::InternalServerError(format!(
+                "oidc issuer mismatch: expected {}, got {}",
+                provider.issuer, metadata.issuer
+            )));
+        }
+        Ok(metadata)
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(&self, url: &str) -> AppResult<T> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(|e| AppError::InternalServerError(format!("oidc request failed: {e}")))?
+            .json::<T>()
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("invalid oidc response: {e}")))
+    }
+}
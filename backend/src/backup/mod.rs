@@ -0,0 +1,386 @@
+//! 数据库备份与恢复：
+//! - `create`：`VACUUM INTO` 生成一致的快照，服务运行时也可以执行
+//! - `prune`：只保留最近的 `storage.backup_keep` 份
+//! - `validate`：恢复前执行 `PRAGMA integrity_check` 并检查迁移版本
+//! - 服务运行中无法替换数据库文件，恢复先暂存为 `<数据库>.restore`，下次启动连接数据库前由 `apply_pending` 替换
+//! - `spawn_scheduler`：每隔 `storage.backup_interval_hours` 小时自动备份
+
+use crate::config::DatabaseConfig;
+use crate::db::{AppState, MIGRATOR, database_path};
+use crate::models::audit::{NewAudit, insert_audit};
+use anyhow::{Context, bail, ensure};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// 备份文件名前缀，`prune` 只清理带此前缀的文件
+const PREFIX: &str = "app-";
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BackupInfo {
+    pub name: String,
+    pub size: u64,
+    pub created_at: String,
+}
+
+impl BackupInfo {
+    fn read(path: &Path) -> anyhow::Result<Self> {
+        let meta = std::fs::metadata(path)?;
+        Ok(Self {
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            size: meta.len(),
+            created_at: DateTime::<Local>::from(meta.modified()?).to_rfc3339(),
+        })
+    }
+}
+
+/// `dir` 下按当前时间命名的备份路径
+pub fn default_path(dir: &Path) -> PathBuf {
+    dir.join(format!(
+        "{PREFIX}{}.db",
+        Local::now().format("%Y%m%d-%H%M%S-%3f")
+    ))
+}
+
+/// 把当前数据库写入 `out`，目标已存在时报错
+pub async fn create(pool: &SqlitePool, out: &Path) -> anyhow::Result<BackupInfo> {
+    if let Some(dir) = out.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("创建目录 {} 失败", dir.display()))?;
+    }
+    ensure!(!out.exists(), "{} 已存在", out.display());
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(out.to_string_lossy().into_owned())
+        .execute(pool)
+        .await
+        .with_context(|| format!("备份到 {} 失败", out.display()))?;
+    BackupInfo::read(out)
+}
+
+/// 在 `dir` 中备份并清理旧备份
+pub async fn rotate(pool: &SqlitePool, dir: &Path, keep: u32) -> anyhow::Result<BackupInfo> {
+    let info = create(pool, &default_path(dir)).await?;
+    for name in prune(dir, keep)? {
+        tracing::info!("已删除旧备份 {name}");
+    }
+    Ok(info)
+}
+
+/// `dir` 中的备份，最新的在前；目录不存在时为空
+pub fn list(dir: &Path) -> anyhow::Result<Vec<BackupInfo>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_file() && name.starts_with(PREFIX) && name.ends_with(".db") {
+            files.push(path);
+        }
+    }
+    // 文件名中的时间可以直接按字典序排序
+    files.sort();
+    files.reverse();
+    files.iter().map(|f| BackupInfo::read(f)).collect()
+}
+
+/// 只保留最近的 `keep` 份，返回删除的文件名
+pub fn prune(dir: &Path, keep: u32) -> anyhow::Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for info in list(dir)?.into_iter().skip(keep as usize) {
+        std::fs::remove_file(dir.join(&info.name))
+            .with_context(|| format!("删除 {} 失败", info.name))?;
+        removed.push(info.name);
+    }
+    Ok(removed)
+}
+
+/// 按文件名在 `dir` 中查找备份，拒绝包含路径的名字
+pub fn find(dir: &Path, name: &str) -> Option<PathBuf> {
+    let plain = Path::new(name).file_name().is_some_and(|n| n == name);
+    let path = dir.join(name);
+    (plain && name.starts_with(PREFIX) && path.is_file()).then_some(path)
+}
+
+/// 恢复前的校验：完整性检查通过，且迁移版本都是当前程序已知的
+pub async fn validate(file: &Path) -> anyhow::Result<()> {
+    ensure!(file.is_file(), "{} 不存在", file.display());
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(SqliteConnectOptions::new().filename(file).read_only(true))
+        .await
+        .with_context(|| format!("{} 不是有效的 SQLite 数据库", file.display()))?;
+    let result = check(&pool).await;
+    pool.close().await;
+    result.with_context(|| format!("{} 校验失败", file.display()))
+}
+
+async fn check(pool: &SqlitePool) -> anyhow::Result<()> {
+    let (integrity,): (String,) = sqlx::query_as("PRAGMA integrity_check")
+        .fetch_one(pool)
+        .await
+        .context("不是有效的 SQLite 数据库")?;
+    ensure!(integrity == "ok", "完整性检查失败: {integrity}");
+
+    let (tables,): (i64,) = sqlx::query_as(
+        r#"SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'"#,
+    )
+    .fetch_one(pool)
+    .await?;
+    ensure!(tables > 0, "缺少迁移记录，不是本程序的数据库");
+
+    let applied: Vec<(i64, bool)> =
+        sqlx::query_as(r#"SELECT version, success FROM _sqlx_migrations"#)
+            .fetch_all(pool)
+            .await?;
+    for (version, success) in applied {
+        ensure!(success, "迁移 {version} 未成功执行");
+        if !MIGRATOR.iter().any(|m| m.version == version) {
+            bail!("包含未知的迁移 {version}，备份来自更新的版本");
+        }
+    }
+    Ok(())
+}
+
+/// 用 `file` 覆盖 `target`，并删除属于旧数据库的 WAL 文件；调用前需确认没有连接
+pub fn replace(file: &Path, target: &Path) -> anyhow::Result<()> {
+    std::fs::copy(file, target).with_context(|| format!("复制到 {} 失败", target.display()))?;
+    for suffix in ["-wal", "-shm"] {
+        let mut side = target.to_path_buf().into_os_string();
+        side.push(suffix);
+        let _ = std::fs::remove_file(side);
+    }
+    Ok(())
+}
+
+/// 暂存的待恢复文件
+fn pending_path(db: &DatabaseConfig) -> anyhow::Result<PathBuf> {
+    let mut path = database_path(db)?.into_os_string();
+    path.push(".restore");
+    Ok(path.into())
+}
+
+/// 校验 `file` 后暂存，下次启动时恢复
+pub async fn stage(db: &DatabaseConfig, file: &Path) -> anyhow::Result<()> {
+    validate(file).await?;
+    let pending = pending_path(db)?;
+    std::fs::copy(file, &pending).with_context(|| format!("写入 {} 失败", pending.display()))?;
+    Ok(())
+}
+
+/// 启动时在连接数据库前应用暂存的恢复，返回是否执行了恢复；
+/// 暂存时写入的审计记录随旧数据库一起被覆盖，恢复后由 `record_applied` 重新记录
+pub async fn apply_pending(db: &DatabaseConfig) -> anyhow::Result<bool> {
+    let pending = pending_path(db)?;
+    if !pending.is_file() {
+        return Ok(false);
+    }
+    // 暂存后文件可能被改动，替换前再校验一次
+    validate(&pending).await?;
+    replace(&pending, &database_path(db)?)?;
+    std::fs::remove_file(&pending)?;
+    tracing::warn!("已从暂存的备份恢复数据库");
+    Ok(true)
+}
+
+/// 在恢复后的数据库中记录本次恢复
+pub async fn record_applied(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    insert_audit(
+        &mut *pool.acquire().await?,
+        NewAudit {
+            actor_id: None,
+            actor_name: Some("system"),
+            action: "db.restore",
+            target_type: "database",
+            target_id: None,
+            before: None,
+            after: None,
+        },
+    )
+    .await
+}
+
+/// 定时备份，间隔为 0 时不启用
+pub fn spawn_scheduler(state: Arc<AppState>) {
+    let hours = state.cfg.storage.backup_interval_hours;
+    if hours == 0 {
+        return;
+    }
+    tracing::info!("定时备份已启用，每 {hours} 小时一次");
+
+    tokio::spawn(async move {
+        let period = Duration::from_secs(u64::from(hours) * 3600);
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let storage = &state.cfg.storage;
+
+        loop {
+            ticker.tick().await;
+            match rotate(
+                &state.pool,
+                Path::new(&storage.backup_dir),
+                storage.backup_keep,
+            )
+            .await
+            {
+                Ok(info) => tracing::info!("定时备份完成: {}", info.name),
+                Err(e) => tracing::error!("定时备份失败: {e:#}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每个测试独立的临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("blog-backup-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// `dir` 下的数据库文件；内存数据库 `VACUUM INTO` 的结果同样只在内存中，不能用来生成备份
+    async fn file_pool(dir: &Path, name: &str) -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(dir.join(name))
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap()
+    }
+
+    /// 迁移到最新版本的数据库
+    async fn migrated_pool(dir: &Path) -> SqlitePool {
+        let pool = file_pool(dir, "source.db").await;
+        MIGRATOR.run(&pool).await.unwrap();
+        pool
+    }
+
+    #[test]
+    fn find_rejects_paths() {
+        let dir = temp_dir("find");
+        std::fs::write(dir.join("app-20240101-000000-000.db"), b"").unwrap();
+        std::fs::write(dir.join("notes.db"), b"").unwrap();
+        let outside = dir.parent().unwrap().join("app-outside.db");
+
+        let cases = [
+            ("普通备份", "app-20240101-000000-000.db".to_string(), true),
+            ("不存在", "app-20990101-000000-000.db".to_string(), false),
+            ("缺少前缀", "notes.db".to_string(), false),
+            ("上级目录", "../app-outside.db".to_string(), false),
+            ("子目录", "./app-20240101-000000-000.db".to_string(), false),
+            ("绝对路径", outside.to_string_lossy().into_owned(), false),
+            (
+                "指向目录内的绝对路径",
+                dir.join("app-20240101-000000-000.db")
+                    .to_string_lossy()
+                    .into_owned(),
+                false,
+            ),
+            ("空名", String::new(), false),
+        ];
+        std::fs::write(&outside, b"").unwrap();
+        for (desc, name, found) in cases {
+            assert_eq!(find(&dir, &name).is_some(), found, "{desc}: {name}");
+        }
+        std::fs::remove_file(outside).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn validate_accepts_backups_of_this_version() {
+        let dir = temp_dir("validate-ok");
+        let pool = migrated_pool(&dir).await;
+        let info = create(&pool, &default_path(&dir)).await.unwrap();
+
+        validate(&dir.join(&info.name)).await.unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn validate_rejects_foreign_files() {
+        let dir = temp_dir("validate-bad");
+
+        let text = dir.join("app-text.db");
+        std::fs::write(&text, "not a database".repeat(100)).unwrap();
+        assert!(validate(&text).await.is_err(), "非 SQLite 文件");
+
+        assert!(
+            validate(&dir.join("app-missing.db")).await.is_err(),
+            "不存在的文件"
+        );
+
+        // 没有迁移记录的 SQLite 数据库
+        let plain = dir.join("app-plain.db");
+        let pool = file_pool(&dir, "plain.db").await;
+        sqlx::query("CREATE TABLE notes (id INTEGER)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        create(&pool, &plain).await.unwrap();
+        let err = validate(&plain).await.unwrap_err();
+        assert!(format!("{err:#}").contains("缺少迁移记录"), "{err:#}");
+
+        // 来自更新版本的备份
+        let future = dir.join("app-future.db");
+        let pool = migrated_pool(&dir).await;
+        sqlx::query(
+            r#"INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+               VALUES (99999, 'future', 1, x'00', 0)"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        create(&pool, &future).await.unwrap();
+        let err = validate(&future).await.unwrap_err();
+        assert!(format!("{err:#}").contains("未知的迁移 99999"), "{err:#}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn prune_keeps_newest_backups() {
+        let dir = temp_dir("prune");
+        let names = [
+            "app-20240101-000000-000.db",
+            "app-20240102-000000-000.db",
+            "app-20240103-000000-000.db",
+            "app-20240104-000000-000.db",
+            "app-20240105-000000-000.db",
+        ];
+        for name in names {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        // 不属于备份的文件不受影响
+        std::fs::write(dir.join("notes.db"), b"").unwrap();
+        std::fs::write(dir.join("app-20230101-000000-000.txt"), b"").unwrap();
+
+        let mut removed = prune(&dir, 2).unwrap();
+        removed.sort();
+        assert_eq!(removed, names[..3]);
+
+        let kept: Vec<_> = list(&dir).unwrap().into_iter().map(|b| b.name).collect();
+        assert_eq!(kept, [names[4], names[3]]);
+        assert!(dir.join("notes.db").exists());
+        assert!(dir.join("app-20230101-000000-000.txt").exists());
+
+        assert!(prune(&dir, 2).unwrap().is_empty(), "数量未超出时不删除");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+//! `backend article ...`：导入（Markdown / WXR）、导出 Markdown、发布与撤回文章
+
+use super::{ArticleCommand, audit};
+use crate::export;
+use crate::import::{self, ImportReport};
+use crate::models::article::{ArticleModel, find_article_by_id, get_articles, patch_article_by_id};
+use crate::routes::articles::{GetArticlesParams, NewStatus};
+use crate::routes::audit::snapshot;
+use anyhow::{Context, bail};
+use sqlx::SqlitePool;
+use std::path::Path;
+
+pub async fn run(pool: &SqlitePool, cmd: ArticleCommand) -> anyhow::Result<()> {
+    match cmd {
+        ArticleCommand::Import { path, options } => {
+            let (posts, skipped) = import::load(&path)?;
+            let report = import::run(pool, posts, skipped, &options).await?;
+            print_report(&report);
+
+            if !report.dry_run && !report.imported.is_empty() {
+                let after = serde_json::json!({
+                    "source": path.display().to_string(),
+                    "articles": report.imported.iter().filter_map(|e| e.id.as_deref()).collect::<Vec<_>>(),
+                    "conflicts": report.conflicts.len(),
+                    "skipped": report.skipped.len(),
+                });
+                let mut conn = pool.acquire().await?;
+                audit(
+                    &mut conn,
+                    "article.import",
+                    "article",
+                    None,
+                    None,
+                    Some(after),
+                )
+                .await?;
+            }
+        }
+        ArticleCommand::Export { out } => {
+            std::fs::create_dir_all(&out)
+                .with_context(|| format!("创建目录 {} 失败", out.display()))?;
+            let list = get_articles(
+                pool,
+                GetArticlesParams {
+                    identity: "admin".into(),
+                    condition: None,
+                },
+            )
+            .await?;
+
+            for item in &list {
+                let Some(article) = find_article_by_id(pool, &item.id).await? else {
+                    continue;
+                };
+                write_markdown(&out, &article)?;
+            }
+            println!("已导出 {} 篇文章到 {}", list.len(), out.display());
+        }
+        ArticleCommand::Publish { id, unpublish } => {
+            let Some(article) = find_article_by_id(pool, &id).await? else {
+                bail!("文章 {id} 不存在");
+            };
+            let status = if unpublish { "draft" } else { "published" };
+            set_status(pool, article, status).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn set_status(pool: &SqlitePool, before: ArticleModel, status: &str) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    let after = patch_article_by_id(
+        &mut tx,
+        &before.id,
+        NewStatus {
+            toggle: status.into(),
+        },
+    )
+    .await?;
+    audit(
+        &mut tx,
+        "article.status",
+        "article",
+        Some(&before.id),
+        snapshot(&before),
+        snapshot(&after),
+    )
+    .await?;
+    tx.commit().await?;
+    println!("文章 {} 状态已改为 {}", after.id, status);
+    Ok(())
+}
+
+fn print_report(report: &ImportReport) {
+    for e in &report.imported {
+        println!(
+            "  + [{}] {} ← {}{}",
+            e.status,
+            e.title,
+            e.source,
+            e.id.as_deref()
+                .map(|id| format!(" → {id}"))
+                .unwrap_or_default()
+        );
+    }
+    for c in &report.conflicts {
+        println!(
+            "  ! 冲突 {} ← {}（已存在 {}）",
+            c.title, c.source, c.existing
+        );
+    }
+    for s in &report.skipped {
+        println!("  - 跳过 {}: {}", s.source, s.reason);
+    }
+
+    let comments: usize = report.imported.iter().map(|e| e.comments).sum();
+    println!(
+        "{}导入 {} 篇（评论 {} 条），冲突 {} 篇，跳过 {} 个",
+        if report.dry_run {
+            "[dry-run] 将"
+        } else {
+            "已"
+        },
+        report.imported.len(),
+        comments,
+        report.conflicts.len(),
+        report.skipped.len()
+    );
+}
+
+fn write_markdown(dir: &Path, article: &ArticleModel) -> anyhow::Result<()> {
+    let path = dir.join(format!("{}.md", article.id));
+    std::fs::write(&path, export::to_markdown(article))
+        .with_context(|| format!("写入 {} 失败", path.display()))
+}
@@ -0,0 +1,94 @@
+//! `backend migrate ...` 与 `backend db ...`：迁移、备份与恢复
+
+use super::DbCommand;
+use crate::backup;
+use crate::config::Config;
+use crate::db::{MIGRATOR, database_path, new_pool, run_migrations};
+use anyhow::bail;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub async fn migrate(cfg: &Config, status_only: bool) -> anyhow::Result<()> {
+    let pool = new_pool(&cfg.database).await?;
+    if !status_only {
+        run_migrations(&pool).await?;
+        println!("迁移已完成");
+    }
+
+    let applied = applied_versions(&pool).await?;
+    for m in MIGRATOR.iter() {
+        let state = match applied.get(&m.version) {
+            Some(true) => "applied",
+            Some(false) => "failed",
+            None => "pending",
+        };
+        println!("{:>4}  {:<8} {}", m.version, state, m.description);
+    }
+    Ok(())
+}
+
+/// 已执行的迁移版本及是否成功
+async fn applied_versions(pool: &SqlitePool) -> anyhow::Result<HashMap<i64, bool>> {
+    let (exists,): (i64,) = sqlx::query_as(
+        r#"SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'"#,
+    )
+    .fetch_one(pool)
+    .await?;
+    if exists == 0 {
+        return Ok(HashMap::new());
+    }
+
+    let rows: Vec<(i64, bool)> = sqlx::query_as(r#"SELECT version, success FROM _sqlx_migrations"#)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+pub async fn run(cfg: &Config, cmd: DbCommand) -> anyhow::Result<()> {
+    match cmd {
+        DbCommand::Backup { out } => {
+            let pool = new_pool(&cfg.database).await?;
+            let info = match out {
+                Some(path) => backup::create(&pool, &path).await?,
+                // 写入默认目录时按 storage.backup_keep 清理旧备份
+                None => {
+                    let dir = Path::new(&cfg.storage.backup_dir);
+                    let info = backup::create(&pool, &backup::default_path(dir)).await?;
+                    for name in backup::prune(dir, cfg.storage.backup_keep)? {
+                        println!("已删除旧备份 {name}");
+                    }
+                    info
+                }
+            };
+            println!("已备份 {}（{} 字节）", info.name, info.size);
+        }
+        DbCommand::List => {
+            for info in backup::list(Path::new(&cfg.storage.backup_dir))? {
+                println!("{:<28} {:>12}  {}", info.name, info.size, info.created_at);
+            }
+        }
+        DbCommand::Check { file } => {
+            let file = match file {
+                Some(file) => file,
+                None => database_path(&cfg.database)?,
+            };
+            backup::validate(&file).await?;
+            println!("{} 校验通过", file.display());
+        }
+        DbCommand::Restore { file, yes } => {
+            backup::validate(&file).await?;
+
+            let target = database_path(&cfg.database)?;
+            if !yes {
+                bail!(
+                    "恢复会覆盖 {}，请先停止服务，确认后加 --yes 重新执行",
+                    target.display()
+                );
+            }
+            backup::replace(&file, &target)?;
+            println!("已从 {} 恢复到 {}", file.display(), target.display());
+        }
+    }
+    Ok(())
+}
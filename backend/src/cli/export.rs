@@ -0,0 +1,64 @@
+//! `backend export ...`：导出 zip 归档或生成静态站点
+
+use super::{ExportCommand, audit};
+use crate::config::Config;
+use crate::export::site::SiteOptions;
+use crate::export::{self, archive, site};
+use chrono::Local;
+use sqlx::SqlitePool;
+use std::path::Path;
+
+pub async fn run(pool: &SqlitePool, cfg: &Config, cmd: ExportCommand) -> anyhow::Result<()> {
+    let snapshot = export::snapshot(pool).await?;
+    let export_dir = Path::new(&cfg.storage.export_dir);
+
+    let (kind, out, summary) = match cmd {
+        ExportCommand::Archive { out } => {
+            let out = out.unwrap_or_else(|| {
+                export_dir.join(format!("blog-{}.zip", Local::now().format("%Y%m%d-%H%M%S")))
+            });
+            let summary = archive::write(&snapshot, &out, Path::new(&cfg.storage.media_dir))?;
+            println!(
+                "已导出 {} 篇文章、{} 条评论、{} 个媒体文件到 {}",
+                summary.articles,
+                summary.comments,
+                summary.media,
+                out.display()
+            );
+            ("archive", out, serde_json::to_value(summary)?)
+        }
+        ExportCommand::Site { out, base_url } => {
+            let out = out.unwrap_or_else(|| export_dir.join("site"));
+            let opts = SiteOptions {
+                title: cfg.feeds.site_title.clone(),
+                base_url: base_url
+                    .unwrap_or_else(|| cfg.feeds.site_url.clone())
+                    .trim_end_matches('/')
+                    .to_string(),
+                feed_items: cfg.feeds.items as usize,
+            };
+            let summary = site::write(&snapshot, &out, &opts)?;
+            println!(
+                "已生成静态站点：{} 篇文章、{} 个标签，共 {} 个文件 → {}",
+                summary.articles,
+                summary.tags,
+                summary.files,
+                out.display()
+            );
+            ("site", out, serde_json::to_value(summary)?)
+        }
+    };
+
+    let mut after = summary;
+    after["kind"] = kind.into();
+    after["out"] = out.display().to_string().into();
+    audit(
+        &mut *pool.acquire().await?,
+        "blog.export",
+        "blog",
+        None,
+        None,
+        Some(after),
+    )
+    .await
+}
@@ -0,0 +1,407 @@
+//! 命令行：
+//! - 全局选项：`--config`、`--print-config`、`--host` / `--port` / `--database-url`、`--set 小节.键=值`
+//! - 子命令：不带子命令或 `serve` 启动 HTTP 服务；其余子命令用于运维管理，执行完即退出
+//!
+//! 管理命令复用 `models::*` 中的数据库函数，对用户与文章的修改同样写入审计日志（执行者记为 `cli`）
+
+mod article;
+mod db;
+mod export;
+pub mod openapi;
+mod token;
+mod user;
+
+use crate::config::Config;
+use crate::db::{new_pool, run_migrations};
+use crate::import::ImportOptions;
+use crate::models::audit::{NewAudit, insert_audit};
+use sqlx::{SqliteConnection, SqlitePool};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+用法: backend [选项] [子命令]
+
+子命令:
+  serve                                     启动 HTTP 服务（默认）
+  migrate up | status                       执行迁移 / 查看迁移状态
+  user create <用户名> [--role <身份>] [--password <密码>]
+  user list [--limit <n>]
+  user set-role <用户名> <admin|user|visitor>
+  user reset-password <用户名> [--password <密码>]
+  article import <目录|file.md|wxr.xml> [--tags <a,b>] [--draft] [--dry-run]
+                                            导入 Markdown（Hexo/Hugo/Jekyll）或 WordPress 导出
+  article export [--out <目录>]            每篇文章导出为一个 Markdown 文件
+  article publish <id> [--unpublish]
+  export archive [--out <file.zip>]          导出 zip：Markdown + 评论 JSON + 媒体文件
+  export site [--out <目录>] [--base-url <url>]
+                                            生成静态站点（文章、标签页、RSS / Atom、sitemap）
+  db backup [<path>]                        在线备份（VACUUM INTO），默认写入 storage.backup_dir 并清理旧备份
+  db list                                   列出 storage.backup_dir 中的备份
+  db check [<path>]                         完整性与迁移版本检查，默认检查当前数据库
+  db restore <path> [--yes]                 校验备份后覆盖当前数据库，需先停止服务
+  token revoke <id> | --user <用户名>      撤销单个令牌 / 用户的全部令牌
+  openapi [--out <file>] [--check <file>]   输出 OpenAPI 文档 / 检查文件是否与代码一致（不读取配置）
+
+选项:
+  --config <path>         TOML 配置文件（默认读取当前目录的 config.toml）
+  --print-config          输出脱敏后的最终配置并退出
+  --host <ip>             覆盖 server.host
+  --port <port>           覆盖 server.port
+  --database-url <url>    覆盖 database.url
+  --set <key=value>       覆盖任意配置项，如 --set server.rate_limit.strict=5/60，可重复
+  -h, --help              显示帮助
+
+未提供 --password 时从标准输入读取密码。";
+
+#[derive(Debug, Default)]
+pub struct Cli {
+    pub config: Option<PathBuf>,
+    pub print_config: bool,
+    /// (配置路径, 原始值)，按出现顺序应用
+    pub overrides: Vec<(String, String)>,
+    pub help: bool,
+    pub command: Command,
+}
+
+#[derive(Debug, Default)]
+pub enum Command {
+    #[default]
+    Serve,
+    Migrate {
+        status_only: bool,
+    },
+    User(UserCommand),
+    Article(ArticleCommand),
+    Db(DbCommand),
+    Export(ExportCommand),
+    TokenRevoke {
+        id: Option<String>,
+        user: Option<String>,
+    },
+    OpenApi {
+        out: Option<PathBuf>,
+        check: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug)]
+pub enum UserCommand {
+    Create {
+        username: String,
+        role: String,
+        password: Option<String>,
+    },
+    List {
+        limit: i32,
+    },
+    SetRole {
+        username: String,
+        role: String,
+    },
+    ResetPassword {
+        username: String,
+        password: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+pub enum ArticleCommand {
+    Import {
+        path: PathBuf,
+        options: ImportOptions,
+    },
+    Export {
+        out: PathBuf,
+    },
+    Publish {
+        id: String,
+        unpublish: bool,
+    },
+}
+
+#[derive(Debug)]
+pub enum ExportCommand {
+    Archive {
+        out: Option<PathBuf>,
+    },
+    Site {
+        out: Option<PathBuf>,
+        base_url: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+pub enum DbCommand {
+    Backup { out: Option<PathBuf> },
+    List,
+    Check { file: Option<PathBuf> },
+    Restore { file: PathBuf, yes: bool },
+}
+
+impl Cli {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = Args(args.into_iter().collect());
+
+        let mut cli = Cli {
+            help: args.flag("-h") | args.flag("--help"),
+            print_config: args.flag("--print-config"),
+            config: args.option("--config")?.map(PathBuf::from),
+            ..Default::default()
+        };
+        for (flag, key) in [
+            ("--host", "server.host"),
+            ("--port", "server.port"),
+            ("--database-url", "database.url"),
+        ] {
+            if let Some(value) = args.option(flag)? {
+                cli.overrides.push((key.into(), value));
+            }
+        }
+        for pair in args.options("--set")? {
+            let (key, val) = pair
+                .split_once('=')
+                .filter(|(k, _)| !k.trim().is_empty())
+                .ok_or_else(|| format!("--set 的参数应为 key=value: {pair}"))?;
+            cli.overrides.push((key.trim().into(), val.into()));
+        }
+
+        if !cli.help {
+            cli.command = parse_command(&mut args)?;
+            args.finish()?;
+        }
+        Ok(cli)
+    }
+}
+
+fn parse_command(args: &mut Args) -> Result<Command, String> {
+    let Some(name) = args.next() else {
+        return Ok(Command::Serve);
+    };
+
+    let command = match name.as_str() {
+        "serve" => Command::Serve,
+        "migrate" => match args.positional("migrate 的子命令")?.as_str() {
+            "up" => Command::Migrate { status_only: false },
+            "status" => Command::Migrate { status_only: true },
+            other => return Err(format!("未知的 migrate 子命令: {other}")),
+        },
+        "user" => Command::User(match args.positional("user 的子命令")?.as_str() {
+            "create" => UserCommand::Create {
+                role: args.option("--role")?.unwrap_or_else(|| "user".into()),
+                password: args.option("--password")?,
+                username: args.positional("用户名")?,
+            },
+            "list" => UserCommand::List {
+                limit: match args.option("--limit")? {
+                    Some(n) => n.parse().map_err(|_| format!("--limit 应为整数: {n}"))?,
+                    None => 100,
+                },
+            },
+            "set-role" => UserCommand::SetRole {
+                username: args.positional("用户名")?,
+                role: args.positional("身份")?,
+            },
+            "reset-password" => UserCommand::ResetPassword {
+                password: args.option("--password")?,
+                username: args.positional("用户名")?,
+            },
+            other => return Err(format!("未知的 user 子命令: {other}")),
+        }),
+        "article" => Command::Article(match args.positional("article 的子命令")?.as_str() {
+            "import" => ArticleCommand::Import {
+                options: ImportOptions {
+                    dry_run: args.flag("--dry-run"),
+                    draft: args.flag("--draft"),
+                    extra_tags: args
+                        .option("--tags")?
+                        .map(|t| {
+                            t.split(',')
+                                .map(str::trim)
+                                .filter(|t| !t.is_empty())
+                                .map(str::to_string)
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                },
+                path: args.positional("导入路径")?.into(),
+            },
+            "export" => ArticleCommand::Export {
+                out: args
+                    .option("--out")?
+                    .unwrap_or_else(|| "articles".into())
+                    .into(),
+            },
+            "publish" => ArticleCommand::Publish {
+                unpublish: args.flag("--unpublish"),
+                id: args.positional("文章 id")?,
+            },
+            other => return Err(format!("未知的 article 子命令: {other}")),
+        }),
+        "db" => Command::Db(match args.positional("db 的子命令")?.as_str() {
+            "backup" => DbCommand::Backup {
+                out: args.next().map(PathBuf::from),
+            },
+            "list" => DbCommand::List,
+            "check" => DbCommand::Check {
+                file: args.next().map(PathBuf::from),
+            },
+            "restore" => DbCommand::Restore {
+                yes: args.flag("--yes"),
+                file: args.positional("备份文件路径")?.into(),
+            },
+            other => return Err(format!("未知的 db 子命令: {other}")),
+        }),
+        "export" => Command::Export(match args.positional("export 的子命令")?.as_str() {
+            "archive" => ExportCommand::Archive {
+                out: args.option("--out")?.map(PathBuf::from),
+            },
+            "site" => ExportCommand::Site {
+                out: args.option("--out")?.map(PathBuf::from),
+                base_url: args.option("--base-url")?,
+            },
+            other => return Err(format!("未知的 export 子命令: {other}")),
+        }),
+        "token" => match args.positional("token 的子命令")?.as_str() {
+            "revoke" => {
+                let user = args.option("--user")?;
+                let id = args.next();
+                if id.is_some() == user.is_some() {
+                    return Err("token revoke 需要令牌 id 或 --user <用户名> 其中之一".into());
+                }
+                Command::TokenRevoke { id, user }
+            }
+            other => return Err(format!("未知的 token 子命令: {other}")),
+        },
+        "openapi" => Command::OpenApi {
+            out: args.option("--out")?.map(PathBuf::from),
+            check: args.option("--check")?.map(PathBuf::from),
+        },
+        other => return Err(format!("未知的子命令: {other}")),
+    };
+    Ok(command)
+}
+
+/// 剩余的命令行参数，选项可以出现在任意位置
+struct Args(Vec<String>);
+
+impl Args {
+    fn flag(&mut self, name: &str) -> bool {
+        let before = self.0.len();
+        self.0.retain(|a| a != name);
+        self.0.len() != before
+    }
+
+    /// 取出 `--name value` 或 `--name=value`
+    fn option(&mut self, name: &str) -> Result<Option<String>, String> {
+        Ok(self.options(name)?.pop())
+    }
+
+    fn options(&mut self, name: &str) -> Result<Vec<String>, String> {
+        let mut values = Vec::new();
+        let mut i = 0;
+        while i < self.0.len() {
+            if self.0[i] == name {
+                if i + 1 >= self.0.len() {
+                    return Err(format!("{name} 缺少参数值"));
+                }
+                values.push(self.0.remove(i + 1));
+                self.0.remove(i);
+            } else if let Some(v) = self.0[i]
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                values.push(v.to_string());
+                self.0.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        Ok(values)
+    }
+
+    /// 下一个位置参数
+    fn next(&mut self) -> Option<String> {
+        (!self.0.is_empty()).then(|| self.0.remove(0))
+    }
+
+    fn positional(&mut self, what: &str) -> Result<String, String> {
+        match self.next() {
+            Some(arg) if arg.starts_with("--") => Err(format!("未知参数: {arg}")),
+            Some(arg) => Ok(arg),
+            None => Err(format!("缺少{what}")),
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self.0.first() {
+            Some(arg) => Err(format!("未知参数: {arg}")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 执行管理子命令
+pub async fn run(command: Command, cfg: &Config) -> anyhow::Result<()> {
+    match command {
+        Command::Serve | Command::OpenApi { .. } => unreachable!("由 main 处理"),
+        Command::Migrate { status_only } => db::migrate(cfg, status_only).await,
+        Command::User(cmd) => user::run(&open(cfg).await?, cmd).await,
+        Command::Article(cmd) => article::run(&open(cfg).await?, cmd).await,
+        Command::Db(cmd) => db::run(cfg, cmd).await,
+        Command::Export(cmd) => export::run(&open(cfg).await?, cfg, cmd).await,
+        Command::TokenRevoke { id, user } => token::revoke(&open(cfg).await?, id, user).await,
+    }
+}
+
+/// 打开数据库并确保迁移已执行，与 `serve` 启动时一致
+async fn open(cfg: &Config) -> anyhow::Result<SqlitePool> {
+    let pool = new_pool(&cfg.database).await?;
+    run_migrations(&pool).await?;
+    Ok(pool)
+}
+
+/// 记录命令行执行的特权操作
+async fn audit(
+    conn: &mut SqliteConnection,
+    action: &str,
+    target_type: &str,
+    target_id: Option<&str>,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+) -> anyhow::Result<()> {
+    insert_audit(
+        conn,
+        NewAudit {
+            actor_id: None,
+            actor_name: Some("cli"),
+            action,
+            target_type,
+            target_id,
+            before,
+            after,
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// 未通过参数提供时，从标准输入读取一行作为密码
+fn read_password(given: Option<String>) -> anyhow::Result<String> {
+    let password = match given {
+        Some(p) => p,
+        None => {
+            eprint!("密码: ");
+            std::io::stderr().flush()?;
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line)?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    anyhow::ensure!(password.len() >= 6, "密码长度至少 6 位");
+    Ok(password)
+}
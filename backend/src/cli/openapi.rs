@@ -0,0 +1,33 @@
+//! `backend openapi ...`：输出 OpenAPI 文档，或检查仓库中的文档是否与代码一致
+
+use crate::routes::openapi::spec_json;
+use anyhow::{Context, bail};
+use std::path::Path;
+
+pub fn run(out: Option<&Path>, check: Option<&Path>) -> anyhow::Result<()> {
+    let spec = spec_json();
+
+    if let Some(file) = check {
+        let current = std::fs::read_to_string(file)
+            .with_context(|| format!("读取 {} 失败", file.display()))?;
+        // 兼容 Windows 检出时转换的换行
+        if current.replace("\r\n", "\n") != spec {
+            bail!(
+                "{} 与代码不一致，请运行 `cargo run -- openapi --out {}` 重新生成",
+                file.display(),
+                file.display()
+            );
+        }
+        println!("{} 与代码一致", file.display());
+        return Ok(());
+    }
+
+    match out {
+        Some(file) => {
+            std::fs::write(file, &spec).with_context(|| format!("写入 {} 失败", file.display()))?;
+            println!("已写入 {}", file.display());
+        }
+        None => print!("{spec}"),
+    }
+    Ok(())
+}
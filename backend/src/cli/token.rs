@@ -0,0 +1,45 @@
+//! `backend token revoke ...`：撤销个人访问令牌
+
+use super::audit;
+use crate::models::api_token::{find_api_token_by_id, revoke_api_token, revoke_api_tokens_by_user};
+use crate::models::user::find_user_by_username;
+use crate::routes::audit::snapshot;
+use anyhow::bail;
+use sqlx::SqlitePool;
+
+pub async fn revoke(
+    pool: &SqlitePool,
+    id: Option<String>,
+    username: Option<String>,
+) -> anyhow::Result<()> {
+    if let Some(id) = id {
+        let Some(token) = find_api_token_by_id(pool, &id).await? else {
+            bail!("令牌 {id} 不存在");
+        };
+        let mut tx = pool.begin().await?;
+        revoke_api_token(&mut tx, &id).await?;
+        audit(
+            &mut tx,
+            "token.revoke",
+            "token",
+            Some(&id),
+            snapshot(&token),
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+        println!("已撤销令牌 {} ({})", token.name, token.token_prefix);
+    }
+
+    if let Some(username) = username {
+        let Some(user) = find_user_by_username(pool, &username).await? else {
+            bail!("用户 {username} 不存在");
+        };
+        let mut tx = pool.begin().await?;
+        revoke_api_tokens_by_user(&mut tx, &user.id).await?;
+        audit(&mut tx, "token.revoke", "user", Some(&user.id), None, None).await?;
+        tx.commit().await?;
+        println!("已撤销用户 {username} 的全部令牌");
+    }
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! `backend user ...`：创建用户、列出用户、修改身份、重置密码
+
+use super::{UserCommand, audit, read_password};
+use crate::auth::hash_password;
+use crate::models::user::{
+    NewUser, User, UserPublic, edit_user_account, find_user_by_id, find_user_by_username,
+    insert_common_user, list_users,
+};
+use crate::routes::audit::snapshot;
+use crate::routes::users::AdminEditAccountPayload;
+use anyhow::{bail, ensure};
+use sqlx::SqlitePool;
+
+const ROLES: &[&str] = &["admin", "user", "visitor"];
+
+pub async fn run(pool: &SqlitePool, cmd: UserCommand) -> anyhow::Result<()> {
+    match cmd {
+        UserCommand::Create {
+            username,
+            role,
+            password,
+        } => {
+            check_role(&role)?;
+            ensure!(!username.trim().is_empty(), "用户名不能为空");
+            if find_user_by_username(pool, &username).await?.is_some() {
+                bail!("用户 {username} 已存在");
+            }
+
+            let password = hash_password(&read_password(password)?)?;
+            let mut tx = pool.begin().await?;
+            let user = insert_common_user(
+                &mut *tx,
+                &NewUser {
+                    username,
+                    password,
+                    identity: role,
+                },
+            )
+            .await?;
+            let user = UserPublic::from(user);
+            audit(
+                &mut tx,
+                "user.create",
+                "user",
+                Some(&user.id),
+                None,
+                snapshot(&user),
+            )
+            .await?;
+            tx.commit().await?;
+            println!(
+                "已创建用户 {}（{}）id = {}",
+                user.username, user.identity, user.id
+            );
+        }
+        UserCommand::List { limit } => {
+            println!("{:<38} {:<20} {:<8} super", "id", "username", "identity");
+            for u in list_users(pool, limit).await? {
+                println!(
+                    "{:<38} {:<20} {:<8} {}",
+                    u.id,
+                    u.username,
+                    u.identity,
+                    if u.is_super_admin { "yes" } else { "" }
+                );
+            }
+        }
+        UserCommand::SetRole { username, role } => {
+            check_role(&role)?;
+            let user = find(pool, &username).await?;
+            ensure!(
+                !user.is_super_admin || role == "admin",
+                "超级管理员不可降级"
+            );
+            update(pool, user, None, Some(role)).await?;
+            println!("已更新用户 {username} 的身份");
+        }
+        UserCommand::ResetPassword { username, password } => {
+            let user = find(pool, &username).await?;
+            let password = hash_password(&read_password(password)?)?;
+            update(pool, user, Some(password), None).await?;
+            println!("已重置用户 {username} 的密码");
+        }
+    }
+    Ok(())
+}
+
+fn check_role(role: &str) -> anyhow::Result<()> {
+    ensure!(
+        ROLES.contains(&role),
+        "身份应为 {} 之一: {role}",
+        ROLES.join(" / ")
+    );
+    Ok(())
+}
+
+async fn find(pool: &SqlitePool, username: &str) -> anyhow::Result<User> {
+    match find_user_by_username(pool, username).await? {
+        Some(user) => Ok(user),
+        None => bail!("用户 {username} 不存在"),
+    }
+}
+
+/// 修改密码或身份，审计快照不含密码哈希
+async fn update(
+    pool: &SqlitePool,
+    user: User,
+    password: Option<String>,
+    identity: Option<String>,
+) -> anyhow::Result<()> {
+    let id = user.id.clone();
+    let password_changed = password.is_some();
+    let mut tx = pool.begin().await?;
+    edit_user_account(
+        &mut tx,
+        AdminEditAccountPayload {
+            current_token: None,
+            edited_id: id.clone(),
+            edited_username: None,
+            edited_password: password,
+            edited_identity: identity,
+        },
+    )
+    .await?;
+
+    let after = find_user_by_id(&mut *tx, id.clone())
+        .await?
+        .map(UserPublic::from)
+        .and_then(|u| snapshot(&u))
+        .map(|mut v| {
+            v["password_changed"] = password_changed.into();
+            v
+        });
+    audit(
+        &mut tx,
+        "user.update",
+        "user",
+        Some(&id),
+        snapshot(&UserPublic::from(user)),
+        after,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
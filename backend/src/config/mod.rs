@@ -0,0 +1,818 @@
+//! 统一配置中心：分层加载，按优先级从低到高覆盖
+//! 内置默认值 → TOML 配置文件 → 环境变量 → 命令行参数
+//! - 配置按用途分为 server / database / auth / mail / storage / feeds / log 几个小节
+//! - 加载后统一校验，所有错误在启动时一次性报告，不再静默回退到默认值
+//! - `--print-config` 输出脱敏后的最终配置，`Debug` 同样隐去密钥；部分小节支持热更新（见 `reload`）
+
+pub mod reload;
+mod sources;
+
+pub use sources::ConfigLoader;
+
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// 示例配置中的 JWT 密钥，生产环境禁止使用
+pub const DEFAULT_JWT_SECRET: &str = "please-change-me";
+
+/// 脱敏后显示的占位符
+const REDACTED: &str = "***";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub mail: MailConfig,
+    pub storage: StorageConfig,
+    pub feeds: FeedsConfig,
+    pub log: LogConfig,
+}
+
+/// 监听地址与 HTTP 层配置（修改需重启，限流除外）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub http: HttpConfig,
+    /// 可热更新
+    pub rate_limit: RateLimitConfig,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 3000,
+            http: HttpConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+        }
+    }
+}
+
+/// HTTP 层配置：跨域、安全响应头、请求体大小与超时
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// 允许跨域的来源，`*` 表示任意来源（此时不能携带凭据）
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_allowed_methods: Vec<String>,
+    /// 是否允许跨域请求携带 Cookie / Authorization 等凭据
+    pub cors_allow_credentials: bool,
+    pub content_security_policy: String,
+    /// 由反向代理终止 TLS 时开启，响应附带 HSTS
+    pub behind_tls: bool,
+    pub hsts_max_age: u64,
+    /// 请求体上限（字节）
+    pub max_body_bytes: usize,
+    /// 单个请求的处理超时（秒）
+    pub request_timeout_secs: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            cors_allowed_origins: vec!["*".into()],
+            cors_allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"]
+                .map(String::from)
+                .to_vec(),
+            cors_allow_credentials: false,
+            content_security_policy: "default-src 'none'; frame-ancestors 'none'".into(),
+            behind_tls: false,
+            hsts_max_age: 365 * 24 * 3600,
+            max_body_bytes: 2 * 1024 * 1024,
+            request_timeout_secs: 30,
+        }
+    }
+}
+
+/// 限流配置，每类接口一个令牌桶策略
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// 受信任的反向代理（IP 或网段），只有直连地址属于其中时才读取
+    /// `X-Forwarded-For` / `X-Real-IP`，为空时一律使用直连地址
+    pub trusted_proxies: Vec<ProxyNet>,
+    /// 登录、注册、发表评论等敏感写操作
+    pub strict: RatePolicy,
+    /// 其他写操作
+    pub write: RatePolicy,
+    /// GET / HEAD 读操作
+    pub read: RatePolicy,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            trusted_proxies: Vec::new(),
+            strict: RatePolicy {
+                requests: 10,
+                per_secs: 60,
+            },
+            write: RatePolicy {
+                requests: 60,
+                per_secs: 60,
+            },
+            read: RatePolicy {
+                requests: 300,
+                per_secs: 60,
+            },
+        }
+    }
+}
+
+/// 令牌桶策略：`per_secs` 秒内最多 `requests` 次，桶容量即 `requests`
+/// 配置中写作 `次数/秒数`，如 `strict = "10/60"`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RatePolicy {
+    pub requests: u32,
+    pub per_secs: u64,
+}
+
+impl FromStr for RatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once('/')
+            .and_then(|(n, secs)| {
+                Some(RatePolicy {
+                    requests: n.trim().parse().ok().filter(|n| *n > 0)?,
+                    per_secs: secs.trim().parse().ok().filter(|s| *s > 0)?,
+                })
+            })
+            .ok_or_else(|| format!("限流策略 {s:?} 格式应为 次数/秒数，且均大于 0"))
+    }
+}
+
+impl TryFrom<String> for RatePolicy {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<RatePolicy> for String {
+    fn from(p: RatePolicy) -> Self {
+        format!("{}/{}", p.requests, p.per_secs)
+    }
+}
+
+/// 受信任的代理地址，配置中写作单个 IP 或 CIDR 网段，如 `127.0.0.1`、`10.0.0.0/8`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ProxyNet(pub IpNet);
+
+impl ProxyNet {
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.contains(ip)
+    }
+}
+
+impl FromStr for ProxyNet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        s.parse::<IpNet>()
+            .or_else(|_| s.parse::<IpAddr>().map(IpNet::from))
+            .map(ProxyNet)
+            .map_err(|_| format!("代理地址 {s:?} 应为 IP 地址或 CIDR 网段"))
+    }
+}
+
+impl TryFrom<String> for ProxyNet {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ProxyNet> for String {
+    fn from(p: ProxyNet) -> Self {
+        p.0.to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// 形如 `sqlite://app.db`，相对路径基于当前工作目录
+    pub url: String,
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite://app.db".into(),
+            max_connections: 10,
+        }
+    }
+}
+
+/// JWT、首次设置与第三方登录
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub jwt_secret: String,
+    /// JWT 过期秒数
+    pub jwt_ttl: i64,
+    /// 过期后仍可通过 `/api/v1/auth/refresh` 换取新 token 的秒数，0 为只能刷新未过期的 token；
+    /// 只用于容忍时钟偏差与请求途中过期，过长会让泄露的 token 可以无限续期。可热更新
+    pub jwt_refresh_grace: i64,
+    /// JWT 签名算法：HS256 / EdDSA / RS256
+    pub jwt_algorithm: String,
+    /// EdDSA / RS256 使用的私钥 PEM 文件
+    pub jwt_private_key_file: Option<String>,
+    /// 当前签名密钥的 kid
+    pub jwt_key_id: String,
+    /// 轮换中仍接受的旧公钥：kid → 公钥 PEM 路径
+    pub jwt_previous_keys: BTreeMap<String, String>,
+    pub jwt_issuer: String,
+    pub jwt_audience: String,
+    /// 开发模式：允许使用默认 JWT 密钥启动
+    pub jwt_allow_default_secret: bool,
+    /// 首次启动时用于创建超管的账号（可选，两者都提供才生效）
+    pub admin_username: Option<String>,
+    pub admin_password: Option<String>,
+    /// 已配置的 OIDC 登录提供方
+    pub oidc_providers: Vec<OidcProviderConfig>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: DEFAULT_JWT_SECRET.into(),
+            jwt_ttl: 7 * 24 * 3600,
+            jwt_refresh_grace: 5 * 60,
+            jwt_algorithm: "HS256".into(),
+            jwt_private_key_file: None,
+            jwt_key_id: "default".into(),
+            jwt_previous_keys: BTreeMap::new(),
+            jwt_issuer: "myblog".into(),
+            jwt_audience: "myblog".into(),
+            jwt_allow_default_secret: false,
+            admin_username: None,
+            admin_password: None,
+            oidc_providers: Vec::new(),
+        }
+    }
+}
+
+/// 单个 OIDC 提供方配置
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    /// 为空时使用 `name`
+    #[serde(default)]
+    pub display_name: String,
+    pub issuer: String,
+    pub client_id: String,
+    /// 公共客户端（仅 PKCE）可以不配置
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    /// 提供方回调地址，一般指向前端的 `/oidc/callback` 页面
+    pub redirect_uri: String,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: String,
+}
+
+fn default_oidc_scopes() -> String {
+    "openid profile email".into()
+}
+
+/// 密钥字段在 Debug 输出中只显示是否已配置
+fn secret(value: &Option<String>) -> Option<&str> {
+    value.as_ref().map(|_| REDACTED)
+}
+
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field("jwt_secret", &REDACTED)
+            .field("jwt_ttl", &self.jwt_ttl)
+            .field("jwt_refresh_grace", &self.jwt_refresh_grace)
+            .field("jwt_algorithm", &self.jwt_algorithm)
+            .field("jwt_private_key_file", &self.jwt_private_key_file)
+            .field("jwt_key_id", &self.jwt_key_id)
+            .field("jwt_previous_keys", &self.jwt_previous_keys)
+            .field("jwt_issuer", &self.jwt_issuer)
+            .field("jwt_audience", &self.jwt_audience)
+            .field("jwt_allow_default_secret", &self.jwt_allow_default_secret)
+            .field("admin_username", &self.admin_username)
+            .field("admin_password", &secret(&self.admin_password))
+            .field("oidc_providers", &self.oidc_providers)
+            .finish()
+    }
+}
+
+impl fmt::Debug for OidcProviderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OidcProviderConfig")
+            .field("name", &self.name)
+            .field("display_name", &self.display_name)
+            .field("issuer", &self.issuer)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &secret(&self.client_secret))
+            .field("redirect_uri", &self.redirect_uri)
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
+/// 发信配置（可热更新）
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    pub enabled: bool,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    /// 连接加密方式：starttls / tls / none
+    pub smtp_tls: String,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    /// 发件人，如 `MyBlog <noreply@example.com>`
+    pub from: Option<String>,
+    /// 发送方式：smtp，或 file（写入 `file_dir`，用于本地调试）
+    pub transport: String,
+    pub file_dir: String,
+    /// 后端对外地址，用于生成确认订阅与退订链接
+    pub public_url: String,
+    /// 文章摘要邮件的发送间隔（小时），0 为关闭
+    pub digest_interval_hours: u32,
+}
+
+impl fmt::Debug for MailConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MailConfig")
+            .field("enabled", &self.enabled)
+            .field("smtp_host", &self.smtp_host)
+            .field("smtp_port", &self.smtp_port)
+            .field("smtp_tls", &self.smtp_tls)
+            .field("smtp_username", &self.smtp_username)
+            .field("smtp_password", &secret(&self.smtp_password))
+            .field("from", &self.from)
+            .field("transport", &self.transport)
+            .field("file_dir", &self.file_dir)
+            .field("public_url", &self.public_url)
+            .field("digest_interval_hours", &self.digest_interval_hours)
+            .finish()
+    }
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: None,
+            smtp_port: 587,
+            smtp_tls: "starttls".into(),
+            smtp_username: None,
+            smtp_password: None,
+            from: None,
+            transport: "smtp".into(),
+            file_dir: "mail".into(),
+            public_url: "http://127.0.0.1:3000".into(),
+            digest_interval_hours: 168,
+        }
+    }
+}
+
+/// 本地文件存放位置（修改需重启）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    pub backup_dir: String,
+    /// 保留的备份份数
+    pub backup_keep: u32,
+    /// 定时备份间隔（小时），0 为关闭
+    pub backup_interval_hours: u32,
+    pub export_dir: String,
+    /// 上传的媒体文件，导出归档时一并打包
+    pub media_dir: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backup_dir: "backups".into(),
+            backup_keep: 7,
+            backup_interval_hours: 24,
+            export_dir: "exports".into(),
+            media_dir: "uploads".into(),
+        }
+    }
+}
+
+/// 站点信息与订阅源（可热更新）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedsConfig {
+    pub enabled: bool,
+    pub site_title: String,
+    /// 站点对外地址，用于生成文章链接
+    pub site_url: String,
+    /// 订阅源中的文章条数
+    pub items: u32,
+}
+
+impl Default for FeedsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            site_title: "MyBlog".into(),
+            site_url: "http://127.0.0.1:5173".into(),
+            items: 20,
+        }
+    }
+}
+
+/// 日志（可热更新）
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// `tracing` 过滤指令，同 `RUST_LOG`
+    pub level: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".into(),
+        }
+    }
+}
+
+/// 配置加载失败的原因
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("读取配置文件 {path} 失败")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("解析配置文件 {path} 失败")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+    #[error("配置无效:\n{}", Issues(.0))]
+    Invalid(Vec<String>),
+}
+
+/// 逐行列出校验错误
+struct Issues<'a>(&'a [String]);
+
+impl fmt::Display for Issues<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, issue) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "  - {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// 补全可推导的默认值
+    fn normalize(&mut self) {
+        for p in &mut self.auth.oidc_providers {
+            p.name = p.name.to_lowercase();
+            if p.display_name.is_empty() {
+                p.display_name = p.name.clone();
+            }
+            p.issuer = p.issuer.trim_end_matches('/').to_string();
+        }
+        for origin in &mut self.server.http.cors_allowed_origins {
+            *origin = origin.trim_end_matches('/').to_string();
+        }
+    }
+
+    /// 校验全部配置项，返回所有发现的问题
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut issues = Vec::new();
+        let mut check = |ok: bool, msg: String| {
+            if !ok {
+                issues.push(msg);
+            }
+        };
+
+        let server = &self.server;
+        check(
+            server.host.parse::<IpAddr>().is_ok(),
+            format!("server.host: {:?} 不是合法的 IP 地址", server.host),
+        );
+        check(server.port != 0, "server.port: 不能为 0".into());
+
+        let http = &server.http;
+        check(
+            !http.cors_allowed_origins.is_empty(),
+            "server.http.cors_allowed_origins: 至少需要一项，放行全部请使用 \"*\"".into(),
+        );
+        check(
+            !(http.cors_allow_credentials && http.cors_allowed_origins.iter().any(|o| o == "*")),
+            "server.http.cors_allow_credentials: 允许携带凭据时不能使用 \"*\" 来源".into(),
+        );
+        for origin in http.cors_allowed_origins.iter().filter(|o| *o != "*") {
+            check(
+                origin.starts_with("http://")
+                    || origin.starts_with("https://")
+                    || origin.starts_with("tauri://"),
+                format!("server.http.cors_allowed_origins: {origin:?} 不是合法的来源"),
+            );
+        }
+        for method in &http.cors_allowed_methods {
+            check(
+                http::Method::from_str(&method.to_uppercase()).is_ok(),
+                format!("server.http.cors_allowed_methods: {method:?} 不是合法的方法"),
+            );
+        }
+        check(
+            http::HeaderValue::from_str(&http.content_security_policy).is_ok(),
+            "server.http.content_security_policy: 含有非法字符".into(),
+        );
+        check(
+            http.max_body_bytes > 0,
+            "server.http.max_body_bytes: 必须大于 0".into(),
+        );
+        check(
+            http.request_timeout_secs > 0,
+            "server.http.request_timeout_secs: 必须大于 0".into(),
+        );
+
+        let db = &self.database;
+        check(
+            db.url.starts_with("sqlite:"),
+            format!("database.url: {:?} 应以 sqlite:// 开头", db.url),
+        );
+        check(
+            db.max_connections > 0,
+            "database.max_connections: 必须大于 0".into(),
+        );
+
+        let auth = &self.auth;
+        check(auth.jwt_ttl > 0, "auth.jwt_ttl: 必须大于 0".into());
+        check(
+            auth.jwt_refresh_grace >= 0,
+            "auth.jwt_refresh_grace: 不能为负数".into(),
+        );
+        check(
+            matches!(auth.jwt_algorithm.as_str(), "HS256" | "EdDSA" | "RS256"),
+            format!(
+                "auth.jwt_algorithm: 不支持 {:?}（可选 HS256 / EdDSA / RS256）",
+                auth.jwt_algorithm
+            ),
+        );
+        check(
+            auth.jwt_algorithm == "HS256" || auth.jwt_private_key_file.is_some(),
+            "auth.jwt_private_key_file: 使用 EdDSA / RS256 时必须配置".into(),
+        );
+        check(
+            auth.jwt_algorithm != "HS256" || !auth.jwt_secret.is_empty(),
+            "auth.jwt_secret: 不能为空".into(),
+        );
+        check(
+            !auth.jwt_key_id.is_empty(),
+            "auth.jwt_key_id: 不能为空".into(),
+        );
+        check(
+            !auth.jwt_issuer.is_empty() && !auth.jwt_audience.is_empty(),
+            "auth.jwt_issuer / auth.jwt_audience: 不能为空".into(),
+        );
+        check(
+            auth.admin_username.is_some() == auth.admin_password.is_some(),
+            "auth.admin_username / auth.admin_password: 需要同时提供".into(),
+        );
+        for (i, p) in auth.oidc_providers.iter().enumerate() {
+            let at = format!("auth.oidc_providers[{i}]");
+            check(!p.name.is_empty(), format!("{at}.name: 不能为空"));
+            check(
+                auth.oidc_providers[..i].iter().all(|q| q.name != p.name),
+                format!("{at}.name: 提供方 {:?} 重复", p.name),
+            );
+            check(
+                p.issuer.starts_with("http://") || p.issuer.starts_with("https://"),
+                format!("{at}.issuer: {:?} 不是合法的 URL", p.issuer),
+            );
+            check(!p.client_id.is_empty(), format!("{at}.client_id: 不能为空"));
+            check(
+                !p.redirect_uri.is_empty(),
+                format!("{at}.redirect_uri: 不能为空"),
+            );
+        }
+
+        let mail = &self.mail;
+        check(
+            matches!(mail.transport.as_str(), "smtp" | "file"),
+            format!(
+                "mail.transport: 不支持 {:?}（可选 smtp / file）",
+                mail.transport
+            ),
+        );
+        check(
+            matches!(mail.smtp_tls.as_str(), "starttls" | "tls" | "none"),
+            format!(
+                "mail.smtp_tls: 不支持 {:?}（可选 starttls / tls / none）",
+                mail.smtp_tls
+            ),
+        );
+        check(
+            mail.public_url.starts_with("http://") || mail.public_url.starts_with("https://"),
+            format!("mail.public_url: {:?} 不是合法的 URL", mail.public_url),
+        );
+        if mail.enabled {
+            check(
+                mail.transport != "smtp" || mail.smtp_host.is_some(),
+                "mail.smtp_host: 使用 SMTP 发信时必须配置".into(),
+            );
+            check(
+                mail.transport != "file" || !mail.file_dir.is_empty(),
+                "mail.file_dir: 使用 file 发信时不能为空".into(),
+            );
+            check(mail.from.is_some(), "mail.from: 启用发信时必须配置".into());
+        }
+
+        let storage = &self.storage;
+        check(
+            !storage.backup_dir.is_empty() && !storage.export_dir.is_empty(),
+            "storage.backup_dir / storage.export_dir: 不能为空".into(),
+        );
+        check(
+            storage.backup_keep > 0,
+            "storage.backup_keep: 必须大于 0".into(),
+        );
+
+        let feeds = &self.feeds;
+        check(
+            feeds.site_url.starts_with("http://") || feeds.site_url.starts_with("https://"),
+            format!("feeds.site_url: {:?} 不是合法的 URL", feeds.site_url),
+        );
+        check(
+            (1..=100).contains(&feeds.items),
+            "feeds.items: 应在 1..=100 之间".into(),
+        );
+
+        check(
+            tracing_subscriber::EnvFilter::try_new(&self.log.level).is_ok(),
+            format!("log.level: {:?} 不是合法的日志过滤指令", self.log.level),
+        );
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(issues))
+        }
+    }
+
+    /// 隐去密钥与密码，供 `--print-config` 使用
+    pub fn redacted(&self) -> Self {
+        let mut cfg = self.clone();
+        let hide = |v: &mut Option<String>| {
+            if v.is_some() {
+                *v = Some(REDACTED.into());
+            }
+        };
+
+        if !cfg.auth.jwt_secret.is_empty() {
+            cfg.auth.jwt_secret = REDACTED.into();
+        }
+        hide(&mut cfg.auth.admin_password);
+        for p in &mut cfg.auth.oidc_providers {
+            hide(&mut p.client_secret);
+        }
+        hide(&mut cfg.mail.smtp_password);
+        cfg
+    }
+
+    /// 以 TOML 格式输出，格式与配置文件一致
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_else(|e| format!("# 序列化失败: {e}\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 配置了全部密钥字段的配置
+    fn with_secrets() -> Config {
+        let mut cfg = Config::default();
+        cfg.auth.jwt_secret = "jwt-secret-value".into();
+        cfg.auth.admin_username = Some("admin".into());
+        cfg.auth.admin_password = Some("admin-password-value".into());
+        cfg.auth.oidc_providers.push(OidcProviderConfig {
+            name: "mock".into(),
+            display_name: "Mock".into(),
+            issuer: "http://127.0.0.1:9000".into(),
+            client_id: "blog".into(),
+            client_secret: Some("client-secret-value".into()),
+            redirect_uri: "http://127.0.0.1:5173/oidc/callback".into(),
+            scopes: default_oidc_scopes(),
+        });
+        cfg.mail.smtp_password = Some("smtp-password-value".into());
+        cfg
+    }
+
+    const SECRETS: [&str; 4] = [
+        "jwt-secret-value",
+        "admin-password-value",
+        "client-secret-value",
+        "smtp-password-value",
+    ];
+
+    #[test]
+    fn default_config_is_valid() {
+        Config::default().validate().unwrap();
+        with_secrets().validate().unwrap();
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let mut cfg = Config::default();
+        cfg.server.host = "localhost:80".into();
+        cfg.server.port = 0;
+        cfg.database.url = "postgres://db".into();
+        cfg.auth.jwt_ttl = 0;
+        cfg.auth.admin_username = Some("admin".into());
+        cfg.feeds.items = 0;
+
+        let Err(ConfigError::Invalid(issues)) = cfg.validate() else {
+            panic!("应校验失败");
+        };
+        let fields: Vec<_> = issues
+            .iter()
+            .map(|issue| issue.split(':').next().unwrap())
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "server.host",
+                "server.port",
+                "database.url",
+                "auth.jwt_ttl",
+                "auth.admin_username / auth.admin_password",
+                "feeds.items",
+            ]
+        );
+
+        // 错误信息逐行列出全部问题
+        let message = ConfigError::Invalid(issues).to_string();
+        assert_eq!(message.lines().count(), 7, "{message}");
+    }
+
+    #[test]
+    fn debug_output_hides_secrets() {
+        let cfg = with_secrets();
+        for output in [
+            format!("{cfg:?}"),
+            format!("{cfg:#?}"),
+            format!("{:?}", cfg.auth),
+            format!("{:?}", cfg.mail),
+        ] {
+            for secret in SECRETS {
+                assert!(!output.contains(secret), "Debug 泄露了 {secret}: {output}");
+            }
+            assert!(output.contains(REDACTED));
+        }
+        // 非密钥字段照常显示，未配置的密钥显示为 None
+        let debug = format!("{:?}", Config::default());
+        assert!(debug.contains("smtp_password: None"), "{debug}");
+        assert!(debug.contains("jwt_issuer: \"myblog\""), "{debug}");
+    }
+
+    #[test]
+    fn printed_config_hides_secrets() {
+        let cfg = with_secrets();
+        let printed = cfg.redacted().to_toml();
+        for secret in SECRETS {
+            assert!(!printed.contains(secret), "--print-config 泄露了 {secret}");
+        }
+
+        // 脱敏后仍是可以重新加载的配置文件，只是密钥被替换
+        let reloaded: Config = toml::from_str(&printed).unwrap();
+        assert_eq!(reloaded.auth.jwt_secret, REDACTED);
+        assert_eq!(reloaded.auth.admin_password.as_deref(), Some(REDACTED));
+        assert_eq!(
+            reloaded.auth.oidc_providers[0].client_secret.as_deref(),
+            Some(REDACTED)
+        );
+        assert_eq!(reloaded.mail.smtp_password.as_deref(), Some(REDACTED));
+        assert_eq!(reloaded.auth.oidc_providers[0].client_id, "blog");
+        assert_eq!(reloaded.server, cfg.server);
+    }
+}
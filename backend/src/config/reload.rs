@@ -0,0 +1,145 @@
+//! 配置热更新：
+//! - 定期检查配置文件的修改时间；Unix 下也可发送 SIGHUP 立即重新加载
+//! - 新配置校验失败时记录错误并保留当前配置
+//! - 限流、日志、token 刷新宽限期、发信、站点订阅即时生效；其余小节的修改提示需要重启
+
+use super::{AuthConfig, Config, ConfigLoader, ServerConfig};
+use crate::db::AppState;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::time::Interval;
+use tracing_subscriber::{EnvFilter, Registry, reload};
+
+/// 日志过滤器的重载句柄
+pub type LogHandle = reload::Handle<EnvFilter, Registry>;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 启动后台任务，没有配置文件时不启用
+pub fn spawn(loader: ConfigLoader, state: Arc<AppState>, log: LogHandle) {
+    let Some(path) = loader.file().map(Path::to_path_buf) else {
+        return;
+    };
+    tracing::info!("配置热更新已启用: {}", path.display());
+
+    tokio::spawn(async move {
+        let mut modified = mtime(&path);
+        let mut trigger = Trigger::new();
+
+        loop {
+            let forced = trigger.next().await;
+            let current = mtime(&path);
+            if !forced && current == modified {
+                continue;
+            }
+            modified = current;
+
+            match loader.load() {
+                Ok(cfg) => apply(&state, &log, cfg),
+                Err(e) => tracing::error!(
+                    "配置重新加载失败，继续使用当前配置: {:#}",
+                    anyhow::Error::from(e)
+                ),
+            }
+        }
+    });
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// 重新加载的触发源：定时轮询，Unix 下加上 SIGHUP
+struct Trigger {
+    ticker: Interval,
+    #[cfg(unix)]
+    hangup: Option<tokio::signal::unix::Signal>,
+}
+
+impl Trigger {
+    fn new() -> Self {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        Self {
+            ticker,
+            #[cfg(unix)]
+            hangup: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok(),
+        }
+    }
+
+    /// 返回 true 表示收到信号，需要无条件重新加载
+    async fn next(&mut self) -> bool {
+        #[cfg(unix)]
+        if let Some(hangup) = &mut self.hangup {
+            return tokio::select! {
+                _ = self.ticker.tick() => false,
+                _ = hangup.recv() => true,
+            };
+        }
+        self.ticker.tick().await;
+        false
+    }
+}
+
+/// 应用可热更新的部分，`live_cfg` 中只保存已生效的值
+fn apply(state: &AppState, log: &LogHandle, new: Config) {
+    let mut live = state.live_cfg.write().unwrap();
+
+    let restart: Vec<&str> = [
+        (
+            "server",
+            live.server.host != new.server.host
+                || live.server.port != new.server.port
+                || live.server.http != new.server.http,
+        ),
+        ("database", live.database != new.database),
+        (
+            "auth",
+            AuthConfig {
+                jwt_refresh_grace: new.auth.jwt_refresh_grace,
+                ..live.auth.clone()
+            } != new.auth,
+        ),
+        ("storage", live.storage != new.storage),
+    ]
+    .into_iter()
+    .filter_map(|(section, changed)| changed.then_some(section))
+    .collect();
+    if !restart.is_empty() {
+        tracing::warn!("{} 的修改需要重启后生效", restart.join(", "));
+    }
+
+    if live.server.rate_limit != new.server.rate_limit {
+        state.rate_limiter.set_config(new.server.rate_limit.clone());
+        tracing::info!("限流配置已更新");
+    }
+    if live.log != new.log {
+        match log.reload(EnvFilter::new(&new.log.level)) {
+            Ok(()) => tracing::info!("日志级别已更新为 {}", new.log.level),
+            Err(e) => tracing::error!("更新日志级别失败: {}", e),
+        }
+    }
+    if live.mail != new.mail || live.feeds != new.feeds {
+        tracing::info!("发信 / 站点订阅配置已更新");
+    }
+
+    if live.auth.jwt_refresh_grace != new.auth.jwt_refresh_grace {
+        tracing::info!("token 刷新宽限期已更新为 {} 秒", new.auth.jwt_refresh_grace);
+    }
+
+    *live = Config {
+        server: ServerConfig {
+            rate_limit: new.server.rate_limit,
+            ..live.server.clone()
+        },
+        auth: AuthConfig {
+            jwt_refresh_grace: new.auth.jwt_refresh_grace,
+            ..live.auth.clone()
+        },
+        mail: new.mail,
+        feeds: new.feeds,
+        log: new.log,
+        ..live.clone()
+    };
+}
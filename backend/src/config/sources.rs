@@ -0,0 +1,405 @@
+//! 配置来源与合并：
+//! - 配置文件：`--config` > `CONFIG_FILE` > 当前目录下的 `config.toml`（不存在则跳过）
+//! - 环境变量：沿用原有变量名，映射到配置路径
+//! - 命令行：`--host` / `--port` / `--database-url` 与通用的 `--set 小节.键=值`
+//!
+//! 各层都先写入同一棵 TOML 表，最后一次性反序列化为 `Config`，类型错误统一报告
+
+use super::{Config, ConfigError};
+use crate::cli::Cli;
+use std::env;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// 默认配置文件名
+const DEFAULT_FILE: &str = "config.toml";
+
+/// 环境变量值的解析方式
+#[derive(Clone, Copy)]
+enum Kind {
+    Str,
+    Int,
+    Bool,
+    /// 逗号分隔的列表
+    List,
+    /// 逗号分隔的 `键=值` 列表
+    Map,
+}
+
+/// 环境变量 → 配置路径
+const ENV_VARS: &[(&str, &str, Kind)] = &[
+    ("HOST", "server.host", Kind::Str),
+    ("PORT", "server.port", Kind::Int),
+    (
+        "CORS_ALLOWED_ORIGINS",
+        "server.http.cors_allowed_origins",
+        Kind::List,
+    ),
+    (
+        "CORS_ALLOWED_METHODS",
+        "server.http.cors_allowed_methods",
+        Kind::List,
+    ),
+    (
+        "CORS_ALLOW_CREDENTIALS",
+        "server.http.cors_allow_credentials",
+        Kind::Bool,
+    ),
+    (
+        "CONTENT_SECURITY_POLICY",
+        "server.http.content_security_policy",
+        Kind::Str,
+    ),
+    ("BEHIND_TLS", "server.http.behind_tls", Kind::Bool),
+    ("HSTS_MAX_AGE", "server.http.hsts_max_age", Kind::Int),
+    ("MAX_BODY_BYTES", "server.http.max_body_bytes", Kind::Int),
+    (
+        "REQUEST_TIMEOUT_SECS",
+        "server.http.request_timeout_secs",
+        Kind::Int,
+    ),
+    (
+        "RATE_LIMIT_ENABLED",
+        "server.rate_limit.enabled",
+        Kind::Bool,
+    ),
+    (
+        "RATE_LIMIT_TRUSTED_PROXIES",
+        "server.rate_limit.trusted_proxies",
+        Kind::List,
+    ),
+    ("RATE_LIMIT_STRICT", "server.rate_limit.strict", Kind::Str),
+    ("RATE_LIMIT_WRITE", "server.rate_limit.write", Kind::Str),
+    ("RATE_LIMIT_READ", "server.rate_limit.read", Kind::Str),
+    ("DATABASE_URL", "database.url", Kind::Str),
+    (
+        "DATABASE_MAX_CONNECTIONS",
+        "database.max_connections",
+        Kind::Int,
+    ),
+    ("JWT_SECRET", "auth.jwt_secret", Kind::Str),
+    ("JWT_TTL", "auth.jwt_ttl", Kind::Int),
+    ("JWT_REFRESH_GRACE", "auth.jwt_refresh_grace", Kind::Int),
+    ("JWT_ALGORITHM", "auth.jwt_algorithm", Kind::Str),
+    (
+        "JWT_PRIVATE_KEY_FILE",
+        "auth.jwt_private_key_file",
+        Kind::Str,
+    ),
+    ("JWT_KEY_ID", "auth.jwt_key_id", Kind::Str),
+    ("JWT_PREVIOUS_KEYS", "auth.jwt_previous_keys", Kind::Map),
+    ("JWT_ISSUER", "auth.jwt_issuer", Kind::Str),
+    ("JWT_AUDIENCE", "auth.jwt_audience", Kind::Str),
+    (
+        "JWT_ALLOW_DEFAULT_SECRET",
+        "auth.jwt_allow_default_secret",
+        Kind::Bool,
+    ),
+    ("ADMIN_USERNAME", "auth.admin_username", Kind::Str),
+    ("ADMIN_PASSWORD", "auth.admin_password", Kind::Str),
+    ("MAIL_ENABLED", "mail.enabled", Kind::Bool),
+    ("SMTP_HOST", "mail.smtp_host", Kind::Str),
+    ("SMTP_PORT", "mail.smtp_port", Kind::Int),
+    ("SMTP_TLS", "mail.smtp_tls", Kind::Str),
+    ("SMTP_USERNAME", "mail.smtp_username", Kind::Str),
+    ("SMTP_PASSWORD", "mail.smtp_password", Kind::Str),
+    ("MAIL_FROM", "mail.from", Kind::Str),
+    ("MAIL_TRANSPORT", "mail.transport", Kind::Str),
+    ("MAIL_FILE_DIR", "mail.file_dir", Kind::Str),
+    ("MAIL_PUBLIC_URL", "mail.public_url", Kind::Str),
+    (
+        "MAIL_DIGEST_INTERVAL_HOURS",
+        "mail.digest_interval_hours",
+        Kind::Int,
+    ),
+    ("BACKUP_DIR", "storage.backup_dir", Kind::Str),
+    ("BACKUP_KEEP", "storage.backup_keep", Kind::Int),
+    (
+        "BACKUP_INTERVAL_HOURS",
+        "storage.backup_interval_hours",
+        Kind::Int,
+    ),
+    ("EXPORT_DIR", "storage.export_dir", Kind::Str),
+    ("MEDIA_DIR", "storage.media_dir", Kind::Str),
+    ("FEEDS_ENABLED", "feeds.enabled", Kind::Bool),
+    ("SITE_TITLE", "feeds.site_title", Kind::Str),
+    ("SITE_URL", "feeds.site_url", Kind::Str),
+    ("FEEDS_ITEMS", "feeds.items", Kind::Int),
+    ("RUST_LOG", "log.level", Kind::Str),
+];
+
+/// 记住配置来源，热更新时按同样的方式重新加载
+#[derive(Clone, Debug)]
+pub struct ConfigLoader {
+    file: Option<PathBuf>,
+    /// 显式指定的文件必须存在
+    required: bool,
+    overrides: Vec<(String, String)>,
+}
+
+impl ConfigLoader {
+    pub fn new(cli: &Cli) -> Self {
+        let (file, required) = match (&cli.config, env::var("CONFIG_FILE")) {
+            (Some(path), _) => (Some(path.clone()), true),
+            (None, Ok(path)) if !path.is_empty() => (Some(PathBuf::from(path)), true),
+            _ => {
+                let path = PathBuf::from(DEFAULT_FILE);
+                (path.exists().then_some(path), false)
+            }
+        };
+
+        Self {
+            file,
+            required,
+            overrides: cli.overrides.clone(),
+        }
+    }
+
+    /// 实际使用的配置文件
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    /// 依次合并各层并校验
+    pub fn load(&self) -> Result<Config, ConfigError> {
+        let mut table = self.read_file()?;
+        let mut issues = Vec::new();
+
+        apply_env(&mut table, &mut issues);
+        for (key, raw) in &self.overrides {
+            set_path(&mut table, key, literal(raw));
+        }
+        if !issues.is_empty() {
+            return Err(ConfigError::Invalid(issues));
+        }
+
+        // 先转回文本再反序列化，类型错误才能定位到具体的配置项
+        let text = toml::to_string(&table).unwrap_or_default();
+        let mut cfg: Config =
+            toml::from_str(&text).map_err(|e| ConfigError::Invalid(vec![describe(&text, &e)]))?;
+        cfg.normalize();
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    fn read_file(&self) -> Result<Table, ConfigError> {
+        let Some(path) = &self.file else {
+            return Ok(Table::new());
+        };
+        if !path.exists() && !self.required {
+            return Ok(Table::new());
+        }
+
+        let display = path.display().to_string();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: display.clone(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ConfigError::Parse {
+            path: display,
+            source,
+        })
+    }
+}
+
+/// 环境变量层，空值视为未设置
+fn apply_env(table: &mut Table, issues: &mut Vec<String>) {
+    for &(var, path, kind) in ENV_VARS {
+        let Ok(raw) = env::var(var) else { continue };
+        if raw.is_empty() {
+            continue;
+        }
+        match parse_env(&raw, kind) {
+            Ok(value) => set_path(table, path, value),
+            Err(expected) => issues.push(format!("{var}: {raw:?} 应为{expected}")),
+        }
+    }
+
+    if let Ok(names) = env::var("OIDC_PROVIDERS") {
+        let providers = names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| Value::Table(oidc_provider_from_env(name)))
+            .collect();
+        set_path(table, "auth.oidc_providers", Value::Array(providers));
+    }
+}
+
+/// 读取 `OIDC_<NAME>_*`，缺失的必填项留给校验阶段报告
+fn oidc_provider_from_env(name: &str) -> Table {
+    let prefix = format!("OIDC_{}_", name.to_uppercase());
+    let mut provider = Table::new();
+    provider.insert("name".into(), Value::String(name.into()));
+    for key in [
+        "display_name",
+        "issuer",
+        "client_id",
+        "client_secret",
+        "redirect_uri",
+        "scopes",
+    ] {
+        match env::var(format!("{prefix}{}", key.to_uppercase())) {
+            Ok(v) if !v.is_empty() => {
+                provider.insert(key.into(), Value::String(v));
+            }
+            _ => {}
+        }
+    }
+    for required in ["issuer", "client_id", "redirect_uri"] {
+        provider
+            .entry(required)
+            .or_insert_with(|| Value::String(String::new()));
+    }
+    provider
+}
+
+fn parse_env(raw: &str, kind: Kind) -> Result<Value, &'static str> {
+    let items = || raw.split(',').map(str::trim).filter(|s| !s.is_empty());
+    match kind {
+        Kind::Str => Ok(Value::String(raw.into())),
+        Kind::Int => raw.trim().parse().map(Value::Integer).map_err(|_| "整数"),
+        Kind::Bool => match raw.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Ok(Value::Boolean(true)),
+            "0" | "false" | "no" | "off" => Ok(Value::Boolean(false)),
+            _ => Err("布尔值（true / false）"),
+        },
+        Kind::List => Ok(Value::Array(
+            items().map(|s| Value::String(s.into())).collect(),
+        )),
+        Kind::Map => items()
+            .map(|item| match item.split_once('=') {
+                Some((k, v)) if !k.trim().is_empty() && !v.trim().is_empty() => {
+                    Ok((k.trim().to_string(), Value::String(v.trim().into())))
+                }
+                _ => Err("逗号分隔的 键=值 列表"),
+            })
+            .collect::<Result<Table, _>>()
+            .map(Value::Table),
+    }
+}
+
+/// 把反序列化错误的位置换算为 `小节.键` 路径
+fn describe(text: &str, err: &toml::de::Error) -> String {
+    let Some(span) = err.span() else {
+        return err.message().to_string();
+    };
+
+    let before = &text[..span.start.min(text.len())];
+    let line = before.rsplit('\n').next().unwrap_or_default();
+    let key = line.split('=').next().unwrap_or_default().trim();
+    let section = before
+        .lines()
+        .rev()
+        .find_map(|l| l.trim().strip_prefix('[')?.strip_suffix(']'))
+        .map(|s| s.trim_matches(['[', ']']));
+
+    // 未知字段的位置落在键本身上，此时只能定位到所在小节
+    match (section, key.is_empty()) {
+        (Some(section), true) => format!("{section}: {}", err.message()),
+        (Some(section), false) => format!("{section}.{key}: {}", err.message()),
+        (None, false) => format!("{key}: {}", err.message()),
+        (None, true) => err.message().to_string(),
+    }
+}
+
+/// 命令行值按 TOML 字面量解析（数字、布尔、数组），否则视为字符串
+fn literal(raw: &str) -> Value {
+    toml::from_str::<Table>(&format!("v = {raw}"))
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| Value::String(raw.into()))
+}
+
+/// 按 `a.b.c` 路径写入，中间层不存在时自动创建
+fn set_path(table: &mut Table, path: &str, value: Value) {
+    let mut current = table;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            current.insert(part.into(), value);
+            return;
+        }
+        let entry = current
+            .entry(part)
+            .or_insert_with(|| Value::Table(Table::new()));
+        if !entry.is_table() {
+            *entry = Value::Table(Table::new());
+        }
+        current = entry.as_table_mut().expect("just ensured table");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let path = env::temp_dir().join(format!("blog-config-test-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+[server]
+host = "0.0.0.0"
+port = 1000
+
+[feeds]
+site_title = "From file"
+items = 5
+"#,
+        )
+        .unwrap();
+
+        // 只有本测试读写这些环境变量
+        unsafe {
+            env::set_var("PORT", "2000");
+            env::set_var("SITE_TITLE", "From env");
+            env::remove_var("HOST");
+            env::remove_var("FEEDS_ITEMS");
+        }
+        let loader = ConfigLoader {
+            file: Some(path.clone()),
+            required: true,
+            overrides: vec![("server.port".into(), "3000".into())],
+        };
+        let cfg = loader.load();
+        unsafe {
+            env::remove_var("PORT");
+            env::remove_var("SITE_TITLE");
+        }
+        std::fs::remove_file(&path).unwrap();
+        let cfg = cfg.unwrap();
+
+        // 命令行 > 环境变量 > 配置文件 > 默认值
+        assert_eq!(cfg.server.port, 3000);
+        assert_eq!(cfg.feeds.site_title, "From env");
+        assert_eq!(cfg.server.host, "0.0.0.0");
+        assert_eq!(cfg.feeds.items, 5);
+        assert_eq!(cfg.storage, crate::config::StorageConfig::default());
+    }
+
+    #[test]
+    fn env_values_are_parsed_by_kind() {
+        let cases = [
+            ("整数", "42", Kind::Int, Ok(Value::Integer(42))),
+            ("非法整数", "4x", Kind::Int, Err("整数")),
+            ("布尔别名", " Yes ", Kind::Bool, Ok(Value::Boolean(true))),
+            (
+                "列表去掉空项",
+                "a, b,,",
+                Kind::List,
+                Ok(Value::Array(vec!["a".into(), "b".into()])),
+            ),
+            (
+                "缺少值的映射",
+                "k=",
+                Kind::Map,
+                Err("逗号分隔的 键=值 列表"),
+            ),
+        ];
+        for (desc, raw, kind, expected) in cases {
+            assert_eq!(parse_env(raw, kind), expected, "{desc}");
+        }
+    }
+}
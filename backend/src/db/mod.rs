@@ -1,41 +1,93 @@
 //! 数据库模块：提供连接池、迁移与全局状态。
 //! 说明：将 `pool` 和 `config` 放入 `AppState`，方便在 handler 与提取器中访问。
 
-use crate::config::Config;
+use crate::auth::keys::JwtKeys;
+use crate::auth::oidc::OidcState;
+use crate::config::{Config, DatabaseConfig};
+use crate::events::{ArticleEvent, EventHub};
+use crate::middleware::rate_limit::{MemoryStore, RateLimiter};
+use crate::models::notification::Notification;
 use anyhow::Ok;
 use sqlx::{
     SqlitePool,
+    migrate::Migrator,
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
 };
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Mutex, Notify};
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: SqlitePool,
+    /// 启动时的配置，修改需要重启的小节（server / database / auth / storage）从这里读取
     pub cfg: Config,
+    /// 热更新后已生效的配置，限流、日志、发信、站点订阅从这里读取
+    pub live_cfg: Arc<RwLock<Config>>,
+    /// JWT 签名与校验密钥
+    pub jwt: Arc<JwtKeys>,
+    /// 首次启动的一次性 setup token，创建超管后清空
+    pub setup_token: Arc<Mutex<Option<String>>>,
+    /// OIDC 登录中尚未完成的授权请求
+    pub oidc: Arc<OidcState>,
+    /// 限流器，默认使用内存计数
+    pub rate_limiter: Arc<RateLimiter>,
+    /// 实时事件广播
+    pub events: Arc<EventHub<ArticleEvent>>,
+    /// 站内通知推送
+    pub notifications: Arc<EventHub<Notification>>,
+    /// 有新的 webhook 投递时唤醒投递任务
+    pub webhook_wakeup: Arc<Notify>,
+    /// 定时任务与手动发送的文章摘要依次进行，避免同一批文章发送两次
+    pub digest_lock: Arc<Mutex<()>>,
 }
 
 impl AppState {
-    pub fn new(pool: SqlitePool, cfg: Config) -> Self {
-        Self { pool, cfg }
+    pub fn new(pool: SqlitePool, cfg: Config, jwt: JwtKeys) -> Self {
+        Self {
+            pool,
+            jwt: Arc::new(jwt),
+            setup_token: Arc::new(Mutex::new(None)),
+            oidc: Arc::new(OidcState::default()),
+            rate_limiter: Arc::new(RateLimiter::new(
+                cfg.server.rate_limit.clone(),
+                Arc::new(MemoryStore::default()),
+            )),
+            events: Arc::new(EventHub::default()),
+            notifications: Arc::new(EventHub::default()),
+            webhook_wakeup: Arc::new(Notify::new()),
+            digest_lock: Arc::new(Mutex::new(())),
+            live_cfg: Arc::new(RwLock::new(cfg.clone())),
+            cfg,
+        }
     }
 }
 
+/// 数据库文件路径，相对路径基于当前工作目录
+pub fn database_path(db: &DatabaseConfig) -> std::io::Result<PathBuf> {
+    // 前缀已在配置校验中检查
+    let db_file = db
+        .url
+        .strip_prefix("sqlite://")
+        .or_else(|| db.url.strip_prefix("sqlite:"))
+        .unwrap_or(&db.url);
+    std::result::Result::Ok(env::current_dir()?.join(db_file))
+}
+
 // 连接数据库
-pub async fn new_pool(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
+pub async fn new_pool(db: &DatabaseConfig) -> Result<SqlitePool, sqlx::Error> {
     // println!("当前工作目录: {:?}", std::env::current_dir()?);
     // panic!("{}", database_url);
     // create_dir(Path::new("test")).unwrap();
     // File::create(format!("{}", database_url))?;
 
-    let db_file = database_url.strip_prefix("sqlite://").unwrap();
-    let db_path = env::current_dir()?.join(db_file);
+    let db_path = database_path(db)?;
 
     // SqliteConnectOptions = 描述“怎么连到某一个数据库”的细粒度参数（文件路径、是否创建、超时、外键开关……）。
     // SqlitePoolOptions = 描述“怎么管理一堆连接”的参数（最大连接数、最小连接数、连接超时、健康检查……）。
     SqlitePoolOptions::new()
-        .max_connections(10)
+        .max_connections(db.max_connections)
         .connect_with(
             SqliteConnectOptions::new()
                 .filename(&db_path)
@@ -44,20 +96,66 @@ pub async fn new_pool(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
         .await
 }
 
-/// 执行迁移（embed 方式，编译期打包）
+/// 迁移脚本（embed 方式，编译期打包）
 /// `./migrations` 目录存放 SQL 脚本
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// 执行迁移与 seeds
 pub async fn run_migrations(pool: &SqlitePool) -> anyhow::Result<()> {
-    sqlx::migrate!("./migrations").run(pool).await?;
+    rename_duplicate_usernames(pool).await?;
+    MIGRATOR.run(pool).await?;
 
     run_seeds(pool, Path::new("./seeds")).await?;
     Ok(())
 }
 
+/// 0011 为用户名加唯一索引，之前的数据库可能已有重名用户，会让这条迁移失败。
+/// 索引建立前先处理：保留管理员（没有管理员时保留最早创建的一个），其余改名为 `用户名-id 前 8 位`，
+/// 由用户或管理员之后自行修改。已执行过的迁移不能再修改，所以放在迁移之外
+async fn rename_duplicate_usernames(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let pending: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'users')
+           AND NOT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'index' AND name = 'idx_users_username')
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+    if !pending {
+        return std::result::Result::Ok(());
+    }
+
+    let renamed = sqlx::query(
+        r#"
+        WITH ranked AS (
+            SELECT
+                id,
+                ROW_NUMBER() OVER (
+                    PARTITION BY username
+                    ORDER BY identity = 'admin' DESC, rowid
+                ) AS n
+            FROM users
+            WHERE username IS NOT NULL
+        )
+        UPDATE users SET username = username || '-' || substr(id, 1, 8)
+        WHERE id IN (SELECT id FROM ranked WHERE n > 1)
+        "#,
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if renamed > 0 {
+        tracing::warn!("建立用户名唯一索引前，已为 {renamed} 个重名用户改名");
+    }
+    std::result::Result::Ok(())
+}
+
 /// 执行 seeds 目录下的所有 .sql 文件
 async fn run_seeds(pool: &SqlitePool, dir: &Path) -> anyhow::Result<()> {
-    // if !dir.is_dir() {
-    //     return Err(anyhow!("dir isn't a correct direction! "));
-    // }
+    // 没有 seeds 目录时直接跳过
+    if !dir.is_dir() {
+        return Ok(());
+    }
 
     // 1. 收集所有 .sql 文件并按文件名排序, 异步环境下使用tokio::fs
     let mut entries = tokio::fs::read_dir(dir).await?;
@@ -78,3 +176,70 @@ async fn run_seeds(pool: &SqlitePool, dir: &Path) -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::migrate::Migrate;
+
+    #[tokio::test]
+    async fn duplicate_usernames_are_renamed_before_the_unique_index() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+
+        // 停在 0011 之前的旧数据库，已有重名用户
+        let mut conn = pool.acquire().await.unwrap();
+        conn.ensure_migrations_table().await.unwrap();
+        for migration in MIGRATOR.iter().filter(|m| m.version < 11) {
+            conn.apply(migration).await.unwrap();
+        }
+        drop(conn);
+        for (id, username, identity) in [
+            ("aaaaaaaa-1", "alice", "visitor"),
+            ("bbbbbbbb-2", "alice", "admin"),
+            ("cccccccc-3", "alice", "visitor"),
+            ("dddddddd-4", "bob", "visitor"),
+            ("eeeeeeee-5", "carol", "visitor"),
+            ("ffffffff-6", "carol", "visitor"),
+        ] {
+            sqlx::query("INSERT INTO users (id, username, identity) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(username)
+                .bind(identity)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        run_migrations(&pool).await.unwrap();
+
+        let users: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, username FROM users ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let expected = [
+            ("aaaaaaaa-1", "alice-aaaaaaaa"),
+            ("bbbbbbbb-2", "alice"),
+            ("cccccccc-3", "alice-cccccccc"),
+            ("dddddddd-4", "bob"),
+            ("eeeeeeee-5", "carol"),
+            ("ffffffff-6", "carol-ffffffff"),
+        ];
+        let users: Vec<_> = users
+            .iter()
+            .map(|(id, name)| (id.as_str(), name.as_str()))
+            .collect();
+        assert_eq!(users, expected);
+
+        // 索引建立后不再处理，再次启动时迁移保持一致
+        run_migrations(&pool).await.unwrap();
+        let duplicate = sqlx::query("INSERT INTO users (id, username) VALUES ('g', 'bob')")
+            .execute(&pool)
+            .await;
+        assert!(duplicate.is_err(), "唯一索引应已建立");
+    }
+}
@@ -0,0 +1,290 @@
+//! 稳定的错误码：客户端据此区分错误，HTTP 状态码与中英文提示都由错误码决定。
+//! 错误码一经发布不再修改含义，新增错误时追加新的错误码。
+
+use super::i18n::Lang;
+use axum::http::StatusCode;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    // 通用
+    InvalidJson,
+    UnsupportedMediaType,
+    PayloadTooLarge,
+    ValidationFailed,
+    Forbidden,
+    NotFound,
+    Conflict,
+    TooManyRequests,
+    Internal,
+    // 认证
+    MissingToken,
+    InvalidToken,
+    TokenExpired,
+    InvalidApiToken,
+    ApiTokenNotAccepted,
+    InsufficientScope,
+    InvalidCredentials,
+    AccountDeleted,
+    InvalidSetupToken,
+    SetupCompleted,
+    OidcLoginFailed,
+    // 资源
+    ArticleNotFound,
+    CommentNotFound,
+    UserNotFound,
+    TokenNotFound,
+    BackupNotFound,
+    OidcProviderNotFound,
+    NotificationNotFound,
+    WebhookNotFound,
+    WebhookDeliveryNotFound,
+    SubscriberNotFound,
+    // 业务规则
+    UsernameTaken,
+    SuperAdminProtected,
+    IdMismatch,
+    InvalidBackup,
+    MailDisabled,
+}
+
+impl ErrorCode {
+    /// (错误码, HTTP 状态码, 中文提示, 英文提示)
+    fn spec(self) -> (&'static str, StatusCode, &'static str, &'static str) {
+        use ErrorCode::*;
+        match self {
+            InvalidJson => (
+                "INVALID_JSON",
+                StatusCode::BAD_REQUEST,
+                "请求体不是有效的 JSON",
+                "The request body is not valid JSON",
+            ),
+            UnsupportedMediaType => (
+                "UNSUPPORTED_MEDIA_TYPE",
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "请求体应为 application/json",
+                "Expected an application/json request body",
+            ),
+            PayloadTooLarge => (
+                "PAYLOAD_TOO_LARGE",
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "请求体过大",
+                "Request body too large",
+            ),
+            ValidationFailed => (
+                "VALIDATION_FAILED",
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "参数校验失败",
+                "Validation failed",
+            ),
+            Forbidden => (
+                "FORBIDDEN",
+                StatusCode::FORBIDDEN,
+                "权限不足",
+                "Insufficient privileges",
+            ),
+            NotFound => (
+                "NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "资源不存在",
+                "Not found",
+            ),
+            Conflict => (
+                "CONFLICT",
+                StatusCode::CONFLICT,
+                "与已有数据冲突",
+                "Conflicts with existing data",
+            ),
+            TooManyRequests => (
+                "TOO_MANY_REQUESTS",
+                StatusCode::TOO_MANY_REQUESTS,
+                "请求过于频繁，请稍后再试",
+                "Too many requests",
+            ),
+            Internal => (
+                "INTERNAL_ERROR",
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "服务器内部错误",
+                "Internal server error",
+            ),
+            MissingToken => (
+                "MISSING_TOKEN",
+                StatusCode::UNAUTHORIZED,
+                "缺少访问令牌",
+                "Missing bearer token",
+            ),
+            InvalidToken => (
+                "INVALID_TOKEN",
+                StatusCode::UNAUTHORIZED,
+                "访问令牌无效",
+                "Invalid token",
+            ),
+            TokenExpired => (
+                "TOKEN_EXPIRED",
+                StatusCode::UNAUTHORIZED,
+                "访问令牌已过期",
+                "Token expired",
+            ),
+            InvalidApiToken => (
+                "INVALID_API_TOKEN",
+                StatusCode::UNAUTHORIZED,
+                "个人访问令牌无效或已过期",
+                "Invalid or expired API token",
+            ),
+            ApiTokenNotAccepted => (
+                "API_TOKEN_NOT_ACCEPTED",
+                StatusCode::UNAUTHORIZED,
+                "该接口不接受个人访问令牌",
+                "API tokens are not accepted here",
+            ),
+            InsufficientScope => (
+                "INSUFFICIENT_SCOPE",
+                StatusCode::FORBIDDEN,
+                "个人访问令牌缺少所需的 scope",
+                "API token is missing the required scope",
+            ),
+            InvalidCredentials => (
+                "INVALID_CREDENTIALS",
+                StatusCode::UNAUTHORIZED,
+                "用户名或密码错误",
+                "Invalid username or password",
+            ),
+            AccountDeleted => (
+                "ACCOUNT_DELETED",
+                StatusCode::UNAUTHORIZED,
+                "用户已不存在",
+                "User no longer exists",
+            ),
+            InvalidSetupToken => (
+                "INVALID_SETUP_TOKEN",
+                StatusCode::UNAUTHORIZED,
+                "setup token 不正确",
+                "Invalid setup token",
+            ),
+            SetupCompleted => (
+                "SETUP_COMPLETED",
+                StatusCode::FORBIDDEN,
+                "已完成首次设置",
+                "Setup has already been completed",
+            ),
+            OidcLoginFailed => (
+                "OIDC_LOGIN_FAILED",
+                StatusCode::UNAUTHORIZED,
+                "第三方登录失败",
+                "Third-party login failed",
+            ),
+            ArticleNotFound => (
+                "ARTICLE_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "文章不存在",
+                "Article not found",
+            ),
+            CommentNotFound => (
+                "COMMENT_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "评论不存在",
+                "Comment not found",
+            ),
+            UserNotFound => (
+                "USER_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "用户不存在",
+                "User not found",
+            ),
+            TokenNotFound => (
+                "TOKEN_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "访问令牌不存在",
+                "API token not found",
+            ),
+            BackupNotFound => (
+                "BACKUP_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "备份不存在",
+                "Backup not found",
+            ),
+            OidcProviderNotFound => (
+                "OIDC_PROVIDER_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "未配置该第三方登录",
+                "OIDC provider not found",
+            ),
+            NotificationNotFound => (
+                "NOTIFICATION_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "通知不存在",
+                "Notification not found",
+            ),
+            WebhookNotFound => (
+                "WEBHOOK_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "Webhook 不存在",
+                "Webhook not found",
+            ),
+            WebhookDeliveryNotFound => (
+                "WEBHOOK_DELIVERY_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "投递记录不存在",
+                "Webhook delivery not found",
+            ),
+            SubscriberNotFound => (
+                "SUBSCRIBER_NOT_FOUND",
+                StatusCode::NOT_FOUND,
+                "订阅者不存在",
+                "Subscriber not found",
+            ),
+            UsernameTaken => (
+                "USERNAME_TAKEN",
+                StatusCode::CONFLICT,
+                "用户名已被注册",
+                "Username already taken",
+            ),
+            SuperAdminProtected => (
+                "SUPERADMIN_PROTECTED",
+                StatusCode::FORBIDDEN,
+                "不能删除超级管理员或更改其权限",
+                "The super admin cannot be deleted or demoted",
+            ),
+            IdMismatch => (
+                "ID_MISMATCH",
+                StatusCode::BAD_REQUEST,
+                "请求体中的 id 与路径不一致",
+                "The id in the body does not match the path",
+            ),
+            InvalidBackup => (
+                "INVALID_BACKUP",
+                StatusCode::BAD_REQUEST,
+                "备份文件无效",
+                "Invalid backup file",
+            ),
+            MailDisabled => (
+                "MAIL_DISABLED",
+                StatusCode::SERVICE_UNAVAILABLE,
+                "未启用邮件发送",
+                "Email delivery is not enabled",
+            ),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.spec().0
+    }
+
+    pub fn status(self) -> StatusCode {
+        self.spec().1
+    }
+
+    pub fn message(self, lang: Lang) -> &'static str {
+        let (_, _, zh, en) = self.spec();
+        match lang {
+            Lang::ZhCn => zh,
+            Lang::En => en,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,81 @@
+//! 错误提示的语言：按请求的 `Accept-Language` 在中文与英文之间选择。
+
+use super::{Format, Violation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    /// 未声明或都不支持时使用中文
+    #[default]
+    ZhCn,
+    En,
+}
+
+impl Lang {
+    /// 解析 `Accept-Language`（如 `en-US,en;q=0.9,zh;q=0.8`），按权重取第一个支持的语言
+    pub fn negotiate(header: Option<&str>) -> Self {
+        let Some(header) = header else {
+            return Lang::default();
+        };
+
+        let mut ranges: Vec<(&str, f32)> = header
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let tag = parts.next()?.trim();
+                let q = parts
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .find_map(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && q > 0.0).then_some((tag, q))
+            })
+            .collect();
+        // 稳定排序，同权重时保持原顺序
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        ranges
+            .into_iter()
+            .find_map(|(tag, _)| {
+                let primary = tag.split('-').next().unwrap_or(tag);
+                if primary.eq_ignore_ascii_case("zh") {
+                    Some(Lang::ZhCn)
+                } else if primary.eq_ignore_ascii_case("en") {
+                    Some(Lang::En)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    /// `Content-Language` 响应头的值
+    pub fn tag(self) -> &'static str {
+        match self {
+            Lang::ZhCn => "zh-CN",
+            Lang::En => "en",
+        }
+    }
+}
+
+/// 字段校验错误的提示，英文提示与桌面端共用（见 `blog_shared::validate`）
+pub fn violation_message(violation: &Violation, lang: Lang) -> String {
+    if lang == Lang::En {
+        return violation.to_string();
+    }
+    match violation {
+        Violation::Required => "不能为空".into(),
+        Violation::Length { min, max } => match (min, max) {
+            (Some(min), Some(max)) => format!("长度应为 {min}-{max}"),
+            (Some(min), None) => format!("长度至少为 {min}"),
+            (None, Some(max)) => format!("长度不能超过 {max}"),
+            (None, None) => "长度不符合要求".into(),
+        },
+        Violation::Range { min, max } => format!("应在 {min}-{max} 之间"),
+        Violation::OneOf { allowed } => format!("应为以下之一: {}", allowed.join(", ")),
+        Violation::Format { format } => match format {
+            Format::Username => "只能包含字母、数字、下划线、连字符与点".into(),
+            Format::Slug => "只能包含小写字母、数字与连字符".into(),
+            Format::Url => "应为 http 或 https 地址".into(),
+            Format::Email => "应为有效的邮箱地址".into(),
+        },
+    }
+}
@@ -0,0 +1,342 @@
+//! 统一错误类型：将业务/系统错误映射为 HTTP 响应，便于 handler 中使用 `?`。
+//! - code：稳定的错误码与对应的状态码、中英文提示
+//! - i18n：按 `Accept-Language` 选择提示语言
+//!
+//! 响应体为 `{ code, status, message, details, request_id }`，
+//! 语言与 request id 由 `middleware::http` 根据请求补全。
+
+pub mod code;
+pub mod i18n;
+
+pub use code::ErrorCode;
+pub use i18n::Lang;
+
+use axum::{
+    http::{
+        HeaderValue,
+        header::{CONTENT_TYPE, RETRY_AFTER},
+    },
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::{Value, json};
+use sqlx::error::ErrorKind;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+#[derive(Error, Debug)]
+pub enum AppError {
+    #[error("insufficient privileges")]
+    Forbidden,
+    /// 带稳定错误码的业务错误
+    #[error("{0}")]
+    Code(ErrorCode),
+    /// 带错误码与具体原因，原因写入 `details.reason`
+    #[error("{0}: {1}")]
+    Reason(ErrorCode, String),
+    /// 字段级校验错误，逐项写入 `details`
+    #[error("validation failed")]
+    Validation(Vec<FieldError>),
+    #[error(transparent)]
+    // #[from] 帮你 自动实现 From<sqlx::Error> for AppError，于是你可以直接 ? 把 sqlx::Error 转成 AppError。
+    Sqlx(#[from] sqlx::Error),
+    #[error(transparent)]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+    #[error(transparent)]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+    #[error("密码哈希错误")]
+    PasswordHash(#[from] argon2::password_hash::Error),
+    #[error("网络服务错误: {0}")]
+    InternalServerError(String),
+    /// 请求过于频繁，携带建议的重试等待秒数
+    #[error("too many requests")]
+    TooManyRequests(u64),
+}
+
+impl From<ErrorCode> for AppError {
+    fn from(code: ErrorCode) -> Self {
+        AppError::Code(code)
+    }
+}
+
+pub use blog_shared::validate::{FieldError, Format, Violation};
+
+impl From<Vec<FieldError>> for AppError {
+    fn from(errors: Vec<FieldError>) -> Self {
+        AppError::Validation(errors)
+    }
+}
+
+/// 错误响应的原始信息：`into_response` 把它放进响应扩展，
+/// 由 `middleware::http` 按请求的语言与 request id 重新生成响应体
+#[derive(Debug, Clone)]
+pub struct ErrorReport {
+    code: ErrorCode,
+    details: Option<Value>,
+    fields: Vec<FieldError>,
+}
+
+/// 错误响应体，OpenAPI 文档中的错误响应均引用此结构
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// 稳定的错误码，如 `ARTICLE_NOT_FOUND`
+    #[schema(example = "ARTICLE_NOT_FOUND")]
+    code: &'static str,
+    status: u16,
+    /// 按 `Accept-Language` 本地化的提示
+    message: String,
+    /// 字段校验错误列表，或 `reason` / `constraint` / `retry_after` 等附加信息
+    #[schema(value_type = Option<Object>)]
+    details: Option<Value>,
+    request_id: Option<String>,
+}
+
+#[derive(Serialize)]
+struct FieldDetail<'a> {
+    field: &'a str,
+    message: String,
+    #[serde(flatten)]
+    violation: &'a Violation,
+}
+
+impl ErrorReport {
+    fn new(code: ErrorCode) -> Self {
+        Self {
+            code,
+            details: None,
+            fields: Vec::new(),
+        }
+    }
+
+    fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    fn with_reason(self, reason: &str) -> Self {
+        self.with_details(json!({ "reason": reason }))
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    /// 按语言生成 JSON 响应体
+    pub fn body(&self, lang: Lang, request_id: Option<&str>) -> Vec<u8> {
+        serde_json::to_vec(&self.error_body(lang, request_id)).unwrap_or_default()
+    }
+
+    /// 按语言生成错误信息的 JSON 值，供不经过 HTTP 错误响应的出口（GraphQL）使用
+    #[cfg(feature = "graphql")]
+    pub fn to_value(&self, lang: Lang) -> Value {
+        serde_json::to_value(self.error_body(lang, None)).unwrap_or_default()
+    }
+
+    fn error_body(&self, lang: Lang, request_id: Option<&str>) -> ErrorBody {
+        let details = if self.fields.is_empty() {
+            self.details.clone()
+        } else {
+            let fields: Vec<_> = self
+                .fields
+                .iter()
+                .map(|f| FieldDetail {
+                    field: &f.field,
+                    message: i18n::violation_message(&f.violation, lang),
+                    violation: &f.violation,
+                })
+                .collect();
+            serde_json::to_value(fields).ok()
+        };
+
+        ErrorBody {
+            code: self.code.as_str(),
+            status: self.code.status().as_u16(),
+            message: self.code.message(lang).to_string(),
+            details,
+            request_id: request_id.map(str::to_string),
+        }
+    }
+}
+
+impl AppError {
+    pub fn report(&self) -> ErrorReport {
+        match self {
+            AppError::Forbidden => ErrorReport::new(ErrorCode::Forbidden),
+            AppError::Code(code) => ErrorReport::new(*code),
+            AppError::Reason(code, reason) => ErrorReport::new(*code).with_reason(reason),
+            AppError::Validation(fields) => ErrorReport {
+                fields: fields.clone(),
+                ..ErrorReport::new(ErrorCode::ValidationFailed)
+            },
+            AppError::Sqlx(e) => sqlx_report(e).unwrap_or_else(|| {
+                tracing::error!("database error: {e}");
+                ErrorReport::new(ErrorCode::Internal)
+            }),
+            AppError::Jwt(e) => match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
+                    ErrorReport::new(ErrorCode::TokenExpired)
+                }
+                _ => ErrorReport::new(ErrorCode::InvalidToken),
+            },
+            AppError::Migrate(_) | AppError::Other(_) | AppError::PasswordHash(_) => {
+                tracing::error!("{self:#}");
+                ErrorReport::new(ErrorCode::Internal)
+            }
+            AppError::InternalServerError(reason) => {
+                tracing::error!("{reason}");
+                ErrorReport::new(ErrorCode::Internal).with_reason(reason)
+            }
+            AppError::TooManyRequests(secs) => ErrorReport::new(ErrorCode::TooManyRequests)
+                .with_details(json!({ "retry_after": secs })),
+        }
+    }
+}
+
+/// 约束冲突映射为 409，未命中的查询映射为 404，其余返回 None 按内部错误处理
+fn sqlx_report(e: &sqlx::Error) -> Option<ErrorReport> {
+    if let sqlx::Error::RowNotFound = e {
+        return Some(ErrorReport::new(ErrorCode::NotFound));
+    }
+
+    let db = e.as_database_error()?;
+    match db.kind() {
+        ErrorKind::UniqueViolation
+        | ErrorKind::ForeignKeyViolation
+        | ErrorKind::NotNullViolation
+        | ErrorKind::CheckViolation => {}
+        _ => return None,
+    }
+
+    // SQLite 的消息形如 "UNIQUE constraint failed: users.username"
+    let constraint = db
+        .message()
+        .split_once(": ")
+        .map_or(db.message(), |(_, target)| target);
+    let code = match constraint {
+        "users.username" => ErrorCode::UsernameTaken,
+        _ => ErrorCode::Conflict,
+    };
+    tracing::info!("constraint violation: {}", db.message());
+    Some(ErrorReport::new(code).with_details(json!({ "constraint": constraint })))
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let report = self.report();
+        let status = report.code().status();
+        let body = report.body(Lang::default(), None);
+
+        let mut response = (
+            status,
+            [(CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+            body,
+        )
+            .into_response();
+        if let AppError::TooManyRequests(secs) = self {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response.extensions_mut().insert(report);
+        response
+    }
+}
+
+/// 统一结果类型，简化返回
+pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use sqlx::SqlitePool;
+
+    fn body(error: &AppError, lang: Lang) -> Value {
+        serde_json::from_slice(&error.report().body(lang, Some("req-1"))).unwrap()
+    }
+
+    /// 在内存库中触发一次约束冲突
+    async fn violation(sql: &str) -> AppError {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::raw_sql(
+            r#"
+            CREATE TABLE users (id TEXT PRIMARY KEY, username TEXT);
+            CREATE UNIQUE INDEX idx_users_username ON users (username);
+            INSERT INTO users (id, username) VALUES ('u1', 'alice');
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(sql).execute(&pool).await.unwrap_err().into()
+    }
+
+    #[tokio::test]
+    async fn duplicate_username_maps_to_409_username_taken() {
+        let error = violation("INSERT INTO users (id, username) VALUES ('u2', 'alice')").await;
+        assert_eq!(error.report().code(), ErrorCode::UsernameTaken);
+        assert_eq!(error.report().code().status(), StatusCode::CONFLICT);
+
+        let body = body(&error, Lang::En);
+        assert_eq!(body["code"], "USERNAME_TAKEN");
+        assert_eq!(body["status"], 409);
+        assert_eq!(body["details"]["constraint"], "users.username");
+        assert_eq!(body["request_id"], "req-1");
+    }
+
+    #[tokio::test]
+    async fn other_constraints_map_to_409_conflict() {
+        let error = violation("INSERT INTO users (id, username) VALUES ('u1', 'bob')").await;
+        assert_eq!(error.report().code(), ErrorCode::Conflict);
+        assert_eq!(body(&error, Lang::En)["details"]["constraint"], "users.id");
+    }
+
+    #[test]
+    fn maps_errors_to_codes() {
+        let cases = [
+            (AppError::Forbidden, ErrorCode::Forbidden),
+            (sqlx::Error::RowNotFound.into(), ErrorCode::NotFound),
+            (sqlx::Error::PoolTimedOut.into(), ErrorCode::Internal),
+            (
+                AppError::Jwt(jsonwebtoken::errors::ErrorKind::ExpiredSignature.into()),
+                ErrorCode::TokenExpired,
+            ),
+            (
+                AppError::Jwt(jsonwebtoken::errors::ErrorKind::InvalidSignature.into()),
+                ErrorCode::InvalidToken,
+            ),
+            (AppError::TooManyRequests(7), ErrorCode::TooManyRequests),
+        ];
+        for (error, code) in cases {
+            assert_eq!(error.report().code(), code, "{error}");
+        }
+    }
+
+    #[test]
+    fn validation_errors_list_fields_in_the_requested_language() {
+        let error = AppError::from(vec![FieldError::new("username", Violation::Required)]);
+        assert_eq!(
+            error.report().code().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+
+        let zh = body(&error, Lang::ZhCn);
+        assert_eq!(zh["code"], "VALIDATION_FAILED");
+        assert_eq!(zh["details"][0]["field"], "username");
+        assert_eq!(zh["details"][0]["code"], "REQUIRED");
+        assert_eq!(zh["details"][0]["message"], "不能为空");
+        assert_eq!(
+            body(&error, Lang::En)["details"][0]["message"],
+            "is required"
+        );
+    }
+
+    #[test]
+    fn too_many_requests_sets_retry_after() {
+        let response = AppError::TooManyRequests(7).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "7");
+    }
+}
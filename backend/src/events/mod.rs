@@ -0,0 +1,101 @@
+//! 实时事件：
+//! - `EventHub` 放在 `AppState` 中，评论、点赞与文章状态变化发生后广播给所有订阅者
+//! - 事件带有所属文章 id，订阅方按文章筛选（见 `routes::events`）
+//! - 发布时附带文章当时是否公开，草稿与归档文章的事件只推送给管理员
+//! - 广播 channel 有固定容量，发布方从不等待；订阅方处理不过来时丢弃最旧的事件并收到 `Lagged`
+//! - 站内通知使用另一个 `EventHub<Notification>`，只推送给接收者（见 `routes::notifications`）
+
+use crate::models::comment::Comment;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+/// 每个订阅者最多积压的事件数
+const CAPACITY: usize = 256;
+
+/// 推送给客户端的事件，`type` 同时作为 SSE 的事件名
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlogEvent {
+    CommentCreated {
+        article_id: String,
+        comment: Comment,
+    },
+    CommentDeleted {
+        article_id: String,
+        comment_id: String,
+    },
+    /// 点赞或取消点赞，`like_count` 为最新的点赞数
+    CommentLiked {
+        article_id: String,
+        comment_id: String,
+        like_count: i64,
+    },
+    ArticleStatusChanged {
+        article_id: String,
+        status: String,
+    },
+}
+
+impl BlogEvent {
+    pub fn article_id(&self) -> &str {
+        match self {
+            BlogEvent::CommentCreated { article_id, .. }
+            | BlogEvent::CommentDeleted { article_id, .. }
+            | BlogEvent::CommentLiked { article_id, .. }
+            | BlogEvent::ArticleStatusChanged { article_id, .. } => article_id,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlogEvent::CommentCreated { .. } => "comment_created",
+            BlogEvent::CommentDeleted { .. } => "comment_deleted",
+            BlogEvent::CommentLiked { .. } => "comment_liked",
+            BlogEvent::ArticleStatusChanged { .. } => "article_status_changed",
+        }
+    }
+}
+
+/// 在广播中传递的文章事件，`public` 为事件发生时文章是否对所有人可见
+#[derive(Debug)]
+pub struct ArticleEvent {
+    pub event: BlogEvent,
+    pub public: bool,
+}
+
+pub struct EventHub<T> {
+    tx: broadcast::Sender<Arc<T>>,
+}
+
+impl<T> Default for EventHub<T> {
+    fn default() -> Self {
+        Self {
+            tx: broadcast::channel(CAPACITY).0,
+        }
+    }
+}
+
+impl<T> EventHub<T> {
+    /// 广播事件，没有订阅者时直接丢弃
+    pub fn publish(&self, event: T) {
+        let _ = self.tx.send(Arc::new(event));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<T>> {
+        self.tx.subscribe()
+    }
+
+    /// 当前连接的订阅者数
+    pub fn subscribers(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl EventHub<ArticleEvent> {
+    /// 广播文章相关事件，`public` 为 false 时只有管理员能收到
+    pub fn publish_article(&self, event: BlogEvent, public: bool) {
+        self.publish(ArticleEvent { event, public });
+    }
+}
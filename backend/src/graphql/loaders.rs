@@ -0,0 +1,100 @@
+//! 按请求创建的 DataLoader：同一轮解析中的多个 key 合并为一条 `IN (...)` 查询
+
+use super::Viewer;
+use crate::db::AppState;
+use crate::error::AppError;
+use crate::models::article::{ArticleModel, find_articles_by_ids};
+use crate::models::comment::{Comment, fetch_comments_by_article_ids, liked_comment_ids};
+use async_graphql::dataloader::{DataLoader, Loader};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct Loaders {
+    /// 文章 id → 文章
+    pub articles: DataLoader<ArticleLoader>,
+    /// 文章 id → 评论（按时间倒序）
+    pub comments: DataLoader<CommentsLoader>,
+    /// 评论 id → 当前用户是否点赞，未登录时为 None
+    pub liked: Option<DataLoader<LikedLoader>>,
+}
+
+impl Loaders {
+    pub fn new(state: &AppState, viewer: Option<&Viewer>) -> Self {
+        let pool = state.pool.clone();
+        Self {
+            articles: DataLoader::new(ArticleLoader(pool.clone()), tokio::spawn),
+            comments: DataLoader::new(CommentsLoader(pool.clone()), tokio::spawn),
+            liked: viewer.map(|v| {
+                DataLoader::new(
+                    LikedLoader {
+                        pool,
+                        user_id: v.claims.user_id.clone(),
+                    },
+                    tokio::spawn,
+                )
+            }),
+        }
+    }
+}
+
+/// DataLoader 要求错误类型可克隆，数据库错误包一层 Arc
+#[derive(Clone)]
+pub struct LoadError(Arc<sqlx::Error>);
+
+impl From<sqlx::Error> for LoadError {
+    fn from(e: sqlx::Error) -> Self {
+        LoadError(Arc::new(e))
+    }
+}
+
+impl From<LoadError> for AppError {
+    fn from(e: LoadError) -> Self {
+        AppError::Other(anyhow::Error::new(e.0))
+    }
+}
+
+pub struct ArticleLoader(SqlitePool);
+
+impl Loader<String> for ArticleLoader {
+    type Value = ArticleModel;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, ArticleModel>, LoadError> {
+        let rows = find_articles_by_ids(&self.0, keys).await?;
+        Ok(rows.into_iter().map(|a| (a.id.clone(), a)).collect())
+    }
+}
+
+pub struct CommentsLoader(SqlitePool);
+
+impl Loader<String> for CommentsLoader {
+    type Value = Vec<Comment>;
+    type Error = LoadError;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, Vec<Comment>>, LoadError> {
+        let mut map: HashMap<String, Vec<Comment>> = HashMap::new();
+        for c in fetch_comments_by_article_ids(&self.0, keys).await? {
+            if let Some(article_id) = c.article_id.clone() {
+                map.entry(article_id).or_default().push(c);
+            }
+        }
+        Ok(map)
+    }
+}
+
+pub struct LikedLoader {
+    pool: SqlitePool,
+    user_id: String,
+}
+
+impl Loader<String> for LikedLoader {
+    type Value = bool;
+    type Error = LoadError;
+
+    /// 只返回点过赞的评论，其余 key 缺省即未点赞
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, bool>, LoadError> {
+        let ids = liked_comment_ids(&self.pool, &self.user_id, keys).await?;
+        Ok(ids.into_iter().map(|id| (id, true)).collect())
+    }
+}
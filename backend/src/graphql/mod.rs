@@ -0,0 +1,272 @@
+//! GraphQL 接口（需启用 `graphql` feature）：
+//! - POST /api/v1/graphql，文章、评论、用户、标签与搜索建议在同一个 schema 中，一次请求取齐页面数据
+//! - 鉴权沿用 `MaybeJwtAuth`：未登录只能读取公开内容，评论、用户与全部 mutation 需要登录
+//! - 关联字段（文章的评论、评论的点赞状态与所属文章）经 DataLoader 按请求批量加载，避免 N+1 查询
+//! - mutation 复用 REST 的业务函数，权限、校验与审计日志保持一致
+//! - 错误的 `extensions` 带上与 REST 相同的 `code` / `status` / `details`
+//! - 限制查询深度与复杂度，过深或过大的查询在执行前被拒绝
+
+mod loaders;
+mod mutation;
+mod query;
+mod types;
+
+use crate::auth::{Claims, MaybeJwtAuth};
+use crate::db::AppState;
+use crate::error::{AppError, AppResult, ErrorCode, Lang};
+use crate::models::article::ArticleModel;
+use crate::models::user::find_user_by_id;
+use async_graphql::{Context, EmptySubscription, ErrorExtensions, Schema};
+use axum::{
+    Extension, Json,
+    extract::State,
+    http::{HeaderMap, header},
+};
+use loaders::Loaders;
+use mutation::Mutation;
+use query::Query;
+use std::sync::Arc;
+
+pub type BlogSchema = Schema<Query, Mutation, EmptySubscription>;
+
+/// 查询的最大嵌套深度，足够覆盖“文章 → 评论 → 所属文章”这类页面查询
+const MAX_DEPTH: usize = 8;
+/// 查询的最大复杂度，列表字段按子字段复杂度放大计算
+const MAX_COMPLEXITY: usize = 2000;
+
+pub fn schema(state: Arc<AppState>) -> BlogSchema {
+    Schema::build(Query, Mutation, EmptySubscription)
+        .data(state)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// 当前登录用户，身份在请求开始时查询一次
+pub struct Viewer {
+    pub claims: Claims,
+    pub identity: String,
+}
+
+impl Viewer {
+    pub fn is_admin(&self) -> bool {
+        self.identity == "admin"
+    }
+}
+
+/// POST /api/v1/graphql
+pub async fn execute(
+    State(state): State<Arc<AppState>>,
+    Extension(schema): Extension<BlogSchema>,
+    MaybeJwtAuth(claims): MaybeJwtAuth,
+    headers: HeaderMap,
+    Json(req): Json<async_graphql::Request>,
+) -> AppResult<Json<async_graphql::Response>> {
+    // 账号已删除的 token 按未登录处理
+    let viewer = match claims {
+        Some(claims) => find_user_by_id(&state.pool, claims.user_id.clone())
+            .await?
+            .map(|u| Viewer {
+                claims,
+                identity: u.identity,
+            }),
+        None => None,
+    };
+    let lang = Lang::negotiate(
+        headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok()),
+    );
+
+    let req = req
+        .data(Loaders::new(&state, viewer.as_ref()))
+        .data(viewer)
+        .data(lang);
+    Ok(Json(schema.execute(req).await))
+}
+
+fn state<'a>(ctx: &Context<'a>) -> &'a AppState {
+    ctx.data_unchecked::<Arc<AppState>>()
+}
+
+fn loaders<'a>(ctx: &Context<'a>) -> &'a Loaders {
+    ctx.data_unchecked::<Loaders>()
+}
+
+fn viewer<'a>(ctx: &Context<'a>) -> Option<&'a Viewer> {
+    ctx.data_unchecked::<Option<Viewer>>().as_ref()
+}
+
+fn require_viewer<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Viewer> {
+    viewer(ctx).ok_or(ErrorCode::MissingToken).gql(ctx)
+}
+
+async fn require_admin<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Viewer> {
+    let viewer = require_viewer(ctx)?;
+    viewer.claims.require_admin(state(ctx)).await.gql(ctx)?;
+    Ok(viewer)
+}
+
+/// 草稿与归档的文章只对管理员可见
+fn visible(ctx: &Context<'_>, article: &ArticleModel) -> bool {
+    blog_shared::export::is_published(article.status.as_deref())
+        || viewer(ctx).is_some_and(Viewer::is_admin)
+}
+
+/// 把业务错误转为 GraphQL 错误，提示按请求语言本地化
+trait GqlResult<T> {
+    fn gql(self, ctx: &Context<'_>) -> async_graphql::Result<T>;
+}
+
+impl<T, E: Into<AppError>> GqlResult<T> for Result<T, E> {
+    fn gql(self, ctx: &Context<'_>) -> async_graphql::Result<T> {
+        self.map_err(|e| {
+            let lang = ctx.data_opt::<Lang>().copied().unwrap_or_default();
+            let body = e.into().report().to_value(lang);
+            async_graphql::Error::new(body["message"].as_str().unwrap_or_default()).extend_with(
+                |_, ext| {
+                    ext.set("code", body["code"].as_str().unwrap_or_default());
+                    ext.set("status", body["status"].as_u64().unwrap_or_default());
+                    if let Ok(details) = async_graphql::Value::from_json(body["details"].clone())
+                        && details != async_graphql::Value::Null
+                    {
+                        ext.set("details", details);
+                    }
+                },
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::keys::JwtKeys;
+    use crate::config::Config;
+    use async_graphql::Value;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn state() -> Arc<AppState> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::MIGRATOR.run(&pool).await.unwrap();
+        for (id, title, status, tags) in [
+            ("a-published", "公开文章", "published", "rust"),
+            ("a-draft", "草稿文章", "draft", "secret"),
+        ] {
+            sqlx::query("INSERT INTO articles (id, title, status, tags) VALUES (?, ?, ?, ?)")
+                .bind(id)
+                .bind(title)
+                .bind(status)
+                .bind(tags)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let mut cfg = Config::default();
+        cfg.auth.jwt_allow_default_secret = true;
+        let jwt = JwtKeys::from_config(&cfg).unwrap();
+        Arc::new(AppState::new(pool, cfg, jwt))
+    }
+
+    fn admin() -> Viewer {
+        Viewer {
+            claims: Claims {
+                user_id: "u-admin".into(),
+                message: String::new(),
+                exp: usize::MAX,
+                iat: 0,
+                iss: String::new(),
+                aud: String::new(),
+                scopes: None,
+            },
+            identity: "admin".into(),
+        }
+    }
+
+    /// 与 `execute` 相同地注入请求数据后执行
+    async fn run(
+        state: &Arc<AppState>,
+        viewer: Option<Viewer>,
+        query: &str,
+    ) -> async_graphql::Response {
+        let req = async_graphql::Request::new(query)
+            .data(Loaders::new(state, viewer.as_ref()))
+            .data(viewer)
+            .data(Lang::default());
+        schema(state.clone()).execute(req).await
+    }
+
+    #[tokio::test]
+    async fn anonymous_queries_never_see_drafts() {
+        let state = state().await;
+
+        let cases = [
+            (
+                "文章列表",
+                "{ articles { id } }",
+                r#"{"articles":[{"id":"a-published"}]}"#,
+            ),
+            (
+                "按 id 获取草稿",
+                r#"{ article(id: "a-draft") { id } }"#,
+                r#"{"article":null}"#,
+            ),
+            (
+                "标签只统计公开文章",
+                "{ tags { name articleCount } }",
+                r#"{"tags":[{"name":"rust","articleCount":1}]}"#,
+            ),
+        ];
+        for (desc, query, expected) in cases {
+            let res = run(&state, None, query).await;
+            assert!(res.errors.is_empty(), "{desc}: {:?}", res.errors);
+            assert_eq!(
+                res.data.into_json().unwrap(),
+                serde_json::from_str::<serde_json::Value>(expected).unwrap(),
+                "{desc}"
+            );
+        }
+
+        // 草稿确实存在，管理员可以看到
+        let res = run(
+            &state,
+            Some(admin()),
+            r#"{ article(id: "a-draft") { id } }"#,
+        )
+        .await;
+        assert_eq!(
+            res.data.into_json().unwrap(),
+            serde_json::json!({ "article": { "id": "a-draft" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn over_deep_queries_are_rejected() {
+        let state = state().await;
+
+        // 页面查询“文章 → 评论 → 所属文章”在限制之内
+        let res = run(
+            &state,
+            Some(admin()),
+            r#"{ article(id: "a-published") { comments { article { id } } } }"#,
+        )
+        .await;
+        assert!(res.errors.is_empty(), "{:?}", res.errors);
+
+        // 每层复杂度只有 1，只会触发深度限制
+        let mut query = "name".to_string();
+        for _ in 0..MAX_DEPTH {
+            query = format!("ofType {{ {query} }}");
+        }
+        let query = format!("{{ __type(name: \"Article\") {{ {query} }} }}");
+        let res = run(&state, None, &query).await;
+        assert_eq!(res.data, Value::Null);
+        assert_eq!(res.errors.len(), 1);
+        assert_eq!(res.errors[0].message, "Query is nested too deep.");
+    }
+}
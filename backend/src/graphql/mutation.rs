@@ -0,0 +1,139 @@
+//! 写操作，与对应的 REST 接口共用业务函数
+
+use super::types::{Article, Comment};
+use super::{GqlResult, require_viewer, state};
+use crate::models::user::UserPublic;
+use crate::routes::{
+    articles::{
+        NewArticle, NewStatus, create_article, remove_article, set_article_status, update_article,
+    },
+    comments::{
+        CommentIncome, CommentLikeState, NewComment, create_comment, remove_comment, set_like,
+    },
+    users::{UpdateUser, edit_user, remove_user},
+};
+use crate::validate::validated;
+use async_graphql::{Context, Object, Result};
+
+pub struct Mutation;
+
+#[Object]
+impl Mutation {
+    /// 新增文章，仅管理员；新文章为草稿
+    async fn create_article(&self, ctx: &Context<'_>, input: NewArticle) -> Result<Article> {
+        let viewer = require_viewer(ctx)?;
+        let input = validated(input).gql(ctx)?;
+        create_article(state(ctx), viewer.claims.clone(), input)
+            .await
+            .map(Article)
+            .gql(ctx)
+    }
+
+    /// 修改文章，仅管理员；修改后回到草稿状态
+    async fn update_article(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        input: NewArticle,
+    ) -> Result<Article> {
+        let viewer = require_viewer(ctx)?;
+        let input = validated(input).gql(ctx)?;
+        update_article(state(ctx), &viewer.claims, &id, input)
+            .await
+            .map(Article)
+            .gql(ctx)
+    }
+
+    /// 更改文章状态（draft / published / archived），仅管理员
+    async fn set_article_status(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        status: String,
+    ) -> Result<Article> {
+        let viewer = require_viewer(ctx)?;
+        let input = validated(NewStatus { toggle: status }).gql(ctx)?;
+        set_article_status(state(ctx), &viewer.claims, &id, input)
+            .await
+            .map(Article)
+            .gql(ctx)
+    }
+
+    /// 删除文章，仅管理员
+    async fn delete_article(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let viewer = require_viewer(ctx)?;
+        remove_article(state(ctx), &viewer.claims, &id)
+            .await
+            .gql(ctx)?;
+        Ok(true)
+    }
+
+    /// 发表评论，游客不可评论
+    async fn create_comment(
+        &self,
+        ctx: &Context<'_>,
+        article_id: String,
+        input: NewComment,
+    ) -> Result<Comment> {
+        let viewer = require_viewer(ctx)?;
+        let input = validated(input).gql(ctx)?;
+        let payload = CommentIncome {
+            article_id,
+            user_id: None,
+            content: input.content,
+            parent_id: input.parent_id,
+        };
+        create_comment(state(ctx), viewer.claims.clone(), payload)
+            .await
+            .map(Comment)
+            .gql(ctx)
+    }
+
+    /// 删除评论，仅管理员
+    async fn delete_comment(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let viewer = require_viewer(ctx)?;
+        remove_comment(state(ctx), &viewer.claims, &id)
+            .await
+            .gql(ctx)?;
+        Ok(true)
+    }
+
+    /// 点赞评论，重复点赞不改变结果
+    async fn like_comment(&self, ctx: &Context<'_>, id: String) -> Result<CommentLikeState> {
+        let viewer = require_viewer(ctx)?;
+        set_like(state(ctx), &viewer.claims, id, true)
+            .await
+            .gql(ctx)
+    }
+
+    /// 取消点赞
+    async fn unlike_comment(&self, ctx: &Context<'_>, id: String) -> Result<CommentLikeState> {
+        let viewer = require_viewer(ctx)?;
+        set_like(state(ctx), &viewer.claims, id, false)
+            .await
+            .gql(ctx)
+    }
+
+    /// 修改用户，仅管理员；省略的字段不修改
+    async fn update_user(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        input: UpdateUser,
+    ) -> Result<UserPublic> {
+        let viewer = require_viewer(ctx)?;
+        let input = validated(input).gql(ctx)?;
+        edit_user(state(ctx), &viewer.claims, id, input)
+            .await
+            .gql(ctx)
+    }
+
+    /// 删除用户，仅管理员，超管不可删除
+    async fn delete_user(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
+        let viewer = require_viewer(ctx)?;
+        remove_user(state(ctx), &viewer.claims, &id)
+            .await
+            .gql(ctx)?;
+        Ok(true)
+    }
+}
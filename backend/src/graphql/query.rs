@@ -0,0 +1,110 @@
+use super::types::{Article, Tag};
+use super::{GqlResult, loaders, require_admin, state, viewer, visible};
+use crate::error::ErrorCode;
+use crate::models::article::list_article_models;
+use crate::models::search::{TmpSuggest, get_suggests_by_keyword};
+use crate::models::user::{UserPublic, find_user_by_id, list_users};
+use async_graphql::{Context, Object, Result};
+use blog_shared::export::split_tags;
+use std::collections::BTreeMap;
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// 文章列表，按发布日期倒序；管理员可见全部文章，其余只含已发布的文章
+    #[graphql(complexity = "20 * child_complexity")]
+    async fn articles(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "标题包含的关键词")] keyword: Option<String>,
+        #[graphql(desc = "只列出带此标签的文章")] tag: Option<String>,
+    ) -> Result<Vec<Article>> {
+        let admin = viewer(ctx).is_some_and(|v| v.is_admin());
+        let rows = list_article_models(&state(ctx).pool, !admin)
+            .await
+            .gql(ctx)?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|a| {
+                keyword
+                    .as_deref()
+                    .is_none_or(|k| a.title.as_deref().is_some_and(|title| title.contains(k)))
+            })
+            .filter(|a| {
+                tag.as_deref()
+                    .is_none_or(|t| split_tags(a.tags.as_deref()).iter().any(|x| x == t))
+            })
+            .map(Article)
+            .collect())
+    }
+
+    /// 获取文章，不存在或不可见时为 null
+    async fn article(&self, ctx: &Context<'_>, id: String) -> Result<Option<Article>> {
+        let article = loaders(ctx).articles.load_one(id).await.gql(ctx)?;
+        Ok(article.filter(|a| visible(ctx, a)).map(Article))
+    }
+
+    /// 可见文章的标签，按名称排序
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<Tag>> {
+        let admin = viewer(ctx).is_some_and(|v| v.is_admin());
+        let rows = list_article_models(&state(ctx).pool, !admin)
+            .await
+            .gql(ctx)?;
+
+        let mut counts: BTreeMap<String, i32> = BTreeMap::new();
+        for tag in rows.iter().flat_map(|a| split_tags(a.tags.as_deref())) {
+            *counts.entry(tag).or_default() += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(name, article_count)| Tag {
+                name,
+                article_count,
+            })
+            .collect())
+    }
+
+    /// 搜索建议，匹配已发布文章的标题；关键词为空时返回空列表
+    async fn search_suggestions(&self, ctx: &Context<'_>, q: String) -> Result<Vec<TmpSuggest>> {
+        let keyword = q.trim();
+        if keyword.is_empty() {
+            return Ok(Vec::new());
+        }
+        get_suggests_by_keyword(&state(ctx).pool, keyword)
+            .await
+            .gql(ctx)
+    }
+
+    /// 当前登录用户，未登录时为 null
+    async fn viewer(&self, ctx: &Context<'_>) -> Result<Option<UserPublic>> {
+        let Some(viewer) = viewer(ctx) else {
+            return Ok(None);
+        };
+        let user = find_user_by_id(&state(ctx).pool, viewer.claims.user_id.clone())
+            .await
+            .gql(ctx)?;
+        Ok(user.map(UserPublic::from))
+    }
+
+    /// 获取用户，仅管理员
+    async fn user(&self, ctx: &Context<'_>, id: String) -> Result<UserPublic> {
+        require_admin(ctx).await?;
+        let user = find_user_by_id(&state(ctx).pool, id).await.gql(ctx)?;
+        user.map(UserPublic::from)
+            .ok_or(ErrorCode::UserNotFound)
+            .gql(ctx)
+    }
+
+    /// 用户列表，仅管理员
+    #[graphql(complexity = "limit.max(1) as usize * child_complexity")]
+    async fn users(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] limit: i32,
+    ) -> Result<Vec<UserPublic>> {
+        require_admin(ctx).await?;
+        list_users(&state(ctx).pool, limit).await.gql(ctx)
+    }
+}
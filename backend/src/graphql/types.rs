@@ -0,0 +1,119 @@
+//! 输出类型：包装数据库模型，关联字段经 DataLoader 加载
+
+use super::{GqlResult, loaders, require_viewer, visible};
+use crate::models::{article::ArticleModel, comment};
+use async_graphql::{Context, Object, Result, SimpleObject};
+
+pub struct Article(pub ArticleModel);
+
+#[Object]
+impl Article {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    async fn title(&self) -> Option<&str> {
+        self.0.title.as_deref()
+    }
+
+    async fn summary(&self) -> Option<&str> {
+        self.0.summary.as_deref()
+    }
+
+    async fn content(&self) -> Option<&str> {
+        self.0.content.as_deref()
+    }
+
+    /// draft / published / archived
+    async fn status(&self) -> Option<&str> {
+        self.0.status.as_deref()
+    }
+
+    async fn views(&self) -> Option<i32> {
+        self.0.views
+    }
+
+    async fn created_at(&self) -> Option<&str> {
+        self.0.created_at.as_deref()
+    }
+
+    async fn updated_at(&self) -> Option<&str> {
+        self.0.update_at.as_deref()
+    }
+
+    async fn tags(&self) -> Vec<String> {
+        blog_shared::export::split_tags(self.0.tags.as_deref())
+    }
+
+    /// 文章评论，按时间倒序，需要登录
+    #[graphql(complexity = "10 * child_complexity")]
+    async fn comments(&self, ctx: &Context<'_>) -> Result<Vec<Comment>> {
+        require_viewer(ctx)?;
+        let rows = loaders(ctx)
+            .comments
+            .load_one(self.0.id.clone())
+            .await
+            .gql(ctx)?;
+        Ok(rows.unwrap_or_default().into_iter().map(Comment).collect())
+    }
+}
+
+pub struct Comment(pub comment::Comment);
+
+#[Object]
+impl Comment {
+    async fn id(&self) -> &str {
+        &self.0.comment_id
+    }
+
+    async fn article_id(&self) -> Option<&str> {
+        self.0.article_id.as_deref()
+    }
+
+    /// 评论者用户名
+    async fn author(&self) -> Option<&str> {
+        self.0.user.as_deref()
+    }
+
+    async fn content(&self) -> Option<&str> {
+        self.0.content.as_deref()
+    }
+
+    async fn created_at(&self) -> Option<&str> {
+        self.0.created_at.as_deref()
+    }
+
+    /// 回复的评论 id
+    async fn parent_id(&self) -> Option<&str> {
+        self.0.parent_id.as_deref()
+    }
+
+    async fn like_count(&self) -> i64 {
+        self.0.like_count.unwrap_or_default()
+    }
+
+    /// 当前用户是否点赞，未登录时为 false
+    async fn liked_by_me(&self, ctx: &Context<'_>) -> Result<bool> {
+        let Some(liked) = &loaders(ctx).liked else {
+            return Ok(false);
+        };
+        let res = liked.load_one(self.0.comment_id.clone()).await.gql(ctx)?;
+        Ok(res.unwrap_or_default())
+    }
+
+    /// 所属文章，对当前用户不可见时为 null
+    async fn article(&self, ctx: &Context<'_>) -> Result<Option<Article>> {
+        let Some(article_id) = self.0.article_id.clone() else {
+            return Ok(None);
+        };
+        let article = loaders(ctx).articles.load_one(article_id).await.gql(ctx)?;
+        Ok(article.filter(|a| visible(ctx, a)).map(Article))
+    }
+}
+
+/// 标签及其下可见文章的数量
+#[derive(SimpleObject)]
+pub struct Tag {
+    pub name: String,
+    pub article_count: i32,
+}
@@ -0,0 +1,549 @@
+//! 发信：
+//! - `Mailer`：发送一封邮件；`SmtpMailer` 通过 SMTP 发送，`FileMailer` 把邮件写成 .eml 文件（`mail.transport = "file"`，用于本地调试）
+//! - 每次发信时按已生效的 `mail` 配置创建 Mailer，配置热更新后立即生效
+//! - `send_confirmation`：订阅后发送确认邮件，点击其中的链接才算订阅成功
+//! - `notify_reply`：站内回复通知写入后，为开启了回复提醒的订阅者发送邮件
+//! - `send_digest`：把上一期之后发布的文章汇总成一期摘要发给订阅者；`spawn_digest_scheduler` 每隔 `digest_interval_hours` 小时自动发送
+//! - 摘要与回复邮件带 `List-Unsubscribe` 头，邮件客户端可一键退订
+
+pub mod template;
+
+use crate::config::{FeedsConfig, MailConfig};
+use crate::db::AppState;
+use crate::models::notification::Notification;
+use crate::models::subscriber::{
+    digest_recipients, digest_sent_within, record_digest, reply_recipient, undigested_articles,
+};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::message::header::{HeaderName, HeaderValue};
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// 检查是否到了发送摘要时间的间隔
+const DIGEST_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 待发送的邮件
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+    /// 退订链接，设置后附带 `List-Unsubscribe` 头
+    pub unsubscribe_url: Option<String>,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: &Mail) -> anyhow::Result<()>;
+}
+
+pub struct SmtpMailer {
+    from: Mailbox,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(cfg: &MailConfig) -> anyhow::Result<Self> {
+        let host = cfg.smtp_host.as_deref().context("未配置 mail.smtp_host")?;
+        let builder = match cfg.smtp_tls.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+        };
+        let mut builder = builder.port(cfg.smtp_port);
+        if let (Some(username), Some(password)) = (&cfg.smtp_username, &cfg.smtp_password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+        Ok(Self {
+            from: sender(cfg)?,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: &Mail) -> anyhow::Result<()> {
+        let message = build_message(&self.from, mail)?;
+        self.transport
+            .send(message)
+            .await
+            .with_context(|| format!("发送邮件到 {} 失败", mail.to))?;
+        Ok(())
+    }
+}
+
+/// 把邮件原文写入目录，每封一个 .eml 文件
+pub struct FileMailer {
+    from: Mailbox,
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(cfg: &MailConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            from: sender(cfg)?,
+            dir: PathBuf::from(&cfg.file_dir),
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: &Mail) -> anyhow::Result<()> {
+        let message = build_message(&self.from, mail)?;
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("创建目录 {} 失败", self.dir.display()))?;
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S"),
+            Uuid::now_v7()
+        ));
+        tokio::fs::write(&path, message.formatted())
+            .await
+            .with_context(|| format!("写入 {} 失败", path.display()))?;
+        tracing::info!("邮件已写入 {}", path.display());
+        Ok(())
+    }
+}
+
+/// 按 `mail.transport` 创建 Mailer
+pub fn from_config(cfg: &MailConfig) -> anyhow::Result<Box<dyn Mailer>> {
+    Ok(match cfg.transport.as_str() {
+        "file" => Box::new(FileMailer::new(cfg)?),
+        _ => Box::new(SmtpMailer::new(cfg)?),
+    })
+}
+
+fn sender(cfg: &MailConfig) -> anyhow::Result<Mailbox> {
+    let from = cfg.from.as_deref().context("未配置 mail.from")?;
+    from.parse()
+        .with_context(|| format!("mail.from 不是有效的发件人: {from}"))
+}
+
+fn build_message(from: &Mailbox, mail: &Mail) -> anyhow::Result<Message> {
+    let mut builder = Message::builder()
+        .from(from.clone())
+        .to(mail.to.parse().context("收件地址无效")?)
+        .subject(&mail.subject);
+    if let Some(url) = &mail.unsubscribe_url {
+        builder = builder
+            .raw_header(HeaderValue::new(
+                HeaderName::new_from_ascii_str("List-Unsubscribe"),
+                format!("<{url}>"),
+            ))
+            .raw_header(HeaderValue::new(
+                HeaderName::new_from_ascii_str("List-Unsubscribe-Post"),
+                "List-Unsubscribe=One-Click".into(),
+            ));
+    }
+    Ok(builder.multipart(MultiPart::alternative_plain_html(
+        mail.text.clone(),
+        mail.html.clone(),
+    ))?)
+}
+
+/// 生成邮件中链接所需的配置
+pub struct Links {
+    pub site: String,
+    site_url: String,
+    public_url: String,
+}
+
+impl Links {
+    pub fn new(mail: &MailConfig, feeds: &FeedsConfig) -> Self {
+        Self {
+            site: feeds.site_title.clone(),
+            site_url: feeds.site_url.trim_end_matches('/').to_string(),
+            public_url: mail.public_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn article(&self, id: &str) -> String {
+        format!("{}/article/{id}", self.site_url)
+    }
+
+    pub fn confirm(&self, token: &str) -> String {
+        format!(
+            "{}/api/v1/subscribers/confirm?token={token}",
+            self.public_url
+        )
+    }
+
+    pub fn unsubscribe(&self, token: &str) -> String {
+        format!(
+            "{}/api/v1/subscribers/unsubscribe?token={token}",
+            self.public_url
+        )
+    }
+}
+
+/// 已生效的发信与站点配置
+pub fn settings(state: &AppState) -> (MailConfig, FeedsConfig) {
+    let live = state.live_cfg.read().unwrap();
+    (live.mail.clone(), live.feeds.clone())
+}
+
+/// 发送确认订阅的邮件
+/// `username` 为申请订阅的登录用户，确认后关联到该账号
+pub async fn send_confirmation(
+    state: &AppState,
+    email: &str,
+    token: &str,
+    username: Option<&str>,
+) -> anyhow::Result<()> {
+    let (mail, feeds) = settings(state);
+    let links = Links::new(&mail, &feeds);
+    let rendered = template::confirm(&links.site, &links.confirm(token), username);
+    from_config(&mail)?
+        .send(&Mail {
+            to: email.to_string(),
+            subject: rendered.subject,
+            text: rendered.text,
+            html: rendered.html,
+            unsubscribe_url: None,
+        })
+        .await
+}
+
+/// 为回复通知的接收者发送邮件：未启用发信或接收者没有开启回复提醒时不发送
+/// 在后台发送，失败只记录日志
+pub fn notify_reply(state: &AppState, notification: &Notification) {
+    let (mail, feeds) = settings(state);
+    if !mail.enabled {
+        return;
+    }
+    let pool = state.pool.clone();
+    let notification = notification.clone();
+    tokio::spawn(async move {
+        if let Err(e) = send_reply(&pool, &mail, &feeds, &notification).await {
+            tracing::warn!("发送回复提醒邮件给 {} 失败: {e:#}", notification.user_id);
+        }
+    });
+}
+
+async fn send_reply(
+    pool: &SqlitePool,
+    mail: &MailConfig,
+    feeds: &FeedsConfig,
+    notification: &Notification,
+) -> anyhow::Result<()> {
+    let Some(recipient) = reply_recipient(pool, &notification.user_id).await? else {
+        return Ok(());
+    };
+    let links = Links::new(mail, feeds);
+    let unsubscribe_url = links.unsubscribe(&recipient.unsubscribe_token);
+    let article_url = notification
+        .article_id
+        .as_deref()
+        .map(|id| links.article(id));
+    let rendered = template::reply(
+        &links.site,
+        notification,
+        article_url.as_deref(),
+        &unsubscribe_url,
+    );
+    from_config(mail)?
+        .send(&Mail {
+            to: recipient.email,
+            subject: rendered.subject,
+            text: rendered.text,
+            html: rendered.html,
+            unsubscribe_url: Some(unsubscribe_url),
+        })
+        .await
+}
+
+/// 一期摘要的发送结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DigestReport {
+    /// 收录的文章数，为 0 时不发送邮件
+    pub articles: usize,
+    /// 发送成功的订阅者数
+    pub sent: usize,
+    pub failed: usize,
+}
+
+/// 发送一期摘要，收录上一期之后发布的文章
+/// 没有新文章时同样记为一期（不发邮件），下一期从此时重新计时；全部发送失败时不记录，文章留到下一期
+/// 同时只发送一期：后到的调用等前一期记录后再查询文章，不会重复收录
+pub async fn send_digest(state: &AppState) -> anyhow::Result<DigestReport> {
+    let (mail, feeds) = settings(state);
+    anyhow::ensure!(mail.enabled, "未启用发信");
+    let _guard = state.digest_lock.lock().await;
+
+    let articles = undigested_articles(&state.pool).await?;
+    let mut report = DigestReport {
+        articles: articles.len(),
+        sent: 0,
+        failed: 0,
+    };
+    if !articles.is_empty() {
+        let mailer = from_config(&mail)?;
+        let links = Links::new(&mail, &feeds);
+        for recipient in digest_recipients(&state.pool).await? {
+            let unsubscribe_url = links.unsubscribe(&recipient.unsubscribe_token);
+            let rendered = template::digest(
+                &links.site,
+                &articles,
+                |id| links.article(id),
+                &unsubscribe_url,
+            );
+            let res = mailer
+                .send(&Mail {
+                    to: recipient.email.clone(),
+                    subject: rendered.subject,
+                    text: rendered.text,
+                    html: rendered.html,
+                    unsubscribe_url: Some(unsubscribe_url),
+                })
+                .await;
+            match res {
+                Ok(()) => report.sent += 1,
+                Err(e) => {
+                    report.failed += 1;
+                    tracing::warn!("发送文章摘要给 {} 失败: {e:#}", recipient.email);
+                }
+            }
+        }
+        anyhow::ensure!(
+            report.sent > 0 || report.failed == 0,
+            "文章摘要全部发送失败（{} 位订阅者）",
+            report.failed
+        );
+    }
+
+    let ids: Vec<&str> = articles.iter().map(|a| a.id.as_str()).collect();
+    record_digest(&state.pool, &ids, report.sent).await?;
+    Ok(report)
+}
+
+/// 启动摘要定时任务：每小时检查一次，距上一期超过 `mail.digest_interval_hours` 小时则发送
+/// 发信配置可热更新，关闭后任务保留，重新开启即恢复
+pub fn spawn_digest_scheduler(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(DIGEST_CHECK_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            let (mail, _) = settings(&state);
+            if !mail.enabled || mail.digest_interval_hours == 0 {
+                continue;
+            }
+            match digest_sent_within(&state.pool, mail.digest_interval_hours).await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::error!("查询文章摘要记录失败: {e}");
+                    continue;
+                }
+            }
+            match send_digest(&state).await {
+                Ok(report) if report.articles > 0 => tracing::info!(
+                    "文章摘要已发送：{} 篇文章，成功 {}，失败 {}",
+                    report.articles,
+                    report.sent,
+                    report.failed
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::error!("发送文章摘要失败: {e:#}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::auth::keys::JwtKeys;
+    use crate::config::Config;
+    use crate::models::subscriber::{
+        NewSubscription, confirm_subscriber, upsert_pending_subscriber,
+    };
+    use base64::Engine;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::path::Path;
+
+    /// 已迁移的内存数据库，发信写入 `dir`
+    pub(crate) async fn mail_state(dir: &Path) -> AppState {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::MIGRATOR.run(&pool).await.unwrap();
+
+        let mut cfg = Config::default();
+        cfg.auth.jwt_allow_default_secret = true;
+        cfg.mail.enabled = true;
+        cfg.mail.transport = "file".into();
+        cfg.mail.file_dir = dir.to_string_lossy().into_owned();
+        cfg.mail.from = Some("Blog <noreply@example.com>".into());
+        cfg.mail.public_url = "https://api.example.com".into();
+        cfg.feeds.site_url = "https://blog.example.com".into();
+        let jwt = JwtKeys::from_config(&cfg).unwrap();
+        AppState::new(pool, cfg, jwt)
+    }
+
+    /// 写入目录的一封邮件
+    pub(crate) struct Sent {
+        /// 原文（头部与编码后的正文）
+        pub raw: String,
+        /// 解码后的纯文本正文
+        pub text: String,
+    }
+
+    /// 读取 `FileMailer` 写入的全部邮件，按文件名（即发送时间）排序
+    pub(crate) fn sent(dir: &Path) -> Vec<Sent> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<_> = entries.map(|e| e.unwrap().path()).collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| {
+                let raw = std::fs::read_to_string(path).unwrap();
+                let text = plain_text(&raw);
+                Sent { raw, text }
+            })
+            .collect()
+    }
+
+    /// 取出 text/plain 部分并按 Content-Transfer-Encoding 解码
+    fn plain_text(raw: &str) -> String {
+        let part = &raw[raw.find("Content-Type: text/plain").unwrap()..];
+        let (headers, body) = part.split_once("\r\n\r\n").unwrap();
+        let body = &body[..body.find("\r\n--").unwrap()];
+        let bytes = if headers.contains("Content-Transfer-Encoding: base64") {
+            base64::engine::general_purpose::STANDARD
+                .decode(body.replace("\r\n", ""))
+                .unwrap()
+        } else if headers.contains("Content-Transfer-Encoding: quoted-printable") {
+            let body = body.replace("=\r\n", "");
+            let mut bytes = Vec::new();
+            let mut rest = body.as_bytes();
+            while let Some((&b, tail)) = rest.split_first() {
+                if b == b'=' {
+                    let hex = std::str::from_utf8(&tail[..2]).unwrap();
+                    bytes.push(u8::from_str_radix(hex, 16).unwrap());
+                    rest = &tail[2..];
+                } else {
+                    bytes.push(b);
+                    rest = tail;
+                }
+            }
+            bytes
+        } else {
+            body.as_bytes().to_vec()
+        };
+        String::from_utf8(bytes).unwrap().replace("\r\n", "\n")
+    }
+
+    async fn add_subscriber(pool: &SqlitePool, email: &str, digest: bool, confirmed: bool) {
+        let confirm_token = format!("confirm-{email}");
+        upsert_pending_subscriber(
+            pool,
+            NewSubscription {
+                email,
+                pending_user_id: None,
+                digest,
+                replies: false,
+                confirm_token: &confirm_token,
+                unsubscribe_token: &format!("unsub-{email}"),
+            },
+        )
+        .await
+        .unwrap();
+        if confirmed {
+            confirm_subscriber(pool, &confirm_token).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_digests_send_each_article_once() {
+        let dir = std::env::temp_dir().join(format!("blog-mail-digest-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let state = mail_state(&dir).await;
+        for (id, title, status) in [
+            ("a1", "第一篇", "published"),
+            ("a2", "第二篇", "published"),
+            ("a3", "草稿", "draft"),
+        ] {
+            sqlx::query("INSERT INTO articles (id, title, status) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(title)
+                .bind(status)
+                .execute(&state.pool)
+                .await
+                .unwrap();
+        }
+        for (email, digest, confirmed) in [
+            ("one@example.com", true, true),
+            ("two@example.com", true, true),
+            ("pending@example.com", true, false),
+            ("replies-only@example.com", false, true),
+        ] {
+            add_subscriber(&state.pool, email, digest, confirmed).await;
+        }
+
+        // 同时触发两期：只有一期收录文章，另一期等它记录后查不到新文章
+        let (first, second) = tokio::join!(send_digest(&state), send_digest(&state));
+        let mut reports = [first.unwrap(), second.unwrap()].map(|r| (r.articles, r.sent, r.failed));
+        reports.sort();
+        assert_eq!(reports, [(0, 0, 0), (2, 2, 0)]);
+
+        let mails = sent(&dir);
+        let mut recipients: Vec<&str> = mails
+            .iter()
+            .map(|m| {
+                let to = m.raw.lines().find_map(|l| l.strip_prefix("To: ")).unwrap();
+                to.trim()
+            })
+            .collect();
+        recipients.sort();
+        assert_eq!(recipients, ["one@example.com", "two@example.com"]);
+        for mail in &mails {
+            let to = if mail.raw.contains("To: one@") {
+                "one"
+            } else {
+                "two"
+            };
+            for link in [
+                "https://blog.example.com/article/a1",
+                "https://blog.example.com/article/a2",
+            ] {
+                assert_eq!(mail.text.matches(link).count(), 1, "{to}: {link}");
+            }
+            assert!(!mail.text.contains("/article/a3"), "{to}: 草稿不收录");
+            let unsubscribe = format!(
+                "https://api.example.com/api/v1/subscribers/unsubscribe?token=unsub-{to}@example.com"
+            );
+            assert!(mail.text.contains(&unsubscribe), "{to}: 正文中的退订链接");
+            assert!(
+                mail.raw
+                    .contains(&format!("List-Unsubscribe: <{unsubscribe}>")),
+                "{to}: List-Unsubscribe 头"
+            );
+        }
+
+        // 文章已收录，下一期不再发送
+        let report = send_digest(&state).await.unwrap();
+        assert_eq!((report.articles, report.sent), (0, 0));
+        assert_eq!(sent(&dir).len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+//! 邮件模板，每封邮件同时生成纯文本与 HTML 两个版本
+
+use crate::models::article::ArticleModel;
+use crate::models::notification::Notification;
+use blog_shared::export::render::escape;
+
+/// 摘要中文章简介的最大字符数
+const SUMMARY_CHARS: usize = 140;
+
+pub struct Rendered {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// 确认订阅；`username` 为申请订阅的账号，确认后该账号的回复提醒会发到这个邮箱
+pub fn confirm(site: &str, confirm_url: &str, username: Option<&str>) -> Rendered {
+    let account = username
+        .map(|name| format!("确认后，账号「{name}」的评论回复提醒也会发送到这个邮箱。"))
+        .unwrap_or_default();
+    Rendered {
+        subject: format!("请确认订阅 {site}"),
+        text: format!(
+            "你好！\n\n有人使用这个邮箱订阅了 {site}。{account}请打开下面的链接确认订阅：\n\n{confirm_url}\n\n如果不是你本人操作，忽略这封邮件即可，不会再收到任何邮件。\n"
+        ),
+        html: layout(
+            site,
+            &format!(
+                "<p>你好！</p>\n<p>有人使用这个邮箱订阅了 {site}。{account}请点击下面的按钮确认订阅：</p>\n<p><a class=\"button\" href=\"{url}\">确认订阅</a></p>\n<p class=\"meta\">如果不是你本人操作，忽略这封邮件即可，不会再收到任何邮件。</p>\n",
+                site = escape(site),
+                account = escape(&account),
+                url = escape(confirm_url),
+            ),
+            None,
+        ),
+    }
+}
+
+/// 文章摘要；`article_url` 由文章 id 生成文章链接
+pub fn digest(
+    site: &str,
+    articles: &[ArticleModel],
+    article_url: impl Fn(&str) -> String,
+    unsubscribe_url: &str,
+) -> Rendered {
+    let mut text = format!("{site} 最近发布了 {} 篇文章：\n", articles.len());
+    let mut html = format!(
+        "<p>{} 最近发布了 {} 篇文章：</p>\n<ul class=\"posts\">\n",
+        escape(site),
+        articles.len()
+    );
+    for article in articles {
+        let title = article.title.as_deref().unwrap_or("无标题");
+        let url = article_url(&article.id);
+        let summary = summary(article);
+        text.push_str(&format!("\n{title}\n{url}\n"));
+        if !summary.is_empty() {
+            text.push_str(&format!("{summary}\n"));
+        }
+        html.push_str(&format!(
+            "<li><a href=\"{url}\">{title}</a><br>{summary}</li>\n",
+            url = escape(&url),
+            title = escape(title),
+            summary = escape(&summary),
+        ));
+    }
+    html.push_str("</ul>\n");
+    text.push_str(&format!(
+        "\n——\n不想再收到这类邮件？退订：{unsubscribe_url}\n"
+    ));
+
+    Rendered {
+        subject: format!("{site} 文章摘要：{} 篇新文章", articles.len()),
+        text,
+        html: layout(site, &html, Some(unsubscribe_url)),
+    }
+}
+
+/// 评论被回复
+pub fn reply(
+    site: &str,
+    notification: &Notification,
+    article_url: Option<&str>,
+    unsubscribe_url: &str,
+) -> Rendered {
+    let actor = notification.actor_name.as_deref().unwrap_or("有人");
+    let mut text = format!(
+        "{actor} 回复了你在 {site} 的评论：\n\n{}\n",
+        notification.message
+    );
+    let mut html = format!(
+        "<p>{actor} 回复了你在 {site} 的评论：</p>\n<blockquote>{message}</blockquote>\n",
+        actor = escape(actor),
+        site = escape(site),
+        message = escape(&notification.message),
+    );
+    if let Some(url) = article_url {
+        text.push_str(&format!("\n查看：{url}\n"));
+        html.push_str(&format!(
+            "<p><a class=\"button\" href=\"{}\">查看回复</a></p>\n",
+            escape(url)
+        ));
+    }
+    text.push_str(&format!(
+        "\n——\n不想再收到这类邮件？退订：{unsubscribe_url}\n"
+    ));
+
+    Rendered {
+        subject: format!("{actor} 回复了你的评论"),
+        text,
+        html: layout(site, &html, Some(unsubscribe_url)),
+    }
+}
+
+/// 确认订阅、退订链接打开后显示的页面
+pub fn page(site: &str, title: &str, message: &str) -> String {
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"zh-CN\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title} - {site}</title>
+</head>
+<body style=\"font-family: sans-serif; max-width: 32rem; margin: 4rem auto; padding: 0 1rem; color: #222\">
+<h1>{title}</h1>
+<p>{message}</p>
+</body>
+</html>
+",
+        site = escape(site),
+        title = escape(title),
+        message = escape(message),
+    )
+}
+
+/// 邮件骨架，样式内联以兼容邮件客户端
+fn layout(site: &str, body: &str, unsubscribe_url: Option<&str>) -> String {
+    let footer = match unsubscribe_url {
+        Some(url) => format!(
+            "<p class=\"meta\">你收到这封邮件是因为订阅了 {}。<a href=\"{}\">退订</a></p>",
+            escape(site),
+            escape(url)
+        ),
+        None => String::new(),
+    };
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"zh-CN\">
+<head>
+<meta charset=\"utf-8\">
+<style>
+body {{ font-family: sans-serif; line-height: 1.6; color: #222; max-width: 36rem; margin: 0 auto; padding: 1rem; }}
+h1 {{ font-size: 1.25rem; }}
+a {{ color: #2563eb; }}
+a.button {{ display: inline-block; padding: .5rem 1rem; background: #2563eb; color: #fff; text-decoration: none; border-radius: 4px; }}
+ul.posts li {{ margin-bottom: 1rem; }}
+blockquote {{ margin: 0; padding-left: 1rem; border-left: 3px solid #ddd; color: #555; }}
+.meta {{ color: #888; font-size: .875rem; }}
+</style>
+</head>
+<body>
+<h1>{site}</h1>
+{body}<hr>
+{footer}
+</body>
+</html>
+",
+        site = escape(site),
+    )
+}
+
+/// 文章简介：优先使用摘要字段，否则截取正文开头
+fn summary(article: &ArticleModel) -> String {
+    let source = article
+        .summary
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+        .or(article.content.as_deref())
+        .unwrap_or_default();
+    let flat = source.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut chars = flat.chars();
+    let head: String = chars.by_ref().take(SUMMARY_CHARS).collect();
+    match chars.next() {
+        Some(_) => format!("{head}…"),
+        None => head,
+    }
+}
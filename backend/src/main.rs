@@ -1,45 +1,111 @@
 //! 程序入口：
-//! - 加载环境与配置
+//! - 解析命令行，分层加载并校验配置（可 `--print-config` 查看）
+//! - 管理子命令（user / article / db / token / migrate）交给 `cli` 执行后退出
+//! - `openapi` 子命令不需要配置，解析命令行后直接执行
 //! - 初始化日志
-//! - 建立数据库连接并执行迁移
-//! - 构建路由并启动 HTTP 服务器
+//! - 应用暂存的数据库恢复，建立数据库连接并执行迁移，启动定时备份、webhook 投递与邮件摘要
+//! - 首次启动时引导创建超级管理员
+//! - 构建路由（含限流等中间件，启用 `graphql` feature 时挂载 GraphQL 接口）并启动 HTTP 服务器
 
 mod auth;
+mod backup;
+mod cli;
 mod config;
 mod db;
 mod error;
+mod events;
+mod export;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod import;
+mod mail;
+mod middleware;
 mod models;
 mod routes;
+mod setup;
+mod sync;
+mod validate;
+mod webhooks;
 
-use crate::config::Config;
+use crate::auth::keys::JwtKeys;
+use crate::cli::{Cli, Command, USAGE};
+use crate::config::{ConfigLoader, reload};
 use crate::db::{AppState, new_pool, run_migrations};
 use crate::routes::create_router;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
 
-    // 配置“日志收集器”（Subscriber），初始化 tracing 日志（支持 RUST_LOG 环境变量），复用性非常高
+    let cli = match Cli::parse() {
+        Ok(cli) if cli.help => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        Ok(cli) => cli,
+        Err(msg) => {
+            eprintln!("{msg}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Command::OpenApi { out, check } = &cli.command {
+        return cli::openapi::run(out.as_deref(), check.as_deref());
+    }
+
+    // 配置“日志收集器”（Subscriber），先用 RUST_LOG 初始化，加载配置后切换为 log.level
+    // 过滤器包在 reload 层里，配置热更新时可以直接替换
+    let (filter, log_handle) = tracing_subscriber::reload::Layer::new(
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    );
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(filter)
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let cfg = Config::from_env();
-    let pool = new_pool(&cfg.database_url).await?;
+    // 配置错误在这里一次性报告并退出
+    let loader = ConfigLoader::new(&cli);
+    let cfg = loader.load()?;
+    if cli.print_config {
+        print!("{}", cfg.redacted().to_toml());
+        return Ok(());
+    }
+    if !matches!(cli.command, Command::Serve) {
+        // 管理命令只输出警告以上的日志，避免和命令输出混在一起
+        log_handle.reload(EnvFilter::new("warn"))?;
+        return cli::run(cli.command, &cfg).await;
+    }
+    log_handle.reload(EnvFilter::new(&cfg.log.level))?;
+
+    // 密钥配置不安全时在连接数据库前拒绝启动
+    let jwt = JwtKeys::from_config(&cfg)?;
+    let restored = backup::apply_pending(&cfg.database).await?;
+    let pool = new_pool(&cfg.database).await?;
     run_migrations(&pool).await?;
+    if restored {
+        backup::record_applied(&pool).await?;
+    }
 
-    let state = Arc::new(AppState::new(pool, cfg.clone()));
+    let state = Arc::new(AppState::new(pool, cfg.clone(), jwt));
+    setup::bootstrap(&state).await?;
+    reload::spawn(loader, state.clone(), log_handle);
+    backup::spawn_scheduler(state.clone());
+    webhooks::spawn_worker(state.clone());
+    mail::spawn_digest_scheduler(state.clone());
 
     let app = create_router(state.clone());
 
-    let addr = SocketAddr::new(cfg.host.parse()?, cfg.port);
+    let addr = SocketAddr::new(cfg.server.host.parse()?, cfg.server.port);
     let listener = tokio::net::TcpListener::bind(addr).await?;
     tracing::info!("🚀 listening on http://{}", addr);
-    axum::serve(listener, app).await?;
+    // 限流需要客户端地址
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
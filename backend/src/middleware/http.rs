@@ -0,0 +1,165 @@
+//! HTTP 层通用中间件：
+//! - 按配置放行跨域来源/方法，可选携带凭据
+//! - 默认安全响应头（CSP、nosniff、Referrer-Policy，TLS 部署时附带 HSTS）
+//! - 请求体大小上限与单请求超时
+//! - 生成/透传 `x-request-id`，并写入 tracing span
+//! - 错误响应按 `Accept-Language` 本地化，并附带 request id 作为关联 id
+
+use crate::config::HttpConfig;
+use crate::error::{ErrorReport, Lang};
+use axum::{
+    Router,
+    body::Body,
+    extract::{DefaultBodyLimit, Request},
+    http::{HeaderName, HeaderValue, Method, header},
+    middleware::{Next, from_fn},
+    response::Response,
+};
+use std::time::Duration;
+use tower_http::{
+    cors::{AllowHeaders, AllowOrigin, Any, CorsLayer},
+    limit::RequestBodyLimitLayer,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    set_header::SetResponseHeaderLayer,
+    timeout::TimeoutLayer,
+    trace::TraceLayer,
+};
+
+const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// 为路由套上全部 HTTP 层中间件
+/// 外层到内层：request id → trace → CORS → 安全头 → 超时 → 请求体上限 → 错误本地化
+pub fn apply(router: Router, cfg: &HttpConfig) -> Router {
+    let router = router
+        .layer(from_fn(localize_errors))
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(cfg.max_body_bytes))
+        .layer(TimeoutLayer::new(Duration::from_secs(
+            cfg.request_timeout_secs,
+        )));
+
+    security_headers(router, cfg)
+        .layer(cors(cfg))
+        .layer(TraceLayer::new_for_http().make_span_with(|req: &Request| {
+            let request_id = req
+                .headers()
+                .get(&REQUEST_ID)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("-");
+            tracing::info_span!(
+                "request",
+                method = %req.method(),
+                uri = %req.uri(),
+                request_id = %request_id,
+            )
+        }))
+        .layer(PropagateRequestIdLayer::new(REQUEST_ID))
+        .layer(SetRequestIdLayer::new(REQUEST_ID, MakeRequestUuid))
+}
+
+/// 按请求的语言与 request id 重新生成 `AppError` 的响应体
+async fn localize_errors(req: Request, next: Next) -> Response {
+    let lang = Lang::negotiate(
+        req.headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok()),
+    );
+    let request_id = req
+        .headers()
+        .get(&REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let mut response = next.run(req).await;
+    let Some(report) = response.extensions_mut().remove::<ErrorReport>() else {
+        return response;
+    };
+
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_LANGUAGE,
+        HeaderValue::from_static(lang.tag()),
+    );
+    Response::from_parts(parts, Body::from(report.body(lang, request_id.as_deref())))
+}
+
+/// 跨域策略：`*` 放行任意来源；凭据模式下必须列出具体来源
+fn cors(cfg: &HttpConfig) -> CorsLayer {
+    let methods: Vec<Method> = cfg
+        .cors_allowed_methods
+        .iter()
+        .filter_map(|m| m.to_uppercase().parse().ok())
+        .collect();
+
+    let layer = CorsLayer::new()
+        .allow_methods(methods)
+        .allow_headers(AllowHeaders::mirror_request())
+        .expose_headers([
+            REQUEST_ID,
+            header::RETRY_AFTER,
+            HeaderName::from_static("ratelimit-limit"),
+            HeaderName::from_static("ratelimit-remaining"),
+            HeaderName::from_static("ratelimit-reset"),
+        ]);
+
+    // 校验阶段已保证 `*` 不会与凭据同时出现
+    if cfg.cors_allowed_origins.iter().any(|o| o == "*") {
+        return layer.allow_origin(Any);
+    }
+
+    let origins: Vec<HeaderValue> = cfg
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|o| match HeaderValue::from_str(o) {
+            Ok(v) => Some(v),
+            Err(_) => {
+                tracing::warn!("跨域来源 {} 无效，已跳过", o);
+                None
+            }
+        })
+        .collect();
+
+    layer
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_credentials(cfg.cors_allow_credentials)
+}
+
+/// 默认安全响应头，handler 已设置的同名头不覆盖
+fn security_headers(router: Router, cfg: &HttpConfig) -> Router {
+    let mut router = router
+        .layer(SetResponseHeaderLayer::if_not_present(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        ))
+        .layer(SetResponseHeaderLayer::if_not_present(
+            header::REFERRER_POLICY,
+            HeaderValue::from_static("no-referrer"),
+        ))
+        .layer(SetResponseHeaderLayer::if_not_present(
+            header::X_FRAME_OPTIONS,
+            HeaderValue::from_static("DENY"),
+        ));
+
+    // 校验阶段已保证 CSP 是合法的头部值，置空表示不发送
+    if let Ok(csp) = HeaderValue::from_str(&cfg.content_security_policy)
+        && !cfg.content_security_policy.is_empty()
+    {
+        router = router.layer(SetResponseHeaderLayer::if_not_present(
+            header::CONTENT_SECURITY_POLICY,
+            csp,
+        ));
+    }
+
+    if cfg.behind_tls {
+        let hsts =
+            HeaderValue::from_str(&format!("max-age={}; includeSubDomains", cfg.hsts_max_age))
+                .expect("hsts header is ascii");
+        router = router.layer(SetResponseHeaderLayer::if_not_present(
+            header::STRICT_TRANSPORT_SECURITY,
+            hsts,
+        ));
+    }
+
+    router
+}
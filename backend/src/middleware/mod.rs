@@ -0,0 +1,6 @@
+//! middleware/ 全局 HTTP 中间件：
+//! - rate_limit：按用户 / IP 的令牌桶限流
+//! - http：跨域、安全响应头、请求体上限、超时与 request id
+
+pub mod http;
+pub mod rate_limit;
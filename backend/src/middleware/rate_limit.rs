@@ -0,0 +1,418 @@
+//! 令牌桶限流：
+//! - 已登录请求按用户计数，有效的个人访问令牌按令牌计数，其余按客户端 IP 计数
+//! - 只有直连地址属于受信任代理时才从 `X-Forwarded-For` 中取客户端 IP
+//! - 按路由选择策略：登录/注册/评论等严格，读接口宽松
+//! - 响应带 `RateLimit-*` 头，超限返回 429 与 `Retry-After`
+//! - 计数存储通过 `RateLimitStore` 抽象，默认使用进程内存
+
+use crate::auth::{api_token, decode_token};
+use crate::config::{ProxyNet, RateLimitConfig, RatePolicy};
+use crate::db::AppState;
+use crate::error::AppError;
+use crate::models::api_token::find_active_token_by_hash;
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue, Method, header::AUTHORIZATION},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+/// 一次计数的结果
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// 桶重新装满所需秒数
+    pub reset_secs: u64,
+    /// 被拒绝时，距离下一个可用令牌的秒数
+    pub retry_after_secs: u64,
+}
+
+/// 限流计数存储，可替换为 Redis 等外部实现以便多实例共享
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// 尝试从 `key` 对应的桶中取一个令牌
+    async fn acquire(&self, key: &str, policy: RatePolicy) -> Decision;
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// 进程内存中的令牌桶
+#[derive(Default)]
+pub struct MemoryStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+/// 桶数量超过该值时清理已装满的桶
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[async_trait]
+impl RateLimitStore for MemoryStore {
+    async fn acquire(&self, key: &str, policy: RatePolicy) -> Decision {
+        let capacity = f64::from(policy.requests);
+        let rate = capacity / policy.per_secs as f64;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() > PRUNE_THRESHOLD {
+            // 空闲到已装满的桶与新建桶等价，可以直接丢弃
+            buckets.retain(|_, b| {
+                b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < capacity
+            });
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        Decision {
+            allowed,
+            limit: policy.requests,
+            remaining: bucket.tokens.floor() as u32,
+            reset_secs: ((capacity - bucket.tokens) / rate).ceil() as u64,
+            retry_after_secs: if allowed {
+                0
+            } else {
+                ((1.0 - bucket.tokens) / rate).ceil().max(1.0) as u64
+            },
+        }
+    }
+}
+
+/// 限流器：策略 + 存储，策略可在运行时热更新
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, store: Arc<dyn RateLimitStore>) -> Self {
+        Self {
+            config: RwLock::new(config),
+            store,
+        }
+    }
+
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// 替换策略，已有的桶按新容量继续计数
+    pub fn set_config(&self, config: RateLimitConfig) {
+        *self.config.write().unwrap() = config;
+    }
+}
+
+impl RateLimitConfig {
+    /// 按方法与路径选择策略，返回 (策略名, 策略)
+    fn policy_for(&self, method: &Method, path: &str) -> (&'static str, RatePolicy) {
+        // 旧路由与 `/api/v1` 下的对应路由使用同一策略
+        let path = path.strip_prefix("/api/v1").unwrap_or(path);
+        let strict = *method == Method::POST
+            && (matches!(
+                path,
+                "/api/login"
+                    | "/api/register"
+                    | "/api/comment"
+                    | "/setup"
+                    | "/auth/login"
+                    | "/auth/register"
+                    | "/subscribers"
+            ) || (path.starts_with("/articles/") && path.ends_with("/comments"))
+                || (path.starts_with("/oidc/") && path.ends_with("/callback")));
+
+        if strict {
+            ("strict", self.strict)
+        } else if *method == Method::GET || *method == Method::HEAD {
+            ("read", self.read)
+        } else {
+            ("write", self.write)
+        }
+    }
+}
+
+/// 限流中间件
+pub async fn rate_limit(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    let limiter = &state.rate_limiter;
+    let config = limiter.config();
+    if !config.enabled || req.method() == Method::OPTIONS {
+        return next.run(req).await;
+    }
+
+    let (name, policy) = config.policy_for(req.method(), req.uri().path());
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let client = client_key(&state, &config, req.headers(), peer).await;
+    let decision = limiter
+        .store
+        .acquire(&format!("{name}:{client}"), policy)
+        .await;
+
+    let mut response = if decision.allowed {
+        next.run(req).await
+    } else {
+        tracing::warn!("rate limited: {} on {} {}", client, name, req.uri().path());
+        AppError::TooManyRequests(decision.retry_after_secs).into_response()
+    };
+
+    let headers = response.headers_mut();
+    insert_header(headers, "ratelimit-limit", decision.limit);
+    insert_header(headers, "ratelimit-remaining", decision.remaining);
+    insert_header(headers, "ratelimit-reset", decision.reset_secs);
+    if let Ok(v) = HeaderValue::from_str(&format!("{};w={}", policy.requests, policy.per_secs)) {
+        headers.insert("ratelimit-policy", v);
+    }
+    response
+}
+
+fn insert_header(headers: &mut HeaderMap, name: &'static str, value: impl Into<u64>) {
+    headers.insert(name, HeaderValue::from(value.into()));
+}
+
+/// 计数主体：有效登录 token 按用户，有效的个人访问令牌按令牌，否则按 IP
+///
+/// 无效的令牌一律按 IP 计数，否则每次换一个伪造的令牌就能得到一个新桶
+async fn client_key(
+    state: &AppState,
+    config: &RateLimitConfig,
+    headers: &HeaderMap,
+    peer: Option<IpAddr>,
+) -> String {
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|hv| hv.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    if let Some(token) = bearer {
+        if token.starts_with(api_token::TOKEN_PREFIX) {
+            if let Ok(Some(record)) =
+                find_active_token_by_hash(&state.pool, &api_token::hash(token)).await
+            {
+                return format!("token:{}", record.id);
+            }
+        } else if let Ok(claims) = decode_token(state, token) {
+            return format!("user:{}", claims.user_id);
+        }
+    }
+
+    match peer {
+        Some(peer) => format!("ip:{}", client_ip(&config.trusted_proxies, peer, headers)),
+        None => "ip:unknown".into(),
+    }
+}
+
+/// 客户端 IP：直连地址不是受信任的代理时直接使用；
+/// 否则从 `X-Forwarded-For` 最右侧向左跳过受信任的代理，取第一个不受信任的地址。
+/// 左侧的条目由客户端任意填写，不能直接采用
+fn client_ip(proxies: &[ProxyNet], peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+    let trusted = |ip: &IpAddr| proxies.iter().any(|p| p.contains(ip));
+    if !trusted(&peer) {
+        return peer;
+    }
+
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .collect();
+    if hops.is_empty() {
+        return headers
+            .get("x-real-ip")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(peer);
+    }
+
+    let mut client = peer;
+    for hop in hops.iter().rev() {
+        // 无法解析的条目之后都不可信，取其右侧最近的地址
+        let Ok(ip) = hop.parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !trusted(&ip) {
+            break;
+        }
+    }
+    client
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxies(nets: &[&str]) -> Vec<ProxyNet> {
+        nets.iter().map(|n| n.parse().unwrap()).collect()
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn proxy_nets_accept_cidr_and_bare_addresses() {
+        let nets = proxies(&["10.0.0.0/8", "127.0.0.1", "::1"]);
+        assert!(nets[0].contains(&ip("10.1.2.3")));
+        assert!(!nets[0].contains(&ip("11.0.0.1")));
+        assert!(nets[1].contains(&ip("127.0.0.1")));
+        assert!(!nets[1].contains(&ip("127.0.0.2")));
+        assert!(nets[2].contains(&ip("::1")));
+        assert!("10.0.0.0/33".parse::<ProxyNet>().is_err());
+        assert!("proxy.local".parse::<ProxyNet>().is_err());
+    }
+
+    #[test]
+    fn forwarded_headers_are_ignored_from_untrusted_peers() {
+        let h = headers(&[("x-forwarded-for", "1.1.1.1"), ("x-real-ip", "2.2.2.2")]);
+        assert_eq!(client_ip(&[], ip("9.9.9.9"), &h), ip("9.9.9.9"));
+        let nets = proxies(&["10.0.0.0/8"]);
+        assert_eq!(client_ip(&nets, ip("9.9.9.9"), &h), ip("9.9.9.9"));
+    }
+
+    #[test]
+    fn takes_the_rightmost_untrusted_hop() {
+        let nets = proxies(&["10.0.0.0/8"]);
+        // 客户端自行伪造了 1.1.1.1，真实地址由代理追加在右侧
+        let h = headers(&[("x-forwarded-for", "1.1.1.1, 3.3.3.3, 10.0.0.2")]);
+        assert_eq!(client_ip(&nets, ip("10.0.0.1"), &h), ip("3.3.3.3"));
+        // 多个头部按出现顺序拼接
+        let h = headers(&[
+            ("x-forwarded-for", "1.1.1.1"),
+            ("x-forwarded-for", "3.3.3.3"),
+        ]);
+        assert_eq!(client_ip(&nets, ip("10.0.0.1"), &h), ip("3.3.3.3"));
+        // 全部为受信任代理时取最左侧
+        let h = headers(&[("x-forwarded-for", "10.0.0.3, 10.0.0.2")]);
+        assert_eq!(client_ip(&nets, ip("10.0.0.1"), &h), ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn stops_at_unparsable_hops() {
+        let nets = proxies(&["10.0.0.0/8"]);
+        let h = headers(&[("x-forwarded-for", "1.1.1.1, garbage, 10.0.0.2")]);
+        assert_eq!(client_ip(&nets, ip("10.0.0.1"), &h), ip("10.0.0.2"));
+        let h = headers(&[("x-forwarded-for", "garbage")]);
+        assert_eq!(client_ip(&nets, ip("10.0.0.1"), &h), ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn falls_back_to_x_real_ip() {
+        let nets = proxies(&["127.0.0.1"]);
+        let h = headers(&[("x-real-ip", "2.2.2.2")]);
+        assert_eq!(client_ip(&nets, ip("127.0.0.1"), &h), ip("2.2.2.2"));
+        let h = headers(&[("x-real-ip", "not-an-ip")]);
+        assert_eq!(client_ip(&nets, ip("127.0.0.1"), &h), ip("127.0.0.1"));
+    }
+
+    const POLICY: RatePolicy = RatePolicy {
+        requests: 3,
+        per_secs: 60,
+    };
+
+    #[tokio::test]
+    async fn bucket_rejects_once_empty() {
+        let store = MemoryStore::default();
+        for remaining in [2, 1, 0] {
+            let d = store.acquire("ip:1.1.1.1", POLICY).await;
+            assert!(d.allowed);
+            assert_eq!(
+                (d.limit, d.remaining, d.retry_after_secs),
+                (3, remaining, 0)
+            );
+        }
+        let d = store.acquire("ip:1.1.1.1", POLICY).await;
+        assert!(!d.allowed);
+        // 每 20 秒补充一个令牌
+        assert_eq!((d.remaining, d.retry_after_secs, d.reset_secs), (0, 20, 60));
+
+        // 各个键互不影响
+        assert!(store.acquire("ip:2.2.2.2", POLICY).await.allowed);
+    }
+
+    #[tokio::test]
+    async fn bucket_refills_over_time_up_to_capacity() {
+        let store = MemoryStore::default();
+        for _ in 0..3 {
+            store.acquire("user:a", POLICY).await;
+        }
+        let rewind = |secs| {
+            let mut buckets = store.buckets.lock().unwrap();
+            let bucket = buckets.get_mut("user:a").unwrap();
+            bucket.updated -= std::time::Duration::from_secs(secs);
+        };
+
+        rewind(20);
+        let d = store.acquire("user:a", POLICY).await;
+        assert!(d.allowed);
+        assert_eq!(d.remaining, 0);
+
+        // 空闲再久也不超过容量
+        rewind(3600);
+        let d = store.acquire("user:a", POLICY).await;
+        assert!(d.allowed);
+        assert_eq!(d.remaining, 2);
+    }
+
+    #[test]
+    fn policy_for_routes() {
+        let config = RateLimitConfig::default();
+        let cases = [
+            (Method::POST, "/api/v1/auth/login", "strict"),
+            (Method::POST, "/api/login", "strict"),
+            (Method::POST, "/api/v1/auth/register", "strict"),
+            (Method::POST, "/api/v1/articles/a1/comments", "strict"),
+            (Method::POST, "/api/v1/oidc/github/callback", "strict"),
+            (Method::POST, "/api/v1/subscribers", "strict"),
+            (Method::POST, "/api/v1/setup", "strict"),
+            (Method::POST, "/setup", "strict"),
+            (Method::GET, "/api/v1/auth/login", "read"),
+            (Method::GET, "/api/v1/articles", "read"),
+            (Method::HEAD, "/api/v1/articles/a1", "read"),
+            (Method::POST, "/api/v1/articles", "write"),
+            (Method::PUT, "/api/v1/articles/a1", "write"),
+            (Method::DELETE, "/api/v1/articles/a1/comments", "write"),
+            (Method::POST, "/api/v1/subscribers/digest", "write"),
+        ];
+        for (method, path, expected) in cases {
+            let (name, policy) = config.policy_for(&method, path);
+            assert_eq!(name, expected, "{method} {path}");
+            let expected = match expected {
+                "strict" => config.strict,
+                "read" => config.read,
+                _ => config.write,
+            };
+            assert_eq!(policy, expected, "{method} {path}");
+        }
+    }
+}
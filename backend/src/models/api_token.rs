@@ -0,0 +1,132 @@
+//! 个人访问令牌模型与持久化操作
+
+use serde::Serialize;
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// 令牌记录（不含哈希，可直接返回给前端）
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct ApiToken {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: String,
+    pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub created_at: Option<String>,
+}
+
+const COLUMNS: &str =
+    "id, user_id, name, token_prefix, scopes, expires_at, last_used_at, revoked_at, created_at";
+
+/// 新建令牌，`expires_in_days` 为空表示永不过期
+pub async fn insert_api_token(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+    name: &str,
+    token_hash: &str,
+    token_prefix: &str,
+    scopes: &str,
+    expires_in_days: Option<i64>,
+) -> Result<ApiToken, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    sqlx::query_as::<_, ApiToken>(&format!(
+        r#"
+        INSERT INTO api_tokens (id, user_id, name, token_hash, token_prefix, scopes, expires_at)
+        VALUES (?, ?, ?, ?, ?, ?,
+            CASE WHEN ? IS NULL THEN NULL ELSE datetime('now', '+' || ? || ' days') END)
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(&id)
+    .bind(user_id)
+    .bind(name)
+    .bind(token_hash)
+    .bind(token_prefix)
+    .bind(scopes)
+    .bind(expires_in_days)
+    .bind(expires_in_days)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// 通过哈希查找仍然有效（未撤销、未过期）的令牌
+pub async fn find_active_token_by_hash(
+    pool: &SqlitePool,
+    token_hash: &str,
+) -> Result<Option<ApiToken>, sqlx::Error> {
+    sqlx::query_as::<_, ApiToken>(&format!(
+        r#"
+        SELECT {COLUMNS} FROM api_tokens
+        WHERE token_hash = ?
+          AND revoked_at IS NULL
+          AND (expires_at IS NULL OR expires_at > datetime('now'))
+        LIMIT 1
+        "#
+    ))
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_api_token_by_id(
+    pool: &SqlitePool,
+    id: &str,
+) -> Result<Option<ApiToken>, sqlx::Error> {
+    sqlx::query_as::<_, ApiToken>(&format!(
+        r#"SELECT {COLUMNS} FROM api_tokens WHERE id = ? LIMIT 1"#
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// 用户的全部令牌（含已撤销、已过期），按创建时间倒序
+pub async fn list_api_tokens_by_user(
+    pool: &SqlitePool,
+    user_id: &str,
+) -> Result<Vec<ApiToken>, sqlx::Error> {
+    sqlx::query_as::<_, ApiToken>(&format!(
+        r#"SELECT {COLUMNS} FROM api_tokens WHERE user_id = ? ORDER BY created_at DESC"#
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// 记录最近一次使用时间
+pub async fn touch_api_token(pool: &SqlitePool, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"UPDATE api_tokens SET last_used_at = datetime('now') WHERE id = ?"#)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 撤销令牌，已撤销的令牌保持原撤销时间
+pub async fn revoke_api_token(conn: &mut SqliteConnection, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE api_tokens SET revoked_at = datetime('now') WHERE id = ? AND revoked_at IS NULL"#,
+    )
+    .bind(id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// 删除用户时撤销其全部令牌
+pub async fn revoke_api_tokens_by_user(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE api_tokens SET revoked_at = datetime('now') WHERE user_id = ? AND revoked_at IS NULL"#,
+    )
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
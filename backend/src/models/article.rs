@@ -1,15 +1,16 @@
 use chrono::Local;
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::{
-    error::AppError,
+    error::{AppError, ErrorCode},
     routes::articles::{GetArticlesParams, NewArticle, NewStatus},
 };
 
-#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, ToSchema)]
 pub struct ArticleModel {
     pub id: String, // 如果数据库的字段可为NULL，rust的字段也必须要可为None
     pub title: Option<String>,
@@ -35,7 +36,7 @@ impl From<ArticleModel> for NewArticle {
     }
 }
 
-#[derive(Debug, Clone, FromRow, Serialize)]
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
 pub struct PubArticles {
     pub id: String,
     pub title: String,
@@ -93,7 +94,7 @@ pub async fn get_articles(
 
 /// 新增文章
 pub async fn post_article(
-    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
     new: &NewArticle,
 ) -> Result<ArticleModel, sqlx::Error> {
     let id = Uuid::now_v7().to_string();
@@ -117,7 +118,7 @@ pub async fn post_article(
     .bind(create_at)
     .bind(status)
     .bind(&new.tags) // Option类型数据库会自己处理空值逻辑，没有的话就存NULL
-    .execute(pool) //执行语句，并 等待一行结果
+    .execute(&mut *conn) //执行语句，并 等待一行结果
     .await
     .map_err(|e| {
         eprintln!("DB error: {:?}", e);
@@ -126,7 +127,7 @@ pub async fn post_article(
 
     sqlx::query_as::<_, ArticleModel>(r#"SELECT * FROM articles WHERE id = ?"#)
         .bind(&id)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await
 }
 
@@ -143,15 +144,18 @@ pub async fn find_article_by_id(
 }
 
 /// 删除文章
-pub async fn delete_article_by_id(pool: &SqlitePool, id: &str) -> Result<StatusCode, AppError> {
+pub async fn delete_article_by_id(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<StatusCode, AppError> {
     // 使用query / execute 代替 query_as::<> / fetch_*
     let res = sqlx::query(r#"DELETE FROM articles where id = ?"#)
         .bind(id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
     if res.rows_affected() == 0 {
-        return Err(AppError::NotFound);
+        return Err(ErrorCode::ArticleNotFound.into());
     }
 
     Ok(StatusCode::NO_CONTENT)
@@ -159,7 +163,7 @@ pub async fn delete_article_by_id(pool: &SqlitePool, id: &str) -> Result<StatusC
 
 /// 修改文章
 pub async fn put_article_by_id(
-    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
     id: &str,
     new: NewArticle,
 ) -> Result<ArticleModel, AppError> {
@@ -187,13 +191,13 @@ pub async fn put_article_by_id(
         "draft",
         id
     )
-    .execute(pool)
+    .execute(&mut *conn)
     .await?;
 
     Ok(
         sqlx::query_as::<_, ArticleModel>(r#"SELECT * FROM articles WHERE id = ?"#)
             .bind(id)
-            .fetch_one(pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 eprintln!("DB select error: {:?}", e);
@@ -204,7 +208,7 @@ pub async fn put_article_by_id(
 
 /// 更变文章状态
 pub async fn patch_article_by_id(
-    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
     id: &str,
     new: NewStatus,
 ) -> Result<ArticleModel, AppError> {
@@ -216,7 +220,7 @@ pub async fn patch_article_by_id(
     )
     .bind(&new.toggle)
     .bind(id)
-    .execute(pool)
+    .execute(&mut *conn)
     .await
     .map_err(|e| {
         eprintln!("DB update error: {:?}", e);
@@ -226,7 +230,7 @@ pub async fn patch_article_by_id(
     Ok(
         sqlx::query_as::<_, ArticleModel>(r#"SELECT * FROM articles WHERE id = ?"#)
             .bind(id)
-            .fetch_one(pool)
+            .fetch_one(&mut *conn)
             .await
             .map_err(|e| {
                 eprintln!("DB select error: {:?}", e);
@@ -234,3 +238,87 @@ pub async fn patch_article_by_id(
             })?,
     )
 }
+
+/// 导入的文章，日期沿用原博客中的日期
+pub struct ImportedArticle<'a> {
+    pub title: &'a str,
+    pub content: &'a str,
+    pub summary: &'a str,
+    pub tags: Option<&'a str>,
+    pub status: &'a str,
+    pub created_at: &'a str,
+    pub update_at: Option<&'a str>,
+}
+
+/// 写入导入的文章，返回新文章 id
+pub async fn insert_imported_article(
+    conn: &mut SqliteConnection,
+    new: &ImportedArticle<'_>,
+) -> Result<String, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO articles (id, title, content, summary, created_at, update_at, status, tags)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(new.title)
+    .bind(new.content)
+    .bind(new.summary)
+    .bind(new.created_at)
+    .bind(new.update_at)
+    .bind(new.status)
+    .bind(new.tags)
+    .execute(conn)
+    .await?;
+    Ok(id)
+}
+
+/// 按标题查找已有文章，用于导入时检测冲突
+pub async fn find_article_id_by_title(
+    conn: &mut SqliteConnection,
+    title: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(r#"SELECT id FROM articles WHERE title = ? LIMIT 1"#)
+        .bind(title)
+        .fetch_optional(conn)
+        .await
+}
+
+/// 按发布日期倒序列出全部文章（含正文），用于导出
+pub async fn list_articles_for_export(pool: &SqlitePool) -> Result<Vec<ArticleModel>, sqlx::Error> {
+    sqlx::query_as::<_, ArticleModel>(r#"SELECT * FROM articles ORDER BY created_at DESC, id DESC"#)
+        .fetch_all(pool)
+        .await
+}
+
+/// 按发布日期倒序列出文章（含正文），`published_only` 时只含已发布的文章
+#[cfg(feature = "graphql")]
+pub async fn list_article_models(
+    pool: &SqlitePool,
+    published_only: bool,
+) -> Result<Vec<ArticleModel>, sqlx::Error> {
+    sqlx::query_as::<_, ArticleModel>(
+        r#"SELECT * FROM articles WHERE ? = 0 OR status = 'published'
+        ORDER BY created_at DESC, id DESC"#,
+    )
+    .bind(published_only)
+    .fetch_all(pool)
+    .await
+}
+
+/// 按 id 批量查找文章
+#[cfg(feature = "graphql")]
+pub async fn find_articles_by_ids(
+    pool: &SqlitePool,
+    ids: &[String],
+) -> Result<Vec<ArticleModel>, sqlx::Error> {
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new("SELECT * FROM articles WHERE id IN (");
+    let mut list = query.separated(", ");
+    for id in ids {
+        list.push_bind(id);
+    }
+    query.push(")");
+    query.build_query_as().fetch_all(pool).await
+}
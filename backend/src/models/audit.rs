@@ -0,0 +1,115 @@
+//! 审计日志模型与持久化操作
+
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct AuditEntry {
+    pub id: String,
+    pub actor_id: Option<String>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    /// 前后快照以 JSON 文本存储
+    #[schema(value_type = Option<Object>)]
+    pub before: Option<sqlx::types::Json<serde_json::Value>>,
+    #[schema(value_type = Option<Object>)]
+    pub after: Option<sqlx::types::Json<serde_json::Value>>,
+    pub created_at: String,
+}
+
+/// 待写入的审计记录
+pub struct NewAudit<'a> {
+    pub actor_id: Option<&'a str>,
+    pub actor_name: Option<&'a str>,
+    pub action: &'a str,
+    pub target_type: &'a str,
+    pub target_id: Option<&'a str>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+/// 查询条件，全部可选
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct AuditFilter {
+    pub actor_id: Option<String>,
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    /// 起止时间，格式同 created_at：`YYYY-MM-DD HH:MM:SS`（也可只写日期）
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+pub async fn insert_audit(
+    conn: &mut SqliteConnection,
+    new: NewAudit<'_>,
+) -> Result<(), sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log (id, actor_id, actor_name, action, target_type, target_id, before, after)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(new.actor_id)
+    .bind(new.actor_name)
+    .bind(new.action)
+    .bind(new.target_type)
+    .bind(new.target_id)
+    .bind(new.before.map(sqlx::types::Json))
+    .bind(new.after.map(sqlx::types::Json))
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// 拼接 WHERE 条件
+fn push_filter(builder: &mut QueryBuilder<'_, Sqlite>, filter: &AuditFilter) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(v) = &filter.actor_id {
+        builder.push(" AND actor_id = ").push_bind(v.clone());
+    }
+    if let Some(v) = &filter.action {
+        builder.push(" AND action = ").push_bind(v.clone());
+    }
+    if let Some(v) = &filter.target_type {
+        builder.push(" AND target_type = ").push_bind(v.clone());
+    }
+    if let Some(v) = &filter.target_id {
+        builder.push(" AND target_id = ").push_bind(v.clone());
+    }
+    if let Some(v) = &filter.since {
+        builder.push(" AND created_at >= ").push_bind(v.clone());
+    }
+    if let Some(v) = &filter.until {
+        builder.push(" AND created_at <= ").push_bind(v.clone());
+    }
+}
+
+/// 分页查询，按时间倒序，返回 (当前页记录, 总数)
+pub async fn list_audit(
+    pool: &SqlitePool,
+    filter: &AuditFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<AuditEntry>, i64), sqlx::Error> {
+    let mut count = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM audit_log");
+    push_filter(&mut count, filter);
+    let (total,): (i64,) = count.build_query_as().fetch_one(pool).await?;
+
+    let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM audit_log");
+    push_filter(&mut query, filter);
+    query
+        .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+    let entries = query.build_query_as::<AuditEntry>().fetch_all(pool).await?;
+
+    Ok((entries, total))
+}
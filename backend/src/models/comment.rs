@@ -1,11 +1,12 @@
 use chrono::Local;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::routes::comments::{CommentIncome, LikeCommentPayload};
 
-#[derive(Serialize, FromRow, Deserialize, Debug)]
+#[derive(Serialize, FromRow, Deserialize, Debug, Clone, ToSchema)]
 pub struct Comment {
     pub comment_id: String,
     pub article_id: Option<String>,
@@ -16,7 +17,7 @@ pub struct Comment {
     pub like_count: Option<i64>,
 }
 
-#[derive(Serialize, FromRow, Deserialize, Debug)]
+#[derive(Serialize, FromRow, Deserialize, Debug, ToSchema)]
 pub struct CommentWithLike {
     pub comment_id: String,
     pub article_id: Option<String>,
@@ -121,14 +122,25 @@ pub async fn post_comment_by_article_id(
     Ok(res)
 }
 
+/// 查找单条评论
+pub async fn find_comment_by_id(
+    pool: &SqlitePool,
+    comment_id: &str,
+) -> Result<Option<Comment>, sqlx::Error> {
+    sqlx::query_as::<_, Comment>(r#"SELECT * FROM comments WHERE comment_id = ?"#)
+        .bind(comment_id)
+        .fetch_optional(pool)
+        .await
+}
+
 /// 删除评论
 pub async fn delete_comment_by_comment_id(
-    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
     comment_id: &str,
 ) -> Result<String, sqlx::Error> {
     tracing::info!("Deleting comment with ID: {:?}", comment_id);
     let _ = sqlx::query!(r#"DELETE FROM comments WHERE comment_id = ?"#, comment_id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await
         .map_err(|e| {
             eprintln!("db delete error: {:?}", e);
@@ -220,3 +232,123 @@ pub async fn like_comment_db(
 
     Ok("liked".to_string())
 }
+
+/// 点赞或取消点赞，重复操作不改变状态，返回 (状态是否改变, 最新的点赞数)
+pub async fn set_comment_like(
+    pool: &SqlitePool,
+    comment_id: &str,
+    user_id: &str,
+    liked: bool,
+) -> Result<(bool, i64), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let changed = if liked {
+        sqlx::query(
+            r#"INSERT OR IGNORE INTO comment_likes (comment_id, user_id, article_id, created_at)
+            SELECT comment_id, ?, article_id, ? FROM comments WHERE comment_id = ?"#,
+        )
+        .bind(user_id)
+        .bind(Local::now().to_rfc3339())
+        .bind(comment_id)
+        .execute(&mut *tx)
+        .await?
+    } else {
+        sqlx::query(r#"DELETE FROM comment_likes WHERE comment_id = ? AND user_id = ?"#)
+            .bind(comment_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+    }
+    .rows_affected();
+
+    if changed > 0 {
+        let delta: i64 = if liked { 1 } else { -1 };
+        sqlx::query(r#"UPDATE comments SET like_count = like_count + ? WHERE comment_id = ?"#)
+            .bind(delta)
+            .bind(comment_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let like_count: i64 =
+        sqlx::query_scalar(r#"SELECT COALESCE(like_count, 0) FROM comments WHERE comment_id = ?"#)
+            .bind(comment_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    tx.commit().await?;
+    Ok((changed > 0, like_count))
+}
+
+/// 导入的评论，`user` 为原评论者的显示名
+pub struct ImportedComment<'a> {
+    pub comment_id: &'a str,
+    pub article_id: &'a str,
+    pub user: &'a str,
+    pub content: &'a str,
+    pub created_at: &'a str,
+    pub parent_id: Option<&'a str>,
+}
+
+/// 写入导入的评论
+pub async fn insert_imported_comment(
+    conn: &mut SqliteConnection,
+    new: &ImportedComment<'_>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO comments
+        (comment_id, article_id, user, content, created_at, parent_id)
+        VALUES (?,?,?,?,?,?)"#,
+    )
+    .bind(new.comment_id)
+    .bind(new.article_id)
+    .bind(new.user)
+    .bind(new.content)
+    .bind(new.created_at)
+    .bind(new.parent_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 列出全部评论，用于导出
+pub async fn list_comments_for_export(pool: &SqlitePool) -> Result<Vec<Comment>, sqlx::Error> {
+    sqlx::query_as::<_, Comment>(r#"SELECT * FROM comments ORDER BY created_at, comment_id"#)
+        .fetch_all(pool)
+        .await
+}
+
+/// 批量获取多篇文章的评论，按时间倒序
+#[cfg(feature = "graphql")]
+pub async fn fetch_comments_by_article_ids(
+    pool: &SqlitePool,
+    article_ids: &[String],
+) -> Result<Vec<Comment>, sqlx::Error> {
+    let mut query =
+        sqlx::QueryBuilder::<sqlx::Sqlite>::new("SELECT * FROM comments WHERE article_id IN (");
+    let mut list = query.separated(", ");
+    for id in article_ids {
+        list.push_bind(id);
+    }
+    query.push(") ORDER BY created_at DESC");
+    query.build_query_as().fetch_all(pool).await
+}
+
+/// 在给定评论中筛出指定用户点过赞的评论 id
+#[cfg(feature = "graphql")]
+pub async fn liked_comment_ids(
+    pool: &SqlitePool,
+    user_id: &str,
+    comment_ids: &[String],
+) -> Result<Vec<String>, sqlx::Error> {
+    let mut query = sqlx::QueryBuilder::<sqlx::Sqlite>::new(
+        "SELECT comment_id FROM comment_likes WHERE user_id = ",
+    );
+    query.push_bind(user_id).push(" AND comment_id IN (");
+    let mut list = query.separated(", ");
+    for id in comment_ids {
+        list.push_bind(id);
+    }
+    query.push(")");
+    query.build_query_scalar().fetch_all(pool).await
+}
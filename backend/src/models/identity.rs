@@ -0,0 +1,81 @@
+//! 外部身份（OIDC）模型与持久化操作
+
+use serde::Serialize;
+use sqlx::{FromRow, SqliteConnection, SqliteExecutor, SqlitePool};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct UserIdentity {
+    pub id: String,
+    pub user_id: String,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: Option<String>,
+}
+
+/// 通过提供方与 subject 查找已关联的身份
+pub async fn find_identity(
+    pool: &SqlitePool,
+    provider: &str,
+    subject: &str,
+) -> Result<Option<UserIdentity>, sqlx::Error> {
+    sqlx::query_as::<_, UserIdentity>(
+        r#"SELECT * FROM user_identities WHERE provider = ? AND subject = ? LIMIT 1"#,
+    )
+    .bind(provider)
+    .bind(subject)
+    .fetch_optional(pool)
+    .await
+}
+
+/// 将外部身份关联到本地用户，可在事务中使用
+pub async fn insert_identity(
+    executor: impl SqliteExecutor<'_>,
+    user_id: &str,
+    provider: &str,
+    subject: &str,
+    email: Option<&str>,
+) -> Result<UserIdentity, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    sqlx::query_as::<_, UserIdentity>(
+        r#"
+        INSERT INTO user_identities (id, user_id, provider, subject, email)
+        VALUES (?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(provider)
+    .bind(subject)
+    .bind(email)
+    .fetch_one(executor)
+    .await
+}
+
+/// 用户已关联的所有外部身份
+pub async fn list_identities_by_user(
+    pool: &SqlitePool,
+    user_id: &str,
+) -> Result<Vec<UserIdentity>, sqlx::Error> {
+    sqlx::query_as::<_, UserIdentity>(
+        r#"SELECT * FROM user_identities WHERE user_id = ? ORDER BY created_at"#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// 删除用户时一并清理其外部身份
+pub async fn delete_identities_by_user(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM user_identities WHERE user_id = ?"#)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -1,6 +1,13 @@
 //! models/ 数据访问层。里的代码通常只跟数据库打交道（定义表结构、CRUD 函数），不关心 HTTP、权限、日志等。只服务 routes
 
+pub mod api_token;
 pub mod article;
+pub mod audit;
 pub mod comment;
+pub mod identity;
+pub mod notification;
 pub mod search;
+pub mod subscriber;
+pub mod sync;
 pub mod user;
+pub mod webhook;
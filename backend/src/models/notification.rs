@@ -0,0 +1,170 @@
+//! 站内通知模型与持久化操作
+
+use serde::Serialize;
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// 评论被回复
+pub const REPLY: &str = "reply";
+/// 评论被点赞
+pub const LIKE: &str = "like";
+/// 评论被管理员删除
+pub const MODERATION: &str = "moderation";
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct Notification {
+    pub id: String,
+    pub user_id: String,
+    /// reply / like / moderation
+    pub kind: String,
+    pub actor_name: Option<String>,
+    pub article_id: Option<String>,
+    pub comment_id: Option<String>,
+    pub message: String,
+    /// 为空表示未读
+    pub read_at: Option<String>,
+    pub created_at: String,
+}
+
+/// 待写入的通知
+pub struct NewNotification<'a> {
+    pub user_id: &'a str,
+    pub kind: &'a str,
+    pub actor_name: Option<&'a str>,
+    pub article_id: Option<&'a str>,
+    pub comment_id: Option<&'a str>,
+    pub message: &'a str,
+}
+
+pub async fn insert_notification(
+    pool: &SqlitePool,
+    new: NewNotification<'_>,
+) -> Result<Notification, sqlx::Error> {
+    sqlx::query_as::<_, Notification>(
+        r#"
+        INSERT INTO notifications (id, user_id, kind, actor_name, article_id, comment_id, message)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(new.user_id)
+    .bind(new.kind)
+    .bind(new.actor_name)
+    .bind(new.article_id)
+    .bind(new.comment_id)
+    .bind(new.message)
+    .fetch_one(pool)
+    .await
+}
+
+/// 分页查询用户的通知，按时间倒序，返回 (当前页记录, 总数)
+pub async fn list_notifications(
+    pool: &SqlitePool,
+    user_id: &str,
+    unread_only: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Notification>, i64), sqlx::Error> {
+    let total: i64 = sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM notifications
+        WHERE user_id = ? AND (? = 0 OR read_at IS NULL)"#,
+    )
+    .bind(user_id)
+    .bind(unread_only)
+    .fetch_one(pool)
+    .await?;
+
+    let entries = sqlx::query_as::<_, Notification>(
+        r#"SELECT * FROM notifications
+        WHERE user_id = ? AND (? = 0 OR read_at IS NULL)
+        ORDER BY created_at DESC, id DESC LIMIT ? OFFSET ?"#,
+    )
+    .bind(user_id)
+    .bind(unread_only)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((entries, total))
+}
+
+pub async fn count_unread(pool: &SqlitePool, user_id: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM notifications WHERE user_id = ? AND read_at IS NULL"#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// 标记为已读，已读的保持原时间；通知不存在或不属于该用户时返回 false
+pub async fn mark_read(pool: &SqlitePool, user_id: &str, id: &str) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(
+        r#"UPDATE notifications SET read_at = COALESCE(read_at, datetime ('now'))
+        WHERE id = ? AND user_id = ?"#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// 全部标记为已读，返回本次标记的条数
+pub async fn mark_all_read(pool: &SqlitePool, user_id: &str) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query(
+        r#"UPDATE notifications SET read_at = datetime ('now')
+        WHERE user_id = ? AND read_at IS NULL"#,
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// 用户关闭的通知类型
+pub async fn disabled_kinds(pool: &SqlitePool, user_id: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"SELECT kind FROM notification_preferences WHERE user_id = ? AND enabled = 0"#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn set_preference(
+    pool: &SqlitePool,
+    user_id: &str,
+    kind: &str,
+    enabled: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO notification_preferences (user_id, kind, enabled) VALUES (?, ?, ?)
+        ON CONFLICT (user_id, kind) DO UPDATE SET enabled = excluded.enabled"#,
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(enabled)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 删除用户的全部通知与偏好
+pub async fn delete_notifications_by_user(
+    conn: &mut SqliteConnection,
+    user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM notifications WHERE user_id = ?"#)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(r#"DELETE FROM notification_preferences WHERE user_id = ?"#)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
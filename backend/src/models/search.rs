@@ -1,7 +1,13 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
+use utoipa::ToSchema;
 
-#[derive(FromRow, Serialize, Deserialize, Clone, Debug)]
+#[derive(FromRow, Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(async_graphql::SimpleObject),
+    graphql(name = "Suggestion")
+)]
 pub struct TmpSuggest {
     pub title: Option<String>,
     pub id: Option<String>,
@@ -0,0 +1,254 @@
+//! 邮件订阅者与文章摘要的模型与持久化操作
+
+use crate::models::article::ArticleModel;
+use serde::Serialize;
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// 等待点击确认链接
+pub const PENDING: &str = "pending";
+pub const CONFIRMED: &str = "confirmed";
+pub const UNSUBSCRIBED: &str = "unsubscribed";
+
+/// 订阅者，令牌不随记录返回
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct Subscriber {
+    pub id: String,
+    pub email: String,
+    /// 确认订阅后关联的用户，用于回复提醒
+    pub user_id: Option<String>,
+    /// 接收文章摘要
+    pub digest: bool,
+    /// 评论被回复时接收邮件
+    pub replies: bool,
+    /// pending / confirmed / unsubscribed
+    pub status: String,
+    pub created_at: String,
+    pub confirmed_at: Option<String>,
+}
+
+const COLUMNS: &str = "id, email, user_id, digest, replies, status, created_at, confirmed_at";
+
+/// 收件人：地址与退订令牌
+#[derive(Debug, FromRow)]
+pub struct Recipient {
+    pub email: String,
+    pub unsubscribe_token: String,
+}
+
+/// 订阅申请
+pub struct NewSubscription<'a> {
+    pub email: &'a str,
+    /// 申请订阅的登录用户，确认后才关联
+    pub pending_user_id: Option<&'a str>,
+    pub digest: bool,
+    pub replies: bool,
+    pub confirm_token: &'a str,
+    pub unsubscribe_token: &'a str,
+}
+
+/// 新建待确认的订阅；地址已存在（未确认或已退订）时改回待确认并更换确认令牌，退订令牌保持不变
+/// 申请用户覆盖此前未确认的申请，已关联的用户保持不变；地址已确认订阅时不做修改，返回 `None`
+pub async fn upsert_pending_subscriber(
+    pool: &SqlitePool,
+    new: NewSubscription<'_>,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO subscribers (id, email, pending_user_id, digest, replies, confirm_token, unsubscribe_token)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (email) DO UPDATE SET
+            pending_user_id = excluded.pending_user_id,
+            digest = excluded.digest,
+            replies = excluded.replies,
+            status = 'pending',
+            confirm_token = excluded.confirm_token,
+            confirmed_at = NULL
+        WHERE status != 'confirmed'
+        RETURNING id
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(new.email)
+    .bind(new.pending_user_id)
+    .bind(new.digest)
+    .bind(new.replies)
+    .bind(new.confirm_token)
+    .bind(new.unsubscribe_token)
+    .fetch_optional(pool)
+    .await
+}
+
+/// 按确认令牌确认订阅并关联申请订阅的用户，令牌无效时返回 `None`
+pub async fn confirm_subscriber(
+    pool: &SqlitePool,
+    token: &str,
+) -> Result<Option<Subscriber>, sqlx::Error> {
+    sqlx::query_as::<_, Subscriber>(&format!(
+        r#"
+        UPDATE subscribers SET status = 'confirmed', confirm_token = NULL,
+            user_id = COALESCE(pending_user_id, user_id), pending_user_id = NULL,
+            confirmed_at = datetime ('now')
+        WHERE confirm_token = ? AND status = 'pending'
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(token)
+    .fetch_optional(pool)
+    .await
+}
+
+/// 按退订令牌退订，令牌无效时返回 `None`；重复退订同样返回记录
+pub async fn unsubscribe(
+    pool: &SqlitePool,
+    token: &str,
+) -> Result<Option<Subscriber>, sqlx::Error> {
+    sqlx::query_as::<_, Subscriber>(&format!(
+        r#"
+        UPDATE subscribers SET status = 'unsubscribed', confirm_token = NULL
+        WHERE unsubscribe_token = ?
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(token)
+    .fetch_optional(pool)
+    .await
+}
+
+/// 分页查询订阅者，按时间倒序，返回 (当前页记录, 总数)
+pub async fn list_subscribers(
+    pool: &SqlitePool,
+    status: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<Subscriber>, i64), sqlx::Error> {
+    let total: i64 =
+        sqlx::query_scalar(r#"SELECT COUNT(*) FROM subscribers WHERE ? IS NULL OR status = ?"#)
+            .bind(status)
+            .bind(status)
+            .fetch_one(pool)
+            .await?;
+
+    let entries = sqlx::query_as::<_, Subscriber>(&format!(
+        r#"SELECT {COLUMNS} FROM subscribers WHERE ? IS NULL OR status = ?
+        ORDER BY created_at DESC, id DESC LIMIT ? OFFSET ?"#
+    ))
+    .bind(status)
+    .bind(status)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((entries, total))
+}
+
+pub async fn find_subscriber_by_id(
+    pool: &SqlitePool,
+    id: &str,
+) -> Result<Option<Subscriber>, sqlx::Error> {
+    sqlx::query_as::<_, Subscriber>(&format!(
+        r#"SELECT {COLUMNS} FROM subscribers WHERE id = ?"#
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_subscriber(conn: &mut SqliteConnection, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM subscribers WHERE id = ?"#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// 用户删除后解除关联，回复提醒随之关闭
+pub async fn unlink_user(conn: &mut SqliteConnection, user_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"UPDATE subscribers SET user_id = NULL, replies = 0 WHERE user_id = ?"#)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(r#"UPDATE subscribers SET pending_user_id = NULL WHERE pending_user_id = ?"#)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// 已确认并接收摘要的订阅者
+pub async fn digest_recipients(pool: &SqlitePool) -> Result<Vec<Recipient>, sqlx::Error> {
+    sqlx::query_as::<_, Recipient>(
+        r#"SELECT email, unsubscribe_token FROM subscribers
+        WHERE status = 'confirmed' AND digest = 1"#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// 用户开启了回复提醒的已确认订阅
+pub async fn reply_recipient(
+    pool: &SqlitePool,
+    user_id: &str,
+) -> Result<Option<Recipient>, sqlx::Error> {
+    sqlx::query_as::<_, Recipient>(
+        r#"SELECT email, unsubscribe_token FROM subscribers
+        WHERE user_id = ? AND status = 'confirmed' AND replies = 1
+        LIMIT 1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// `hours` 小时内是否已经发送过摘要
+pub async fn digest_sent_within(pool: &SqlitePool, hours: u32) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"SELECT EXISTS (SELECT 1 FROM mail_digests
+        WHERE sent_at > datetime ('now', '-' || ? || ' hours'))"#,
+    )
+    .bind(hours)
+    .fetch_one(pool)
+    .await
+}
+
+/// 尚未进入摘要的已发布文章，按创建时间倒序
+pub async fn undigested_articles(pool: &SqlitePool) -> Result<Vec<ArticleModel>, sqlx::Error> {
+    sqlx::query_as::<_, ArticleModel>(
+        r#"SELECT * FROM articles
+        WHERE status = 'published' AND id NOT IN (SELECT article_id FROM digest_articles)
+        ORDER BY created_at DESC, id DESC"#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// 记录一期摘要并标记其中的文章
+pub async fn record_digest(
+    pool: &SqlitePool,
+    article_ids: &[&str],
+    recipient_count: usize,
+) -> Result<String, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r#"INSERT INTO mail_digests (id, article_count, recipient_count) VALUES (?, ?, ?)"#,
+    )
+    .bind(&id)
+    .bind(article_ids.len() as i64)
+    .bind(recipient_count as i64)
+    .execute(&mut *tx)
+    .await?;
+    for article_id in article_ids {
+        sqlx::query(
+            r#"INSERT OR IGNORE INTO digest_articles (article_id, digest_id) VALUES (?, ?)"#,
+        )
+        .bind(article_id)
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(id)
+}
@@ -0,0 +1,221 @@
+//! 同步用的数据访问：按变更序号读取，以及写入客户端推送的数据
+//! 序号、字段时钟与删除记录由触发器维护，这里只在合并后覆盖 `clocks`
+
+use crate::sync::{Changes, SyncArticle, SyncComment, Tombstone};
+use sqlx::{SqliteConnection, SqlitePool};
+
+/// 读取序号大于 `since` 的变更，最多 `limit` 条
+pub async fn list_changes(
+    pool: &SqlitePool,
+    since: i64,
+    limit: i64,
+) -> Result<Changes, sqlx::Error> {
+    // 在同一个读事务里查询，保证各表看到的是同一时刻的数据
+    let mut tx = pool.begin().await?;
+
+    let nth = |offset: i64| {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT seq FROM (
+                SELECT seq FROM articles WHERE seq > ?1
+                UNION ALL SELECT seq FROM comments WHERE seq > ?1
+                UNION ALL SELECT seq FROM sync_tombstones WHERE seq > ?1
+            ) ORDER BY seq LIMIT 1 OFFSET ?2
+            "#,
+        )
+        .bind(since)
+        .bind(offset)
+    };
+    let upto = match nth(limit - 1).fetch_optional(&mut *tx).await? {
+        Some(seq) => seq,
+        None => {
+            sqlx::query_scalar(r#"SELECT value FROM sync_counter WHERE id = 1"#)
+                .fetch_one(&mut *tx)
+                .await?
+        }
+    };
+    let more = nth(limit).fetch_optional(&mut *tx).await?.is_some();
+
+    let articles = sqlx::query_as::<_, SyncArticle>(
+        r#"
+        SELECT id, title, content, summary, tags, status, created_at, update_at, clocks
+        FROM articles WHERE seq > ? AND seq <= ? ORDER BY seq
+        "#,
+    )
+    .bind(since)
+    .bind(upto)
+    .fetch_all(&mut *tx)
+    .await?;
+    let comments = sqlx::query_as::<_, SyncComment>(
+        r#"
+        SELECT comment_id, article_id, user, content, created_at, parent_id
+        FROM comments WHERE seq > ? AND seq <= ? ORDER BY seq
+        "#,
+    )
+    .bind(since)
+    .bind(upto)
+    .fetch_all(&mut *tx)
+    .await?;
+    let deleted = sqlx::query_as::<_, Tombstone>(
+        r#"SELECT entity, id FROM sync_tombstones WHERE seq > ? AND seq <= ? ORDER BY seq"#,
+    )
+    .bind(since)
+    .bind(upto)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(Changes {
+        cursor: upto.max(since),
+        more,
+        articles,
+        comments,
+        deleted,
+    })
+}
+
+pub async fn find_sync_article(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<Option<SyncArticle>, sqlx::Error> {
+    sqlx::query_as::<_, SyncArticle>(
+        r#"
+        SELECT id, title, content, summary, tags, status, created_at, update_at, clocks
+        FROM articles WHERE id = ?
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+}
+
+pub async fn is_deleted(
+    conn: &mut SqliteConnection,
+    entity: &str,
+    id: &str,
+) -> Result<bool, sqlx::Error> {
+    let found: Option<i64> =
+        sqlx::query_scalar(r#"SELECT 1 FROM sync_tombstones WHERE entity = ? AND id = ?"#)
+            .bind(entity)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?;
+    Ok(found.is_some())
+}
+
+/// 写入客户端新建的文章，保留客户端的字段时钟
+pub async fn insert_sync_article(
+    conn: &mut SqliteConnection,
+    article: &SyncArticle,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO articles (id, title, content, summary, tags, status, created_at, update_at, clocks)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&article.id)
+    .bind(&article.title)
+    .bind(&article.content)
+    .bind(&article.summary)
+    .bind(&article.tags)
+    .bind(&article.status)
+    .bind(&article.created_at)
+    .bind(&article.update_at)
+    .bind(sqlx::types::Json(&article.clocks))
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 写入合并结果，触发器刷新时钟后再以合并得到的时钟覆盖
+pub async fn update_sync_article(
+    conn: &mut SqliteConnection,
+    article: &SyncArticle,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE articles SET title = ?, content = ?, summary = ?, tags = ?, status = ?, update_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(&article.title)
+    .bind(&article.content)
+    .bind(&article.summary)
+    .bind(&article.tags)
+    .bind(&article.status)
+    .bind(&article.update_at)
+    .bind(&article.id)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(r#"UPDATE articles SET clocks = ? WHERE id = ?"#)
+        .bind(sqlx::types::Json(&article.clocks))
+        .bind(&article.id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn comment_exists(conn: &mut SqliteConnection, id: &str) -> Result<bool, sqlx::Error> {
+    let found: Option<i64> = sqlx::query_scalar(r#"SELECT 1 FROM comments WHERE comment_id = ?"#)
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+    Ok(found.is_some())
+}
+
+pub async fn article_exists(conn: &mut SqliteConnection, id: &str) -> Result<bool, sqlx::Error> {
+    let found: Option<i64> = sqlx::query_scalar(r#"SELECT 1 FROM articles WHERE id = ?"#)
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+    Ok(found.is_some())
+}
+
+pub async fn insert_sync_comment(
+    conn: &mut SqliteConnection,
+    comment: &SyncComment,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO comments (comment_id, article_id, user, content, created_at, parent_id)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&comment.comment_id)
+    .bind(&comment.article_id)
+    .bind(&comment.user)
+    .bind(&comment.content)
+    .bind(&comment.created_at)
+    .bind(&comment.parent_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 删除文章及其评论，返回是否删除了文章
+pub async fn delete_sync_article(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query(r#"DELETE FROM comments WHERE article_id = ?"#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    let res = sqlx::query(r#"DELETE FROM articles WHERE id = ?"#)
+        .bind(id)
+        .execute(conn)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+pub async fn delete_sync_comment(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(r#"DELETE FROM comments WHERE comment_id = ?"#)
+        .bind(id)
+        .execute(conn)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
@@ -2,7 +2,8 @@
 //! 说明：将数据访问与业务/路由解耦，便于测试与复用。
 
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqliteConnection, SqliteExecutor, SqlitePool};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::routes::users::AdminEditAccountPayload;
@@ -14,13 +15,21 @@ pub struct User {
     pub password: String,
     /// 为简化与 SQLite datetime TEXT 的映射，这里使用 String
     pub identity: String,
+    /// 超级管理员标记，不可被降级或删除
+    pub is_super_admin: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(async_graphql::SimpleObject),
+    graphql(name = "User")
+)]
 pub struct UserPublic {
     pub id: String,
     pub username: String,
     pub identity: String,
+    pub is_super_admin: bool,
 }
 
 impl From<User> for UserPublic {
@@ -29,6 +38,7 @@ impl From<User> for UserPublic {
             id: u.id,
             username: u.username,
             identity: u.identity,
+            is_super_admin: u.is_super_admin,
         }
     }
 }
@@ -42,7 +52,10 @@ pub struct NewUser {
 }
 
 /// 新增用户
-pub async fn insert_common_user(pool: &SqlitePool, new: &NewUser) -> Result<User, sqlx::Error> {
+pub async fn insert_common_user(
+    executor: impl SqliteExecutor<'_>,
+    new: &NewUser,
+) -> Result<User, sqlx::Error> {
     let id = Uuid::now_v7().to_string(); // 相比new(), now()可以调用当前时间
     // query_as 是 sqlx 的宏：它在 编译期 检查 SQL 语法，并把结果行直接 按列名映射 到你指定的结构体 User。
     // 第一个类型参数 _ 让编译器推断数据库驱动（这里是 SQLite，只有一种数据库的话可以自己推导）；第二个 User 指定目标结构体。
@@ -51,72 +64,93 @@ pub async fn insert_common_user(pool: &SqlitePool, new: &NewUser) -> Result<User
         r#"
         INSERT INTO users (id, username, password, identity)
         VALUES (?, ?, ?, ?)
-        RETURNING id, username, password, identity
+        RETURNING id, username, password, identity, is_super_admin
         "#,
     )
     .bind(&id) // 需要uuid的feature
     .bind(&new.username)
     .bind(&new.password)
     .bind(&new.identity)
-    .fetch_one(pool) //执行语句，并 等待一行结果
+    .fetch_one(executor) //执行语句，并 等待一行结果
     .await
 }
 
-/// 通过用户名查找用户
+/// 新增超级管理员（仅首次启动时使用）
+pub async fn insert_super_admin(
+    conn: &mut SqliteConnection,
+    username: &str,
+    password_hash: &str,
+) -> Result<User, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (id, username, password, identity, is_super_admin)
+        VALUES (?, ?, ?, 'admin', 1)
+        RETURNING id, username, password, identity, is_super_admin
+        "#,
+    )
+    .bind(&id)
+    .bind(username)
+    .bind(password_hash)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// 是否已经存在超级管理员，没有时需要首次设置
+pub async fn has_super_admin(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(r#"SELECT COUNT(*) FROM users WHERE is_super_admin = 1"#)
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
+
+/// 通过用户名查找用户，可在事务中使用
 pub async fn find_user_by_username(
-    pool: &SqlitePool,
+    executor: impl SqliteExecutor<'_>,
     username: &str,
 ) -> Result<Option<User>, sqlx::Error> {
     sqlx::query_as::<_, User>(
-        r#"SELECT id, username, password, identity FROM users WHERE username = ? LIMIT 1"#,
+        r#"SELECT id, username, password, identity, is_super_admin FROM users WHERE username = ? LIMIT 1"#,
     )
     .bind(username)
-    .fetch_optional(pool) // 执行语句，允许一行或零行
+    .fetch_optional(executor) // 执行语句，允许一行或零行
     .await
 }
 
-/// 通过id查找用户
-pub async fn find_user_by_id(pool: &SqlitePool, id: String) -> Result<Option<User>, sqlx::Error> {
+/// 通过id查找用户，可在事务中使用
+pub async fn find_user_by_id(
+    executor: impl SqliteExecutor<'_>,
+    id: String,
+) -> Result<Option<User>, sqlx::Error> {
     sqlx::query_as::<_, User>(
-        r#"SELECT id, username, password, identity FROM users WHERE id = ? LIMIT 1"#,
+        r#"SELECT id, username, password, identity, is_super_admin FROM users WHERE id = ? LIMIT 1"#,
     )
     .bind(id)
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await
 }
 
 /// 用户列表
 pub async fn list_users(pool: &SqlitePool, limit: i32) -> Result<Vec<UserPublic>, sqlx::Error> {
-    let rows = sqlx::query!(
-        r#"SELECT id, username, identity FROM users ORDER BY id LIMIT ?"#,
-        limit
+    sqlx::query_as::<_, UserPublic>(
+        r#"SELECT id, username, identity, is_super_admin FROM users ORDER BY id LIMIT ?"#,
     )
+    .bind(limit)
     .fetch_all(pool)
-    .await?;
-
-    let users = rows
-        .into_iter()
-        .map(|row| UserPublic {
-            id: row.id.unwrap_or_default(),
-            username: row.username.unwrap_or_default(),
-            identity: row.identity.unwrap_or_default(),
-        })
-        .collect::<Vec<UserPublic>>();
-
-    Ok(users)
+    .await
 }
 
 /// 通过id删除用户
-pub async fn delete_user_by_id(pool: &SqlitePool, id: &str) -> Result<(), sqlx::Error> {
+pub async fn delete_user_by_id(conn: &mut SqliteConnection, id: &str) -> Result<(), sqlx::Error> {
     sqlx::query!(r#"DELETE FROM users WHERE id = ?"#, id)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
     Ok(())
 }
 
 /// 编辑用户账号
 pub async fn edit_user_account(
-    pool: &SqlitePool,
+    conn: &mut SqliteConnection,
     new_data: AdminEditAccountPayload,
 ) -> Result<(), sqlx::Error> {
     sqlx::query!(
@@ -133,7 +167,7 @@ pub async fn edit_user_account(
         new_data.edited_identity,
         new_data.edited_id,
     )
-    .execute(pool)
+    .execute(&mut *conn)
     .await?;
 
     Ok(())
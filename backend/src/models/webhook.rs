@@ -0,0 +1,294 @@
+//! Webhook 订阅与投递记录的模型与持久化操作
+
+use serde::Serialize;
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// 等待投递或重试
+pub const PENDING: &str = "pending";
+/// 接收方返回 2xx
+pub const DELIVERED: &str = "delivered";
+/// 重试次数用尽，不再投递
+pub const DEAD: &str = "dead";
+
+/// Webhook 订阅，密钥不随记录返回
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// 订阅的事件，空格分隔
+    pub events: String,
+    /// 停用后不再产生新的投递
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Webhook {
+    pub fn subscribes(&self, event: &str) -> bool {
+        self.events.split_whitespace().any(|e| e == event)
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event: String,
+    /// 请求体原文
+    pub payload: String,
+    /// pending / delivered / dead
+    pub status: String,
+    pub attempts: i64,
+    pub next_attempt_at: String,
+    /// 最近一次请求的 HTTP 状态码，未收到响应时为空
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+}
+
+/// 到期待投递的记录，附带订阅的地址与密钥
+#[derive(Debug, FromRow)]
+pub struct DueDelivery {
+    pub id: String,
+    pub event: String,
+    pub payload: String,
+    pub attempts: i64,
+    pub url: String,
+    pub secret: String,
+}
+
+pub async fn insert_webhook(
+    conn: &mut SqliteConnection,
+    url: &str,
+    secret: &str,
+    events: &str,
+    active: bool,
+) -> Result<Webhook, sqlx::Error> {
+    sqlx::query_as::<_, Webhook>(
+        r#"
+        INSERT INTO webhooks (id, url, secret, events, active) VALUES (?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::now_v7().to_string())
+    .bind(url)
+    .bind(secret)
+    .bind(events)
+    .bind(active)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+pub async fn list_webhooks(pool: &SqlitePool) -> Result<Vec<Webhook>, sqlx::Error> {
+    sqlx::query_as::<_, Webhook>(r#"SELECT * FROM webhooks ORDER BY created_at, id"#)
+        .fetch_all(pool)
+        .await
+}
+
+/// 启用中的订阅
+pub async fn list_active_webhooks(pool: &SqlitePool) -> Result<Vec<Webhook>, sqlx::Error> {
+    sqlx::query_as::<_, Webhook>(r#"SELECT * FROM webhooks WHERE active = 1"#)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_webhook_by_id(
+    pool: &SqlitePool,
+    id: &str,
+) -> Result<Option<Webhook>, sqlx::Error> {
+    sqlx::query_as::<_, Webhook>(r#"SELECT * FROM webhooks WHERE id = ?"#)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// 修改订阅，为空的字段保持不变
+pub async fn update_webhook(
+    conn: &mut SqliteConnection,
+    id: &str,
+    url: Option<&str>,
+    secret: Option<&str>,
+    events: Option<&str>,
+    active: Option<bool>,
+) -> Result<Option<Webhook>, sqlx::Error> {
+    sqlx::query_as::<_, Webhook>(
+        r#"
+        UPDATE webhooks SET
+            url = COALESCE(?, url),
+            secret = COALESCE(?, secret),
+            events = COALESCE(?, events),
+            active = COALESCE(?, active),
+            updated_at = datetime ('now')
+        WHERE id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(url)
+    .bind(secret)
+    .bind(events)
+    .bind(active)
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await
+}
+
+/// 删除订阅及其投递记录，订阅不存在时返回 false
+pub async fn delete_webhook(conn: &mut SqliteConnection, id: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query(r#"DELETE FROM webhook_deliveries WHERE webhook_id = ?"#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    let res = sqlx::query(r#"DELETE FROM webhooks WHERE id = ?"#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// 加入投递队列，立即到期
+pub async fn insert_delivery(
+    pool: &SqlitePool,
+    id: &str,
+    webhook_id: &str,
+    event: &str,
+    payload: &str,
+) -> Result<WebhookDelivery, sqlx::Error> {
+    sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        INSERT INTO webhook_deliveries (id, webhook_id, event, payload) VALUES (?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(webhook_id)
+    .bind(event)
+    .bind(payload)
+    .fetch_one(pool)
+    .await
+}
+
+/// 分页查询订阅的投递记录，按时间倒序，返回 (当前页记录, 总数)
+pub async fn list_deliveries(
+    pool: &SqlitePool,
+    webhook_id: &str,
+    status: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<WebhookDelivery>, i64), sqlx::Error> {
+    let total: i64 = sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM webhook_deliveries
+        WHERE webhook_id = ? AND (? IS NULL OR status = ?)"#,
+    )
+    .bind(webhook_id)
+    .bind(status)
+    .bind(status)
+    .fetch_one(pool)
+    .await?;
+
+    let entries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"SELECT * FROM webhook_deliveries
+        WHERE webhook_id = ? AND (? IS NULL OR status = ?)
+        ORDER BY created_at DESC, id DESC LIMIT ? OFFSET ?"#,
+    )
+    .bind(webhook_id)
+    .bind(status)
+    .bind(status)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((entries, total))
+}
+
+/// 到期的待投递记录，订阅已删除的不会返回
+pub async fn list_due_deliveries(
+    pool: &SqlitePool,
+    limit: i64,
+) -> Result<Vec<DueDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, DueDelivery>(
+        r#"
+        SELECT d.id, d.event, d.payload, d.attempts, w.url, w.secret
+        FROM webhook_deliveries d JOIN webhooks w ON w.id = d.webhook_id
+        WHERE d.status = 'pending' AND d.next_attempt_at <= datetime ('now')
+        ORDER BY d.next_attempt_at, d.id
+        LIMIT ?
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn mark_delivered(
+    pool: &SqlitePool,
+    id: &str,
+    attempts: i64,
+    response_status: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries SET status = 'delivered', attempts = ?, response_status = ?,
+            last_error = NULL, delivered_at = datetime ('now')
+        WHERE id = ?
+        "#,
+    )
+    .bind(attempts)
+    .bind(response_status)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 记录一次失败，`retry_in_secs` 秒后重试；为空时标记为 dead
+pub async fn mark_failed(
+    pool: &SqlitePool,
+    id: &str,
+    attempts: i64,
+    response_status: Option<i64>,
+    error: &str,
+    retry_in_secs: Option<i64>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries SET
+            status = CASE WHEN ? IS NULL THEN 'dead' ELSE 'pending' END,
+            attempts = ?, response_status = ?, last_error = ?,
+            next_attempt_at = datetime ('now', '+' || COALESCE(?, 0) || ' seconds')
+        WHERE id = ?
+        "#,
+    )
+    .bind(retry_in_secs)
+    .bind(attempts)
+    .bind(response_status)
+    .bind(error)
+    .bind(retry_in_secs)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 重新投递：重置为待投递并立即到期，记录不存在时返回 `None`
+pub async fn reset_delivery(
+    pool: &SqlitePool,
+    webhook_id: &str,
+    id: &str,
+) -> Result<Option<WebhookDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        UPDATE webhook_deliveries SET status = 'pending', attempts = 0,
+            next_attempt_at = datetime ('now'), delivered_at = NULL
+        WHERE id = ? AND webhook_id = ?
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(webhook_id)
+    .fetch_optional(pool)
+    .await
+}
@@ -4,29 +4,41 @@ use axum::{
     Json,
     extract::{Path, Query, State},
 };
+use blog_shared::export::is_published;
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
 // use sqlx::types::Json;
 
 use crate::{
-    auth::JwtAuth,
+    auth::{ApiAuth, Claims, api_token::ARTICLES_WRITE},
     db::AppState,
-    error::{AppError, AppResult},
-    models::{
-        article::{
-            ArticleModel, PubArticles, delete_article_by_id, find_article_by_id, get_articles,
-            patch_article_by_id, post_article, put_article_by_id,
-        },
-        user::find_user_by_id,
+    error::{AppResult, ErrorBody, ErrorCode, Format},
+    events::BlogEvent,
+    models::article::{
+        ArticleModel, PubArticles, delete_article_by_id, find_article_by_id, get_articles,
+        patch_article_by_id, post_article, put_article_by_id,
+    },
+    routes::audit::{record, snapshot},
+    validate::{
+        self, ARTICLE_STATUSES, CONTENT_MAX, SUMMARY_MAX, TAGS_MAX, TITLE_MAX, Validate,
+        ValidatedJson, Validator,
     },
+    webhooks::{self, ARTICLE_PUBLISHED},
 };
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, ToSchema)]
 pub struct ArticleResponse {
     articles: Vec<PubArticles>,
 }
 
-#[derive(Deserialize, Clone, Serialize)]
+#[derive(Deserialize, Clone, Serialize, ToSchema)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(async_graphql::InputObject),
+    graphql(name = "ArticleInput")
+)]
 pub struct NewArticle {
     pub id: Option<String>,
     pub title: Option<String>,
@@ -36,11 +48,52 @@ pub struct NewArticle {
     pub tags: Option<String>,
 }
 
-#[derive(Deserialize, Clone, Serialize, Debug)]
+impl Validate for NewArticle {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim_opt(&mut self.id);
+        validate::trim_opt(&mut self.status);
+        for value in [&mut self.title, &mut self.summary, &mut self.tags]
+            .into_iter()
+            .flatten()
+        {
+            validate::trim(value);
+        }
+
+        if let Some(id) = &self.id {
+            v.format("id", id, Format::Slug);
+        }
+        v.length(
+            "title",
+            self.title.as_deref().unwrap_or_default(),
+            1,
+            TITLE_MAX,
+        )
+        .length(
+            "content",
+            self.content.as_deref().unwrap_or_default().trim(),
+            1,
+            CONTENT_MAX,
+        )
+        .max_length("summary", self.summary.as_deref(), SUMMARY_MAX)
+        .max_length("tags", self.tags.as_deref(), TAGS_MAX);
+        if let Some(status) = &self.status {
+            v.one_of("status", status, ARTICLE_STATUSES);
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Serialize, Debug, ToSchema)]
 pub struct NewStatus {
     pub toggle: String,
 }
 
+impl Validate for NewStatus {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.toggle);
+        v.one_of("toggle", &self.toggle, ARTICLE_STATUSES);
+    }
+}
+
 #[derive(Deserialize)]
 #[allow(dead_code)]
 pub struct Params {
@@ -48,7 +101,7 @@ pub struct Params {
 }
 
 // 获取文章的参数
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, IntoParams)]
 pub struct GetArticlesParams {
     pub identity: String,
     pub condition: Option<String>,
@@ -56,6 +109,15 @@ pub struct GetArticlesParams {
 
 /// 获取文章列表
 /// 函数签名的参数、返回值都必须能被handle所识别，才能作为路由处理函数
+#[utoipa::path(
+    get,
+    path = "/api/v1/articles",
+    operation_id = "listArticles",
+    summary = "文章列表",
+    tag = "articles",
+    params(GetArticlesParams),
+    responses((status = 200, body = ArticleResponse))
+)]
 pub async fn articles(
     State(state): State<Arc<AppState>>,
     Query(params): Query<GetArticlesParams>,
@@ -68,29 +130,94 @@ pub async fn articles(
     Ok(Json(ArticleResponse { articles: res }))
 }
 
-/// 新增文章
-pub async fn handle_post_article(
-    State(state): State<Arc<AppState>>,
-    JwtAuth(auth): JwtAuth,
-    Json(payload): Json<NewArticle>,
-) -> AppResult<Json<NewArticle>> {
-    // 验证权限
-    match find_user_by_id(&state.pool, auth.user_id).await? {
-        Some(u) => {
-            if u.identity != "admin" {
-                return Err(AppError::Forbidden);
-            }
-        }
-        _ => return Err(AppError::BadRequest("No authority".into())),
-    };
+/// 新增文章，仅管理员
+pub(crate) async fn create_article(
+    state: &AppState,
+    auth: Claims,
+    payload: NewArticle,
+) -> AppResult<ArticleModel> {
+    auth.require_scope(ARTICLES_WRITE)?;
+    auth.require_admin(state).await?;
 
-    let res = post_article(&state.pool, &payload).await?;
+    let mut tx = state.pool.begin().await?;
+    let res = post_article(&mut tx, &payload).await?;
     tracing::info!("Posting new article: {:?}", payload.title);
+    record(
+        &mut tx,
+        Some(&auth),
+        "article.create",
+        "article",
+        Some(&res.id),
+        None,
+        snapshot(&res),
+    )
+    .await?;
+    tx.commit().await?;
 
+    Ok(res)
+}
+
+/// 新增文章（旧接口，返回提交的字段）
+#[utoipa::path(
+    post,
+    path = "/api/article",
+    operation_id = "createArticleLegacy",
+    summary = "新增文章（已弃用）",
+    tag = "articles",
+    request_body = NewArticle,
+    responses(
+        (status = 200, body = NewArticle),
+        (status = 403, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn handle_post_article(
+    State(state): State<Arc<AppState>>,
+    ApiAuth(auth): ApiAuth,
+    ValidatedJson(payload): ValidatedJson<NewArticle>,
+) -> AppResult<Json<NewArticle>> {
+    let res = create_article(&state, auth, payload).await?;
     Ok(Json(res.into()))
 }
 
+/// 新增文章
+#[utoipa::path(
+    post,
+    path = "/api/v1/articles",
+    operation_id = "createArticle",
+    summary = "新增文章",
+    tag = "articles",
+    request_body = NewArticle,
+    responses(
+        (status = 201, body = ArticleModel),
+        (status = 403, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn add_article(
+    State(state): State<Arc<AppState>>,
+    ApiAuth(auth): ApiAuth,
+    ValidatedJson(payload): ValidatedJson<NewArticle>,
+) -> AppResult<(StatusCode, Json<ArticleModel>)> {
+    let res = create_article(&state, auth, payload).await?;
+    Ok((StatusCode::CREATED, Json(res)))
+}
+
 /// 获取指定文章
+#[utoipa::path(
+    get,
+    path = "/api/v1/articles/{id}",
+    operation_id = "getArticle",
+    summary = "获取文章",
+    tag = "articles",
+    params(("id" = String, Path, description = "文章 id")),
+    responses(
+        (status = 200, body = ArticleModel),
+        (status = 404, body = ErrorBody),
+    )
+)]
 pub async fn handle_get_article(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>, // Path提取器，解析动态路由
@@ -102,66 +229,197 @@ pub async fn handle_get_article(
         tracing::info!("getted article: {:?}", &v.title);
         Ok(Json(v))
     } else {
-        Err(AppError::NotFound)
+        Err(ErrorCode::ArticleNotFound.into())
     }
 }
 
 /// 删除文章
+#[utoipa::path(
+    delete,
+    path = "/api/v1/articles/{id}",
+    operation_id = "deleteArticle",
+    summary = "删除文章",
+    tag = "articles",
+    params(("id" = String, Path, description = "文章 id")),
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub async fn handle_delete_article(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
+    ApiAuth(auth): ApiAuth,
 ) -> AppResult<StatusCode> {
-    delete_article_by_id(&state.pool, &id).await
+    remove_article(&state, &auth, &id).await
+}
+
+/// 删除文章，仅管理员
+pub(crate) async fn remove_article(
+    state: &AppState,
+    auth: &Claims,
+    id: &str,
+) -> AppResult<StatusCode> {
+    auth.require_scope(ARTICLES_WRITE)?;
+    auth.require_admin(state).await?;
+
+    let before = find_article_by_id(&state.pool, id).await?;
+    let mut tx = state.pool.begin().await?;
+    let res = delete_article_by_id(&mut tx, id).await?;
+    record(
+        &mut tx,
+        Some(auth),
+        "article.delete",
+        "article",
+        Some(id),
+        before.as_ref().and_then(snapshot),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(res)
 }
 
 /// 修改文章
+#[utoipa::path(
+    put,
+    path = "/api/v1/articles/{id}",
+    operation_id = "updateArticle",
+    summary = "修改文章",
+    tag = "articles",
+    params(("id" = String, Path, description = "文章 id")),
+    request_body = NewArticle,
+    responses(
+        (status = 200, body = ArticleModel),
+        (status = 400, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub async fn handle_put_article(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-    JwtAuth(auth): JwtAuth,
-    Json(payload): Json<NewArticle>,
+    ApiAuth(auth): ApiAuth,
+    ValidatedJson(payload): ValidatedJson<NewArticle>,
 ) -> AppResult<Json<ArticleModel>> {
-    let res;
+    Ok(Json(update_article(&state, &auth, &id, payload).await?))
+}
+
+/// 修改文章，仅管理员
+pub(crate) async fn update_article(
+    state: &AppState,
+    auth: &Claims,
+    id: &str,
+    payload: NewArticle,
+) -> AppResult<ArticleModel> {
+    auth.require_scope(ARTICLES_WRITE)?;
     if let Some(v) = payload.id.clone()
         && id != v
     {
-        return Err(AppError::BadRequest("Json 与路由信息不一".into()));
+        return Err(ErrorCode::IdMismatch.into());
     }
+    auth.require_admin(state).await?;
 
-    let ident = find_user_by_id(&state.pool, auth.user_id).await?;
-    if let Some(v) = ident {
-        if v.identity != "admin" {
-            return Err(AppError::Forbidden);
-        }
-        res = put_article_by_id(&state.pool, &id, payload).await?;
-        return Ok(Json(res));
+    let before = find_article_by_id(&state.pool, id).await?;
+    let mut tx = state.pool.begin().await?;
+    let res = put_article_by_id(&mut tx, id, payload).await?;
+    record(
+        &mut tx,
+        Some(auth),
+        "article.update",
+        "article",
+        Some(id),
+        before.as_ref().and_then(snapshot),
+        snapshot(&res),
+    )
+    .await?;
+    tx.commit().await?;
+
+    // 修改会把文章重置为草稿
+    if let Some(before) = &before
+        && before.status != res.status
+    {
+        state.events.publish_article(
+            BlogEvent::ArticleStatusChanged {
+                article_id: res.id.clone(),
+                status: res.status.clone().unwrap_or_default(),
+            },
+            is_published(before.status.as_deref()),
+        );
     }
-    Err(AppError::BadRequest("修改失败".into()))
+    Ok(res)
 }
 
 /// 更变文章状态
+#[utoipa::path(
+    patch,
+    path = "/api/v1/articles/{id}",
+    operation_id = "setArticleStatus",
+    summary = "更改文章状态",
+    tag = "articles",
+    params(("id" = String, Path, description = "文章 id")),
+    request_body = NewStatus,
+    responses(
+        (status = 200, body = ArticleModel),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub async fn handle_patch_article(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
-    JwtAuth(auth): JwtAuth,
-    Json(payload): Json<NewStatus>,
+    ApiAuth(auth): ApiAuth,
+    ValidatedJson(payload): ValidatedJson<NewStatus>,
 ) -> AppResult<Json<ArticleModel>> {
-    let res;
+    Ok(Json(set_article_status(&state, &auth, &id, payload).await?))
+}
+
+/// 更变文章状态，仅管理员
+pub(crate) async fn set_article_status(
+    state: &AppState,
+    auth: &Claims,
+    id: &str,
+    payload: NewStatus,
+) -> AppResult<ArticleModel> {
+    auth.require_scope(ARTICLES_WRITE)?;
 
     tracing::info!("Patching article status with payload: {:?}", payload);
 
-    if (find_article_by_id(&state.pool, &id).await?).is_none() {
-        return Err(AppError::Forbidden);
-    }
+    let Some(before) = find_article_by_id(&state.pool, id).await? else {
+        return Err(ErrorCode::ArticleNotFound.into());
+    };
+    auth.require_admin(state).await?;
 
-    let ident = find_user_by_id(&state.pool, auth.user_id).await?;
-    if let Some(v) = ident {
-        if v.identity != "admin" {
-            return Err(AppError::Forbidden);
-        }
-        res = patch_article_by_id(&state.pool, &id, payload).await?;
-        return Ok(Json(res));
-    }
+    let mut tx = state.pool.begin().await?;
+    let res = patch_article_by_id(&mut tx, id, payload).await?;
+    record(
+        &mut tx,
+        Some(auth),
+        "article.status",
+        "article",
+        Some(id),
+        snapshot(&before),
+        snapshot(&res),
+    )
+    .await?;
+    tx.commit().await?;
 
-    Err(AppError::BadRequest("更变文章状态失败".into()))
+    // 变化前后任一状态公开时推送给所有人，以便客户端移除下线的文章
+    state.events.publish_article(
+        BlogEvent::ArticleStatusChanged {
+            article_id: res.id.clone(),
+            status: res.status.clone().unwrap_or_default(),
+        },
+        is_published(before.status.as_deref()) || is_published(res.status.as_deref()),
+    );
+    if res.status.as_deref() == Some("published") && before.status.as_deref() != Some("published") {
+        webhooks::dispatch(state, ARTICLE_PUBLISHED, json!({ "article": res })).await;
+    }
+    Ok(res)
 }
@@ -0,0 +1,119 @@
+//! /api/v1/audit 审计日志：
+//! - `record`：供其他 handler 在特权操作中写入记录，与操作本身处于同一事务
+//! - 列表查询：仅管理员，支持按执行者、操作、对象、时间过滤与分页
+
+use crate::auth::{Claims, JwtAuth};
+use crate::db::AppState;
+use crate::error::{AppResult, ErrorBody};
+use crate::models::audit::{AuditEntry, AuditFilter, NewAudit, insert_audit, list_audit};
+use crate::models::user::find_user_by_id;
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Debug)]
+pub struct AuditQuery {
+    #[serde(flatten)]
+    pub filter: AuditFilter,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditResponse {
+    pub entries: Vec<AuditEntry>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+/// GET /api/v1/audit
+#[utoipa::path(
+    get,
+    path = "/api/v1/audit",
+    operation_id = "listAudit",
+    summary = "审计日志",
+    tag = "audit",
+    params(AuditFilter, ("page" = Option<i64>, Query), ("per_page" = Option<i64>, Query)),
+    responses(
+        (status = 200, body = AuditResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Query(query): Query<AuditQuery>,
+) -> AppResult<Json<AuditResponse>> {
+    auth.require_admin(&state).await?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+    let (entries, total) =
+        list_audit(&state.pool, &query.filter, per_page, (page - 1) * per_page).await?;
+
+    Ok(Json(AuditResponse {
+        entries,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// 将对象转为快照，序列化失败时不记录快照
+pub fn snapshot<T: Serialize>(value: &T) -> Option<serde_json::Value> {
+    serde_json::to_value(value).ok()
+}
+
+/// 写入一条审计记录，执行者用户名在写入时解析
+/// 通过个人访问令牌执行的操作会附带令牌名称
+/// `conn` 应为执行修改的事务，修改与审计记录要么都提交，要么都回滚
+pub async fn record(
+    conn: &mut SqliteConnection,
+    actor: Option<&Claims>,
+    action: &str,
+    target_type: &str,
+    target_id: Option<&str>,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+) -> AppResult<()> {
+    let actor_name = match actor {
+        Some(claims) => find_user_by_id(&mut *conn, claims.user_id.clone())
+            .await?
+            .map(|u| match claims.scopes {
+                Some(_) => format!("{} (token: {})", u.username, claims.message),
+                None => u.username,
+            }),
+        None => None,
+    };
+
+    insert_audit(
+        conn,
+        NewAudit {
+            actor_id: actor.map(|c| c.user_id.as_str()),
+            actor_name: actor_name.as_deref(),
+            action,
+            target_type,
+            target_id,
+            before,
+            after,
+        },
+    )
+    .await?;
+
+    tracing::info!(
+        "audit: {} {} {:?} by {:?}",
+        action,
+        target_type,
+        target_id,
+        actor.map(|c| &c.user_id)
+    );
+    Ok(())
+}
@@ -1,31 +1,59 @@
 //! /auth 相关路由：注册、登录、获取当前用户信息
 //! 说明：演示如何组合 models + auth + error + state
 
-use crate::auth::{generate_token, hash_password, verify_password};
+use crate::auth::{
+    api_token, decode_token_for_refresh, generate_token, hash_password, verify_password,
+};
 use crate::db::AppState;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppResult, ErrorBody, ErrorCode};
 use crate::models::user::{
     NewUser, UserPublic, find_user_by_id, find_user_by_username, insert_common_user,
 };
-use axum::{Json, extract::State, http::StatusCode};
+use crate::validate::{self, IDENTITIES, Validate, ValidatedJson, Validator};
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Clone, ToSchema)]
 pub struct RegisterPayload {
     pub username: String,
     pub password: String,
     pub identity: Option<String>, // 可选字段，仅 admin 创建用户时使用
 }
 
-#[derive(Deserialize, Clone, Debug)]
+impl Validate for RegisterPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.username);
+        validate::trim_opt(&mut self.identity);
+        v.username("username", &self.username)
+            .password("password", &self.password, 3);
+        if let Some(identity) = &self.identity {
+            v.one_of("identity", identity, IDENTITIES);
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, ToSchema)]
 pub struct LoginPayload {
     pub password: String,
     pub username: String,
 }
 
-#[derive(Serialize)]
+impl Validate for LoginPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.username);
+        v.required("username", &self.username)
+            .required("password", &self.password);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct AuthResponse {
     pub token: String,
     pub user: UserPublic,
@@ -36,36 +64,37 @@ pub struct AuthResponse {
 /// 接受前端注册以及admin页面的新建用户请求
 /// 前端注册时不带身份字段，默认注册为普通用户
 /// admin创建用户时可带身份字段
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register",
+    operation_id = "register",
+    summary = "注册",
+    tag = "auth",
+    request_body = RegisterPayload,
+    responses(
+        (status = 201, body = AuthResponse),
+        (status = 409, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    )
+)]
 pub async fn register(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<RegisterPayload>,
+    ValidatedJson(payload): ValidatedJson<RegisterPayload>,
 ) -> AppResult<(StatusCode, Json<AuthResponse>)> {
-    // 简单校验
-    if payload.username.trim().is_empty() || payload.password.len() < 3 {
-        return Err(AppError::BadRequest(
-            "invalid username or password too short".into(),
-        ));
-    }
-
     // 检查是否已经存在
     if find_user_by_username(&state.pool, &payload.username)
         .await?
         .is_some()
     {
         tracing::info!("用户名已被注册: {}", payload.username);
-        return Err(AppError::BadRequest("username already registered".into()));
+        return Err(ErrorCode::UsernameTaken.into());
     }
 
-    // 检查身份字段（仅限 admin 创建用户时使用）
-    let identity = match payload.clone().identity {
-        Some(id) if id == "admin" || id == "user" || id == "visitor" => id.clone(),
-        Some(_) => {
-            return Err(AppError::BadRequest(
-                "invalid identity, must be 'admin', 'user' or 'visitor'".into(),
-            ));
-        }
-        None => "user".to_string(), // 默认身份为普通用户
-    };
+    // 身份字段仅限 admin 创建用户时使用，取值已在校验时检查
+    let identity = payload
+        .identity
+        .clone()
+        .unwrap_or_else(|| "user".to_string()); // 默认身份为普通用户
 
     let password_hash = hash_password(&payload.password)?;
     let new = NewUser {
@@ -95,18 +124,31 @@ pub async fn register(
 // 签名解析：
 // State(state) = “把全局 AppState 拿出来，变量叫 state”；
 // Json(payload) = “把请求体 JSON 解析成 LoginPayload，变量叫 payload”。
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    operation_id = "login",
+    summary = "登录",
+    tag = "auth",
+    request_body = LoginPayload,
+    responses(
+        (status = 200, body = AuthResponse),
+        (status = 401, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    )
+)]
 pub async fn login(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<LoginPayload>,
+    ValidatedJson(payload): ValidatedJson<LoginPayload>,
 ) -> AppResult<Json<AuthResponse>> {
     let Some(user) = find_user_by_username(&state.pool, &payload.username).await? else {
         tracing::warn!("/login: 用户不存在: {}", payload.username);
-        return Err(AppError::Unauthorized("invalid credentials".into()));
+        return Err(ErrorCode::InvalidCredentials.into());
     };
 
     if !verify_password(&payload.password, &user.password) {
         tracing::warn!("/login: 密码错误 for user: {}", payload.username);
-        return Err(AppError::Unauthorized("invalid credentials".into()));
+        return Err(ErrorCode::InvalidCredentials.into());
     }
 
     tracing::info!("/login: {:?}", payload.clone());
@@ -118,6 +160,46 @@ pub async fn login(
     }))
 }
 
+/// POST /api/v1/auth/refresh
+/// 用当前登录 token 换取新 token；过期不超过 `auth.jwt_refresh_grace` 秒的 token 也可以刷新
+/// 个人访问令牌不能刷新，已删除的用户刷新失败
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh",
+    operation_id = "refreshToken",
+    summary = "刷新登录令牌",
+    tag = "auth",
+    responses(
+        (status = 200, body = AuthResponse),
+        (status = 401, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn refresh(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> AppResult<Json<AuthResponse>> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|hv| hv.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(ErrorCode::MissingToken)?;
+    if token.starts_with(api_token::TOKEN_PREFIX) {
+        return Err(ErrorCode::ApiTokenNotAccepted.into());
+    }
+
+    let claims = decode_token_for_refresh(&state, token)?;
+    let Some(user) = find_user_by_id(&state.pool, claims.user_id.clone()).await? else {
+        return Err(ErrorCode::AccountDeleted.into());
+    };
+
+    let token = generate_token(&state, user.id.clone(), &user.username)?;
+    Ok(Json(AuthResponse {
+        token,
+        user: user.into(),
+    }))
+}
+
 // 身份验证
 pub async fn get_ident_by_id(pool: &SqlitePool, id: &str) -> Result<String, sqlx::Error> {
     let ident = match find_user_by_id(pool, id.to_string()).await? {
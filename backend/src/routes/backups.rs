@@ -0,0 +1,136 @@
+//! /api/v1/backups 数据库备份（仅管理员）：
+//! - 列表 / 立即备份（同时按 `storage.backup_keep` 清理旧备份）
+//! - 恢复：校验通过后先备份当前数据库，再暂存所选备份，重启服务后生效
+
+use crate::auth::JwtAuth;
+use crate::backup::{self, BackupInfo};
+use crate::db::AppState;
+use crate::error::{AppError, AppResult, ErrorBody, ErrorCode};
+use crate::routes::audit::{record, snapshot};
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct RestoreResponse {
+    /// 恢复前自动生成的当前数据库备份
+    pub safety_backup: BackupInfo,
+    pub restart_required: bool,
+}
+
+/// GET /api/v1/backups
+#[utoipa::path(
+    get,
+    path = "/api/v1/backups",
+    operation_id = "listBackups",
+    summary = "备份列表",
+    tag = "backups",
+    responses(
+        (status = 200, body = Vec<BackupInfo>),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<Vec<BackupInfo>>> {
+    auth.require_admin(&state).await?;
+    let dir = std::path::Path::new(&state.cfg.storage.backup_dir);
+    Ok(Json(backup::list(dir)?))
+}
+
+/// POST /api/v1/backups
+#[utoipa::path(
+    post,
+    path = "/api/v1/backups",
+    operation_id = "createBackup",
+    summary = "立即备份",
+    tag = "backups",
+    responses(
+        (status = 201, body = BackupInfo),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<(StatusCode, Json<BackupInfo>)> {
+    auth.require_admin(&state).await?;
+    let storage = &state.cfg.storage;
+    let info = backup::rotate(
+        &state.pool,
+        std::path::Path::new(&storage.backup_dir),
+        storage.backup_keep,
+    )
+    .await?;
+
+    record(
+        &mut *state.pool.acquire().await?,
+        Some(&auth),
+        "db.backup",
+        "database",
+        Some(&info.name),
+        None,
+        snapshot(&info),
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(info)))
+}
+
+/// POST /api/v1/backups/{name}/restore
+#[utoipa::path(
+    post,
+    path = "/api/v1/backups/{name}/restore",
+    operation_id = "restoreBackup",
+    summary = "恢复备份",
+    tag = "backups",
+    params(("name" = String, Path, description = "备份文件名")),
+    responses(
+        (status = 200, body = RestoreResponse),
+        (status = 400, body = ErrorBody),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn restore(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Path(name): Path<String>,
+) -> AppResult<Json<RestoreResponse>> {
+    auth.require_admin(&state).await?;
+    let dir = std::path::Path::new(&state.cfg.storage.backup_dir);
+    let file = backup::find(dir, &name).ok_or(ErrorCode::BackupNotFound)?;
+
+    backup::validate(&file)
+        .await
+        .map_err(|e| AppError::Reason(ErrorCode::InvalidBackup, format!("{e:#}")))?;
+    let safety_backup = backup::create(&state.pool, &backup::default_path(dir)).await?;
+    backup::stage(&state.cfg.database, &file).await?;
+
+    record(
+        &mut *state.pool.acquire().await?,
+        Some(&auth),
+        "db.restore",
+        "database",
+        Some(&name),
+        snapshot(&safety_backup),
+        None,
+    )
+    .await?;
+    tracing::warn!("已暂存备份 {name}，重启服务后恢复");
+    Ok(Json(RestoreResponse {
+        safety_backup,
+        restart_required: true,
+    }))
+}
@@ -3,30 +3,45 @@ use std::sync::Arc;
 use axum::{
     Json,
     extract::{Path, State},
+    http::StatusCode,
 };
+use blog_shared::export::is_published;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
 
 use crate::{
-    auth::JwtAuth,
+    auth::{
+        ApiAuth, Claims, JwtAuth,
+        api_token::{COMMENTS_MODERATE, COMMENTS_WRITE},
+    },
     db::AppState,
-    error::{AppError, AppResult},
+    error::{AppError, AppResult, ErrorBody, ErrorCode, Format},
+    events::BlogEvent,
+    models::notification::{LIKE, MODERATION, REPLY},
     models::{
         article::find_article_by_id,
         comment::{
             Comment, CommentWithLike, delete_comment_by_comment_id, fetch_comments_by_article_id,
-            like_comment_db, post_comment_by_article_id,
+            find_comment_by_id, like_comment_db, post_comment_by_article_id, set_comment_like,
         },
         user::find_user_by_id,
     },
-    routes::auth::get_ident_by_id,
+    routes::{
+        audit::{record, snapshot},
+        auth::get_ident_by_id,
+        notifications::{excerpt, notify},
+    },
+    validate::{self, COMMENT_MAX, Validate, ValidatedJson, Validator},
+    webhooks::{self, COMMENT_CREATED},
 };
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct CommentsResponse {
     pub comments: Vec<CommentWithLike>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct CommentIncome {
     pub article_id: String,
     pub user_id: Option<String>,
@@ -34,26 +49,91 @@ pub struct CommentIncome {
     pub parent_id: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+impl Validate for CommentIncome {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.article_id);
+        validate::trim(&mut self.content);
+        validate::trim_opt(&mut self.parent_id);
+        v.format("article_id", &self.article_id, Format::Slug)
+            .length("content", &self.content, 1, COMMENT_MAX);
+        if let Some(parent_id) = &self.parent_id {
+            v.format("parent_id", parent_id, Format::Slug);
+        }
+    }
+}
+
+/// v1 发表评论，文章 id 取自路径
+#[derive(Deserialize, Debug, ToSchema)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(async_graphql::InputObject),
+    graphql(name = "CommentInput")
+)]
+pub struct NewComment {
+    pub content: String,
+    pub parent_id: Option<String>,
+}
+
+impl Validate for NewComment {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.content);
+        validate::trim_opt(&mut self.parent_id);
+        v.length("content", &self.content, 1, COMMENT_MAX);
+        if let Some(parent_id) = &self.parent_id {
+            v.format("parent_id", parent_id, Format::Slug);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 pub struct DeleteCommentParams {
     pub comment_id: String,
     // pub article_id: String,
     pub message: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, ToSchema)]
 #[warn(non_snake_case)]
 pub struct LikeCommentPayload {
     pub comment_id: String,
 }
 
-#[derive(Serialize)]
+impl Validate for LikeCommentPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.comment_id);
+        v.format("comment_id", &self.comment_id, Format::Slug);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct CommentsLikeResponse {
     pub comment_id: String,
     pub like_or_unlike: String,
 }
 
+/// v1 点赞状态
+#[derive(Serialize, ToSchema)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct CommentLikeState {
+    pub comment_id: String,
+    pub liked: bool,
+    pub like_count: i64,
+}
+
 /// 获取评论
+#[utoipa::path(
+    get,
+    path = "/api/v1/articles/{id}/comments",
+    operation_id = "listComments",
+    summary = "文章评论",
+    tag = "comments",
+    params(("id" = String, Path, description = "文章 id")),
+    responses(
+        (status = 200, body = CommentsResponse),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub async fn handle_get_comments(
     State(state): State<Arc<AppState>>,
     Path(arti_id): Path<String>,
@@ -63,7 +143,7 @@ pub async fn handle_get_comments(
 
     let arti_id = match find_article_by_id(&state.pool, &arti_id).await? {
         Some(v) => v.id,
-        None => return Err(AppError::BadRequest("未找到文章".into())),
+        None => return Err(ErrorCode::ArticleNotFound.into()),
     };
 
     let res = fetch_comments_by_article_id(&state.pool, &arti_id, &auth.user_id).await?;
@@ -71,18 +151,18 @@ pub async fn handle_get_comments(
     Ok(Json(CommentsResponse { comments: res }))
 }
 
-/// 发表评论
-// 有个坑，jwt如果放在后面，axum提取器可能不会识别从而报错
-pub async fn handle_post_comment(
-    State(state): State<Arc<AppState>>,
-    JwtAuth(auth): JwtAuth,
-    Json(payload): Json<CommentIncome>,
-) -> AppResult<Json<Comment>> {
+/// 发表评论，游客不可评论
+pub(crate) async fn create_comment(
+    state: &AppState,
+    auth: Claims,
+    payload: CommentIncome,
+) -> AppResult<Comment> {
+    auth.require_scope(COMMENTS_WRITE)?;
     let mut username = "".to_string();
-    if let Some(u) = find_user_by_id(&state.pool, auth.user_id).await? {
+    if let Some(u) = find_user_by_id(&state.pool, auth.user_id.clone()).await? {
         if u.identity == "visitor" {
             tracing::info!("游客身份,禁止评论,当前用户身份: {}", u.identity);
-            return Err(AppError::Unauthorized("未登录".into()));
+            return Err(AppError::Forbidden);
         }
         username = u.username;
     }
@@ -90,25 +170,167 @@ pub async fn handle_post_comment(
     let res = if (find_article_by_id(&state.pool, &payload.article_id).await?).is_some() {
         post_comment_by_article_id(&state.pool, payload, &username).await?
     } else {
-        return Err(AppError::BadRequest("未找到文章".into()));
+        return Err(ErrorCode::ArticleNotFound.into());
     };
     tracing::info!("用户 {} 发表评论 {:?} 成功", username, res.content.clone());
-    Ok(Json(res))
+    if let Some(parent_id) = &res.parent_id
+        && let Some(parent) = find_comment_by_id(&state.pool, parent_id).await?
+        && let Some(author) = &parent.user
+    {
+        notify(
+            state,
+            &auth,
+            author,
+            REPLY,
+            res.article_id.as_deref(),
+            Some(&res.comment_id),
+            &excerpt(res.content.as_deref().unwrap_or_default()),
+        )
+        .await;
+    }
+    state.events.publish_article(
+        BlogEvent::CommentCreated {
+            article_id: res.article_id.clone().unwrap_or_default(),
+            comment: res.clone(),
+        },
+        article_public(state, res.article_id.as_deref()).await?,
+    );
+    webhooks::dispatch(
+        state,
+        COMMENT_CREATED,
+        json!({ "article_id": res.article_id, "comment": res }),
+    )
+    .await;
+    Ok(res)
 }
 
-/// 删除评论
-pub async fn handle_delete_comment(
+/// 发表评论（旧接口，文章 id 在请求体中）
+// 有个坑，jwt如果放在后面，axum提取器可能不会识别从而报错
+#[utoipa::path(
+    post,
+    path = "/api/comment",
+    operation_id = "createCommentLegacy",
+    summary = "发表评论（已弃用）",
+    tag = "comments",
+    request_body = CommentIncome,
+    responses(
+        (status = 200, body = Comment),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn handle_post_comment(
     State(state): State<Arc<AppState>>,
-    JwtAuth(auth): JwtAuth,
-    Path(comment_id): Path<String>,
-) -> AppResult<Json<DeleteCommentParams>> {
-    if get_ident_by_id(&state.pool, &auth.user_id).await? != "admin" {
-        return Err(AppError::Unauthorized("权限不足".into()));
+    ApiAuth(auth): ApiAuth,
+    ValidatedJson(payload): ValidatedJson<CommentIncome>,
+) -> AppResult<Json<Comment>> {
+    Ok(Json(create_comment(&state, auth, payload).await?))
+}
+
+/// 发表评论
+#[utoipa::path(
+    post,
+    path = "/api/v1/articles/{id}/comments",
+    operation_id = "createComment",
+    summary = "发表评论",
+    tag = "comments",
+    params(("id" = String, Path, description = "文章 id")),
+    request_body = NewComment,
+    responses(
+        (status = 201, body = Comment),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn post_comment(
+    State(state): State<Arc<AppState>>,
+    Path(article_id): Path<String>,
+    ApiAuth(auth): ApiAuth,
+    ValidatedJson(payload): ValidatedJson<NewComment>,
+) -> AppResult<(StatusCode, Json<Comment>)> {
+    let payload = CommentIncome {
+        article_id,
+        user_id: None,
+        content: payload.content,
+        parent_id: payload.parent_id,
     };
+    let res = create_comment(&state, auth, payload).await?;
+    Ok((StatusCode::CREATED, Json(res)))
+}
+
+/// 删除评论，仅管理员
+pub(crate) async fn remove_comment(
+    state: &AppState,
+    auth: &Claims,
+    comment_id: &str,
+) -> AppResult<()> {
+    auth.require_scope(COMMENTS_MODERATE)?;
+    auth.require_admin(state).await?;
+
+    let Some(before) = find_comment_by_id(&state.pool, comment_id).await? else {
+        return Err(ErrorCode::CommentNotFound.into());
+    };
+    let mut tx = state.pool.begin().await?;
+    delete_comment_by_comment_id(&mut tx, comment_id).await?;
+    record(
+        &mut tx,
+        Some(auth),
+        "comment.delete",
+        "comment",
+        Some(comment_id),
+        snapshot(&before),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
 
-    if delete_comment_by_comment_id(&state.pool, &comment_id).await? != "done" {
-        return Err(AppError::BadRequest("删除失败".into()));
+    state.events.publish_article(
+        BlogEvent::CommentDeleted {
+            article_id: before.article_id.clone().unwrap_or_default(),
+            comment_id: comment_id.to_string(),
+        },
+        article_public(state, before.article_id.as_deref()).await?,
+    );
+    if let Some(author) = &before.user {
+        notify(
+            state,
+            auth,
+            author,
+            MODERATION,
+            before.article_id.as_deref(),
+            Some(comment_id),
+            &excerpt(before.content.as_deref().unwrap_or_default()),
+        )
+        .await;
     }
+    Ok(())
+}
+
+/// 删除评论（旧接口）
+#[utoipa::path(
+    delete,
+    path = "/comment/{comment_id}",
+    operation_id = "deleteCommentLegacy",
+    summary = "删除评论（已弃用）",
+    tag = "comments",
+    params(("comment_id" = String, Path, description = "评论 id")),
+    responses(
+        (status = 200, body = DeleteCommentParams),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn handle_delete_comment(
+    State(state): State<Arc<AppState>>,
+    ApiAuth(auth): ApiAuth,
+    Path(comment_id): Path<String>,
+) -> AppResult<Json<DeleteCommentParams>> {
+    remove_comment(&state, &auth, &comment_id).await?;
 
     let res = DeleteCommentParams {
         comment_id,
@@ -118,30 +340,89 @@ pub async fn handle_delete_comment(
     Ok(Json(res))
 }
 
-// 点赞评论
+/// 删除评论
+#[utoipa::path(
+    delete,
+    path = "/api/v1/comments/{id}",
+    operation_id = "deleteComment",
+    summary = "删除评论",
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 id")),
+    responses(
+        (status = 204),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn delete_comment(
+    State(state): State<Arc<AppState>>,
+    ApiAuth(auth): ApiAuth,
+    Path(comment_id): Path<String>,
+) -> AppResult<StatusCode> {
+    remove_comment(&state, &auth, &comment_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 仅管理员与普通用户可以点赞
+async fn require_liker(state: &AppState, auth: &Claims) -> AppResult<()> {
+    auth.require_scope(COMMENTS_WRITE)?;
+    // 权限检查
+    if (find_user_by_id(&state.pool, auth.user_id.clone()).await?).is_none() {
+        return Err(ErrorCode::AccountDeleted.into());
+    }
+    let ident = get_ident_by_id(&state.pool, &auth.user_id).await?;
+    if ident != "admin" && ident != "user" {
+        return Err(AppError::Forbidden);
+    }
+    Ok(())
+}
+
+/// 点赞或取消点赞评论（旧接口，每次调用切换状态）
+#[utoipa::path(
+    put,
+    path = "/api/comment/like",
+    operation_id = "toggleCommentLike",
+    summary = "切换评论点赞（已弃用）",
+    tag = "comments",
+    request_body = LikeCommentPayload,
+    responses(
+        (status = 200, body = CommentsLikeResponse),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub async fn like_comment(
     State(state): State<Arc<AppState>>,
-    JwtAuth(auth): JwtAuth,
-    Json(payload): Json<LikeCommentPayload>,
+    ApiAuth(auth): ApiAuth,
+    ValidatedJson(payload): ValidatedJson<LikeCommentPayload>,
 ) -> AppResult<Json<CommentsLikeResponse>> {
     tracing::info!(
         "trying to like comment with ID: {:?} by user ID: {:?}",
         &payload.comment_id,
         auth.user_id
     );
-    // 权限检查
-    if (find_user_by_id(&state.pool, auth.user_id.clone()).await?).is_some() {
-    } else {
-        return Err(AppError::BadRequest("用户不存在，无法点赞".into()));
-    }
-    let ident = get_ident_by_id(&state.pool, &auth.user_id).await?;
-    if ident != "admin" && ident != "user" {
-        return Err(AppError::Unauthorized("权限不足，无法点赞".into()));
-    }
+    require_liker(&state, &auth).await?;
 
     let res = like_comment_db(&state.pool, payload.clone(), &auth.user_id).await?;
 
     tracing::info!("Like or unlike comment successfully: {:?}", res);
+    if let Some(comment) = find_comment_by_id(&state.pool, &payload.comment_id).await? {
+        if res == "liked"
+            && let Some(author) = &comment.user
+        {
+            notify_like(&state, &auth, author, &comment).await;
+        }
+        let public = article_public(&state, comment.article_id.as_deref()).await?;
+        state.events.publish_article(
+            BlogEvent::CommentLiked {
+                article_id: comment.article_id.unwrap_or_default(),
+                comment_id: comment.comment_id,
+                like_count: comment.like_count.unwrap_or_default(),
+            },
+            public,
+        );
+    }
 
     Ok(Json(CommentsLikeResponse {
         comment_id: payload.comment_id,
@@ -149,6 +430,111 @@ pub async fn like_comment(
     }))
 }
 
+/// 评论所属文章是否已发布，决定评论事件推送给所有人还是只给管理员
+async fn article_public(state: &AppState, article_id: Option<&str>) -> AppResult<bool> {
+    let Some(id) = article_id else {
+        return Ok(false);
+    };
+    Ok(find_article_by_id(&state.pool, id)
+        .await?
+        .is_some_and(|a| is_published(a.status.as_deref())))
+}
+
+async fn notify_like(state: &AppState, auth: &Claims, author: &str, comment: &Comment) {
+    notify(
+        state,
+        auth,
+        author,
+        LIKE,
+        comment.article_id.as_deref(),
+        Some(&comment.comment_id),
+        &excerpt(comment.content.as_deref().unwrap_or_default()),
+    )
+    .await;
+}
+
+/// 点赞或取消点赞，重复请求结果不变
+pub(crate) async fn set_like(
+    state: &AppState,
+    auth: &Claims,
+    comment_id: String,
+    liked: bool,
+) -> AppResult<CommentLikeState> {
+    require_liker(state, auth).await?;
+    let Some(comment) = find_comment_by_id(&state.pool, &comment_id).await? else {
+        return Err(ErrorCode::CommentNotFound.into());
+    };
+
+    let (changed, like_count) =
+        set_comment_like(&state.pool, &comment_id, &auth.user_id, liked).await?;
+    if changed
+        && liked
+        && let Some(author) = &comment.user
+    {
+        notify_like(state, auth, author, &comment).await;
+    }
+    let public = article_public(state, comment.article_id.as_deref()).await?;
+    state.events.publish_article(
+        BlogEvent::CommentLiked {
+            article_id: comment.article_id.unwrap_or_default(),
+            comment_id: comment_id.clone(),
+            like_count,
+        },
+        public,
+    );
+    Ok(CommentLikeState {
+        comment_id,
+        liked,
+        like_count,
+    })
+}
+
+/// 点赞评论
+#[utoipa::path(
+    put,
+    path = "/api/v1/comments/{id}/likes",
+    operation_id = "likeComment",
+    summary = "点赞评论",
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 id")),
+    responses(
+        (status = 200, body = CommentLikeState),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn put_like(
+    State(state): State<Arc<AppState>>,
+    ApiAuth(auth): ApiAuth,
+    Path(comment_id): Path<String>,
+) -> AppResult<Json<CommentLikeState>> {
+    Ok(Json(set_like(&state, &auth, comment_id, true).await?))
+}
+
+/// 取消点赞
+#[utoipa::path(
+    delete,
+    path = "/api/v1/comments/{id}/likes",
+    operation_id = "unlikeComment",
+    summary = "取消点赞",
+    tag = "comments",
+    params(("id" = String, Path, description = "评论 id")),
+    responses(
+        (status = 200, body = CommentLikeState),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn delete_like(
+    State(state): State<Arc<AppState>>,
+    ApiAuth(auth): ApiAuth,
+    Path(comment_id): Path<String>,
+) -> AppResult<Json<CommentLikeState>> {
+    Ok(Json(set_like(&state, &auth, comment_id, false).await?))
+}
+
 // todo 也许这是不需要的接口
 // 获取评论点赞情况
 // pub async fn get_comments_like(
@@ -166,3 +552,156 @@ pub async fn like_comment(
 
 //     Ok(Json(res))
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::keys::JwtKeys;
+    use crate::config::Config;
+    use crate::models::notification::set_preference;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    /// 测试数据：carol 关闭了回复通知，ghost 的账号已删除
+    async fn state() -> AppState {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::MIGRATOR.run(&pool).await.unwrap();
+        for (id, username, identity) in [
+            ("u-alice", "alice", "user"),
+            ("u-bob", "bob", "user"),
+            ("u-carol", "carol", "user"),
+            ("u-admin", "admin", "admin"),
+        ] {
+            sqlx::query("INSERT INTO users (id, username, identity) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(username)
+                .bind(identity)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        set_preference(&pool, "u-carol", REPLY, false)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO articles (id, title, status) VALUES ('a1', '文章', 'published')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (id, user) in [
+            ("c-alice", "alice"),
+            ("c-carol", "carol"),
+            ("c-ghost", "ghost"),
+            ("c-admin", "admin"),
+        ] {
+            sqlx::query(
+                "INSERT INTO comments (comment_id, article_id, user, content) VALUES (?, 'a1', ?, '评论')",
+            )
+            .bind(id)
+            .bind(user)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let mut cfg = Config::default();
+        cfg.auth.jwt_allow_default_secret = true;
+        let jwt = JwtKeys::from_config(&cfg).unwrap();
+        AppState::new(pool, cfg, jwt)
+    }
+
+    fn claims(user_id: &str) -> Claims {
+        Claims {
+            user_id: user_id.into(),
+            message: String::new(),
+            exp: usize::MAX,
+            iat: 0,
+            iss: String::new(),
+            aud: String::new(),
+            scopes: None,
+        }
+    }
+
+    enum Action {
+        Reply(&'static str),
+        Like(&'static str),
+        Delete(&'static str),
+    }
+
+    #[tokio::test]
+    async fn notifications_reach_the_comment_author_only() {
+        let cases = [
+            (
+                "回复他人的评论",
+                "u-bob",
+                Action::Reply("c-alice"),
+                vec![("alice", REPLY)],
+            ),
+            (
+                "回复自己的评论",
+                "u-alice",
+                Action::Reply("c-alice"),
+                vec![],
+            ),
+            (
+                "点赞他人的评论",
+                "u-bob",
+                Action::Like("c-alice"),
+                vec![("alice", LIKE)],
+            ),
+            ("点赞自己的评论", "u-alice", Action::Like("c-alice"), vec![]),
+            (
+                "作者关闭了回复通知",
+                "u-bob",
+                Action::Reply("c-carol"),
+                vec![],
+            ),
+            ("作者账号已删除", "u-bob", Action::Reply("c-ghost"), vec![]),
+            (
+                "管理员删除他人的评论",
+                "u-admin",
+                Action::Delete("c-alice"),
+                vec![("alice", MODERATION)],
+            ),
+            (
+                "管理员删除自己的评论",
+                "u-admin",
+                Action::Delete("c-admin"),
+                vec![],
+            ),
+        ];
+        for (desc, actor, action, expected) in cases {
+            let state = state().await;
+            let auth = claims(actor);
+            match action {
+                Action::Reply(parent) => {
+                    let payload = CommentIncome {
+                        article_id: "a1".into(),
+                        user_id: None,
+                        content: "回复".into(),
+                        parent_id: Some(parent.into()),
+                    };
+                    create_comment(&state, auth, payload).await.unwrap();
+                }
+                Action::Like(comment) => {
+                    set_like(&state, &auth, comment.into(), true).await.unwrap();
+                }
+                Action::Delete(comment) => remove_comment(&state, &auth, comment).await.unwrap(),
+            }
+
+            let sent: Vec<(String, String)> = sqlx::query_as(
+                "SELECT u.username, n.kind FROM notifications n JOIN users u ON u.id = n.user_id",
+            )
+            .fetch_all(&state.pool)
+            .await
+            .unwrap();
+            let expected: Vec<(String, String)> = expected
+                .into_iter()
+                .map(|(user, kind)| (user.to_string(), kind.to_string()))
+                .collect();
+            assert_eq!(sent, expected, "{desc}");
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! /api/v1/events 实时事件（Server-Sent Events），需要登录
+//! - `articles` 为逗号分隔的文章 id，只推送这些文章的事件；省略时推送全部
+//! - 非管理员只收到已发布文章的事件，与 GraphQL 中文章的可见性一致；身份在连接时查询一次
+//! - 事件名与数据中的 `type` 一致，数据为 `BlogEvent` 的 JSON
+//! - 积压过多时丢弃最旧的事件并推送 `lagged`（数据为丢弃的条数），客户端应重新拉取数据
+//! - 每 15 秒发送一行注释保活
+
+use crate::auth::JwtAuth;
+use crate::db::AppState;
+use crate::error::{AppError, AppResult, ErrorBody};
+use crate::events::{BlogEvent, EventHub};
+use crate::models::user::find_user_by_id;
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::{
+    Stream, StreamExt,
+    wrappers::{BroadcastStream, errors::BroadcastStreamRecvError},
+};
+use utoipa::IntoParams;
+
+/// 同时保持的连接数上限，超出时返回 429
+const MAX_SUBSCRIBERS: usize = 1024;
+/// 保活注释的间隔
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct EventsQuery {
+    /// 逗号分隔的文章 id，省略时接收全部文章的事件
+    pub articles: Option<String>,
+}
+
+/// GET /api/v1/events?articles=
+#[utoipa::path(
+    get,
+    path = "/api/v1/events",
+    operation_id = "subscribeEvents",
+    summary = "订阅实时事件",
+    tag = "events",
+    params(EventsQuery),
+    responses(
+        (status = 200, content_type = "text/event-stream", body = BlogEvent),
+        (status = 401, body = ErrorBody),
+        (status = 429, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn events(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Query(query): Query<EventsQuery>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, axum::Error>>>> {
+    let topics: Option<HashSet<String>> = query.articles.map(|ids| {
+        ids.split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(str::to_string)
+            .collect()
+    });
+    let is_admin = find_user_by_id(&state.pool, auth.user_id.clone())
+        .await?
+        .is_some_and(|u| u.identity == "admin");
+    tracing::info!("user {} subscribed to events: {:?}", auth.user_id, topics);
+
+    stream(&state.events, move |msg| {
+        let event = &msg.event;
+        ((msg.public || is_admin)
+            && topics
+                .as_ref()
+                .is_none_or(|t| t.contains(event.article_id())))
+        .then(|| Event::default().event(event.name()).json_data(event))
+    })
+}
+
+/// 把广播转为 SSE 响应：`to_event` 返回 `None` 的消息不推送，积压时推送 `lagged`；连接数超过上限时返回 429
+pub(crate) fn stream<T, F>(
+    hub: &EventHub<T>,
+    mut to_event: F,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, axum::Error>> + use<T, F>>>
+where
+    T: Send + Sync + 'static,
+    F: FnMut(&T) -> Option<Result<Event, axum::Error>> + Send + 'static,
+{
+    if hub.subscribers() >= MAX_SUBSCRIBERS {
+        return Err(AppError::TooManyRequests(KEEP_ALIVE.as_secs()));
+    }
+
+    let stream = BroadcastStream::new(hub.subscribe()).filter_map(move |msg| match msg {
+        Ok(event) => to_event(&event),
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => Some(Ok(Event::default()
+            .event("lagged")
+            .data(skipped.to_string()))),
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(KEEP_ALIVE)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Claims;
+    use crate::auth::keys::JwtKeys;
+    use crate::config::Config;
+    use axum::response::IntoResponse;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    fn claims(user_id: &str) -> Claims {
+        Claims {
+            user_id: user_id.into(),
+            message: String::new(),
+            exp: usize::MAX,
+            iat: 0,
+            iss: String::new(),
+            aud: String::new(),
+            scopes: None,
+        }
+    }
+
+    /// 订阅全部文章，读取推送的 SSE 文本直到看到 `until`
+    async fn received(
+        stream: Sse<impl Stream<Item = Result<Event, axum::Error>> + Send + 'static>,
+        until: &str,
+    ) -> String {
+        let mut body = stream.into_response().into_body().into_data_stream();
+        let mut text = String::new();
+        while !text.contains(until) {
+            let frame = tokio::time::timeout(Duration::from_secs(5), body.next())
+                .await
+                .expect("没有等到事件")
+                .unwrap()
+                .unwrap();
+            text.push_str(std::str::from_utf8(&frame).unwrap());
+        }
+        text
+    }
+
+    #[tokio::test]
+    async fn non_admins_never_receive_private_article_events() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::db::MIGRATOR.run(&pool).await.unwrap();
+        for (id, username, identity) in [
+            ("u-admin", "admin", "admin"),
+            ("u-reader", "reader", "visitor"),
+        ] {
+            sqlx::query("INSERT INTO users (id, username, identity) VALUES (?, ?, ?)")
+                .bind(id)
+                .bind(username)
+                .bind(identity)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        let mut cfg = Config::default();
+        cfg.auth.jwt_allow_default_secret = true;
+        let jwt = JwtKeys::from_config(&cfg).unwrap();
+        let state = Arc::new(AppState::new(pool, cfg, jwt));
+
+        let subscribe = |user_id: &str| {
+            events(
+                State(state.clone()),
+                JwtAuth(claims(user_id)),
+                Query(EventsQuery { articles: None }),
+            )
+        };
+        let reader = subscribe("u-reader").await.unwrap();
+        let admin = subscribe("u-admin").await.unwrap();
+
+        let cases = [
+            (
+                "草稿上的评论被删除",
+                BlogEvent::CommentDeleted {
+                    article_id: "a-draft".into(),
+                    comment_id: "c-draft".into(),
+                },
+                false,
+            ),
+            (
+                "草稿上的评论被点赞",
+                BlogEvent::CommentLiked {
+                    article_id: "a-draft".into(),
+                    comment_id: "c-draft".into(),
+                    like_count: 1,
+                },
+                false,
+            ),
+            (
+                "归档文章改回草稿",
+                BlogEvent::ArticleStatusChanged {
+                    article_id: "a-archived".into(),
+                    status: "draft".into(),
+                },
+                false,
+            ),
+            (
+                "公开文章上的点赞",
+                BlogEvent::CommentLiked {
+                    article_id: "a-published".into(),
+                    comment_id: "c-published".into(),
+                    like_count: 2,
+                },
+                true,
+            ),
+        ];
+        for (_, event, public) in cases.clone() {
+            state.events.publish_article(event, public);
+        }
+
+        // 广播保持顺序：收到最后一条公开事件时，之前的事件都已处理过
+        let reader = received(reader, "a-published").await;
+        let admin = received(admin, "a-published").await;
+        for (desc, event, public) in cases {
+            let article_id = format!(r#""article_id":"{}""#, event.article_id());
+            assert_eq!(reader.contains(&article_id), public, "{desc}");
+            assert!(admin.contains(&article_id), "{desc}");
+        }
+    }
+}
@@ -2,12 +2,23 @@
 
 use axum::{Json, http::StatusCode};
 use serde::Serialize;
+use utoipa::ToSchema;
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct Health {
     status: &'static str,
 }
 
+#[utoipa::path(
+    get,
+    path = "/health",
+    operation_id = "health",
+    summary = "健康检查",
+    tag = "meta",
+    responses(
+        (status = 200, body = Health),
+    )
+)]
 pub async fn health() -> (StatusCode, Json<Health>) {
     (StatusCode::OK, Json(Health { status: "ok" }))
 }
@@ -0,0 +1,22 @@
+//! JWKS 路由：公布 JWT 校验公钥，便于其他服务验证本站签发的 token
+
+use crate::db::AppState;
+use axum::{Json, extract::State};
+use jsonwebtoken::jwk::JwkSet;
+use std::sync::Arc;
+
+/// GET /.well-known/jwks.json
+/// 使用 HS256 时密钥不可公开，返回空集合
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    operation_id = "getJwks",
+    summary = "JWT 公钥集合",
+    tag = "meta",
+    responses(
+        (status = 200, body = Object, description = "JWK Set"),
+    )
+)]
+pub async fn jwks(State(state): State<Arc<AppState>>) -> Json<JwkSet> {
+    Json(state.jwt.jwks().clone())
+}
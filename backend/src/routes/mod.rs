@@ -5,38 +5,172 @@
 //! 把结果包装成 JSON 返回给前端
 
 pub mod articles;
+pub mod audit;
 pub mod auth;
+pub mod backups;
 pub mod comments;
+pub mod events;
 pub mod health;
+pub mod jwks;
+pub mod notifications;
+pub mod oidc;
+pub mod openapi;
 pub mod searches;
+pub mod setup;
+pub mod subscribers;
+pub mod sync;
+pub mod tokens;
 pub mod users;
+pub mod webhooks;
 
 // 路由聚合：
-// - 将各路由模块组合在一起
-// - 添加全局中间件（例如 CORS、Trace）
+// - `/api/v1` 下为按资源组织的路由，复数资源名，创建返回 201、删除返回 204
+// - 启用 `graphql` feature 时在 `/api/v1/graphql` 挂载 GraphQL 接口
+// - 旧路由保留为弃用别名，响应附带 `Deprecation` 与指向文档的 `Link` 头
+// - 添加全局中间件（CORS、安全头、超时、Trace 等，见 middleware::http）
 // - 将全局状态 `AppState` 注入，供提取器与 handler 使用
 
 use crate::db::AppState;
+use crate::middleware::{http, rate_limit::rate_limit};
 use axum::{
     Router,
+    http::{HeaderName, HeaderValue, header},
+    middleware::from_fn_with_state,
     routing::{delete, get, patch, post, put},
 };
 use std::sync::Arc;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::TraceLayer,
-};
+use tower_http::set_header::SetResponseHeaderLayer;
+
+/// 旧路由的弃用时间（2026-10-19），RFC 9745 格式
+const DEPRECATED_SINCE: &str = "@1792368000";
 
 pub fn create_router(state: Arc<AppState>) -> Router {
-    // 浏览器跨域请求，一律放行，不拦。
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let v1 = v1();
+    #[cfg(feature = "graphql")]
+    let v1 = v1.route(
+        "/graphql",
+        post(crate::graphql::execute).layer(axum::Extension(crate::graphql::schema(state.clone()))),
+    );
 
     // 路由只负责匹配路径和方法，参数由框架自动提取
     let api = Router::new()
         .route("/health", get(health::health))
+        .route("/.well-known/jwks.json", get(jwks::jwks))
+        .route("/openapi.json", get(openapi::openapi_json))
+        .route("/docs", get(openapi::docs))
+        .nest("/api/v1", v1)
+        .merge(legacy())
+        .route_layer(from_fn_with_state(state.clone(), rate_limit))
+        .with_state(state.clone());
+
+    // 返回路由，外面包上跨域、安全头、超时和日志等“膜”
+    http::apply(Router::new().merge(api), &state.cfg.server.http)
+}
+
+fn v1() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/setup", get(setup::setup_status).post(setup::setup))
+        // auth
+        .route("/auth/register", post(auth::register))
+        .route("/auth/login", post(auth::login))
+        .route("/auth/refresh", post(auth::refresh))
+        // oidc
+        .route("/oidc/providers", get(oidc::providers))
+        .route("/oidc/identities", get(oidc::identities))
+        .route("/oidc/{provider}/authorize", get(oidc::authorize))
+        .route("/oidc/{provider}/callback", post(oidc::callback))
+        // users
+        .route("/users", get(users::list_all))
+        .route(
+            "/users/{id}",
+            get(users::get_user)
+                .patch(users::update_user)
+                .delete(users::delete_users),
+        )
+        // personal access tokens
+        .route(
+            "/tokens",
+            get(tokens::list_tokens).post(tokens::create_token),
+        )
+        .route("/tokens/{id}", delete(tokens::revoke_token))
+        // audit
+        .route("/audit", get(audit::list))
+        // desktop sync
+        .route("/sync/changes", get(sync::changes))
+        .route("/sync/push", post(sync::push))
+        // backups
+        .route("/backups", get(backups::list).post(backups::create))
+        .route("/backups/{name}/restore", post(backups::restore))
+        // articles
+        .route(
+            "/articles",
+            get(articles::articles).post(articles::add_article),
+        )
+        .route(
+            "/articles/{id}",
+            get(articles::handle_get_article)
+                .put(articles::handle_put_article)
+                .patch(articles::handle_patch_article)
+                .delete(articles::handle_delete_article),
+        )
+        .route(
+            "/articles/{id}/comments",
+            get(comments::handle_get_comments).post(comments::post_comment),
+        )
+        // comments
+        .route("/comments/{id}", delete(comments::delete_comment))
+        .route(
+            "/comments/{id}/likes",
+            put(comments::put_like).delete(comments::delete_like),
+        )
+        // searches
+        .route("/search/suggestions", get(searches::suggestions))
+        // realtime
+        .route("/events", get(events::events))
+        // notifications
+        .route("/notifications", get(notifications::list))
+        .route(
+            "/notifications/unread-count",
+            get(notifications::unread_count),
+        )
+        .route("/notifications/read-all", post(notifications::read_all))
+        .route(
+            "/notifications/preferences",
+            get(notifications::preferences).put(notifications::update_preferences),
+        )
+        .route("/notifications/stream", get(notifications::subscribe))
+        .route("/notifications/{id}/read", put(notifications::read))
+        // webhooks
+        .route("/webhooks", get(webhooks::list).post(webhooks::create))
+        .route(
+            "/webhooks/{id}",
+            put(webhooks::update).delete(webhooks::remove),
+        )
+        .route("/webhooks/{id}/deliveries", get(webhooks::deliveries))
+        .route("/webhooks/{id}/test", post(webhooks::test))
+        .route(
+            "/webhooks/{id}/deliveries/{delivery_id}/retry",
+            post(webhooks::retry),
+        )
+        // subscribers
+        .route(
+            "/subscribers",
+            get(subscribers::list).post(subscribers::subscribe),
+        )
+        .route("/subscribers/confirm", get(subscribers::confirm))
+        .route(
+            "/subscribers/unsubscribe",
+            get(subscribers::unsubscribe).post(subscribers::unsubscribe_one_click),
+        )
+        .route("/subscribers/digest", post(subscribers::digest))
+        .route("/subscribers/{id}", delete(subscribers::remove))
+}
+
+/// 旧路由，行为保持不变，新客户端应使用 `/api/v1`
+/// 只保留 `/api/v1` 之前已有的路由（含首次设置、令牌与审计），之后新增的接口只在 `/api/v1` 下提供
+fn legacy() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/setup", get(setup::setup_status).post(setup::setup))
         .route("/api/register", post(auth::register))
         .route("/api/login", post(auth::login))
         .route("/api/users", get(users::get_users)) // debug route
@@ -63,11 +197,162 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/suggestions/{keyword}",
             get(searches::handle_suggests_by_keys),
         )
-        .with_state(state.clone());
+        // personal access tokens
+        .route(
+            "/api/tokens",
+            get(tokens::list_tokens).post(tokens::create_token),
+        )
+        .route("/api/tokens/{id}", delete(tokens::revoke_token))
+        // audit
+        .route("/api/audit", get(audit::list))
+        .layer(SetResponseHeaderLayer::overriding(
+            HeaderName::from_static("deprecation"),
+            HeaderValue::from_static(DEPRECATED_SINCE),
+        ))
+        .layer(SetResponseHeaderLayer::overriding(
+            header::LINK,
+            HeaderValue::from_static("</docs>; rel=\"deprecation\"; type=\"text/html\""),
+        ))
+}
 
-    // 返回路由
-    Router::new()
-        .merge(api)
-        .layer(TraceLayer::new_for_http()) // “监控膜”: 每个请求进来/出去时，自动打印日志。
-        .layer(cors) // 跨域通行证
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::keys::JwtKeys;
+    use crate::config::Config;
+    use crate::routes::openapi::ApiDoc;
+    use axum::{
+        body::Body,
+        extract::Request,
+        http::{Method, StatusCode},
+    };
+    use sqlx::SqlitePool;
+    use std::collections::BTreeSet;
+    use tower::Service;
+    use utoipa::OpenApi;
+
+    /// `v1()` 中注册的路径模板，取自本文件源码
+    fn v1_paths() -> BTreeSet<String> {
+        let source = include_str!("mod.rs");
+        let body = &source[source.find("fn v1()").unwrap()..source.find("fn legacy()").unwrap()];
+        body.split(".route(")
+            .skip(1)
+            .map(|rest| {
+                let rest = rest.trim_start().strip_prefix('"').unwrap();
+                rest[..rest.find('"').unwrap()].to_string()
+            })
+            .collect()
+    }
+
+    /// 文档中 `/api/v1` 下的 (路径, 方法)
+    fn documented() -> BTreeSet<(String, String)> {
+        let mut routes = BTreeSet::new();
+        for (path, item) in ApiDoc::openapi().paths.paths {
+            let Some(path) = path.strip_prefix("/api/v1") else {
+                continue;
+            };
+            for (method, op) in [
+                (Method::GET, &item.get),
+                (Method::POST, &item.post),
+                (Method::PUT, &item.put),
+                (Method::PATCH, &item.patch),
+                (Method::DELETE, &item.delete),
+            ] {
+                if op.is_some() {
+                    routes.insert((path.to_string(), method.to_string()));
+                }
+            }
+        }
+        routes
+    }
+
+    #[tokio::test]
+    async fn v1_routes_match_openapi() {
+        let mut cfg = Config::default();
+        cfg.auth.jwt_allow_default_secret = true;
+        let jwt = JwtKeys::from_config(&cfg).unwrap();
+        let pool = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let state = Arc::new(AppState::new(pool, cfg, jwt));
+
+        let app = v1().with_state(state);
+
+        let documented = documented();
+        let paths: BTreeSet<String> = v1_paths()
+            .into_iter()
+            .chain(documented.iter().map(|(path, _)| path.clone()))
+            .collect();
+        // 没有路由接受 OPTIONS：路径存在时返回 405，`Allow` 头列出注册的方法，不会执行 handler
+        let mut routed = BTreeSet::new();
+        for path in paths {
+            let uri = path.replace(['{', '}'], "");
+            let request = Request::builder()
+                .method(Method::OPTIONS)
+                .uri(&uri)
+                .body(Body::empty())
+                .unwrap();
+            // Router 总是就绪，无需先 poll_ready
+            let response = app.clone().call(request).await.unwrap();
+            match response.status() {
+                StatusCode::METHOD_NOT_ALLOWED => {}
+                StatusCode::NOT_FOUND => continue,
+                other => panic!("OPTIONS {uri} 返回了意外的 {other}"),
+            }
+            let allow = response.headers()[header::ALLOW].to_str().unwrap();
+            for method in allow.split(',').map(str::trim).filter(|m| *m != "HEAD") {
+                routed.insert((path.clone(), method.to_string()));
+            }
+        }
+
+        let undocumented: Vec<_> = routed.difference(&documented).collect();
+        let unrouted: Vec<_> = documented.difference(&routed).collect();
+        assert!(
+            undocumented.is_empty() && unrouted.is_empty(),
+            "v1 路由缺少文档: {undocumented:?}\n文档中的接口没有路由: {unrouted:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn legacy_aliases_are_deprecated() {
+        let mut cfg = Config::default();
+        cfg.auth.jwt_allow_default_secret = true;
+        let jwt = JwtKeys::from_config(&cfg).unwrap();
+        let pool = SqlitePool::connect_lazy("sqlite::memory:").unwrap();
+        let state = Arc::new(AppState::new(pool, cfg, jwt));
+
+        let app = legacy().with_state(state);
+
+        let cases = [
+            ("首次设置", "/setup", "GET,POST"),
+            ("令牌列表与创建", "/api/tokens", "GET,POST"),
+            ("吊销令牌", "/api/tokens/1", "DELETE"),
+            ("审计日志", "/api/audit", "GET"),
+            ("文章列表", "/articles", "GET"),
+        ];
+        for (desc, uri, methods) in cases {
+            let request = Request::builder()
+                .method(Method::OPTIONS)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{desc}");
+            let headers = response.headers();
+            let allow: BTreeSet<&str> = headers[header::ALLOW]
+                .to_str()
+                .unwrap()
+                .split(',')
+                .map(str::trim)
+                .filter(|m| *m != "HEAD")
+                .collect();
+            assert_eq!(allow, methods.split(',').collect(), "{desc}");
+            assert_eq!(headers["deprecation"], DEPRECATED_SINCE, "{desc}");
+            assert!(
+                headers[header::LINK]
+                    .to_str()
+                    .unwrap()
+                    .contains("rel=\"deprecation\""),
+                "{desc}"
+            );
+        }
+    }
 }
@@ -0,0 +1,336 @@
+//! /api/v1/notifications 站内通知，只能访问自己的通知：
+//! - `notify`：评论被回复、被点赞或被管理员删除时由对应 handler 调用，写入后推送给接收者
+//! - 列表（可只看未读）、未读数、标记已读、全部已读
+//! - 偏好：按类型开关，关闭的类型不再产生通知
+//! - `/stream`：SSE 推送新通知，事件名为 `notification`
+
+use crate::auth::{Claims, JwtAuth};
+use crate::db::AppState;
+use crate::error::{AppResult, ErrorBody, ErrorCode};
+use crate::mail;
+use crate::models::notification::{
+    LIKE, MODERATION, NewNotification, Notification, REPLY, count_unread, disabled_kinds,
+    insert_notification, list_notifications, mark_all_read, mark_read, set_preference,
+};
+use crate::models::user::{find_user_by_id, find_user_by_username};
+use crate::routes::events::stream;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::sse::{Event, Sse},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio_stream::Stream;
+use utoipa::{IntoParams, ToSchema};
+
+/// 通知摘要的最大字符数
+const EXCERPT_CHARS: usize = 80;
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct NotificationsQuery {
+    /// 只返回未读通知
+    #[serde(default)]
+    pub unread: bool,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct NotificationsResponse {
+    pub notifications: Vec<Notification>,
+    pub total: i64,
+    /// 全部未读数，不受分页影响
+    pub unread: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UnreadCount {
+    pub unread: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadAllResponse {
+    /// 本次标记为已读的条数
+    pub updated: u64,
+}
+
+/// 各类型通知是否开启
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferences {
+    pub reply: bool,
+    pub like: bool,
+    pub moderation: bool,
+}
+
+/// 修改偏好，省略的类型不修改
+#[derive(Deserialize, ToSchema)]
+pub struct UpdatePreferences {
+    pub reply: Option<bool>,
+    pub like: Option<bool>,
+    pub moderation: Option<bool>,
+}
+
+/// GET /api/v1/notifications
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications",
+    operation_id = "listNotifications",
+    summary = "通知列表",
+    tag = "notifications",
+    params(NotificationsQuery),
+    responses(
+        (status = 200, body = NotificationsResponse),
+        (status = 401, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Query(query): Query<NotificationsQuery>,
+) -> AppResult<Json<NotificationsResponse>> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+    let (notifications, total) = list_notifications(
+        &state.pool,
+        &auth.user_id,
+        query.unread,
+        per_page,
+        (page - 1) * per_page,
+    )
+    .await?;
+    let unread = count_unread(&state.pool, &auth.user_id).await?;
+
+    Ok(Json(NotificationsResponse {
+        notifications,
+        total,
+        unread,
+        page,
+        per_page,
+    }))
+}
+
+/// GET /api/v1/notifications/unread-count
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications/unread-count",
+    operation_id = "countUnreadNotifications",
+    summary = "未读通知数",
+    tag = "notifications",
+    responses(
+        (status = 200, body = UnreadCount),
+        (status = 401, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn unread_count(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<UnreadCount>> {
+    let unread = count_unread(&state.pool, &auth.user_id).await?;
+    Ok(Json(UnreadCount { unread }))
+}
+
+/// PUT /api/v1/notifications/{id}/read
+#[utoipa::path(
+    put,
+    path = "/api/v1/notifications/{id}/read",
+    operation_id = "markNotificationRead",
+    summary = "标记通知为已读",
+    tag = "notifications",
+    params(("id" = String, Path, description = "通知 id")),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn read(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    if !mark_read(&state.pool, &auth.user_id, &id).await? {
+        return Err(ErrorCode::NotificationNotFound.into());
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/notifications/read-all
+#[utoipa::path(
+    post,
+    path = "/api/v1/notifications/read-all",
+    operation_id = "markAllNotificationsRead",
+    summary = "全部标记为已读",
+    tag = "notifications",
+    responses(
+        (status = 200, body = ReadAllResponse),
+        (status = 401, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn read_all(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<ReadAllResponse>> {
+    let updated = mark_all_read(&state.pool, &auth.user_id).await?;
+    Ok(Json(ReadAllResponse { updated }))
+}
+
+async fn load_preferences(state: &AppState, user_id: &str) -> AppResult<NotificationPreferences> {
+    let disabled = disabled_kinds(&state.pool, user_id).await?;
+    let enabled = |kind: &str| !disabled.iter().any(|k| k == kind);
+    Ok(NotificationPreferences {
+        reply: enabled(REPLY),
+        like: enabled(LIKE),
+        moderation: enabled(MODERATION),
+    })
+}
+
+/// GET /api/v1/notifications/preferences
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications/preferences",
+    operation_id = "getNotificationPreferences",
+    summary = "通知偏好",
+    tag = "notifications",
+    responses(
+        (status = 200, body = NotificationPreferences),
+        (status = 401, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn preferences(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<NotificationPreferences>> {
+    Ok(Json(load_preferences(&state, &auth.user_id).await?))
+}
+
+/// PUT /api/v1/notifications/preferences
+#[utoipa::path(
+    put,
+    path = "/api/v1/notifications/preferences",
+    operation_id = "updateNotificationPreferences",
+    summary = "修改通知偏好",
+    tag = "notifications",
+    request_body = UpdatePreferences,
+    responses(
+        (status = 200, body = NotificationPreferences),
+        (status = 401, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn update_preferences(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Json(payload): Json<UpdatePreferences>,
+) -> AppResult<Json<NotificationPreferences>> {
+    for (kind, enabled) in [
+        (REPLY, payload.reply),
+        (LIKE, payload.like),
+        (MODERATION, payload.moderation),
+    ] {
+        if let Some(enabled) = enabled {
+            set_preference(&state.pool, &auth.user_id, kind, enabled).await?;
+        }
+    }
+    Ok(Json(load_preferences(&state, &auth.user_id).await?))
+}
+
+/// GET /api/v1/notifications/stream
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications/stream",
+    operation_id = "streamNotifications",
+    summary = "订阅新通知",
+    tag = "notifications",
+    responses(
+        (status = 200, content_type = "text/event-stream", body = Notification),
+        (status = 401, body = ErrorBody),
+        (status = 429, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn subscribe(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, axum::Error>>>> {
+    let user_id = auth.user_id;
+    stream(&state.notifications, move |n: &Notification| {
+        (n.user_id == user_id).then(|| Event::default().event("notification").json_data(n))
+    })
+}
+
+/// 内容摘要，过长时截断
+pub fn excerpt(content: &str) -> String {
+    let mut chars = content.chars();
+    let head: String = chars.by_ref().take(EXCERPT_CHARS).collect();
+    match chars.next() {
+        Some(_) => format!("{}…", head),
+        None => head,
+    }
+}
+
+/// 通知评论作者 `recipient`（用户名）：作者就是操作者本人、账号已不存在或关闭了该类型时不发送
+/// 通知是附带效果，写入失败只记录日志，不影响触发它的操作
+pub async fn notify(
+    state: &AppState,
+    actor: &Claims,
+    recipient: &str,
+    kind: &str,
+    article_id: Option<&str>,
+    comment_id: Option<&str>,
+    message: &str,
+) {
+    let res: AppResult<()> = async {
+        let Some(user) = find_user_by_username(&state.pool, recipient).await? else {
+            return Ok(());
+        };
+        if user.id == actor.user_id
+            || disabled_kinds(&state.pool, &user.id)
+                .await?
+                .iter()
+                .any(|k| k == kind)
+        {
+            return Ok(());
+        }
+
+        let actor_name = find_user_by_id(&state.pool, actor.user_id.clone())
+            .await?
+            .map(|u| u.username);
+        let created = insert_notification(
+            &state.pool,
+            NewNotification {
+                user_id: &user.id,
+                kind,
+                actor_name: actor_name.as_deref(),
+                article_id,
+                comment_id,
+                message,
+            },
+        )
+        .await?;
+        tracing::info!("notification {} sent to {}", kind, user.id);
+        if kind == REPLY {
+            mail::notify_reply(state, &created);
+        }
+        state.notifications.publish(created);
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = res {
+        tracing::warn!(
+            "failed to send {} notification to {}: {:?}",
+            kind,
+            recipient,
+            e
+        );
+    }
+}
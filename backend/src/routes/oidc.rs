@@ -0,0 +1,288 @@
+//! /api/v1/oidc 外部身份登录：
+//! - 列出已配置的提供方
+//! - 生成授权地址（前端跳转），已登录时 state 与当前用户绑定
+//! - 回调：校验授权码，关联或自动创建本地用户（用户名遵循注册规则），签发本站 JWT；
+//!   只有由同一已登录用户发起的授权才会关联到该用户
+
+use crate::auth::oidc::Exchanged;
+use crate::auth::{JwtAuth, MaybeJwtAuth, generate_token, hash_password};
+use crate::config::OidcProviderConfig;
+use crate::db::AppState;
+use crate::error::{AppError, AppResult, ErrorBody, ErrorCode};
+use crate::models::identity::{
+    UserIdentity, find_identity, insert_identity, list_identities_by_user,
+};
+use crate::models::user::{
+    NewUser, User, find_user_by_id, find_user_by_username, insert_common_user,
+};
+use crate::routes::auth::AuthResponse;
+use crate::validate::{Validate, ValidatedJson, Validator};
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use blog_shared::random_string;
+use blog_shared::validate::{derive_username, username_with_suffix};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct ProviderInfo {
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProvidersResponse {
+    pub providers: Vec<ProviderInfo>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuthorizeResponse {
+    pub authorization_url: String,
+    pub state: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CallbackPayload {
+    pub code: String,
+    pub state: String,
+}
+
+impl Validate for CallbackPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        v.required("code", &self.code)
+            .required("state", &self.state);
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IdentitiesResponse {
+    pub identities: Vec<UserIdentity>,
+}
+
+fn find_provider<'a>(state: &'a AppState, name: &str) -> AppResult<&'a OidcProviderConfig> {
+    state
+        .cfg
+        .auth
+        .oidc_providers
+        .iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| ErrorCode::OidcProviderNotFound.into())
+}
+
+/// GET /api/v1/oidc/providers
+#[utoipa::path(
+    get,
+    path = "/api/v1/oidc/providers",
+    operation_id = "listOidcProviders",
+    summary = "已配置的第三方登录",
+    tag = "oidc",
+    responses(
+        (status = 200, body = ProvidersResponse),
+    )
+)]
+pub async fn providers(State(state): State<Arc<AppState>>) -> Json<ProvidersResponse> {
+    let providers = state
+        .cfg
+        .auth
+        .oidc_providers
+        .iter()
+        .map(|p| ProviderInfo {
+            name: p.name.clone(),
+            display_name: p.display_name.clone(),
+        })
+        .collect();
+
+    Json(ProvidersResponse { providers })
+}
+
+/// GET /api/v1/oidc/{provider}/authorize
+#[utoipa::path(
+    get,
+    path = "/api/v1/oidc/{provider}/authorize",
+    operation_id = "authorizeOidc",
+    summary = "获取授权地址",
+    description = "已登录时返回的 state 与当前用户绑定，回调时会把外部身份关联到该用户",
+    tag = "oidc",
+    params(("provider" = String, Path, description = "提供方名称")),
+    responses(
+        (status = 200, body = AuthorizeResponse),
+        (status = 404, body = ErrorBody),
+    )
+)]
+pub async fn authorize(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    MaybeJwtAuth(current): MaybeJwtAuth,
+) -> AppResult<Json<AuthorizeResponse>> {
+    let provider = find_provider(&state, &provider)?;
+    let (authorization_url, oidc_state) = state
+        .oidc
+        .authorization_url(provider, current.map(|c| c.user_id))
+        .await?;
+
+    Ok(Json(AuthorizeResponse {
+        authorization_url,
+        state: oidc_state,
+    }))
+}
+
+/// POST /api/v1/oidc/{provider}/callback
+/// 授权由已登录用户发起时将外部身份关联到该用户，否则登录已关联的用户或自动注册
+#[utoipa::path(
+    post,
+    path = "/api/v1/oidc/{provider}/callback",
+    operation_id = "oidcCallback",
+    summary = "第三方登录回调",
+    tag = "oidc",
+    params(("provider" = String, Path, description = "提供方名称")),
+    request_body = CallbackPayload,
+    responses(
+        (status = 200, body = AuthResponse),
+        (status = 401, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    )
+)]
+pub async fn callback(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    MaybeJwtAuth(current): MaybeJwtAuth,
+    ValidatedJson(payload): ValidatedJson<CallbackPayload>,
+) -> AppResult<Json<AuthResponse>> {
+    let provider = find_provider(&state, &provider)?;
+    let Exchanged { claims, initiator } = state
+        .oidc
+        .exchange_code(provider, &payload.code, &payload.state)
+        .await?;
+
+    // 已登录用户发起的授权只能由该用户完成，防止他人诱导受害者关联攻击者的外部身份
+    let link_to = match (initiator, current) {
+        (Some(initiator), Some(current)) if initiator == current.user_id => Some(initiator),
+        (Some(_), _) => {
+            return Err(AppError::Reason(
+                ErrorCode::OidcLoginFailed,
+                "oidc state was issued to a different user".into(),
+            ));
+        }
+        (None, _) => None,
+    };
+
+    let linked = find_identity(&state.pool, &provider.name, &claims.sub).await?;
+
+    let user = match (linked, link_to) {
+        // 已关联：直接登录
+        (Some(identity), _) => find_user_by_id(&state.pool, identity.user_id)
+            .await?
+            .ok_or(ErrorCode::UserNotFound)?,
+        // 已登录用户发起且未关联：关联到该用户
+        (None, Some(user_id)) => {
+            let user = find_user_by_id(&state.pool, user_id)
+                .await?
+                .ok_or(ErrorCode::AccountDeleted)?;
+            insert_identity(
+                &state.pool,
+                &user.id,
+                &provider.name,
+                &claims.sub,
+                claims.email.as_deref(),
+            )
+            .await?;
+            tracing::info!("用户 {} 关联了 {} 身份", user.username, provider.name);
+            user
+        }
+        // 首次登录：自动创建普通用户，与身份在同一事务中写入，避免留下没有身份的用户
+        (None, None) => {
+            let mut tx = state.pool.begin().await?;
+            let user = create_user_for_identity(
+                &mut tx,
+                &provider.name,
+                &claims.sub,
+                [
+                    claims.preferred_username.as_deref(),
+                    claims.email.as_deref().and_then(|e| e.split('@').next()),
+                    claims.name.as_deref(),
+                ],
+            )
+            .await?;
+            insert_identity(
+                &mut *tx,
+                &user.id,
+                &provider.name,
+                &claims.sub,
+                claims.email.as_deref(),
+            )
+            .await?;
+            tx.commit().await?;
+            tracing::info!("通过 {} 自动创建用户: {}", provider.name, user.username);
+            user
+        }
+    };
+
+    let token = generate_token(&state, user.id.clone(), &user.username)?;
+    Ok(Json(AuthResponse {
+        token,
+        user: user.into(),
+    }))
+}
+
+/// GET /api/v1/oidc/identities
+/// 当前用户已关联的外部身份
+#[utoipa::path(
+    get,
+    path = "/api/v1/oidc/identities",
+    operation_id = "listIdentities",
+    summary = "已关联的外部身份",
+    tag = "oidc",
+    responses(
+        (status = 200, body = IdentitiesResponse),
+        (status = 401, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn identities(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<IdentitiesResponse>> {
+    let identities = list_identities_by_user(&state.pool, &auth.user_id).await?;
+    Ok(Json(IdentitiesResponse { identities }))
+}
+
+/// 为外部身份创建本地用户：取第一个符合注册规则的候选用户名，冲突时追加随机后缀
+async fn create_user_for_identity(
+    conn: &mut SqliteConnection,
+    provider: &str,
+    subject: &str,
+    candidates: [Option<&str>; 3],
+) -> AppResult<User> {
+    let fallback = format!(
+        "{}_{}",
+        provider,
+        subject.chars().take(8).collect::<String>()
+    );
+    let base = derive_username(candidates, &fallback);
+
+    let mut username = base.clone();
+    while find_user_by_username(&mut *conn, &username)
+        .await?
+        .is_some()
+    {
+        username = username_with_suffix(&base, &random_string(6));
+    }
+
+    // 外部账号不使用本地密码登录，存入随机密码的哈希
+    let password = hash_password(&random_string(32))?;
+    let user = insert_common_user(
+        &mut *conn,
+        &NewUser {
+            username,
+            password,
+            identity: "user".into(),
+        },
+    )
+    .await?;
+    Ok(user)
+}
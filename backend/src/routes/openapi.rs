@@ -0,0 +1,194 @@
+//! OpenAPI 文档：
+//! - 由各 handler 上的 `#[utoipa::path]` 与请求/响应类型的 `ToSchema` 生成，不手写
+//! - GET /openapi.json 返回 OpenAPI 3.1 文档，GET /docs 为交互式文档页面（Scalar）
+//! - 文档以 `/api/v1` 为准，与 v1 行为不同的旧路由单独列出并标记为弃用
+//! - 仓库中的 `openapi.json` 由 `backend openapi --out openapi.json` 生成，CI 用 `--check` 保证与代码一致
+
+use crate::error::ErrorBody;
+use crate::routes::{
+    articles, audit, auth, backups, comments, events, health, jwks, notifications, oidc, searches,
+    setup, subscribers, sync, tokens, users, webhooks,
+};
+use axum::{
+    Json,
+    http::{HeaderValue, header},
+    response::{Html, IntoResponse},
+};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::{
+        Deprecated,
+        security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+use utoipa_scalar::Scalar;
+
+/// 文档页面从 CDN 加载脚本、样式与字体，需放宽默认的 CSP
+const DOCS_CSP: &str = "default-src 'none'; script-src https://cdn.jsdelivr.net; \
+    style-src 'unsafe-inline' https://cdn.jsdelivr.net https://fonts.scalar.com; \
+    font-src https://fonts.scalar.com data:; img-src 'self' data: https:; \
+    connect-src 'self'; frame-ancestors 'none'";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "myBlog API",
+        description = "博客后端接口。错误响应统一为 `ErrorBody`，需要登录的接口通过 `Authorization: Bearer` 传递令牌。"
+    ),
+    paths(
+        health::health,
+        jwks::jwks,
+        setup::setup_status,
+        setup::setup,
+        auth::register,
+        auth::login,
+        auth::refresh,
+        oidc::providers,
+        oidc::identities,
+        oidc::authorize,
+        oidc::callback,
+        users::list_all,
+        users::get_user,
+        users::update_user,
+        users::delete_users,
+        users::get_users,
+        users::edit_account,
+        tokens::list_tokens,
+        tokens::create_token,
+        tokens::revoke_token,
+        audit::list,
+        sync::changes,
+        sync::push,
+        backups::list,
+        backups::create,
+        backups::restore,
+        articles::articles,
+        articles::add_article,
+        articles::handle_get_article,
+        articles::handle_delete_article,
+        articles::handle_put_article,
+        articles::handle_patch_article,
+        articles::handle_post_article,
+        comments::handle_get_comments,
+        comments::post_comment,
+        comments::delete_comment,
+        comments::put_like,
+        comments::delete_like,
+        comments::handle_post_comment,
+        comments::handle_delete_comment,
+        comments::like_comment,
+        searches::suggestions,
+        searches::handle_suggests_by_keys,
+        events::events,
+        notifications::list,
+        notifications::unread_count,
+        notifications::read,
+        notifications::read_all,
+        notifications::preferences,
+        notifications::update_preferences,
+        notifications::subscribe,
+        webhooks::list,
+        webhooks::create,
+        webhooks::update,
+        webhooks::remove,
+        webhooks::deliveries,
+        webhooks::test,
+        webhooks::retry,
+        subscribers::subscribe,
+        subscribers::confirm,
+        subscribers::unsubscribe,
+        subscribers::unsubscribe_one_click,
+        subscribers::list,
+        subscribers::remove,
+        subscribers::digest,
+    ),
+    components(schemas(ErrorBody)),
+    modifiers(&Finalize),
+    tags(
+        (name = "meta", description = "健康检查与公钥"),
+        (name = "setup", description = "首次启动设置"),
+        (name = "auth", description = "注册、登录与刷新令牌"),
+        (name = "oidc", description = "第三方登录"),
+        (name = "users", description = "用户管理"),
+        (name = "tokens", description = "个人访问令牌"),
+        (name = "audit", description = "审计日志"),
+        (name = "sync", description = "桌面端同步"),
+        (name = "backups", description = "数据库备份"),
+        (name = "articles", description = "文章与搜索"),
+        (name = "comments", description = "评论"),
+        (name = "events", description = "实时事件"),
+        (name = "notifications", description = "站内通知"),
+        (name = "webhooks", description = "Webhook 订阅与投递"),
+        (name = "subscribers", description = "邮件订阅"),
+    )
+)]
+pub struct ApiDoc;
+
+/// 补充派生宏无法表达的部分：
+/// - 登录 JWT 与个人访问令牌均通过 `Authorization: Bearer` 传递
+/// - 包未声明 license，去掉派生时从 Cargo 元数据带入的空 license
+/// - `/api/v1` 之外仍保留的旧路由标记为弃用
+struct Finalize;
+
+/// 不做版本划分的路径
+const UNVERSIONED: &[&str] = &["/health", "/.well-known/jwks.json"];
+
+impl Modify for Finalize {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.license = None;
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            if path.starts_with("/api/v1/") || UNVERSIONED.contains(&path.as_str()) {
+                continue;
+            }
+            for op in [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+                &mut item.patch,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                op.deprecated = Some(Deprecated::True);
+            }
+        }
+
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("登录 JWT，或以 `pat_` 开头的个人访问令牌"))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// 生成格式化后的文档，末尾带换行，与仓库中的 `openapi.json` 逐字节比较
+pub fn spec_json() -> String {
+    let mut json = ApiDoc::openapi()
+        .to_pretty_json()
+        .expect("openapi spec serializes");
+    json.push('\n');
+    json
+}
+
+/// GET /openapi.json
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// GET /docs
+pub async fn docs() -> impl IntoResponse {
+    (
+        [(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static(DOCS_CSP),
+        )],
+        Html(Scalar::new(ApiDoc::openapi()).to_html()),
+    )
+}
@@ -2,9 +2,10 @@ use std::sync::Arc;
 
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     db::AppState,
@@ -12,12 +13,29 @@ use crate::{
     models::search::{TmpSuggest, get_suggests_by_keyword},
 };
 
-#[derive(Deserialize, Clone, Serialize)]
+#[derive(Deserialize, Clone, Serialize, ToSchema)]
 pub struct SuggestRespond {
     pub item: Vec<TmpSuggest>,
 }
 
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct SuggestQuery {
+    /// 搜索关键词，匹配已发布文章的标题
+    pub q: String,
+}
+
 // 获取关键词搜索建议
+#[utoipa::path(
+    get,
+    path = "/suggestions/{keyword}",
+    operation_id = "searchSuggestionsLegacy",
+    summary = "搜索建议（已弃用）",
+    tag = "articles",
+    params(("keyword" = String, Path, description = "搜索关键词，可带引号")),
+    responses(
+        (status = 200, body = SuggestRespond),
+    )
+)]
 pub async fn handle_suggests_by_keys(
     State(state): State<Arc<AppState>>,
     Path(keyword): Path<String>,
@@ -27,3 +45,29 @@ pub async fn handle_suggests_by_keys(
 
     Ok(Json(SuggestRespond { item }))
 }
+
+/// 搜索建议，关键词为空时返回空列表
+#[utoipa::path(
+    get,
+    path = "/api/v1/search/suggestions",
+    operation_id = "searchSuggestions",
+    summary = "搜索建议",
+    tag = "articles",
+    params(SuggestQuery),
+    responses(
+        (status = 200, body = SuggestRespond),
+    )
+)]
+pub async fn suggestions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SuggestQuery>,
+) -> AppResult<Json<SuggestRespond>> {
+    let keyword = query.q.trim();
+    let item = if keyword.is_empty() {
+        Vec::new()
+    } else {
+        get_suggests_by_keyword(&state.pool, keyword).await?
+    };
+
+    Ok(Json(SuggestRespond { item }))
+}
@@ -0,0 +1,124 @@
+//! /setup 首次启动设置：在没有超级管理员时创建
+
+use crate::auth::{generate_token, hash_password};
+use crate::db::AppState;
+use crate::error::{AppResult, ErrorBody, ErrorCode};
+use crate::models::user::{UserPublic, find_user_by_username, has_super_admin, insert_super_admin};
+use crate::routes::audit::{record, snapshot};
+use crate::routes::auth::AuthResponse;
+use crate::validate::{self, Validate, ValidatedJson, Validator};
+use axum::{Json, extract::State, http::StatusCode};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct SetupStatus {
+    pub needs_setup: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetupPayload {
+    pub token: String,
+    pub username: String,
+    pub password: String,
+}
+
+impl Validate for SetupPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.token);
+        validate::trim(&mut self.username);
+        v.required("token", &self.token)
+            .username("username", &self.username)
+            .password("password", &self.password, 6);
+    }
+}
+
+/// GET /setup
+/// 前端据此决定是否展示首次设置页面
+#[utoipa::path(
+    get,
+    path = "/api/v1/setup",
+    operation_id = "getSetupStatus",
+    summary = "是否需要首次设置",
+    tag = "setup",
+    responses(
+        (status = 200, body = SetupStatus),
+    )
+)]
+pub async fn setup_status(State(state): State<Arc<AppState>>) -> AppResult<Json<SetupStatus>> {
+    let needs_setup = !has_super_admin(&state.pool).await?;
+    Ok(Json(SetupStatus { needs_setup }))
+}
+
+/// POST /setup
+/// 校验启动日志中打印的一次性 token，并创建超级管理员
+#[utoipa::path(
+    post,
+    path = "/api/v1/setup",
+    operation_id = "completeSetup",
+    summary = "创建超级管理员",
+    tag = "setup",
+    request_body = SetupPayload,
+    responses(
+        (status = 201, body = AuthResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    )
+)]
+pub async fn setup(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<SetupPayload>,
+) -> AppResult<(StatusCode, Json<AuthResponse>)> {
+    // 持有锁直到创建完成，避免并发请求创建多个超管
+    let mut setup_token = state.setup_token.lock().await;
+
+    if has_super_admin(&state.pool).await? {
+        return Err(ErrorCode::SetupCompleted.into());
+    }
+
+    match setup_token.as_deref() {
+        // 常量时间比较，避免按响应时间逐字节猜出 token
+        Some(expected) if bool::from(expected.as_bytes().ct_eq(payload.token.as_bytes())) => {}
+        _ => {
+            tracing::warn!("/setup: setup token 不正确");
+            return Err(ErrorCode::InvalidSetupToken.into());
+        }
+    }
+
+    if find_user_by_username(&state.pool, &payload.username)
+        .await?
+        .is_some()
+    {
+        return Err(ErrorCode::UsernameTaken.into());
+    }
+
+    let password_hash = hash_password(&payload.password)?;
+    let mut tx = state.pool.begin().await?;
+    let user = insert_super_admin(&mut tx, &payload.username, &password_hash).await?;
+    record(
+        &mut tx,
+        None,
+        "user.setup",
+        "user",
+        Some(&user.id),
+        None,
+        snapshot(&UserPublic::from(user.clone())),
+    )
+    .await?;
+    tx.commit().await?;
+    *setup_token = None;
+
+    let token = generate_token(&state, user.id.clone(), &user.username)?;
+    tracing::info!("首次设置完成，超级管理员: {}", user.username);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(AuthResponse {
+            token,
+            user: user.into(),
+        }),
+    ))
+}
@@ -0,0 +1,493 @@
+//! /api/v1/subscribers 邮件订阅：
+//! - 订阅：提交邮箱后发送确认邮件（双重确认），点击确认链接后生效；登录后订阅可同时开启评论回复提醒
+//! - 确认 / 退订：由邮件中的链接打开，返回 HTML 页面；退订同时支持 `List-Unsubscribe-Post` 一键退订
+//! - 管理员：订阅者列表（按状态过滤与分页）、删除订阅者、立即发送一期文章摘要
+//! - 未启用发信（`mail.enabled`）时订阅与发送摘要返回 503
+
+use crate::auth::{JwtAuth, MaybeJwtAuth};
+use crate::db::AppState;
+use crate::error::{AppResult, ErrorBody, ErrorCode, Format};
+use crate::mail::{self, DigestReport, template};
+use crate::models::subscriber::{
+    CONFIRMED, NewSubscription, PENDING, Subscriber, UNSUBSCRIBED, confirm_subscriber,
+    delete_subscriber, find_subscriber_by_id, list_subscribers, unsubscribe as unsubscribe_token,
+    upsert_pending_subscriber,
+};
+use crate::models::user::find_user_by_id;
+use crate::routes::audit::{record, snapshot};
+use crate::validate::{self, Validate, ValidatedJson, Validator};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Html,
+};
+use blog_shared::random_string;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+/// 邮箱地址长度上限
+const EMAIL_MAX: usize = 254;
+/// 确认与退订令牌长度
+const TOKEN_LEN: usize = 32;
+
+#[derive(Deserialize, ToSchema)]
+pub struct SubscribePayload {
+    pub email: String,
+    /// 接收文章摘要，默认开启
+    pub digest: Option<bool>,
+    /// 评论被回复时接收邮件，需要登录，默认关闭
+    pub replies: Option<bool>,
+}
+
+impl Validate for SubscribePayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.email);
+        v.format("email", &self.email, Format::Email).max_length(
+            "email",
+            Some(&self.email),
+            EMAIL_MAX,
+        );
+    }
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct TokenQuery {
+    /// 邮件链接中的令牌
+    pub token: String,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct SubscribersQuery {
+    /// pending / confirmed / unsubscribed
+    pub status: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SubscribersResponse {
+    pub subscribers: Vec<Subscriber>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+fn require_mail(state: &AppState) -> AppResult<()> {
+    if !mail::settings(state).0.enabled {
+        return Err(ErrorCode::MailDisabled.into());
+    }
+    Ok(())
+}
+
+fn generate_token() -> String {
+    random_string(TOKEN_LEN)
+}
+
+fn page(
+    state: &AppState,
+    status: StatusCode,
+    title: &str,
+    message: &str,
+) -> (StatusCode, Html<String>) {
+    let site = mail::settings(state).1.site_title;
+    (status, Html(template::page(&site, title, message)))
+}
+
+/// POST /api/v1/subscribers
+#[utoipa::path(
+    post,
+    path = "/api/v1/subscribers",
+    operation_id = "subscribe",
+    summary = "订阅邮件",
+    description = "发送确认邮件，点击其中的链接后订阅生效；已确认订阅的地址不会重复发送。开启 `replies` 需要登录",
+    tag = "subscribers",
+    request_body = SubscribePayload,
+    responses(
+        (status = 202, description = "已发送确认邮件"),
+        (status = 401, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+        (status = 503, body = ErrorBody),
+    )
+)]
+pub async fn subscribe(
+    State(state): State<Arc<AppState>>,
+    MaybeJwtAuth(auth): MaybeJwtAuth,
+    ValidatedJson(payload): ValidatedJson<SubscribePayload>,
+) -> AppResult<StatusCode> {
+    require_mail(&state)?;
+    let replies = payload.replies.unwrap_or(false);
+    if replies && auth.is_none() {
+        return Err(ErrorCode::MissingToken.into());
+    }
+
+    // 用户没有已验证的邮箱，不能直接关联；确认邮件中写明申请的账号，邮箱所有者确认后才关联
+    let requester = match &auth {
+        Some(auth) => find_user_by_id(&state.pool, auth.user_id.clone()).await?,
+        None => None,
+    };
+    let confirm_token = generate_token();
+    let created = upsert_pending_subscriber(
+        &state.pool,
+        NewSubscription {
+            email: &payload.email,
+            pending_user_id: requester.as_ref().map(|u| u.id.as_str()),
+            digest: payload.digest.unwrap_or(true),
+            replies,
+            confirm_token: &confirm_token,
+            unsubscribe_token: &generate_token(),
+        },
+    )
+    .await?;
+    // 已确认的地址不再发送确认邮件，响应相同，不暴露地址是否已订阅
+    if created.is_some() {
+        mail::send_confirmation(
+            &state,
+            &payload.email,
+            &confirm_token,
+            requester.as_ref().map(|u| u.username.as_str()),
+        )
+        .await?;
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// GET /api/v1/subscribers/confirm
+#[utoipa::path(
+    get,
+    path = "/api/v1/subscribers/confirm",
+    operation_id = "confirmSubscription",
+    summary = "确认订阅",
+    description = "确认邮件中的链接，返回 HTML 页面",
+    tag = "subscribers",
+    params(TokenQuery),
+    responses(
+        (status = 200, description = "订阅成功", content_type = "text/html", body = String),
+        (status = 404, description = "链接无效或已使用", content_type = "text/html", body = String),
+    )
+)]
+pub async fn confirm(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TokenQuery>,
+) -> AppResult<(StatusCode, Html<String>)> {
+    Ok(match confirm_subscriber(&state.pool, &query.token).await? {
+        Some(subscriber) => {
+            tracing::info!("订阅者 {} 已确认订阅", subscriber.id);
+            page(
+                &state,
+                StatusCode::OK,
+                "订阅成功",
+                "感谢订阅！有新文章时会通过邮件通知你。",
+            )
+        }
+        None => page(
+            &state,
+            StatusCode::NOT_FOUND,
+            "链接无效",
+            "确认链接无效或已经使用过。",
+        ),
+    })
+}
+
+/// GET /api/v1/subscribers/unsubscribe
+#[utoipa::path(
+    get,
+    path = "/api/v1/subscribers/unsubscribe",
+    operation_id = "unsubscribe",
+    summary = "退订",
+    description = "邮件中的退订链接，返回 HTML 页面；重复退订同样成功",
+    tag = "subscribers",
+    params(TokenQuery),
+    responses(
+        (status = 200, description = "已退订", content_type = "text/html", body = String),
+        (status = 404, description = "链接无效", content_type = "text/html", body = String),
+    )
+)]
+pub async fn unsubscribe(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TokenQuery>,
+) -> AppResult<(StatusCode, Html<String>)> {
+    Ok(match unsubscribe_token(&state.pool, &query.token).await? {
+        Some(subscriber) => {
+            tracing::info!("订阅者 {} 已退订", subscriber.id);
+            page(&state, StatusCode::OK, "已退订", "你将不再收到本站的邮件。")
+        }
+        None => page(&state, StatusCode::NOT_FOUND, "链接无效", "退订链接无效。"),
+    })
+}
+
+/// POST /api/v1/subscribers/unsubscribe
+#[utoipa::path(
+    post,
+    path = "/api/v1/subscribers/unsubscribe",
+    operation_id = "unsubscribeOneClick",
+    summary = "一键退订",
+    description = "供邮件客户端按 `List-Unsubscribe-Post` 调用（RFC 8058），请求体被忽略",
+    tag = "subscribers",
+    params(TokenQuery),
+    responses(
+        (status = 200, description = "已退订", content_type = "text/html", body = String),
+        (status = 404, description = "链接无效", content_type = "text/html", body = String),
+    )
+)]
+pub async fn unsubscribe_one_click(
+    state: State<Arc<AppState>>,
+    query: Query<TokenQuery>,
+) -> AppResult<(StatusCode, Html<String>)> {
+    unsubscribe(state, query).await
+}
+
+/// GET /api/v1/subscribers
+#[utoipa::path(
+    get,
+    path = "/api/v1/subscribers",
+    operation_id = "listSubscribers",
+    summary = "订阅者列表",
+    tag = "subscribers",
+    params(SubscribersQuery),
+    responses(
+        (status = 200, body = SubscribersResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Query(query): Query<SubscribersQuery>,
+) -> AppResult<Json<SubscribersResponse>> {
+    auth.require_admin(&state).await?;
+    if let Some(status) = &query.status {
+        let mut v = Validator::default();
+        v.one_of("status", status, &[PENDING, CONFIRMED, UNSUBSCRIBED]);
+        v.finish()?;
+    }
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+    let (subscribers, total) = list_subscribers(
+        &state.pool,
+        query.status.as_deref(),
+        per_page,
+        (page - 1) * per_page,
+    )
+    .await?;
+
+    Ok(Json(SubscribersResponse {
+        subscribers,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// DELETE /api/v1/subscribers/{id}
+#[utoipa::path(
+    delete,
+    path = "/api/v1/subscribers/{id}",
+    operation_id = "deleteSubscriber",
+    summary = "删除订阅者",
+    tag = "subscribers",
+    params(("id" = String, Path, description = "订阅者 id")),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn remove(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    auth.require_admin(&state).await?;
+
+    let subscriber = find_subscriber_by_id(&state.pool, &id)
+        .await?
+        .ok_or(ErrorCode::SubscriberNotFound)?;
+    let mut tx = state.pool.begin().await?;
+    delete_subscriber(&mut tx, &id).await?;
+    record(
+        &mut tx,
+        Some(&auth),
+        "subscriber.delete",
+        "subscriber",
+        Some(&id),
+        snapshot(&subscriber),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/v1/subscribers/digest
+#[utoipa::path(
+    post,
+    path = "/api/v1/subscribers/digest",
+    operation_id = "sendDigest",
+    summary = "立即发送文章摘要",
+    description = "收录上一期之后发布的文章发送一期摘要，没有新文章时不发送邮件；定时发送从此时重新计时",
+    tag = "subscribers",
+    responses(
+        (status = 200, body = DigestReport),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 503, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn digest(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<DigestReport>> {
+    auth.require_admin(&state).await?;
+    require_mail(&state)?;
+
+    let report = mail::send_digest(&state).await?;
+    tracing::info!(
+        "用户 {} 发送了文章摘要：{} 篇文章，成功 {}，失败 {}",
+        auth.user_id,
+        report.articles,
+        report.sent,
+        report.failed
+    );
+    record(
+        &mut *state.pool.acquire().await?,
+        Some(&auth),
+        "mail.digest",
+        "mail",
+        None,
+        None,
+        Some(json!(report)),
+    )
+    .await?;
+
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Claims;
+    use crate::mail::tests::{mail_state, sent};
+    use crate::models::subscriber::reply_recipient;
+
+    fn claims(user_id: &str) -> Claims {
+        Claims {
+            user_id: user_id.into(),
+            message: String::new(),
+            exp: usize::MAX,
+            iat: 0,
+            iss: String::new(),
+            aud: String::new(),
+            scopes: None,
+        }
+    }
+
+    async fn request(state: &Arc<AppState>, user_id: &str, email: &str) -> StatusCode {
+        subscribe(
+            State(state.clone()),
+            MaybeJwtAuth(Some(claims(user_id))),
+            ValidatedJson(SubscribePayload {
+                email: email.into(),
+                digest: None,
+                replies: Some(true),
+            }),
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn linked_user(state: &AppState, email: &str) -> Option<String> {
+        sqlx::query_scalar("SELECT user_id FROM subscribers WHERE email = ?")
+            .bind(email)
+            .fetch_one(&state.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn subscriptions_link_the_user_only_on_confirmation() {
+        let dir = std::env::temp_dir().join(format!("blog-mail-confirm-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let state = Arc::new(mail_state(&dir).await);
+        for (id, username) in [("u-bob", "bob"), ("u-mallory", "mallory")] {
+            sqlx::query("INSERT INTO users (id, username, identity) VALUES (?, ?, 'user')")
+                .bind(id)
+                .bind(username)
+                .execute(&state.pool)
+                .await
+                .unwrap();
+        }
+        let email = "bob@example.com";
+
+        // 申请后只发确认邮件，邮件写明申请的账号，此时尚未关联
+        assert_eq!(request(&state, "u-bob", email).await, StatusCode::ACCEPTED);
+        let mails = sent(&dir);
+        assert_eq!(mails.len(), 1);
+        assert!(mails[0].raw.contains(&format!("To: {email}")));
+        assert!(mails[0].text.contains("账号「bob」"), "{}", mails[0].text);
+        assert_eq!(linked_user(&state, email).await, None);
+        assert!(
+            reply_recipient(&state.pool, "u-bob")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // 打开邮件中的链接后关联到申请的账号
+        let prefix = "https://api.example.com/api/v1/subscribers/confirm?token=";
+        let link = mails[0]
+            .text
+            .lines()
+            .find(|l| l.starts_with(prefix))
+            .unwrap();
+        let token = link[prefix.len()..].to_string();
+        let (status, _) = confirm(
+            State(state.clone()),
+            Query(TokenQuery {
+                token: token.clone(),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(linked_user(&state, email).await.as_deref(), Some("u-bob"));
+        let recipient = reply_recipient(&state.pool, "u-bob")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(recipient.email, email);
+
+        // 确认链接只能使用一次
+        let (status, _) = confirm(State(state.clone()), Query(TokenQuery { token }))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // 他人再用这个地址申请：不发邮件，关联保持不变
+        assert_eq!(
+            request(&state, "u-mallory", email).await,
+            StatusCode::ACCEPTED
+        );
+        assert_eq!(sent(&dir).len(), 1);
+        assert_eq!(linked_user(&state, email).await.as_deref(), Some("u-bob"));
+        assert!(
+            reply_recipient(&state.pool, "u-mallory")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
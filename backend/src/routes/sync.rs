@@ -0,0 +1,95 @@
+//! /api/v1/sync 桌面端同步（仅管理员，个人访问令牌需要 `sync` scope），协议见 `crate::sync`
+
+use crate::auth::{ApiAuth, api_token::SYNC};
+use crate::db::AppState;
+use crate::error::{AppResult, ErrorBody};
+use crate::models::sync::list_changes;
+use crate::routes::audit::record;
+use crate::sync::{self, Changes, ChangesQuery, PushRequest, PushResponse, PushStatus};
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use std::sync::Arc;
+
+/// 单次拉取的默认与最大条数
+const DEFAULT_LIMIT: i64 = 200;
+const MAX_LIMIT: i64 = 1000;
+
+/// GET /api/v1/sync/changes?since=&limit=
+#[utoipa::path(
+    get,
+    path = "/api/v1/sync/changes",
+    operation_id = "syncChanges",
+    summary = "拉取变更",
+    tag = "sync",
+    params(ChangesQuery),
+    responses(
+        (status = 200, body = Changes),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn changes(
+    State(state): State<Arc<AppState>>,
+    ApiAuth(auth): ApiAuth,
+    Query(query): Query<ChangesQuery>,
+) -> AppResult<Json<Changes>> {
+    auth.require_scope(SYNC)?;
+    auth.require_admin(&state).await?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    Ok(Json(
+        list_changes(&state.pool, query.since.max(0), limit).await?,
+    ))
+}
+
+/// POST /api/v1/sync/push
+#[utoipa::path(
+    post,
+    path = "/api/v1/sync/push",
+    operation_id = "syncPush",
+    summary = "推送本地修改",
+    tag = "sync",
+    request_body = PushRequest,
+    responses(
+        (status = 200, body = PushResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn push(
+    State(state): State<Arc<AppState>>,
+    ApiAuth(auth): ApiAuth,
+    Json(req): Json<PushRequest>,
+) -> AppResult<Json<PushResponse>> {
+    auth.require_scope(SYNC)?;
+    auth.require_admin(&state).await?;
+
+    let mut tx = state.pool.begin().await?;
+    let resp = sync::push(&mut tx, req).await?;
+
+    // 一次推送记一条审计，只记录数量
+    let count =
+        |list: &[sync::PushResult], status| list.iter().filter(|r| r.status == status).count();
+    let after = serde_json::json!({
+        "articles": count(&resp.articles, PushStatus::Applied),
+        "conflicts": count(&resp.articles, PushStatus::Conflict),
+        "comments": count(&resp.comments, PushStatus::Applied),
+        "deleted": count(&resp.deleted, PushStatus::Applied),
+    });
+    record(
+        &mut tx,
+        Some(&auth),
+        "sync.push",
+        "blog",
+        None,
+        None,
+        Some(after),
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(Json(resp))
+}
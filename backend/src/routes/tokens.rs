@@ -0,0 +1,200 @@
+//! /api/v1/tokens 个人访问令牌管理（仅管理员）：
+//! - 创建：指定名称、scope 与有效期，明文令牌只返回一次
+//! - 列表：当前用户的全部令牌（不含明文与哈希）
+//! - 撤销：本人的令牌，超管可撤销任意令牌
+
+use crate::auth::{JwtAuth, api_token};
+use crate::db::AppState;
+use crate::error::{AppError, AppResult, ErrorBody, ErrorCode, Violation};
+use crate::models::api_token::{
+    ApiToken, find_api_token_by_id, insert_api_token, list_api_tokens_by_user, revoke_api_token,
+};
+use crate::routes::audit::{record, snapshot};
+use crate::validate::{self, Validate, ValidatedJson, Validator};
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateTokenPayload {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// 有效天数，为空表示永不过期
+    pub expires_in_days: Option<i64>,
+}
+
+impl Validate for CreateTokenPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.name);
+        v.length("name", &self.name, 1, 50).check(
+            "scopes",
+            !self.scopes.is_empty(),
+            Violation::Required,
+        );
+        for (i, scope) in self.scopes.iter().enumerate() {
+            v.one_of(format!("scopes[{}]", i), scope, api_token::ALL_SCOPES);
+        }
+        if let Some(days) = self.expires_in_days {
+            v.range("expires_in_days", days, 1, 3650);
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateTokenResponse {
+    /// 明文令牌，仅在创建时返回
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiToken,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TokensResponse {
+    pub tokens: Vec<ApiToken>,
+    /// 可申请的 scope，便于前端展示
+    #[schema(value_type = Vec<String>)]
+    pub available_scopes: &'static [&'static str],
+}
+
+/// GET /api/v1/tokens
+#[utoipa::path(
+    get,
+    path = "/api/v1/tokens",
+    operation_id = "listTokens",
+    summary = "个人访问令牌列表",
+    tag = "tokens",
+    responses(
+        (status = 200, body = TokensResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn list_tokens(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<TokensResponse>> {
+    auth.require_admin(&state).await?;
+
+    let tokens = list_api_tokens_by_user(&state.pool, &auth.user_id).await?;
+    Ok(Json(TokensResponse {
+        tokens,
+        available_scopes: api_token::ALL_SCOPES,
+    }))
+}
+
+/// POST /api/v1/tokens
+#[utoipa::path(
+    post,
+    path = "/api/v1/tokens",
+    operation_id = "createToken",
+    summary = "创建个人访问令牌",
+    tag = "tokens",
+    request_body = CreateTokenPayload,
+    responses(
+        (status = 201, body = CreateTokenResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn create_token(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    ValidatedJson(payload): ValidatedJson<CreateTokenPayload>,
+) -> AppResult<(StatusCode, Json<CreateTokenResponse>)> {
+    auth.require_admin(&state).await?;
+
+    let mut scopes = payload.scopes.clone();
+    scopes.sort();
+    scopes.dedup();
+
+    let (token, token_hash, prefix) = api_token::generate();
+    let mut tx = state.pool.begin().await?;
+    let info = insert_api_token(
+        &mut tx,
+        &auth.user_id,
+        &payload.name,
+        &token_hash,
+        &prefix,
+        &scopes.join(" "),
+        payload.expires_in_days,
+    )
+    .await?;
+    tracing::info!(
+        "用户 {} 创建了访问令牌 {} ({})",
+        auth.user_id,
+        info.name,
+        info.scopes
+    );
+    record(
+        &mut tx,
+        Some(&auth),
+        "token.create",
+        "token",
+        Some(&info.id),
+        None,
+        snapshot(&info),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateTokenResponse { token, info }),
+    ))
+}
+
+/// DELETE /api/v1/tokens/{id}
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tokens/{id}",
+    operation_id = "revokeToken",
+    summary = "撤销个人访问令牌",
+    tag = "tokens",
+    params(("id" = String, Path, description = "令牌 id")),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn revoke_token(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<StatusCode> {
+    let user = auth.require_admin(&state).await?;
+
+    let token = find_api_token_by_id(&state.pool, &id)
+        .await?
+        .ok_or(ErrorCode::TokenNotFound)?;
+    if token.user_id != auth.user_id && !user.is_super_admin {
+        return Err(AppError::Forbidden);
+    }
+
+    let mut tx = state.pool.begin().await?;
+    revoke_api_token(&mut tx, &id).await?;
+    record(
+        &mut tx,
+        Some(&auth),
+        "token.revoke",
+        "token",
+        Some(&id),
+        snapshot(&token),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+    tracing::info!("用户 {} 撤销了访问令牌 {}", auth.user_id, token.name);
+
+    Ok(StatusCode::NO_CONTENT)
+}
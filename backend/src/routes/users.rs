@@ -1,22 +1,41 @@
 //! /users 相关路由：列表（受保护）
 
-use crate::auth::{JwtAuth, hash_password};
+use crate::auth::{Claims, JwtAuth, hash_password};
 use crate::db::AppState;
-use crate::error::{AppError, AppResult};
-use crate::models::user::{UserPublic, delete_user_by_id, edit_user_account, list_users};
-use crate::routes::auth::get_ident_by_id;
+use crate::error::{AppError, AppResult, ErrorBody, ErrorCode, Format};
+use crate::models::api_token::revoke_api_tokens_by_user;
+use crate::models::identity::delete_identities_by_user;
+use crate::models::notification::delete_notifications_by_user;
+use crate::models::subscriber::unlink_user;
+use crate::models::user::{
+    UserPublic, delete_user_by_id, edit_user_account, find_user_by_id, list_users,
+};
+use crate::routes::audit::{record, snapshot};
+use crate::validate::{self, IDENTITIES, Validate, ValidatedJson, Validator};
 use axum::extract::{Path, Query};
 use axum::{Json, extract::State};
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ListUsersResponse {
     users: Vec<UserPublic>,
 }
 
 // HTTP层函数，对外接口
+#[utoipa::path(
+    get,
+    path = "/api/users",
+    operation_id = "listUsersLegacy",
+    summary = "用户列表（已弃用）",
+    tag = "users",
+    params(UsersQuery),
+    responses(
+        (status = 200, body = ListUsersResponse),
+    )
+)]
 pub async fn get_users(
     State(state): State<Arc<AppState>>,
     Query(query): Query<UsersQuery>,
@@ -30,79 +49,220 @@ pub async fn get_users(
     Ok(Json(ListUsersResponse { users }))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/users/{id}",
+    operation_id = "deleteUser",
+    summary = "删除用户",
+    tag = "users",
+    params(("id" = String, Path, description = "用户 id")),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
 pub async fn delete_users(
     State(state): State<Arc<AppState>>,
     Path(user_id): Path<String>,
     JwtAuth(auth): JwtAuth,
 ) -> AppResult<StatusCode> {
+    remove_user(&state, &auth, &user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 删除用户并吊销其登录方式，仅管理员，超管不可删除
+pub(crate) async fn remove_user(state: &AppState, auth: &Claims, user_id: &str) -> AppResult<()> {
     tracing::info!("Received request to delete user: {:?}", user_id);
 
     // 验证前端传来的token
-    if get_ident_by_id(&state.pool, &auth.user_id).await? != "admin" {
+    auth.require_admin(state).await.inspect_err(|_| {
         tracing::warn!("Unauthorized delete attempt by user: {:?}", auth.user_id);
-        return Err(crate::error::AppError::Unauthorized(
-            "only admin can delete users".into(),
-        ));
+    })?;
+
+    // 超管不可被删除
+    let target = find_user_by_id(&state.pool, user_id.to_string()).await?;
+    if target.as_ref().is_some_and(|t| t.is_super_admin) {
+        tracing::warn!("Attempt to delete superadmin by user: {:?}", auth.user_id);
+        return Err(ErrorCode::SuperAdminProtected.into());
     }
 
-    delete_user_by_id(&state.pool, &user_id).await?;
+    let mut tx = state.pool.begin().await?;
+    delete_user_by_id(&mut tx, user_id).await?;
+    delete_identities_by_user(&mut tx, user_id).await?;
+    revoke_api_tokens_by_user(&mut tx, user_id).await?;
+    delete_notifications_by_user(&mut tx, user_id).await?;
+    unlink_user(&mut tx, user_id).await?;
+    record(
+        &mut tx,
+        Some(auth),
+        "user.delete",
+        "user",
+        Some(user_id),
+        target.map(UserPublic::from).as_ref().and_then(snapshot),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
     tracing::info!("Deleted user: {:?}", user_id);
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, IntoParams)]
 pub struct UsersQuery {
     pub limit: Option<i32>,
 }
 
-// 编辑用户账号（仅管理员可用）
-pub async fn edit_account(
+/// 用户列表，仅管理员
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    operation_id = "listUsers",
+    summary = "用户列表",
+    tag = "users",
+    params(UsersQuery),
+    responses(
+        (status = 200, body = ListUsersResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn list_all(
     State(state): State<Arc<AppState>>,
     JwtAuth(auth): JwtAuth,
-    Json(payload): Json<AdminEditAccountPayload>,
-) -> AppResult<StatusCode> {
+    Query(query): Query<UsersQuery>,
+) -> AppResult<Json<ListUsersResponse>> {
+    auth.require_admin(&state).await?;
+    let users = list_users(&state.pool, query.limit.unwrap_or(10)).await?;
+    Ok(Json(ListUsersResponse { users }))
+}
+
+/// 获取用户，仅管理员
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}",
+    operation_id = "getUser",
+    summary = "获取用户",
+    tag = "users",
+    params(("id" = String, Path, description = "用户 id")),
+    responses(
+        (status = 200, body = UserPublic),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn get_user(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<UserPublic>> {
+    auth.require_admin(&state).await?;
+    let user = find_user_by_id(&state.pool, user_id)
+        .await?
+        .ok_or(ErrorCode::UserNotFound)?;
+    Ok(Json(user.into()))
+}
+
+/// 修改用户，只需提交要修改的字段
+#[utoipa::path(
+    patch,
+    path = "/api/v1/users/{id}",
+    operation_id = "updateUser",
+    summary = "修改用户",
+    tag = "users",
+    params(("id" = String, Path, description = "用户 id")),
+    request_body = UpdateUser,
+    responses(
+        (status = 200, body = UserPublic),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn update_user(
+    State(state): State<Arc<AppState>>,
+    Path(user_id): Path<String>,
+    JwtAuth(auth): JwtAuth,
+    ValidatedJson(payload): ValidatedJson<UpdateUser>,
+) -> AppResult<Json<UserPublic>> {
+    Ok(Json(edit_user(&state, &auth, user_id, payload).await?))
+}
+
+/// 修改用户，仅管理员
+pub(crate) async fn edit_user(
+    state: &AppState,
+    auth: &Claims,
+    user_id: String,
+    payload: UpdateUser,
+) -> AppResult<UserPublic> {
+    auth.require_admin(state).await?;
+    if find_user_by_id(&state.pool, user_id.clone())
+        .await?
+        .is_none()
+    {
+        return Err(ErrorCode::UserNotFound.into());
+    }
+
+    let payload = AdminEditAccountPayload {
+        current_token: None,
+        edited_id: user_id,
+        edited_username: payload.username,
+        edited_password: payload.password,
+        edited_identity: payload.identity,
+    };
+    let user = apply_edit(state, auth, payload)
+        .await?
+        .ok_or(ErrorCode::UserNotFound)?;
+    Ok(user)
+}
+
+/// 编辑用户账号（仅管理员），返回修改后的用户；用户不存在时返回 None
+async fn apply_edit(
+    state: &AppState,
+    auth: &Claims,
+    payload: AdminEditAccountPayload,
+) -> AppResult<Option<UserPublic>> {
     tracing::info!(
         "AdminEditAccountPayload() Received request to edit account: {:?}",
         auth.user_id
     );
 
-    // 验证前端传来的token是否为空
-    if payload.current_token.clone().is_none() {
-        tracing::warn!(
-            "No current_token provided in edit account request by user: {:?}",
-            auth.user_id
-        );
-        return Err(AppError::BadRequest("current_token is required".into()));
-    }
-
     // 仅管理员编辑
-    if get_ident_by_id(&state.pool, &auth.user_id).await? != "admin" {
+    auth.require_admin(state).await.inspect_err(|_| {
         tracing::warn!(
             "Unauthorized edit attempt by user: {:?}, current identity: {:?}",
             auth.user_id,
             payload.edited_identity
         );
-        return Err(AppError::Unauthorized(
-            "cannot edit other user's account".into(),
-        ));
-    }
+    })?;
 
-    // 防止更改超管的权限
-    if payload.edited_identity.clone().unwrap_or_default() == "admin" && &payload.edited_id == "1" {
-        tracing::warn!(
-            "Attempt to change identity to superadmin by user: {:?}",
-            auth.user_id
-        );
-        return Err(AppError::Unauthorized(
-            "cannot change identity to superadmin".into(),
-        ));
+    // 防止更改超管的权限：只有超管本人能编辑自己的账号，且身份不可降级
+    let before = find_user_by_id(&state.pool, payload.edited_id.clone()).await?;
+    if let Some(target) = &before
+        && target.is_super_admin
+    {
+        let demoted = payload
+            .edited_identity
+            .as_deref()
+            .is_some_and(|ident| ident != "admin");
+        if target.id != auth.user_id || demoted {
+            tracing::warn!(
+                "Attempt to change superadmin account by user: {:?}",
+                auth.user_id
+            );
+            return Err(ErrorCode::SuperAdminProtected.into());
+        }
     }
 
     // 将传递的密码转为hash
-    let new_password = if let Some(ref password) = payload.edited_password
-        && !password.is_empty()
-    {
+    let new_password = if let Some(ref password) = payload.edited_password {
         match hash_password(password) {
             Ok(hashed) => Some(hashed),
             Err(e) => {
@@ -119,6 +279,7 @@ pub async fn edit_account(
     } else {
         None
     };
+    let password_changed = new_password.is_some();
     let payload = AdminEditAccountPayload {
         current_token: payload.current_token,
         edited_id: payload.edited_id,
@@ -126,15 +287,61 @@ pub async fn edit_account(
         edited_password: new_password,
         edited_identity: payload.edited_identity,
     };
+    let edited_id = payload.edited_id.clone();
 
-    edit_user_account(&state.pool, payload).await?;
+    let mut tx = state.pool.begin().await?;
+    edit_user_account(&mut tx, payload).await?;
 
+    // 快照不含密码哈希，仅标记密码是否被修改
+    let updated = find_user_by_id(&mut *tx, edited_id.clone())
+        .await?
+        .map(UserPublic::from);
+    let after = updated.as_ref().and_then(snapshot).map(|mut v| {
+        v["password_changed"] = password_changed.into();
+        v
+    });
+    record(
+        &mut tx,
+        Some(auth),
+        "user.update",
+        "user",
+        Some(&edited_id),
+        before.map(UserPublic::from).as_ref().and_then(snapshot),
+        after,
+    )
+    .await?;
+    tx.commit().await?;
     tracing::info!("Edited account for user: {:?}", auth.user_id);
 
+    Ok(updated)
+}
+
+// 编辑用户账号（仅管理员可用）
+#[utoipa::path(
+    put,
+    path = "/api/editAccount",
+    operation_id = "editAccountLegacy",
+    summary = "编辑用户账号（已弃用）",
+    tag = "users",
+    request_body = AdminEditAccountPayload,
+    responses(
+        (status = 200),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn edit_account(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    ValidatedJson(payload): ValidatedJson<AdminEditAccountPayload>,
+) -> AppResult<StatusCode> {
+    apply_edit(&state, &auth, payload).await?;
     Ok(StatusCode::OK)
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct AdminEditAccountPayload {
     pub current_token: Option<String>,
     pub edited_id: String,
@@ -142,3 +349,63 @@ pub struct AdminEditAccountPayload {
     pub edited_password: Option<String>,
     pub edited_identity: Option<String>,
 }
+
+impl Validate for AdminEditAccountPayload {
+    /// 空的用户名、密码与身份视为不修改
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim_opt(&mut self.current_token);
+        validate::trim(&mut self.edited_id);
+        validate::trim_opt(&mut self.edited_username);
+        validate::trim_opt(&mut self.edited_identity);
+        if self.edited_password.as_deref() == Some("") {
+            self.edited_password = None;
+        }
+
+        v.required(
+            "current_token",
+            self.current_token.as_deref().unwrap_or_default(),
+        )
+        .format("edited_id", &self.edited_id, Format::Slug);
+        if let Some(username) = &self.edited_username {
+            v.username("edited_username", username);
+        }
+        if let Some(password) = &self.edited_password {
+            v.password("edited_password", password, 3);
+        }
+        if let Some(identity) = &self.edited_identity {
+            v.one_of("edited_identity", identity, IDENTITIES);
+        }
+    }
+}
+
+/// v1 修改用户，省略的字段不修改
+#[derive(Deserialize, Debug, ToSchema)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(async_graphql::InputObject),
+    graphql(name = "UserInput")
+)]
+pub struct UpdateUser {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub identity: Option<String>,
+}
+
+impl Validate for UpdateUser {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim_opt(&mut self.username);
+        validate::trim_opt(&mut self.identity);
+        if self.password.as_deref() == Some("") {
+            self.password = None;
+        }
+        if let Some(username) = &self.username {
+            v.username("username", username);
+        }
+        if let Some(password) = &self.password {
+            v.password("password", password, 3);
+        }
+        if let Some(identity) = &self.identity {
+            v.one_of("identity", identity, IDENTITIES);
+        }
+    }
+}
@@ -0,0 +1,431 @@
+//! /api/v1/webhooks Webhook 订阅管理（仅管理员）：
+//! - 创建：指定地址、订阅的事件与签名密钥，未指定密钥时自动生成，密钥只在创建时返回
+//! - 列表 / 修改（可停用、更换密钥）/ 删除（同时删除投递记录）
+//! - 投递记录：按状态过滤与分页，失败的记录可重新投递
+//! - 发送测试事件：写入一条 `ping` 投递，不要求订阅，停用的订阅也会投递
+
+use crate::auth::JwtAuth;
+use crate::db::AppState;
+use crate::error::{AppResult, ErrorBody, ErrorCode, Format, Violation};
+use crate::models::webhook::{
+    DEAD, DELIVERED, PENDING, Webhook, WebhookDelivery, delete_webhook, find_webhook_by_id,
+    insert_webhook, list_deliveries, list_webhooks, reset_delivery, update_webhook,
+};
+use crate::routes::audit::{record, snapshot};
+use crate::validate::{self, Validate, ValidatedJson, Validator};
+use crate::webhooks::{self, EVENTS, PING};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+/// 订阅地址长度上限
+const URL_MAX: usize = 2000;
+/// 密钥长度
+const SECRET_LEN: (usize, usize) = (16, 256);
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateWebhookPayload {
+    pub url: String,
+    /// 订阅的事件：article.published / comment.created
+    pub events: Vec<String>,
+    /// 签名密钥，为空时自动生成
+    pub secret: Option<String>,
+    /// 默认启用
+    pub active: Option<bool>,
+}
+
+impl Validate for CreateWebhookPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.url);
+        validate::trim_opt(&mut self.secret);
+        check_url(v, &self.url);
+        check_events(v, &self.events);
+        if let Some(secret) = &self.secret {
+            v.length("secret", secret, SECRET_LEN.0, SECRET_LEN.1);
+        }
+    }
+}
+
+/// 修改订阅，省略的字段不修改
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateWebhookPayload {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    /// 更换签名密钥
+    pub secret: Option<String>,
+    pub active: Option<bool>,
+}
+
+impl Validate for UpdateWebhookPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim_opt(&mut self.url);
+        validate::trim_opt(&mut self.secret);
+        if let Some(url) = &self.url {
+            check_url(v, url);
+        }
+        if let Some(events) = &self.events {
+            check_events(v, events);
+        }
+        if let Some(secret) = &self.secret {
+            v.length("secret", secret, SECRET_LEN.0, SECRET_LEN.1);
+        }
+    }
+}
+
+fn check_url(v: &mut Validator, url: &str) {
+    v.format("url", url, Format::Url)
+        .max_length("url", Some(url), URL_MAX);
+}
+
+fn check_events(v: &mut Validator, events: &[String]) {
+    v.check("events", !events.is_empty(), Violation::Required);
+    for (i, event) in events.iter().enumerate() {
+        v.one_of(format!("events[{}]", i), event, EVENTS);
+    }
+}
+
+/// 去重排序后以空格连接
+fn join_events(events: &[String]) -> String {
+    let mut events = events.to_vec();
+    events.sort();
+    events.dedup();
+    events.join(" ")
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateWebhookResponse {
+    /// 签名密钥，仅在创建时返回
+    pub secret: String,
+    #[serde(flatten)]
+    pub webhook: Webhook,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WebhooksResponse {
+    pub webhooks: Vec<Webhook>,
+    /// 可订阅的事件，便于前端展示
+    #[schema(value_type = Vec<String>)]
+    pub available_events: &'static [&'static str],
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+pub struct DeliveriesQuery {
+    /// pending / delivered / dead
+    pub status: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeliveriesResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+async fn find_webhook(state: &AppState, id: &str) -> AppResult<Webhook> {
+    Ok(find_webhook_by_id(&state.pool, id)
+        .await?
+        .ok_or(ErrorCode::WebhookNotFound)?)
+}
+
+/// GET /api/v1/webhooks
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks",
+    operation_id = "listWebhooks",
+    summary = "Webhook 列表",
+    tag = "webhooks",
+    responses(
+        (status = 200, body = WebhooksResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+) -> AppResult<Json<WebhooksResponse>> {
+    auth.require_admin(&state).await?;
+    Ok(Json(WebhooksResponse {
+        webhooks: list_webhooks(&state.pool).await?,
+        available_events: EVENTS,
+    }))
+}
+
+/// POST /api/v1/webhooks
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks",
+    operation_id = "createWebhook",
+    summary = "创建 Webhook",
+    tag = "webhooks",
+    request_body = CreateWebhookPayload,
+    responses(
+        (status = 201, body = CreateWebhookResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    ValidatedJson(payload): ValidatedJson<CreateWebhookPayload>,
+) -> AppResult<(StatusCode, Json<CreateWebhookResponse>)> {
+    auth.require_admin(&state).await?;
+
+    let secret = payload.secret.unwrap_or_else(webhooks::generate_secret);
+    let mut tx = state.pool.begin().await?;
+    let webhook = insert_webhook(
+        &mut tx,
+        &payload.url,
+        &secret,
+        &join_events(&payload.events),
+        payload.active.unwrap_or(true),
+    )
+    .await?;
+    record(
+        &mut tx,
+        Some(&auth),
+        "webhook.create",
+        "webhook",
+        Some(&webhook.id),
+        None,
+        snapshot(&webhook),
+    )
+    .await?;
+    tx.commit().await?;
+    tracing::info!("用户 {} 创建了 webhook {}", auth.user_id, webhook.url);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateWebhookResponse { secret, webhook }),
+    ))
+}
+
+/// PUT /api/v1/webhooks/{id}
+#[utoipa::path(
+    put,
+    path = "/api/v1/webhooks/{id}",
+    operation_id = "updateWebhook",
+    summary = "修改 Webhook",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    request_body = UpdateWebhookPayload,
+    responses(
+        (status = 200, body = Webhook),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Path(id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<UpdateWebhookPayload>,
+) -> AppResult<Json<Webhook>> {
+    auth.require_admin(&state).await?;
+
+    let before = find_webhook(&state, &id).await?;
+    let events = payload.events.as_deref().map(join_events);
+    let mut tx = state.pool.begin().await?;
+    let webhook = update_webhook(
+        &mut tx,
+        &id,
+        payload.url.as_deref(),
+        payload.secret.as_deref(),
+        events.as_deref(),
+        payload.active,
+    )
+    .await?
+    .ok_or(ErrorCode::WebhookNotFound)?;
+    record(
+        &mut tx,
+        Some(&auth),
+        if payload.secret.is_some() {
+            "webhook.rotate_secret"
+        } else {
+            "webhook.update"
+        },
+        "webhook",
+        Some(&id),
+        snapshot(&before),
+        snapshot(&webhook),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(webhook))
+}
+
+/// DELETE /api/v1/webhooks/{id}
+#[utoipa::path(
+    delete,
+    path = "/api/v1/webhooks/{id}",
+    operation_id = "deleteWebhook",
+    summary = "删除 Webhook",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn remove(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    auth.require_admin(&state).await?;
+
+    let webhook = find_webhook(&state, &id).await?;
+    let mut tx = state.pool.begin().await?;
+    if !delete_webhook(&mut tx, &id).await? {
+        return Err(ErrorCode::WebhookNotFound.into());
+    }
+    record(
+        &mut tx,
+        Some(&auth),
+        "webhook.delete",
+        "webhook",
+        Some(&id),
+        snapshot(&webhook),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+    tracing::info!("用户 {} 删除了 webhook {}", auth.user_id, webhook.url);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/v1/webhooks/{id}/deliveries
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}/deliveries",
+    operation_id = "listWebhookDeliveries",
+    summary = "Webhook 投递记录",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id"), DeliveriesQuery),
+    responses(
+        (status = 200, body = DeliveriesResponse),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+        (status = 422, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn deliveries(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Path(id): Path<String>,
+    Query(query): Query<DeliveriesQuery>,
+) -> AppResult<Json<DeliveriesResponse>> {
+    auth.require_admin(&state).await?;
+    find_webhook(&state, &id).await?;
+    if let Some(status) = &query.status {
+        let mut v = Validator::default();
+        v.one_of("status", status, &[PENDING, DELIVERED, DEAD]);
+        v.finish()?;
+    }
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+    let (deliveries, total) = list_deliveries(
+        &state.pool,
+        &id,
+        query.status.as_deref(),
+        per_page,
+        (page - 1) * per_page,
+    )
+    .await?;
+
+    Ok(Json(DeliveriesResponse {
+        deliveries,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// POST /api/v1/webhooks/{id}/test
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/{id}/test",
+    operation_id = "testWebhook",
+    summary = "发送测试事件",
+    description = "写入一条 `ping` 投递并立即发送，结果见投递记录",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 202, body = WebhookDelivery),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn test(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Path(id): Path<String>,
+) -> AppResult<(StatusCode, Json<WebhookDelivery>)> {
+    auth.require_admin(&state).await?;
+    let webhook = find_webhook(&state, &id).await?;
+
+    let data = json!({ "webhook_id": webhook.id, "message": "测试事件" });
+    let delivery = webhooks::enqueue(&state.pool, &webhook.id, PING, &data).await?;
+    state.webhook_wakeup.notify_one();
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
+
+/// POST /api/v1/webhooks/{id}/deliveries/{delivery_id}/retry
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/{id}/deliveries/{delivery_id}/retry",
+    operation_id = "retryWebhookDelivery",
+    summary = "重新投递",
+    description = "重置尝试次数并立即投递，可用于 dead 的记录",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+        ("delivery_id" = String, Path, description = "投递记录 id"),
+    ),
+    responses(
+        (status = 202, body = WebhookDelivery),
+        (status = 401, body = ErrorBody),
+        (status = 403, body = ErrorBody),
+        (status = 404, body = ErrorBody),
+    ),
+    security(("bearer" = []))
+)]
+pub async fn retry(
+    State(state): State<Arc<AppState>>,
+    JwtAuth(auth): JwtAuth,
+    Path((id, delivery_id)): Path<(String, String)>,
+) -> AppResult<(StatusCode, Json<WebhookDelivery>)> {
+    auth.require_admin(&state).await?;
+
+    let delivery = reset_delivery(&state.pool, &id, &delivery_id)
+        .await?
+        .ok_or(ErrorCode::WebhookDeliveryNotFound)?;
+    state.webhook_wakeup.notify_one();
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
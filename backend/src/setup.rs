@@ -0,0 +1,50 @@
+//! 首次启动引导：
+//! - 数据库中没有超级管理员时，优先使用 `ADMIN_USERNAME`/`ADMIN_PASSWORD` 创建超管
+//! - 未提供环境变量时，生成一次性 setup token 打印到日志，交给 `POST /setup` 使用
+
+use crate::auth::hash_password;
+use crate::db::AppState;
+use crate::models::user::{UserPublic, has_super_admin, insert_super_admin};
+use crate::routes::audit::{record, snapshot};
+use blog_shared::random_string;
+
+/// 启动时调用，检查是否需要首次设置
+pub async fn bootstrap(state: &AppState) -> anyhow::Result<()> {
+    if has_super_admin(&state.pool).await? {
+        return Ok(());
+    }
+
+    if let (Some(username), Some(password)) = (
+        &state.cfg.auth.admin_username,
+        &state.cfg.auth.admin_password,
+    ) {
+        let password_hash = hash_password(password)?;
+        let mut tx = state.pool.begin().await?;
+        let user = insert_super_admin(&mut tx, username, &password_hash).await?;
+        record(
+            &mut tx,
+            None,
+            "user.setup",
+            "user",
+            Some(&user.id),
+            None,
+            snapshot(&UserPublic::from(user.clone())),
+        )
+        .await?;
+        tx.commit().await?;
+        tracing::info!("已通过环境变量创建超级管理员: {}", user.username);
+        return Ok(());
+    }
+
+    let token = generate_setup_token();
+    tracing::warn!("未找到管理员账号，请使用一次性 setup token 调用 POST /setup 创建超级管理员");
+    tracing::warn!("setup token: {}", token);
+    *state.setup_token.lock().await = Some(token);
+
+    Ok(())
+}
+
+/// 生成一次性 setup token
+fn generate_setup_token() -> String {
+    random_string(32)
+}
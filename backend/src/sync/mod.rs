@@ -0,0 +1,440 @@
+//! 桌面端同步协议：
+//! - 拉取：`GET /api/v1/sync/changes?since=<序号>`，按全局变更序号增量返回文章、评论与删除记录
+//! - 推送：`POST /api/v1/sync/push`，客户端带上本地修改与上次同步时看到的字段时钟（`base_clocks`）
+//!
+//! 合并规则（按字段）：
+//! - 服务端字段自 `base_clocks` 以来未变化：直接采用客户端的值
+//! - 双方都修改了：正文（content）记为冲突，由用户手动处理；其余字段比较时钟，后写者胜
+//! - 删除文章时客户端带上删除前的 `base_clocks`：服务端此后又有修改则拒绝删除（记为冲突），否则删除
+//!
+//! 字段时钟与变更序号由数据库触发器维护（见 `migrations/0010_add_sync.sql`）
+
+use crate::models::sync::{
+    article_exists, comment_exists, delete_sync_article, delete_sync_comment, find_sync_article,
+    insert_sync_article, insert_sync_comment, is_deleted, update_sync_article,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+/// 字段名 → 最后修改的毫秒时间戳
+pub type Clocks = BTreeMap<String, i64>;
+
+/// 需要手动处理冲突的字段
+pub const CONTENT: &str = "content";
+
+/// 参与合并的文章字段，`created_at` 只在创建时写入
+pub const ARTICLE_FIELDS: [&str; 6] =
+    ["title", "content", "summary", "tags", "status", "update_at"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SyncArticle {
+    pub id: String,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub summary: Option<String>,
+    pub tags: Option<String>,
+    pub status: Option<String>,
+    pub created_at: Option<String>,
+    pub update_at: Option<String>,
+    #[sqlx(json)]
+    #[schema(inline)]
+    pub clocks: Clocks,
+}
+
+impl SyncArticle {
+    fn field(&self, name: &str) -> &Option<String> {
+        match name {
+            "title" => &self.title,
+            "content" => &self.content,
+            "summary" => &self.summary,
+            "tags" => &self.tags,
+            "status" => &self.status,
+            "update_at" => &self.update_at,
+            _ => unreachable!("unknown sync field {name}"),
+        }
+    }
+
+    fn field_mut(&mut self, name: &str) -> &mut Option<String> {
+        match name {
+            "title" => &mut self.title,
+            "content" => &mut self.content,
+            "summary" => &mut self.summary,
+            "tags" => &mut self.tags,
+            "status" => &mut self.status,
+            "update_at" => &mut self.update_at,
+            _ => unreachable!("unknown sync field {name}"),
+        }
+    }
+
+    fn clock(&self, name: &str) -> i64 {
+        self.clocks.get(name).copied().unwrap_or(0)
+    }
+
+    /// 是否有字段在 `base` 之后被修改过
+    pub fn changed_since(&self, base: &Clocks) -> bool {
+        ARTICLE_FIELDS
+            .iter()
+            .any(|name| self.clock(name) > base.get(*name).copied().unwrap_or(0))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SyncComment {
+    pub comment_id: String,
+    pub article_id: Option<String>,
+    pub user: Option<String>,
+    pub content: Option<String>,
+    pub created_at: Option<String>,
+    pub parent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Tombstone {
+    /// `article` / `comment`
+    pub entity: String,
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ChangesQuery {
+    #[serde(default)]
+    pub since: i64,
+    pub limit: Option<i64>,
+}
+
+/// 拉取结果，客户端保存 `cursor` 作为下次的 `since`；`more` 为真时继续拉取
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Changes {
+    pub cursor: i64,
+    pub more: bool,
+    pub articles: Vec<SyncArticle>,
+    pub comments: Vec<SyncComment>,
+    pub deleted: Vec<Tombstone>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PushArticle {
+    #[serde(flatten)]
+    pub article: SyncArticle,
+    /// 上次同步时服务端的字段时钟，从未同步过的文章为空
+    #[schema(inline)]
+    pub base_clocks: Option<Clocks>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PushTombstone {
+    #[serde(flatten)]
+    pub tombstone: Tombstone,
+    /// 删除前上次同步时服务端的字段时钟，只对文章有效；为空时服务端已有的文章不会被删除
+    #[serde(default)]
+    #[schema(inline)]
+    pub base_clocks: Option<Clocks>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct PushRequest {
+    #[serde(default)]
+    pub articles: Vec<PushArticle>,
+    #[serde(default)]
+    pub comments: Vec<SyncComment>,
+    #[serde(default)]
+    pub deleted: Vec<PushTombstone>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PushStatus {
+    /// 已写入（或服务端已有相同数据）
+    Applied,
+    /// 正文冲突，其余字段已合并；删除文章时表示服务端有更新的修改，未删除。`article` 为服务端当前版本
+    Conflict,
+    /// 服务端已删除
+    Deleted,
+    /// 无法写入，如评论所属的文章不存在
+    Skipped,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PushResult {
+    pub id: String,
+    pub status: PushStatus,
+    /// 文章合并后的服务端版本，客户端据此更新本地数据与 `base_clocks`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub article: Option<SyncArticle>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct PushResponse {
+    pub articles: Vec<PushResult>,
+    pub comments: Vec<PushResult>,
+    pub deleted: Vec<PushResult>,
+}
+
+/// 按字段合并客户端的修改，返回合并结果及正文是否冲突
+pub fn merge(server: &SyncArticle, incoming: &SyncArticle, base: &Clocks) -> (SyncArticle, bool) {
+    let mut merged = server.clone();
+    let mut conflict = false;
+
+    for name in ARTICLE_FIELDS {
+        let (sc, cc) = (server.clock(name), incoming.clock(name));
+        let bc = base.get(name).copied().unwrap_or(0);
+
+        if server.field(name) == incoming.field(name) {
+            merged.clocks.insert(name.to_string(), sc.max(cc));
+        } else if cc <= bc {
+            // 客户端没有修改这个字段，保留服务端的值
+        } else if sc <= bc || (name != CONTENT && cc > sc) {
+            *merged.field_mut(name) = incoming.field(name).clone();
+            merged.clocks.insert(name.to_string(), cc);
+        } else if name == CONTENT {
+            conflict = true;
+        }
+    }
+    (merged, conflict)
+}
+
+/// 写入客户端推送的数据：先文章，再评论，最后删除
+/// 由调用方开启并提交事务，以便与审计记录一起提交
+pub async fn push(
+    conn: &mut SqliteConnection,
+    req: PushRequest,
+) -> Result<PushResponse, sqlx::Error> {
+    let mut resp = PushResponse::default();
+
+    for PushArticle {
+        article,
+        base_clocks,
+    } in req.articles
+    {
+        let id = article.id.clone();
+        let (status, article) = match find_sync_article(&mut *conn, &id).await? {
+            None if is_deleted(&mut *conn, "article", &id).await? => (PushStatus::Deleted, None),
+            None => {
+                insert_sync_article(&mut *conn, &article).await?;
+                (
+                    PushStatus::Applied,
+                    find_sync_article(&mut *conn, &id).await?,
+                )
+            }
+            Some(server) => {
+                let (merged, conflict) = merge(&server, &article, &base_clocks.unwrap_or_default());
+                if merged != server {
+                    update_sync_article(&mut *conn, &merged).await?;
+                }
+                let status = if conflict {
+                    PushStatus::Conflict
+                } else {
+                    PushStatus::Applied
+                };
+                (status, Some(merged))
+            }
+        };
+        resp.articles.push(PushResult {
+            id,
+            status,
+            article,
+        });
+    }
+
+    for comment in req.comments {
+        let id = comment.comment_id.clone();
+        let article_id = comment.article_id.as_deref().unwrap_or_default();
+        let status = if comment_exists(&mut *conn, &id).await? {
+            PushStatus::Applied
+        } else if is_deleted(&mut *conn, "comment", &id).await? {
+            PushStatus::Deleted
+        } else if !article_exists(&mut *conn, article_id).await? {
+            PushStatus::Skipped
+        } else {
+            insert_sync_comment(&mut *conn, &comment).await?;
+            PushStatus::Applied
+        };
+        resp.comments.push(PushResult {
+            id,
+            status,
+            article: None,
+        });
+    }
+
+    for PushTombstone {
+        tombstone: Tombstone { entity, id },
+        base_clocks,
+    } in req.deleted
+    {
+        let (status, article) = match entity.as_str() {
+            "article" => match find_sync_article(&mut *conn, &id).await? {
+                Some(server) if server.changed_since(&base_clocks.unwrap_or_default()) => {
+                    (PushStatus::Conflict, Some(server))
+                }
+                _ => {
+                    delete_sync_article(&mut *conn, &id).await?;
+                    (PushStatus::Applied, None)
+                }
+            },
+            "comment" => {
+                delete_sync_comment(&mut *conn, &id).await?;
+                (PushStatus::Applied, None)
+            }
+            _ => (PushStatus::Skipped, None),
+        };
+        resp.deleted.push(PushResult {
+            id,
+            status,
+            article,
+        });
+    }
+
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clocks(pairs: &[(&str, i64)]) -> Clocks {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    fn article(title: &str, content: &str, c: &[(&str, i64)]) -> SyncArticle {
+        SyncArticle {
+            id: "a1".into(),
+            title: Some(title.into()),
+            content: Some(content.into()),
+            summary: None,
+            tags: None,
+            status: None,
+            created_at: None,
+            update_at: None,
+            clocks: clocks(c),
+        }
+    }
+
+    #[test]
+    fn merge_cases() {
+        // (说明, 服务端, 客户端, base, 期望标题, 期望正文, 是否冲突)
+        let cases = [
+            (
+                "相同的值取较大的时钟",
+                article("t", "c", &[("title", 5), ("content", 5)]),
+                article("t", "c", &[("title", 7), ("content", 3)]),
+                clocks(&[("title", 5), ("content", 5)]),
+                "t",
+                "c",
+                false,
+            ),
+            (
+                "只有客户端修改",
+                article("t", "c", &[("title", 5), ("content", 5)]),
+                article("T", "C", &[("title", 8), ("content", 8)]),
+                clocks(&[("title", 5), ("content", 5)]),
+                "T",
+                "C",
+                false,
+            ),
+            (
+                "只有服务端修改",
+                article("T", "C", &[("title", 8), ("content", 8)]),
+                article("t", "c", &[("title", 5), ("content", 5)]),
+                clocks(&[("title", 5), ("content", 5)]),
+                "T",
+                "C",
+                false,
+            ),
+            (
+                "双方修改标题，后写者胜",
+                article("server", "c", &[("title", 8), ("content", 5)]),
+                article("client", "c", &[("title", 9), ("content", 5)]),
+                clocks(&[("title", 5), ("content", 5)]),
+                "client",
+                "c",
+                false,
+            ),
+            (
+                "双方修改标题且时钟相同，保留服务端",
+                article("server", "c", &[("title", 8), ("content", 5)]),
+                article("client", "c", &[("title", 8), ("content", 5)]),
+                clocks(&[("title", 5), ("content", 5)]),
+                "server",
+                "c",
+                false,
+            ),
+            (
+                "双方修改正文，冲突且保留服务端正文，其余字段照常合并",
+                article("t", "server", &[("title", 5), ("content", 8)]),
+                article("T", "client", &[("title", 9), ("content", 9)]),
+                clocks(&[("title", 5), ("content", 5)]),
+                "T",
+                "server",
+                true,
+            ),
+            (
+                "从未同步过的文章与服务端正文不同",
+                article("t", "server", &[("title", 5), ("content", 5)]),
+                article("t", "client", &[("title", 5), ("content", 3)]),
+                Clocks::new(),
+                "t",
+                "server",
+                true,
+            ),
+        ];
+
+        for (name, server, incoming, base, title, content, conflict) in cases {
+            let (merged, got) = merge(&server, &incoming, &base);
+            assert_eq!(merged.title.as_deref(), Some(title), "{name}");
+            assert_eq!(merged.content.as_deref(), Some(content), "{name}");
+            assert_eq!(got, conflict, "{name}");
+        }
+    }
+
+    #[test]
+    fn merge_keeps_clock_of_adopted_value() {
+        let server = article("t", "c", &[("title", 5), ("content", 5)]);
+        let incoming = article("T", "c", &[("title", 9), ("content", 7)]);
+        let (merged, _) = merge(&server, &incoming, &clocks(&[("title", 5), ("content", 5)]));
+        assert_eq!(merged.clock("title"), 9);
+        assert_eq!(merged.clock("content"), 7);
+    }
+
+    #[test]
+    fn delete_cases() {
+        // (说明, 服务端时钟, 删除时带上的 base, 服务端是否有更新的修改)
+        let cases = [
+            (
+                "服务端未变化",
+                &[("title", 5), ("content", 5)][..],
+                Some(clocks(&[("title", 5), ("content", 5)])),
+                false,
+            ),
+            (
+                "base 比服务端新",
+                &[("title", 5)][..],
+                Some(clocks(&[("title", 6), ("content", 6)])),
+                false,
+            ),
+            (
+                "服务端在上次同步后修改了正文",
+                &[("title", 5), ("content", 8)][..],
+                Some(clocks(&[("title", 5), ("content", 5)])),
+                true,
+            ),
+            (
+                "服务端新增了字段时钟",
+                &[("title", 5), ("tags", 1)][..],
+                Some(clocks(&[("title", 5)])),
+                true,
+            ),
+            ("没有 base", &[("title", 5)][..], None, true),
+        ];
+
+        for (name, server, base, changed) in cases {
+            let server = article("t", "c", server);
+            assert_eq!(
+                server.changed_since(&base.unwrap_or_default()),
+                changed,
+                "{name}"
+            );
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! 请求参数校验：
+//! - payload 实现 `Validate`，先规整输入（去除首尾空白等），再把问题逐项交给 `Validator`
+//! - handler 使用 `ValidatedJson<T>` 代替 `Json<T>`，校验失败时一次性返回全部字段错误（422）
+//! - 取值范围与格式规则在 `blog_shared::validate` 中与桌面端共用，这里只负责接入 axum 与 `AppError`
+
+use axum::{
+    Json,
+    extract::{FromRequest, Request, rejection::JsonRejection},
+    http::StatusCode,
+};
+use serde::de::DeserializeOwned;
+
+use crate::error::{AppError, AppResult, ErrorCode};
+
+pub use blog_shared::validate::{
+    ARTICLE_STATUSES, COMMENT_MAX, CONTENT_MAX, IDENTITIES, SUMMARY_MAX, TAGS_MAX, TITLE_MAX,
+    Validate, Validator, trim, trim_opt,
+};
+
+/// 解析 JSON 请求体并校验，校验失败返回 422 与字段错误列表
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(json_rejection)?;
+
+        Ok(ValidatedJson(validated(value)?))
+    }
+}
+
+/// 规整并校验已解析的输入，供不经过 `ValidatedJson` 的入口（GraphQL）使用
+pub fn validated<T: Validate>(value: T) -> AppResult<T> {
+    Ok(blog_shared::validate::validated(value)?)
+}
+
+fn json_rejection(rejection: JsonRejection) -> AppError {
+    let code = match &rejection {
+        JsonRejection::MissingJsonContentType(_) => ErrorCode::UnsupportedMediaType,
+        _ if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+        _ => ErrorCode::InvalidJson,
+    };
+    AppError::Reason(code, rejection.body_text())
+}
@@ -0,0 +1,292 @@
+//! Webhook 投递：
+//! - `dispatch`：文章发布、评论创建后由对应 handler 调用，为订阅了该事件的启用中的 webhook 写入投递队列
+//! - 投递队列保存在数据库中，服务重启后继续投递；同一条记录可能被重复投递，接收方可按 `X-Blog-Delivery` 去重
+//! - `spawn_worker`：后台任务取出到期的记录并 POST 给订阅地址，2xx 视为成功，不跟随重定向
+//! - 失败后按 `RETRY_BASE_SECS` 指数退避重试，共尝试 `MAX_ATTEMPTS` 次，之后标记为 dead，可通过接口重新投递
+//! - 请求体为 JSON，`X-Blog-Signature` 为 `sha256=<hex>`，即以订阅密钥对请求体原文计算的 HMAC-SHA256
+
+use crate::db::AppState;
+use crate::models::webhook::{
+    DueDelivery, WebhookDelivery, insert_delivery, list_active_webhooks, list_due_deliveries,
+    mark_delivered, mark_failed,
+};
+use blog_shared::random_string;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+/// 文章由其他状态变为已发布
+pub const ARTICLE_PUBLISHED: &str = "article.published";
+/// 新评论（含回复）
+pub const COMMENT_CREATED: &str = "comment.created";
+/// 可订阅的事件
+pub const EVENTS: &[&str] = &[ARTICLE_PUBLISHED, COMMENT_CREATED];
+/// 测试事件，由“发送测试事件”产生，不需要订阅
+pub const PING: &str = "ping";
+
+/// 最多尝试次数（含首次）
+const MAX_ATTEMPTS: i64 = 8;
+/// 首次重试的等待秒数，之后每次翻倍
+const RETRY_BASE_SECS: i64 = 30;
+/// 单次请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// 没有新投递时检查到期重试的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// 每批并发投递的记录数
+const BATCH: i64 = 20;
+/// 错误信息中保留的响应体字符数
+const ERROR_BODY_CHARS: usize = 200;
+
+/// 为订阅了 `event` 的 webhook 写入投递队列并唤醒投递任务
+/// 失败只记录日志，不影响触发事件的请求
+pub async fn dispatch(state: &AppState, event: &str, data: Value) {
+    let webhooks = match list_active_webhooks(&state.pool).await {
+        Ok(webhooks) => webhooks,
+        Err(e) => {
+            tracing::warn!("查询 webhook 失败，{event} 未投递: {e}");
+            return;
+        }
+    };
+
+    let mut queued = false;
+    for webhook in webhooks.iter().filter(|w| w.subscribes(event)) {
+        match enqueue(&state.pool, &webhook.id, event, &data).await {
+            Ok(_) => queued = true,
+            Err(e) => tracing::warn!("写入 webhook {} 的投递队列失败: {e}", webhook.id),
+        }
+    }
+    if queued {
+        state.webhook_wakeup.notify_one();
+    }
+}
+
+/// 写入一条投递记录，记录 id 同时作为请求体中的 `id`
+pub async fn enqueue(
+    pool: &SqlitePool,
+    webhook_id: &str,
+    event: &str,
+    data: &Value,
+) -> Result<WebhookDelivery, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    let payload = json!({
+        "id": id,
+        "event": event,
+        "created_at": Utc::now().to_rfc3339(),
+        "data": data,
+    });
+    insert_delivery(pool, &id, webhook_id, event, &payload.to_string()).await
+}
+
+/// 未指定密钥时生成的随机密钥
+pub fn generate_secret() -> String {
+    format!("whsec_{}", random_string(32))
+}
+
+/// `X-Blog-Signature` 的值
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    mac.update(body.as_bytes());
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// 启动投递任务：有新投递时立即处理，否则每隔 `POLL_INTERVAL` 检查到期的重试
+pub fn spawn_worker(state: Arc<AppState>) {
+    let client = match reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!("blog-webhook/", env!("CARGO_PKG_VERSION")))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("创建 webhook HTTP 客户端失败，webhook 不会投递: {e}");
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        loop {
+            match deliver_due(&state.pool, &client).await {
+                // 一批取满时可能还有到期的记录，继续处理
+                Ok(n) if n as i64 == BATCH => continue,
+                Ok(_) => {}
+                Err(e) => tracing::error!("读取 webhook 投递队列失败: {e}"),
+            }
+            tokio::select! {
+                _ = state.webhook_wakeup.notified() => {}
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    });
+}
+
+/// 并发投递一批到期的记录，返回本批条数
+async fn deliver_due(pool: &SqlitePool, client: &reqwest::Client) -> Result<usize, sqlx::Error> {
+    let due = list_due_deliveries(pool, BATCH).await?;
+    let n = due.len();
+
+    let mut tasks = JoinSet::new();
+    for delivery in due {
+        let (pool, client) = (pool.clone(), client.clone());
+        tasks.spawn(async move {
+            if let Err(e) = deliver(&pool, &client, delivery).await {
+                tracing::error!("更新 webhook 投递记录失败: {e}");
+            }
+        });
+    }
+    while tasks.join_next().await.is_some() {}
+    Ok(n)
+}
+
+async fn deliver(
+    pool: &SqlitePool,
+    client: &reqwest::Client,
+    delivery: DueDelivery,
+) -> Result<(), sqlx::Error> {
+    let attempts = delivery.attempts + 1;
+    let res = client
+        .post(&delivery.url)
+        .header("Content-Type", "application/json")
+        .header("X-Blog-Event", &delivery.event)
+        .header("X-Blog-Delivery", &delivery.id)
+        .header(
+            "X-Blog-Signature",
+            sign(&delivery.secret, &delivery.payload),
+        )
+        .body(delivery.payload)
+        .send()
+        .await;
+
+    let (status, error) = match res {
+        Ok(resp) if resp.status().is_success() => {
+            let status = i64::from(resp.status().as_u16());
+            return mark_delivered(pool, &delivery.id, attempts, status).await;
+        }
+        Ok(resp) => {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            let body: String = body.chars().take(ERROR_BODY_CHARS).collect();
+            (
+                Some(i64::from(status.as_u16())),
+                format!("HTTP {status}: {body}"),
+            )
+        }
+        Err(e) => (None, format!("{:#}", anyhow::Error::from(e))),
+    };
+
+    let retry_in = (attempts < MAX_ATTEMPTS).then(|| RETRY_BASE_SECS << (attempts - 1));
+    match retry_in {
+        Some(secs) => tracing::info!(
+            "webhook 投递 {} 第 {attempts} 次失败，{secs} 秒后重试: {error}",
+            delivery.id
+        ),
+        None => tracing::warn!(
+            "webhook 投递 {} 失败 {attempts} 次，不再重试: {error}",
+            delivery.id
+        ),
+    }
+    mark_failed(pool, &delivery.id, attempts, status, &error, retry_in).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::MIGRATOR;
+    use crate::models::webhook::{DEAD, PENDING, insert_webhook, list_deliveries};
+    use axum::{Router, http::HeaderMap, http::StatusCode, routing::post};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::sync::Mutex;
+
+    #[test]
+    fn sign_matches_hmac_sha256_vector() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    /// 本地接收方，总是返回 500，记录收到的签名与请求体
+    async fn failing_receiver() -> (String, Arc<Mutex<Vec<(String, String)>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: String| async move {
+                let signature = headers["X-Blog-Signature"].to_str().unwrap().to_string();
+                log.lock().unwrap().push((signature, body));
+                (StatusCode::INTERNAL_SERVER_ERROR, "boom")
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    async fn delivery(pool: &SqlitePool, webhook_id: &str) -> WebhookDelivery {
+        let (mut entries, _) = list_deliveries(pool, webhook_id, None, 1, 0).await.unwrap();
+        entries.pop().unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_until_dead() {
+        // 内存库每个连接相互独立，只保留一个连接
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        let (url, received) = failing_receiver().await;
+        let client = reqwest::Client::new();
+
+        let mut conn = pool.acquire().await.unwrap();
+        let webhook = insert_webhook(&mut conn, &url, "whsec_test", PING, true)
+            .await
+            .unwrap();
+        drop(conn);
+        enqueue(&pool, &webhook.id, PING, &json!({})).await.unwrap();
+
+        // 首次失败：保持 pending，退避后才再次到期
+        assert_eq!(deliver_due(&pool, &client).await.unwrap(), 1);
+        let first = delivery(&pool, &webhook.id).await;
+        assert_eq!(first.status, PENDING);
+        assert_eq!(first.attempts, 1);
+        assert_eq!(first.response_status, Some(500));
+        assert_eq!(
+            first.last_error.as_deref(),
+            Some("HTTP 500 Internal Server Error: boom")
+        );
+        assert_eq!(deliver_due(&pool, &client).await.unwrap(), 0);
+        {
+            let received = received.lock().unwrap();
+            let (signature, body) = &received[0];
+            assert_eq!(body, &first.payload);
+            assert_eq!(signature, &sign("whsec_test", body));
+        }
+
+        // 跳过退避等待，直到用尽尝试次数
+        let rewind =
+            "UPDATE webhook_deliveries SET next_attempt_at = datetime ('now', '-1 seconds')";
+        for _ in 1..MAX_ATTEMPTS {
+            sqlx::query(rewind).execute(&pool).await.unwrap();
+            assert_eq!(deliver_due(&pool, &client).await.unwrap(), 1);
+        }
+        let last = delivery(&pool, &webhook.id).await;
+        assert_eq!(last.status, DEAD);
+        assert_eq!(last.attempts, MAX_ATTEMPTS);
+        assert_eq!(received.lock().unwrap().len() as i64, MAX_ATTEMPTS);
+
+        // dead 的记录不再投递
+        sqlx::query(rewind).execute(&pool).await.unwrap();
+        assert_eq!(deliver_due(&pool, &client).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,9 @@
+-- 0006_add_super_admin.sql
+-- 超管改为用户上的标记，而不是固定的 id = '1'
+ALTER TABLE users
+ADD COLUMN is_super_admin INTEGER NOT NULL DEFAULT 0;
+
+-- 兼容旧数据：之前种子数据插入的超管
+UPDATE users
+SET is_super_admin = 1
+WHERE id = '1' AND identity = 'admin';
@@ -0,0 +1,14 @@
+-- 0007_create_user_identities.sql
+-- 外部身份（OIDC）与本地用户的关联
+CREATE TABLE
+    IF NOT EXISTS user_identities (
+        id TEXT PRIMARY KEY,
+        user_id TEXT NOT NULL,        -- 外键，指向用户表
+        provider TEXT NOT NULL,       -- 提供方名称，对应 OIDC_PROVIDERS 中的配置
+        subject TEXT NOT NULL,        -- 提供方下的用户唯一标识 (sub)
+        email TEXT,
+        created_at TEXT DEFAULT (datetime ('now')),
+        UNIQUE (provider, subject)
+    );
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user_id ON user_identities (user_id);
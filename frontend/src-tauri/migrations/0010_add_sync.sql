@@ -0,0 +1,97 @@
+-- 0010_add_sync.sql
+-- 与服务端同步（见 src/sync）：
+-- - articles.clocks 记录每个字段最后修改的毫秒时间戳（JSON），base_clocks 为上次同步时服务端的时钟
+-- - dirty 表示本地有尚未推送的修改，已有数据默认需要推送
+-- - 本地删除写入 sync_tombstones，连同删除前的 base_clocks，服务端据此判断删除是否过期；推送后清除
+-- 以上均由触发器维护；同步写入远端数据时在事务内设置 sync_state.applying，触发器据此跳过
+ALTER TABLE articles ADD COLUMN clocks TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE articles ADD COLUMN base_clocks TEXT;
+ALTER TABLE articles ADD COLUMN dirty INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE comments ADD COLUMN dirty INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE
+    IF NOT EXISTS sync_tombstones (
+        entity TEXT NOT NULL,         -- article / comment
+        id TEXT NOT NULL,
+        deleted_at TEXT NOT NULL DEFAULT (datetime ('now')),
+        base_clocks TEXT,             -- 仅文章
+        PRIMARY KEY (entity, id)
+    );
+
+-- 键值对：server_url、cursor（已拉取到的服务端序号）、last_synced_at，以及写入远端数据时临时设置的 applying
+CREATE TABLE
+    IF NOT EXISTS sync_state (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+
+-- 正文冲突，等待用户选择保留哪一方
+CREATE TABLE
+    IF NOT EXISTS sync_conflicts (
+        article_id TEXT PRIMARY KEY,
+        title TEXT,
+        local_content TEXT,
+        remote_content TEXT,
+        remote_clocks TEXT NOT NULL,  -- 冲突时服务端的字段时钟，解决后作为 base_clocks
+        detected_at TEXT NOT NULL DEFAULT (datetime ('now'))
+    );
+
+CREATE INDEX IF NOT EXISTS idx_articles_dirty ON articles (dirty);
+CREATE INDEX IF NOT EXISTS idx_comments_dirty ON comments (dirty);
+
+-- 本地新建的文章：所有字段记为当前时间，并清除同 id 的删除记录
+CREATE TRIGGER IF NOT EXISTS articles_sync_insert AFTER INSERT ON articles
+WHEN NEW.clocks = '{}'
+BEGIN
+    UPDATE articles SET clocks = json_object(
+        'title', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+        'content', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+        'summary', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+        'tags', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+        'status', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER),
+        'update_at', CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+    ) WHERE id = NEW.id;
+    DELETE FROM sync_tombstones WHERE entity = 'article' AND id = NEW.id;
+END;
+
+-- 本地修改：标记待推送，并刷新实际变化字段的时钟
+CREATE TRIGGER IF NOT EXISTS articles_sync_update
+AFTER UPDATE OF title, content, summary, tags, status, update_at ON articles
+WHEN NOT EXISTS (SELECT 1 FROM sync_state WHERE key = 'applying')
+BEGIN
+    UPDATE articles SET
+        dirty = 1,
+        clocks = json_set(clocks,
+            '$.title', CASE WHEN OLD.title IS NOT NEW.title
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.title'), 0) END,
+            '$.content', CASE WHEN OLD.content IS NOT NEW.content
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.content'), 0) END,
+            '$.summary', CASE WHEN OLD.summary IS NOT NEW.summary
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.summary'), 0) END,
+            '$.tags', CASE WHEN OLD.tags IS NOT NEW.tags
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.tags'), 0) END,
+            '$.status', CASE WHEN OLD.status IS NOT NEW.status
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.status'), 0) END,
+            '$.update_at', CASE WHEN OLD.update_at IS NOT NEW.update_at
+                THEN CAST((julianday ('now') - 2440587.5) * 86400000 AS INTEGER)
+                ELSE COALESCE(json_extract(clocks, '$.update_at'), 0) END)
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS articles_sync_delete AFTER DELETE ON articles
+WHEN NOT EXISTS (SELECT 1 FROM sync_state WHERE key = 'applying')
+BEGIN
+    INSERT OR REPLACE INTO sync_tombstones (entity, id, base_clocks)
+    VALUES ('article', OLD.id, OLD.base_clocks);
+END;
+
+CREATE TRIGGER IF NOT EXISTS comments_sync_delete AFTER DELETE ON comments
+WHEN NOT EXISTS (SELECT 1 FROM sync_state WHERE key = 'applying')
+BEGIN
+    INSERT OR REPLACE INTO sync_tombstones (entity, id) VALUES ('comment', OLD.comment_id);
+END;
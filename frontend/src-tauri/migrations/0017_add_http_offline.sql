@@ -0,0 +1,28 @@
+-- 0017_add_http_offline.sql
+-- 原为 0011，迁移编号与服务端共用，同一编号只对应同一个迁移（见 src/db.rs）
+-- HTTP 代理离线支持（见 src/offline）：
+-- - http_cache 缓存成功的 GET 响应，离线时返回；按用户区分，登录与退出登录时清空
+-- - http_outbox 保存离线时的写请求，联网后按顺序重放
+CREATE TABLE
+    IF NOT EXISTS http_cache (
+        key TEXT PRIMARY KEY,         -- 用户 + 路径 + 排序后的查询参数
+        body TEXT NOT NULL,           -- JSON 响应
+        fetched_at TEXT NOT NULL DEFAULT (datetime ('now'))
+    );
+
+CREATE TABLE
+    IF NOT EXISTS http_outbox (
+        id TEXT PRIMARY KEY,          -- UUID v7，按 id 顺序重放
+        method TEXT NOT NULL,
+        url TEXT NOT NULL,
+        params TEXT,                  -- JSON 对象
+        data TEXT,                    -- JSON 请求体
+        user_id TEXT,                 -- 发起请求的用户，重放时使用其当前 token；不保存 token
+        attempts INTEGER NOT NULL DEFAULT 0,
+        next_attempt_at INTEGER NOT NULL DEFAULT 0, -- 毫秒时间戳
+        last_error TEXT,
+        failed INTEGER NOT NULL DEFAULT 0, -- 服务端拒绝或重试次数用尽，不再自动重放
+        created_at TEXT NOT NULL DEFAULT (datetime ('now'))
+    );
+
+CREATE INDEX IF NOT EXISTS idx_http_cache_fetched_at ON http_cache (fetched_at);
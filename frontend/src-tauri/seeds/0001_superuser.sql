@@ -1,9 +0,0 @@
-INSERT OR IGNORE INTO users(id, username, password, identity)
-VALUES (
-    '1',
-    'admin',
-    '$argon2id$v=19$m=19456,t=2,p=1$xJUF+YJE+OglZNMlk0fQrQ$Hj6T+uOmomQ3vyDcqUvn7fUmf8QKXoOOMCZZU8H+5xA',
-    'admin'
-);
-
--- 超管密码：tfF;1J(2WokG,5
@@ -3,9 +3,12 @@
 //! - 密码哈希/校验（基于 argon2）
 
 use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
+use crate::repositories::user::get_ident_by_id;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 
 use argon2::{
     Argon2,
@@ -74,3 +77,20 @@ pub fn decode_token(config: &Config, token: &str) -> Result<Claims, jsonwebtoken
 
     Ok(data.claims)
 }
+
+/// 校验 token 并要求当前用户是管理员，返回其 claims；`action` 用于拒绝时的提示
+pub async fn require_admin(
+    pool: &SqlitePool,
+    config: &Config,
+    token: &str,
+    action: &str,
+) -> CommandResult<Claims> {
+    let claims = decode_token(config, token)?;
+    if get_ident_by_id(pool, &claims.user_id).await? != "admin" {
+        return Err(CommandError::Forbidden(format!(
+            "only admin can {}",
+            action
+        )));
+    }
+    Ok(claims)
+}
@@ -0,0 +1,235 @@
+//! 数据库备份与恢复，备份写入 `app_data_dir/backups`：
+//! - `create`：`VACUUM INTO` 生成一致的快照，应用运行时也可以执行
+//! - `prune`：只保留最近的 `backup_keep` 份
+//! - `validate`：恢复前执行 `PRAGMA integrity_check` 并检查迁移版本
+//! - 运行中无法替换数据库文件，恢复先暂存为 `app.db.restore`，下次启动连接数据库前由 `apply_pending` 替换
+//! - `spawn_scheduler`：每隔 `backup_interval_hours` 小时自动备份
+
+use crate::config::Config;
+use crate::db::{MIGRATOR, RENUMBERED};
+use crate::repositories::audit::record;
+use anyhow::{bail, ensure, Context};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// 备份文件名前缀，`prune` 只清理带此前缀的文件
+const PREFIX: &str = "app-";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub size: u64,
+    pub created_at: String,
+}
+
+impl BackupInfo {
+    fn read(path: &Path) -> anyhow::Result<Self> {
+        let meta = std::fs::metadata(path)?;
+        Ok(Self {
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+            size: meta.len(),
+            created_at: DateTime::<Local>::from(meta.modified()?).to_rfc3339(),
+        })
+    }
+}
+
+/// 备份目录
+pub fn backup_dir(app: &AppHandle) -> anyhow::Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join("backups"))
+}
+
+/// 数据库文件，与 `Config::get_database_path` 一致
+pub fn database_file(app: &AppHandle) -> anyhow::Result<PathBuf> {
+    Ok(app.path().app_data_dir()?.join("app.db"))
+}
+
+/// `dir` 下按当前时间命名的备份路径
+pub fn default_path(dir: &Path) -> PathBuf {
+    dir.join(format!(
+        "{}{}.db",
+        PREFIX,
+        Local::now().format("%Y%m%d-%H%M%S-%3f")
+    ))
+}
+
+/// 把当前数据库写入 `out`，目标已存在时报错
+pub async fn create(pool: &SqlitePool, out: &Path) -> anyhow::Result<BackupInfo> {
+    if let Some(dir) = out.parent() {
+        std::fs::create_dir_all(dir).with_context(|| format!("创建目录 {} 失败", dir.display()))?;
+    }
+    ensure!(!out.exists(), "{} 已存在", out.display());
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(out.to_string_lossy().into_owned())
+        .execute(pool)
+        .await
+        .with_context(|| format!("备份到 {} 失败", out.display()))?;
+    BackupInfo::read(out)
+}
+
+/// `dir` 中的备份，最新的在前；目录不存在时为空
+pub fn list(dir: &Path) -> anyhow::Result<Vec<BackupInfo>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_file() && name.starts_with(PREFIX) && name.ends_with(".db") {
+            files.push(path);
+        }
+    }
+    // 文件名中的时间可以直接按字典序排序
+    files.sort();
+    files.reverse();
+    files.iter().map(|f| BackupInfo::read(f)).collect()
+}
+
+/// 只保留最近的 `keep` 份，返回删除的文件名
+pub fn prune(dir: &Path, keep: u32) -> anyhow::Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for info in list(dir)?.into_iter().skip(keep as usize) {
+        std::fs::remove_file(dir.join(&info.name))
+            .with_context(|| format!("删除 {} 失败", info.name))?;
+        removed.push(info.name);
+    }
+    Ok(removed)
+}
+
+/// 按文件名在 `dir` 中查找备份，拒绝包含路径的名字
+pub fn find(dir: &Path, name: &str) -> Option<PathBuf> {
+    let plain = Path::new(name).file_name().is_some_and(|n| n == name);
+    let path = dir.join(name);
+    (plain && name.starts_with(PREFIX) && path.is_file()).then_some(path)
+}
+
+/// 立即备份并清理旧备份，托盘菜单与 `backup_now` 命令共用
+pub async fn backup_now(app: &AppHandle) -> anyhow::Result<BackupInfo> {
+    let pool = app.try_state::<SqlitePool>().context("数据库尚未初始化")?;
+    let keep = app.state::<Config>().backup_keep;
+    let dir = backup_dir(app)?;
+
+    let info = create(pool.inner(), &default_path(&dir)).await?;
+    for name in prune(&dir, keep)? {
+        log::info!("已删除旧备份 {}", name);
+    }
+    Ok(info)
+}
+
+/// 恢复前的校验：完整性检查通过，且迁移版本都是当前程序已知的
+pub async fn validate(file: &Path) -> anyhow::Result<()> {
+    ensure!(file.is_file(), "{} 不存在", file.display());
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(SqliteConnectOptions::new().filename(file).read_only(true))
+        .await
+        .with_context(|| format!("{} 不是有效的 SQLite 数据库", file.display()))?;
+    let result = check(&pool).await;
+    pool.close().await;
+    result.with_context(|| format!("{} 校验失败", file.display()))
+}
+
+async fn check(pool: &SqlitePool) -> anyhow::Result<()> {
+    let (integrity,): (String,) = sqlx::query_as("PRAGMA integrity_check")
+        .fetch_one(pool)
+        .await
+        .context("不是有效的 SQLite 数据库")?;
+    ensure!(integrity == "ok", "完整性检查失败: {}", integrity);
+
+    let (tables,): (i64,) = sqlx::query_as(
+        r#"SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'"#,
+    )
+    .fetch_one(pool)
+    .await?;
+    ensure!(tables > 0, "缺少迁移记录，不是本程序的数据库");
+
+    let applied: Vec<(i64, String, bool)> =
+        sqlx::query_as(r#"SELECT version, description, success FROM _sqlx_migrations"#)
+            .fetch_all(pool)
+            .await?;
+    for (version, description, success) in applied {
+        ensure!(success, "迁移 {} 未成功执行", version);
+        // 改过编号的旧迁移在恢复后启动时按新编号重新记录
+        let renumbered = RENUMBERED
+            .iter()
+            .any(|&(v, d)| v == version && d == description);
+        if !renumbered && !MIGRATOR.iter().any(|m| m.version == version) {
+            bail!("包含未知的迁移 {}，备份来自更新的版本", version);
+        }
+    }
+    Ok(())
+}
+
+/// 暂存的待恢复文件
+fn pending_path(db_file: &Path) -> PathBuf {
+    let mut path = db_file.to_path_buf().into_os_string();
+    path.push(".restore");
+    path.into()
+}
+
+/// 校验 `file` 后暂存，下次启动时恢复
+pub async fn stage(db_file: &Path, file: &Path) -> anyhow::Result<()> {
+    validate(file).await?;
+    let pending = pending_path(db_file);
+    std::fs::copy(file, &pending).with_context(|| format!("写入 {} 失败", pending.display()))?;
+    Ok(())
+}
+
+/// 启动时在连接数据库前应用暂存的恢复，返回是否执行了恢复；
+/// 暂存时写入的审计记录随旧数据库一起被覆盖，恢复后由 `record_applied` 重新记录
+pub async fn apply_pending(db_file: &Path) -> anyhow::Result<bool> {
+    let pending = pending_path(db_file);
+    if !pending.is_file() {
+        return Ok(false);
+    }
+    // 暂存后文件可能被改动，替换前再校验一次
+    validate(&pending).await?;
+    std::fs::copy(&pending, db_file)
+        .with_context(|| format!("复制到 {} 失败", db_file.display()))?;
+    // 旧的 WAL 文件属于被覆盖的数据库，不能留给新文件
+    for suffix in ["-wal", "-shm"] {
+        let mut side = db_file.to_path_buf().into_os_string();
+        side.push(suffix);
+        let _ = std::fs::remove_file(side);
+    }
+    std::fs::remove_file(&pending)?;
+    log::warn!("已从暂存的备份恢复数据库");
+    Ok(true)
+}
+
+/// 在恢复后的数据库中记录本次恢复
+pub async fn record_applied(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    record(pool, None, "db.restore", "database", None, None, None).await
+}
+
+/// 定时备份，间隔为 0 时不启用
+pub fn spawn_scheduler(app: AppHandle, hours: u32) {
+    if hours == 0 {
+        return;
+    }
+    log::info!("定时备份已启用，每 {} 小时一次", hours);
+
+    tauri::async_runtime::spawn(async move {
+        let period = Duration::from_secs(u64::from(hours) * 3600);
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            match backup_now(&app).await {
+                Ok(info) => log::info!("定时备份完成: {}", info.name),
+                Err(e) => log::error!("定时备份失败: {:#}", e),
+            }
+        }
+    });
+}
@@ -2,9 +2,12 @@
 
 use crate::auth::decode_token;
 use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
 use crate::models::article::{ArticleModel, PubArticles};
 use crate::models::ResponseMessage;
 use crate::repositories::article;
+use crate::repositories::audit::{record, snapshot};
+use crate::validate::validated;
 use serde::Serialize;
 use sqlx::SqlitePool;
 use tauri::State;
@@ -20,16 +23,14 @@ pub async fn get_articles(
     identity: String,
     condition: Option<String>,
     pool: State<'_, SqlitePool>,
-) -> Result<ArticleResponse, String> {
+) -> CommandResult<ArticleResponse> {
     log::info!("attempt to get_articles");
     let params = article::GetArticlesParams {
         identity,
         condition,
     };
 
-    let articles = article::get_articles(pool.inner(), params)
-        .await
-        .map_err(|e| format!("Failed to fetch articles: {}", e))?;
+    let articles = article::get_articles(pool.inner(), params).await?;
 
     log::info!("success get_articles");
     Ok(ArticleResponse { articles })
@@ -40,12 +41,11 @@ pub async fn get_articles(
 pub async fn get_article_by_id(
     id: String,
     pool: State<'_, SqlitePool>,
-) -> Result<ArticleModel, String> {
+) -> CommandResult<ArticleModel> {
     log::info!("attempt to get_article_by_id");
     let result = article::find_article_by_id(pool.inner(), &id)
-        .await
-        .map_err(|e| format!("Failed to fetch article: {}", e))?
-        .ok_or("Article not found")?;
+        .await?
+        .ok_or_else(|| CommandError::NotFound(format!("article {}", id)))?;
 
     log::info!("success get_article_by_id");
     Ok(result)
@@ -58,14 +58,24 @@ pub async fn create_article(
     article_data: article::NewArticle,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<ArticleModel, String> {
+) -> CommandResult<ArticleModel> {
     log::info!("attempt to create_article");
     // 验证 token
-    let _claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
-
-    let result = article::post_article(pool.inner(), &article_data)
-        .await
-        .map_err(|e| format!("Failed to create article: {}", e))?;
+    let claims = decode_token(&config, &token)?;
+    let article_data = validated(article_data)?;
+
+    let result = article::post_article(pool.inner(), &article_data).await?;
+
+    record(
+        pool.inner(),
+        Some(&claims.user_id),
+        "article.create",
+        "article",
+        Some(&result.id),
+        None,
+        snapshot(&result),
+    )
+    .await?;
 
     log::info!("success create_article");
     Ok(ArticleModel {
@@ -82,14 +92,26 @@ pub async fn update_article(
     article_data: article::NewArticle,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<ArticleModel, String> {
+) -> CommandResult<ArticleModel> {
     log::info!("attempt to update_article");
     // 验证 token
-    let _claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
+    let claims = decode_token(&config, &token)?;
+    let article_data = validated(article_data)?;
 
-    let result = article::put_article_by_id(pool.inner(), &id, article_data)
-        .await
-        .map_err(|e| format!("Failed to update article: {}", e))?;
+    let before = article::find_article_by_id(pool.inner(), &id).await?;
+
+    let result = article::put_article_by_id(pool.inner(), &id, article_data).await?;
+
+    record(
+        pool.inner(),
+        Some(&claims.user_id),
+        "article.update",
+        "article",
+        Some(&id),
+        before.as_ref().and_then(snapshot),
+        snapshot(&result),
+    )
+    .await?;
 
     log::info!("success update_article");
     Ok(ArticleModel {
@@ -105,14 +127,25 @@ pub async fn delete_article(
     id: String,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<ResponseMessage, String> {
+) -> CommandResult<ResponseMessage> {
     log::info!("attempt to delete_article");
     // 验证 token
-    let _claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
+    let claims = decode_token(&config, &token)?;
 
-    article::delete_article_by_id(pool.inner(), &id)
-        .await
-        .map_err(|e| format!("Failed to delete article: {}", e))?;
+    let before = article::find_article_by_id(pool.inner(), &id).await?;
+
+    article::delete_article_by_id(pool.inner(), &id).await?;
+
+    record(
+        pool.inner(),
+        Some(&claims.user_id),
+        "article.delete",
+        "article",
+        Some(&id),
+        before.as_ref().and_then(snapshot),
+        None,
+    )
+    .await?;
 
     log::info!("success delete_article");
     Ok(ResponseMessage {
@@ -128,21 +161,35 @@ pub async fn toggle_article_status(
     status: article::NewStatus,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<ArticleModel, String> {
+) -> CommandResult<ArticleModel> {
     log::info!("attemp to toggle_article_status");
     // 验证 token
-    decode_token(&config, &token).map_err(|e| {
+    let claims = decode_token(&config, &token).map_err(|e| {
         log::error!("Invalid token: {}", e);
-        format!("Invalid token: {}", e)
+        e
     })?;
+    let status = validated(status)?;
+
+    let before = article::find_article_by_id(pool.inner(), &id).await?;
 
     let result = article::patch_article_by_id(pool.inner(), &id, status)
         .await
         .map_err(|e| {
             log::error!("Failed to toggle article status: {}", e);
-            format!("Failed to toggle article status: {}", e)
+            e
         })?;
 
+    record(
+        pool.inner(),
+        Some(&claims.user_id),
+        "article.status",
+        "article",
+        Some(&id),
+        before.as_ref().and_then(snapshot),
+        snapshot(&result),
+    )
+    .await?;
+
     log::info!("success toggle_article_status");
     Ok(result)
 }
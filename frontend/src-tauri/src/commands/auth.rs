@@ -2,11 +2,14 @@
 
 use crate::auth::{decode_token, generate_token, hash_password, verify_password, Claims};
 use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
 use crate::models::user::NewUser;
+use crate::offline;
 use crate::repositories::user::{find_user_by_username, insert_common_user};
+use crate::validate::{self, validated, Validate, Validator, IDENTITIES};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
@@ -14,6 +17,14 @@ pub struct LoginRequest {
     pub password: String,
 }
 
+impl Validate for LoginRequest {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.username);
+        v.required("username", &self.username)
+            .required("password", &self.password);
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
     pub token: String,
@@ -29,14 +40,27 @@ pub struct RegisterRequest {
     pub identity: Option<String>,
 }
 
+impl Validate for RegisterRequest {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.username);
+        validate::trim_opt(&mut self.identity);
+        v.username("username", &self.username)
+            .password("password", &self.password, 3);
+        if let Some(identity) = &self.identity {
+            v.one_of("identity", identity, IDENTITIES);
+        }
+    }
+}
+
 /// 登录命令
 #[tauri::command]
 pub async fn login(
     credentials: LoginRequest,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<LoginResponse, String> {
+) -> CommandResult<LoginResponse> {
     log::info!("login attempt");
+    let credentials = validated(credentials)?;
 
     // 查询用户
     let user = match find_user_by_username(pool.inner(), &credentials.username).await {
@@ -45,14 +69,14 @@ pub async fn login(
             log::error!(
                 "login failed - user not found"
             );
-            return Err("未注册用户".to_string());
+            return Err(CommandError::NotFound("user is not registered".to_string()));
         }
         Err(e) => {
             log::error!(
                 "login failed due to database error: {}",
                 e.to_string()
             );
-            return Err(format!("Database error: {}", e));
+            return Err(e.into());
         }
     };
 
@@ -61,7 +85,9 @@ pub async fn login(
         log::error!(
             "login failed due to incorrect password"
         );
-        return Err("用户名或密码错误".to_string());
+        return Err(CommandError::Unauthorized(
+            "incorrect username or password".to_string(),
+        ));
     }
 
     // 生成 token
@@ -70,10 +96,11 @@ pub async fn login(
             "login failed due to token generation error: {}",
             e.to_string()
         );
-        format!("Failed to generate token: {}", e)
+        e
     })?;
 
     log::info!("login successful");
+    offline::start_session(pool.inner()).await;
 
     Ok(LoginResponse {
         token,
@@ -89,22 +116,23 @@ pub async fn register(
     user_info: RegisterRequest,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<LoginResponse, String> {
+) -> CommandResult<LoginResponse> {
+    let user_info = validated(user_info)?;
+
     // 检查用户名是否已存在
-    let existing = find_user_by_username(pool.inner(), &user_info.username)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
+    let existing = find_user_by_username(pool.inner(), &user_info.username).await?;
 
     if existing.is_some() {
         log::warn!(
             "register attempt with existing username"
         );
-        return Err("用户名已存在".to_string());
+        return Err(CommandError::BadRequest(
+            "username already exists".to_string(),
+        ));
     }
 
     // 哈希密码
-    let password_hash = hash_password(&user_info.password)
-        .map_err(|e| format!("Failed to hash password: {}", e))?;
+    let password_hash = hash_password(&user_info.password)?;
 
     // 创建新用户
     let new_user = NewUser {
@@ -113,15 +141,13 @@ pub async fn register(
         identity: user_info.identity.unwrap_or("user".to_string()),
     };
 
-    let user = insert_common_user(pool.inner(), &new_user)
-        .await
-        .map_err(|e| format!("Failed to create user: {}", e))?;
+    let user = insert_common_user(pool.inner(), &new_user).await?;
 
     // 生成 token
-    let token = generate_token(&config, user.id.clone(), &user.username)
-        .map_err(|e| format!("Failed to generate token: {}", e))?;
+    let token = generate_token(&config, user.id.clone(), &user.username)?;
 
     log::info!("User registered successfully");
+    offline::start_session(pool.inner()).await;
 
     Ok(LoginResponse {
         token,
@@ -131,10 +157,17 @@ pub async fn register(
     })
 }
 
+/// 退出登录：清空响应缓存，该用户尚未重放的离线请求标记为失败（见 `crate::offline`）
+#[tauri::command]
+pub async fn logout(token: Option<String>, app: AppHandle) -> CommandResult<()> {
+    offline::end_session(&app, token.as_deref()).await?;
+    Ok(())
+}
+
 /// 验证 token 命令
 #[tauri::command]
-pub async fn verify_token(token: String, config: State<'_, Config>) -> Result<Claims, String> {
-    decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))
+pub async fn verify_token(token: String, config: State<'_, Config>) -> CommandResult<Claims> {
+    Ok(decode_token(&config, &token)?)
 }
 
 /// 获取当前用户信息
@@ -143,9 +176,9 @@ pub async fn get_current_user(
     token: String,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<serde_json::Value, String> {
+) -> CommandResult<serde_json::Value> {
     // 验证 token
-    let claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
+    let claims = decode_token(&config, &token)?;
 
     // 查询用户信息
     let user = sqlx::query!(
@@ -157,9 +190,8 @@ pub async fn get_current_user(
         claims.user_id
     )
     .fetch_optional(pool.inner())
-    .await
-    .map_err(|e| format!("Database error: {}", e))?
-    .ok_or("User not found")?;
+    .await?
+    .ok_or_else(|| CommandError::NotFound(format!("user {}", claims.user_id)))?;
 
     Ok(serde_json::json!({
         "id": user.id,
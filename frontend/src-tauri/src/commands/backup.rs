@@ -0,0 +1,133 @@
+//! 备份命令：立即备份、列出备份、从备份恢复（均需要管理员权限）
+
+use crate::auth::require_admin;
+use crate::backup::{self, BackupInfo};
+use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
+use crate::repositories::audit::record;
+use anyhow::Context;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+#[derive(Serialize)]
+pub struct RestoreResponse {
+    /// 恢复前自动生成的当前数据库备份
+    safety_backup: BackupInfo,
+}
+
+/// 立即备份，并按 `backup_keep` 清理旧备份
+#[tauri::command]
+pub async fn backup_now(
+    token: String,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<BackupInfo> {
+    log::info!("attempt to backup_now");
+    let user_id = require_admin(pool.inner(), &config, &token, "manage backups")
+        .await?
+        .user_id;
+
+    let info = backup::backup_now(&app)
+        .await
+        .context("failed to back up")?;
+    record(
+        pool.inner(),
+        Some(&user_id),
+        "db.backup",
+        "database",
+        Some(&info.name),
+        None,
+        serde_json::to_string(&info).ok(),
+    )
+    .await?;
+
+    log::info!("success backup_now: {}", info.name);
+    Ok(info)
+}
+
+/// 列出备份，最新的在前
+#[tauri::command]
+pub async fn list_backups(
+    token: String,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<Vec<BackupInfo>> {
+    require_admin(pool.inner(), &config, &token, "manage backups").await?;
+    let dir = backup::backup_dir(&app)?;
+    Ok(backup::list(&dir).context("failed to list backups")?)
+}
+
+/// 从备份恢复
+///
+/// `name` 为 `list_backups` 返回的文件名；未传时弹出对话框选择任意备份文件，取消时返回 `None`。
+/// 校验通过后先备份当前数据库，再暂存所选备份，随后应用自动重启并完成替换
+#[tauri::command]
+pub async fn restore_backup(
+    token: String,
+    name: Option<String>,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<Option<RestoreResponse>> {
+    log::info!("attempt to restore_backup");
+    let user_id = require_admin(pool.inner(), &config, &token, "manage backups")
+        .await?
+        .user_id;
+
+    let dir = backup::backup_dir(&app)?;
+    let file = match name {
+        Some(name) => backup::find(&dir, &name)
+            .ok_or_else(|| CommandError::NotFound(format!("backup {}", name)))?,
+        None => {
+            let picked = app
+                .dialog()
+                .file()
+                .set_title("选择要恢复的备份")
+                .add_filter("SQLite 数据库", &["db", "sqlite"])
+                .blocking_pick_file();
+            match picked {
+                Some(p) => p
+                    .into_path()
+                    .map_err(|e| CommandError::BadRequest(format!("invalid path: {}", e)))?,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    backup::validate(&file)
+        .await
+        .map_err(|e| CommandError::BadRequest(format!("invalid backup: {:#}", e)))?;
+    let safety_backup = backup::create(pool.inner(), &backup::default_path(&dir))
+        .await
+        .context("failed to back up")?;
+    let db_file = backup::database_file(&app)?;
+    backup::stage(&db_file, &file)
+        .await
+        .context("failed to stage backup")?;
+
+    record(
+        pool.inner(),
+        Some(&user_id),
+        "db.restore",
+        "database",
+        Some(&file.display().to_string()),
+        serde_json::to_string(&safety_backup).ok(),
+        None,
+    )
+    .await?;
+
+    // 留出时间把结果返回给前端，再重启以替换数据库
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        handle.restart();
+    });
+
+    log::info!("success restore_backup: {}", file.display());
+    Ok(Some(RestoreResponse { safety_backup }))
+}
@@ -2,8 +2,11 @@
 
 use crate::auth::decode_token;
 use crate::config::Config;
+use crate::error::CommandResult;
 use crate::models::comment::{Comment, CommentWithLike};
+use crate::repositories::audit::{record, snapshot};
 use crate::repositories::comment::*;
+use crate::validate::validated;
 use sqlx::SqlitePool;
 use tauri::State;
 
@@ -14,7 +17,7 @@ pub async fn get_comments(
     token: Option<String>,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<Vec<CommentWithLike>, String> {
+) -> CommandResult<Vec<CommentWithLike>> {
     log::info!("attempt to get comments for article_id: {}", article_id);
     // 获取用户ID（如果有token）
     let user_id = if let Some(token) = token {
@@ -26,9 +29,7 @@ pub async fn get_comments(
         String::new()
     };
 
-    let comments = fetch_comments_by_article_id(pool.inner(), &article_id, &user_id)
-        .await
-        .map_err(|e| format!("Failed to fetch comments: {}", e))?;
+    let comments = fetch_comments_by_article_id(pool.inner(), &article_id, &user_id).await?;
 
     log::info!(
         "fetched {} comments for article_id: {}",
@@ -46,14 +47,13 @@ pub async fn post_comment(
     comment_data: CommentIncome,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<Comment, String> {
+) -> CommandResult<Comment> {
     // 验证 token 并获取用户名
-    let claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
+    let claims = decode_token(&config, &token)?;
     let username = claims.message; // message 字段存储的是用户名
+    let comment_data = validated(comment_data)?;
 
-    let comment = post_comment_by_article_id(pool.inner(), comment_data, &username)
-        .await
-        .map_err(|e| format!("Failed to post comment: {}", e))?;
+    let comment = post_comment_by_article_id(pool.inner(), comment_data, &username).await?;
 
     Ok(comment)
 }
@@ -65,13 +65,24 @@ pub async fn delete_comment(
     comment_id: String,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     // 验证 token
-    let _claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
+    let claims = decode_token(&config, &token)?;
 
-    delete_comment_by_comment_id(pool.inner(), &comment_id)
-        .await
-        .map_err(|e| format!("Failed to delete comment: {}", e))?;
+    let before = find_comment_by_id(pool.inner(), &comment_id).await?;
+
+    delete_comment_by_comment_id(pool.inner(), &comment_id).await?;
+
+    record(
+        pool.inner(),
+        Some(&claims.user_id),
+        "comment.delete",
+        "comment",
+        Some(&comment_id),
+        before.as_ref().and_then(snapshot),
+        None,
+    )
+    .await?;
 
     Ok(())
 }
@@ -83,13 +94,12 @@ pub async fn like_comment(
     payload: LikeCommentPayload,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<String, String> {
+) -> CommandResult<String> {
     // 验证 token 并获取用户ID
-    let claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
+    let claims = decode_token(&config, &token)?;
+    let payload = validated(payload)?;
 
-    let result = like_comment_db(pool.inner(), payload, &claims.user_id)
-        .await
-        .map_err(|e| format!("Failed to like comment: {}", e))?;
+    let result = like_comment_db(pool.inner(), payload, &claims.user_id).await?;
 
     Ok(result)
 }
@@ -0,0 +1,44 @@
+//! 实时事件命令：订阅服务端的文章事件（`blog_event`）与站内通知（`notification`，同时弹出系统通知）
+
+use crate::error::CommandResult;
+use crate::events::EventRelay;
+use tauri::{AppHandle, State};
+
+/// 订阅 `article_ids` 的事件，为空时订阅全部文章；会替换之前的订阅
+#[tauri::command]
+pub async fn subscribe_events(
+    token: String,
+    article_ids: Vec<String>,
+    app: AppHandle,
+    relay: State<'_, EventRelay>,
+) -> CommandResult<()> {
+    log::info!("attempt to subscribe_events: {:?}", article_ids);
+    relay.subscribe(app, token, article_ids);
+    Ok(())
+}
+
+/// 取消订阅
+#[tauri::command]
+pub async fn unsubscribe_events(relay: State<'_, EventRelay>) -> CommandResult<()> {
+    relay.unsubscribe();
+    Ok(())
+}
+
+/// 登录后开始接收站内通知；会替换之前的连接
+#[tauri::command]
+pub async fn watch_notifications(
+    token: String,
+    app: AppHandle,
+    relay: State<'_, EventRelay>,
+) -> CommandResult<()> {
+    log::info!("attempt to watch_notifications");
+    relay.watch_notifications(app, token);
+    Ok(())
+}
+
+/// 退出登录时停止接收
+#[tauri::command]
+pub async fn unwatch_notifications(relay: State<'_, EventRelay>) -> CommandResult<()> {
+    relay.unwatch_notifications();
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! 导出命令：把整站内容导出为 zip 归档或静态站点
+
+use crate::auth::require_admin;
+use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
+use crate::export::site::SiteOptions;
+use crate::export::{self, archive, site};
+use crate::repositories::audit::record;
+use anyhow::Context;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_dialog::DialogExt;
+
+/// 订阅源中的文章条数
+const FEED_ITEMS: usize = 20;
+
+#[derive(Serialize)]
+pub struct ExportResponse {
+    /// 生成的 zip 文件或站点目录
+    path: String,
+    summary: serde_json::Value,
+}
+
+/// 导出整站内容（需要管理员权限）
+///
+/// 弹出对话框选择目标目录：`kind` 为 `site` 时在其中生成 `site/` 静态站点，
+/// 否则写入 `blog-<时间>.zip` 归档。用户取消选择时返回 `None`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_blog(
+    token: String,
+    kind: String,
+    site_title: Option<String>,
+    base_url: Option<String>,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<Option<ExportResponse>> {
+    log::info!("attempt to export_blog");
+    let claims = require_admin(pool.inner(), &config, &token, "export the blog").await?;
+
+    let picked = app
+        .dialog()
+        .file()
+        .set_title("选择导出位置")
+        .blocking_pick_folder();
+    let dir = match picked {
+        Some(p) => p
+            .into_path()
+            .map_err(|e| CommandError::BadRequest(format!("invalid path: {}", e)))?,
+        None => return Ok(None),
+    };
+
+    let snapshot = export::snapshot(pool.inner()).await?;
+
+    let (out, summary) = if kind == "site" {
+        let out = dir.join("site");
+        let opts = SiteOptions {
+            title: site_title.unwrap_or_else(|| "Blog".to_string()),
+            base_url: base_url
+                .unwrap_or_default()
+                .trim_end_matches('/')
+                .to_string(),
+            feed_items: FEED_ITEMS,
+        };
+        let summary = site::write(&snapshot, &out, &opts).context("failed to export site")?;
+        (out, serde_json::to_value(summary))
+    } else {
+        let out = dir.join(format!(
+            "blog-{}.zip",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let media_dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| CommandError::Internal(format!("failed to get app data dir: {}", e)))?
+            .join("icons");
+        let summary =
+            archive::write(&snapshot, &out, &media_dir).context("failed to export archive")?;
+        (out, serde_json::to_value(summary))
+    };
+    let mut summary = summary.map_err(|e| CommandError::Internal(e.to_string()))?;
+
+    summary["kind"] = kind.as_str().into();
+    summary["out"] = out.display().to_string().into();
+    record(
+        pool.inner(),
+        Some(&claims.user_id),
+        "blog.export",
+        "blog",
+        None,
+        None,
+        Some(summary.to_string()),
+    )
+    .await?;
+
+    log::info!("success export_blog: {}", out.display());
+    Ok(Some(ExportResponse {
+        path: out.display().to_string(),
+        summary,
+    }))
+}
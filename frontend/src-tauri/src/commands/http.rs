@@ -1,85 +1,157 @@
 //! HTTP 请求处理命令
-//! 处理来自前端的 HTTP 请求，自动携带 token 进行鉴权
+//! 处理来自前端的 HTTP 请求，自动携带 token 进行鉴权（见 `crate::proxy`）；服务端不可达时使用缓存与离线队列
 
-use crate::config::Config;
-use serde::Deserialize;
-use serde_json::Value;
-use std::collections::HashMap;
-use tauri::State;
-
-#[derive(Debug, Deserialize)]
-pub struct HttpRequest {
-    pub method: String,
-    pub url: String,
-    #[serde(rename = "__token")]
-    pub token: Option<String>,
-    pub params: Option<HashMap<String, String>>,
-    pub data: Option<Value>,
-}
+use crate::error::{CommandError, CommandResult};
+use crate::models::offline::OutboxEntry;
+use crate::offline::{self, NetworkMonitor, NetworkStatus};
+use crate::proxy::{
+    is_network_error, read_response, HttpProxy, HttpRequest, ProxyError, ProxyErrorKind,
+    ResponseType,
+};
+use crate::repositories::offline as store;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, State};
 
 /// 处理 HTTP 请求的 Tauri command
+///
+/// 代理失败时返回 `CommandError::Proxy`，`details` 为 `ProxyError`；服务端不可达时 GET 返回缓存的 JSON 响应，
+/// 写请求排队并返回 `{ "queued": true, "id": ... }`，见 `crate::offline`
 #[tauri::command]
 pub async fn http_request(
     request: HttpRequest,
-    config: State<'_, Config>,
-) -> Result<Value, String> {
-    // 构建完整 URL
-    let base_url = format!("http://{}:{}", config.host, config.port);
-    let full_url = if request.url.starts_with('/') {
-        format!("{}{}", base_url, request.url)
-    } else {
-        format!("{}/{}", base_url, request.url)
-    };
-
-    // 创建 HTTP 客户端
-    let client = reqwest::Client::new();
-    let mut req_builder = match request.method.to_uppercase().as_str() {
-        "GET" => client.get(&full_url),
-        "POST" => client.post(&full_url),
-        "PUT" => client.put(&full_url),
-        "DELETE" => client.delete(&full_url),
-        "PATCH" => client.patch(&full_url),
-        _ => return Err(format!("Unsupported HTTP method: {}", request.method)),
-    };
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    proxy: State<'_, HttpProxy>,
+    monitor: State<'_, NetworkMonitor>,
+) -> CommandResult<Value> {
+    proxy.check(&request)?;
+    let is_get = request.method.eq_ignore_ascii_case("GET");
+    let cacheable = is_get && request.response_type == ResponseType::Json;
+    let queueable = !is_get && request.multipart.is_none();
+    let user = offline::token_user(request.token.as_deref());
+    if let (Some(user), Some(token)) = (&user, &request.token) {
+        monitor.remember_token(user, token);
+    }
+    let cache_key = offline::cache_key(user.as_deref(), &request.url, request.params.as_ref());
 
-    // 添加 Authorization 头
-    if let Some(token) = &request.token {
-        if !token.is_empty() {
-            req_builder = req_builder.header("Authorization", format!("Bearer {}", token));
+    // 队列中还有未重放的写请求时新请求也排队，保证服务端按顺序收到
+    if queueable {
+        let (pending, _) = store::outbox_counts(pool.inner()).await?;
+        if pending > 0 {
+            return enqueue(&app, pool.inner(), &monitor, &request, user.as_deref()).await;
         }
     }
 
-    // 添加查询参数
-    if let Some(params) = &request.params {
-        req_builder = req_builder.query(params);
+    let response = match proxy.execute(&app, &request).await? {
+        Ok(response) => response,
+        Err(e) if is_network_error(&e) => {
+            offline::set_online(&app, false).await;
+            if queueable {
+                return enqueue(&app, pool.inner(), &monitor, &request, user.as_deref()).await;
+            }
+            let cached = if cacheable {
+                store::cache_get(pool.inner(), &cache_key).await?
+            } else {
+                None
+            };
+            return match cached {
+                Some(body) => serde_json::from_str(&body).map_err(|e| {
+                    ProxyError::new(
+                        ProxyErrorKind::Response,
+                        format!("Failed to parse cached response: {}", e),
+                    )
+                }),
+                None => Err(ProxyError::new(
+                    ProxyErrorKind::Offline,
+                    format!("Server unreachable: {}", e),
+                )),
+            }
+            .map_err(Into::into);
+        }
+        Err(e) => {
+            return Err(
+                ProxyError::new(ProxyErrorKind::Network, format!("Request failed: {}", e)).into(),
+            )
+        }
+    };
+    offline::set_online(&app, true).await;
+
+    if !response.status().is_success() {
+        return Err(ProxyError::from_response(response).await.into());
     }
+    let result = read_response(response, &request).await?;
 
-    // 添加请求体
-    if let Some(data) = &request.data {
-        req_builder = req_builder.json(data);
+    if cacheable {
+        if let Err(e) = store::cache_put(pool.inner(), &cache_key, &result.to_string()).await {
+            log::warn!("缓存响应失败: {}", e);
+        }
     }
 
-    // 发送请求
-    let response = req_builder
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+    Ok(result)
+}
 
-    // 检查响应状态
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("HTTP {} - {}", status, error_text));
-    }
+/// 写请求入队，等待联网后重放
+async fn enqueue(
+    app: &AppHandle,
+    pool: &SqlitePool,
+    monitor: &NetworkMonitor,
+    request: &HttpRequest,
+    user: Option<&str>,
+) -> CommandResult<Value> {
+    let id = store::outbox_push(
+        pool,
+        &request.method.to_uppercase(),
+        &request.url,
+        request.params.as_ref().map(|p| json!(p).to_string()),
+        request.data.as_ref().map(|d| d.to_string()),
+        user,
+    )
+    .await?;
+    log::info!("离线写请求已排队: {} {}", request.method, request.url);
 
-    // 解析响应
-    let result = response
-        .json::<Value>()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    offline::emit_status(app).await;
+    monitor.wake();
+    Ok(json!({ "queued": true, "id": id }))
+}
 
-    Ok(result)
+/// 当前网络状态与队列长度
+#[tauri::command]
+pub async fn get_network_status(app: AppHandle) -> CommandResult<NetworkStatus> {
+    Ok(offline::status(&app).await?)
+}
+
+/// 排队中的写请求（含已失败的），按入队顺序
+#[tauri::command]
+pub async fn list_outbox(pool: State<'_, SqlitePool>) -> CommandResult<Vec<OutboxEntry>> {
+    Ok(store::outbox_list(pool.inner()).await?)
+}
+
+/// 立即重试，`id` 为空时重试全部
+#[tauri::command]
+pub async fn retry_outbox(
+    id: Option<String>,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    monitor: State<'_, NetworkMonitor>,
+) -> CommandResult<u64> {
+    let count = store::outbox_reset(pool.inner(), id.as_deref()).await?;
+    offline::emit_status(&app).await;
+    monitor.wake();
+    Ok(count)
+}
+
+/// 放弃排队的写请求
+#[tauri::command]
+pub async fn discard_outbox(
+    id: String,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+) -> CommandResult<()> {
+    let deleted = store::outbox_delete(pool.inner(), &id).await?;
+    if !deleted {
+        return Err(CommandError::NotFound(format!("outbox entry {}", id)));
+    }
+    offline::emit_status(&app).await;
+    Ok(())
 }
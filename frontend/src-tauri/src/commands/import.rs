@@ -0,0 +1,100 @@
+//! 导入命令：从 Markdown 目录或 WordPress 导出文件迁移文章
+
+use crate::auth::require_admin;
+use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
+use crate::import::{self, ImportOptions, ImportReport};
+use crate::repositories::audit::record;
+use anyhow::Context;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
+
+#[derive(Serialize)]
+pub struct ImportResponse {
+    /// 实际导入的路径，dry-run 后可原样传回以跳过对话框
+    path: String,
+    report: ImportReport,
+}
+
+/// 导入文章（需要管理员权限）
+///
+/// 未传 `path` 时弹出对话框：`kind` 为 `wxr` 时选择 XML 文件，否则选择 Markdown 目录。
+/// 用户取消选择时返回 `None`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn import_articles(
+    token: String,
+    kind: String,
+    path: Option<String>,
+    dry_run: bool,
+    draft: Option<bool>,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<Option<ImportResponse>> {
+    log::info!("attempt to import_articles");
+    let claims = require_admin(pool.inner(), &config, &token, "import articles").await?;
+
+    let path = match path {
+        Some(p) => std::path::PathBuf::from(p),
+        None => {
+            let dialog = app.dialog().file().set_title("选择要导入的内容");
+            let picked = if kind == "wxr" {
+                dialog
+                    .add_filter("WordPress 导出文件", &["xml"])
+                    .blocking_pick_file()
+            } else {
+                dialog.blocking_pick_folder()
+            };
+            match picked {
+                Some(p) => p
+                    .into_path()
+                    .map_err(|e| CommandError::BadRequest(format!("invalid path: {}", e)))?,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    let (posts, skipped) =
+        import::load(&path).map_err(|e| CommandError::BadRequest(format!("{:#}", e)))?;
+    let opts = ImportOptions {
+        dry_run,
+        draft: draft.unwrap_or(false),
+        extra_tags: Vec::new(),
+    };
+    let report = import::run(pool.inner(), posts, skipped, &opts)
+        .await
+        .context("failed to import articles")?;
+
+    if !report.dry_run && !report.imported.is_empty() {
+        let after = serde_json::json!({
+            "source": path.display().to_string(),
+            "articles": report.imported.iter().filter_map(|e| e.id.as_deref()).collect::<Vec<_>>(),
+            "conflicts": report.conflicts.len(),
+            "skipped": report.skipped.len(),
+        });
+        record(
+            pool.inner(),
+            Some(&claims.user_id),
+            "article.import",
+            "article",
+            None,
+            None,
+            Some(after.to_string()),
+        )
+        .await?;
+    }
+
+    log::info!(
+        "success import_articles: {} imported, {} conflicts, {} skipped",
+        report.imported.len(),
+        report.conflicts.len(),
+        report.skipped.len()
+    );
+    Ok(Some(ImportResponse {
+        path: path.display().to_string(),
+        report,
+    }))
+}
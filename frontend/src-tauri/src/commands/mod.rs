@@ -2,14 +2,28 @@
 
 pub mod articles;
 pub mod auth;
+pub mod backup;
 pub mod comments;
+pub mod events;
+pub mod export;
 pub mod http;
+pub mod import;
+pub mod oidc;
 pub mod searches;
+pub mod setup;
+pub mod sync;
 pub mod users;
 
 pub use articles::*;
 pub use auth::*;
+pub use backup::*;
 pub use comments::*;
+pub use events::*;
+pub use export::*;
 pub use http::*;
+pub use import::*;
+pub use oidc::*;
 pub use searches::*;
+pub use setup::*;
+pub use sync::*;
 pub use users::*;
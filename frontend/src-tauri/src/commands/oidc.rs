@@ -0,0 +1,175 @@
+//! OIDC 登录相关命令
+//! 桌面端通过系统浏览器 + loopback 回调完成登录
+
+use crate::auth::{decode_token, generate_token, hash_password};
+use crate::commands::auth::LoginResponse;
+use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
+use crate::models::user::{NewUser, User};
+use crate::offline;
+use crate::oidc::{IdTokenClaims, LoopbackLogin};
+use crate::repositories::identity::{find_identity, insert_identity};
+use crate::repositories::user::{find_user_by_id, find_user_by_username, insert_common_user};
+use blog_shared::random_string;
+use blog_shared::validate::{derive_username, username_with_suffix};
+use serde::Serialize;
+use sqlx::{SqliteConnection, SqlitePool};
+use tauri::{AppHandle, State};
+use tauri_plugin_opener::OpenerExt;
+
+#[derive(Debug, Serialize)]
+pub struct OidcProviderInfo {
+    pub name: String,
+    pub display_name: String,
+}
+
+/// 获取已配置的 OIDC 提供方
+#[tauri::command]
+pub async fn get_oidc_providers(config: State<'_, Config>) -> CommandResult<Vec<OidcProviderInfo>> {
+    Ok(config
+        .oidc_providers
+        .iter()
+        .map(|p| OidcProviderInfo {
+            name: p.name.clone(),
+            display_name: p.display_name.clone().unwrap_or_else(|| p.name.clone()),
+        })
+        .collect())
+}
+
+/// OIDC 登录：打开系统浏览器，等待 loopback 回调
+/// 传入 token 时将外部身份关联到当前用户
+#[tauri::command]
+pub async fn oidc_login(
+    app: AppHandle,
+    provider: String,
+    token: Option<String>,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<LoginResponse> {
+    log::info!("attempt to oidc_login with provider: {}", provider);
+
+    let provider = config
+        .oidc_providers
+        .iter()
+        .find(|p| p.name == provider)
+        .ok_or_else(|| CommandError::NotFound(format!("OIDC provider {}", provider)))?;
+
+    let (login, authorization_url) = LoopbackLogin::start(provider).await?;
+
+    app.opener()
+        .open_url(&authorization_url, None::<&str>)
+        .map_err(|e| CommandError::Internal(format!("failed to open browser: {}", e)))?;
+
+    let claims = login.finish(provider).await.map_err(|e| {
+        log::error!("oidc_login failed: {}", e);
+        e
+    })?;
+
+    let current_user_id = token
+        .filter(|t| !t.is_empty())
+        .and_then(|t| decode_token(&config, &t).ok())
+        .map(|c| c.user_id);
+
+    let user = resolve_user(pool.inner(), &provider.name, &claims, current_user_id).await?;
+
+    let token = generate_token(&config, user.id.clone(), &user.username)?;
+
+    log::info!("oidc_login successful");
+    offline::start_session(pool.inner()).await;
+
+    Ok(LoginResponse {
+        token,
+        user_id: user.id,
+        username: user.username,
+        identity: user.identity,
+    })
+}
+
+/// 已关联则直接返回用户；已登录则关联到当前用户；否则自动创建普通用户
+async fn resolve_user(
+    pool: &SqlitePool,
+    provider: &str,
+    claims: &IdTokenClaims,
+    current_user_id: Option<String>,
+) -> CommandResult<User> {
+    let linked = find_identity(pool, provider, &claims.sub).await?;
+
+    let user = match (linked, current_user_id) {
+        (Some(identity), _) => find_user_by_id(pool, identity.user_id.clone())
+            .await?
+            .ok_or_else(|| CommandError::NotFound(format!("user {}", identity.user_id)))?,
+        (None, Some(user_id)) => {
+            let user = find_user_by_id(pool, user_id.clone())
+                .await?
+                .ok_or_else(|| CommandError::NotFound(format!("user {}", user_id)))?;
+            insert_identity(
+                pool,
+                &user.id,
+                provider,
+                &claims.sub,
+                claims.email.as_deref(),
+            )
+            .await?;
+            user
+        }
+        // 新用户与身份在同一事务中写入，避免留下没有身份的用户
+        (None, None) => {
+            let mut tx = pool.begin().await?;
+            let user = create_user_for_identity(&mut tx, provider, claims).await?;
+            insert_identity(
+                &mut *tx,
+                &user.id,
+                provider,
+                &claims.sub,
+                claims.email.as_deref(),
+            )
+            .await?;
+            tx.commit().await?;
+            user
+        }
+    };
+
+    Ok(user)
+}
+
+/// 为外部身份创建本地用户：取第一个符合注册规则的候选用户名，冲突时追加随机后缀
+async fn create_user_for_identity(
+    conn: &mut SqliteConnection,
+    provider: &str,
+    claims: &IdTokenClaims,
+) -> CommandResult<User> {
+    let fallback = format!(
+        "{}_{}",
+        provider,
+        claims.sub.chars().take(8).collect::<String>()
+    );
+    let base = derive_username(
+        [
+            claims.preferred_username.as_deref(),
+            claims.email.as_deref().and_then(|e| e.split('@').next()),
+            claims.name.as_deref(),
+        ],
+        &fallback,
+    );
+
+    let mut username = base.clone();
+    while find_user_by_username(&mut *conn, &username)
+        .await?
+        .is_some()
+    {
+        username = username_with_suffix(&base, &random_string(6));
+    }
+
+    // 外部账号不使用本地密码登录，存入随机密码的哈希
+    let password = hash_password(&random_string(32))?;
+
+    Ok(insert_common_user(
+        &mut *conn,
+        &NewUser {
+            username,
+            password,
+            identity: "user".to_string(),
+        },
+    )
+    .await?)
+}
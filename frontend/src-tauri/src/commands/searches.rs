@@ -1,5 +1,6 @@
 //! 搜索相关命令
 
+use crate::error::CommandResult;
 use crate::repositories::search::*;
 use sqlx::SqlitePool;
 use tauri::State;
@@ -9,10 +10,8 @@ use tauri::State;
 pub async fn get_suggestions(
     keyword: String,
     pool: State<'_, SqlitePool>,
-) -> Result<Vec<TmpSuggest>, String> {
-    let suggestions = get_suggests_by_keyword(pool.inner(), &keyword)
-        .await
-        .map_err(|e| format!("Failed to fetch suggestions: {}", e))?;
+) -> CommandResult<Vec<TmpSuggest>> {
+    let suggestions = get_suggests_by_keyword(pool.inner(), &keyword).await?;
 
     Ok(suggestions)
 }
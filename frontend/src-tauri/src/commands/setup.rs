@@ -0,0 +1,92 @@
+//! 首次设置命令
+//! 桌面端没有超级管理员时，由前端展示设置页面创建
+
+use crate::auth::{generate_token, hash_password};
+use crate::commands::auth::LoginResponse;
+use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
+use crate::models::user::UserPublic;
+use crate::repositories::audit::{record, snapshot};
+use crate::repositories::user::{find_user_by_username, has_super_admin, insert_super_admin};
+use crate::validate::{self, validated, Validate, Validator};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::State;
+
+#[derive(Debug, Serialize)]
+pub struct SetupStatus {
+    pub needs_setup: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetupRequest {
+    pub username: String,
+    pub password: String,
+}
+
+impl Validate for SetupRequest {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.username);
+        v.username("username", &self.username)
+            .password("password", &self.password, 6);
+    }
+}
+
+/// 查询是否需要首次设置
+#[tauri::command]
+pub async fn get_setup_status(pool: State<'_, SqlitePool>) -> CommandResult<SetupStatus> {
+    let needs_setup = !has_super_admin(pool.inner()).await?;
+
+    Ok(SetupStatus { needs_setup })
+}
+
+/// 创建超级管理员（仅在没有超级管理员时可用）
+#[tauri::command]
+pub async fn setup_admin(
+    payload: SetupRequest,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<LoginResponse> {
+    log::info!("attempt to setup_admin");
+
+    if has_super_admin(pool.inner()).await? {
+        return Err(CommandError::Forbidden(
+            "setup has already been completed".to_string(),
+        ));
+    }
+
+    let payload = validated(payload)?;
+
+    let existing = find_user_by_username(pool.inner(), &payload.username).await?;
+    if existing.is_some() {
+        return Err(CommandError::BadRequest(
+            "username already exists".to_string(),
+        ));
+    }
+
+    let password_hash = hash_password(&payload.password)?;
+
+    let user = insert_super_admin(pool.inner(), &payload.username, &password_hash).await?;
+
+    let token = generate_token(&config, user.id.clone(), &user.username)?;
+
+    record(
+        pool.inner(),
+        None,
+        "user.setup",
+        "user",
+        Some(&user.id),
+        None,
+        snapshot(&UserPublic::from(user.clone())),
+    )
+    .await?;
+
+    log::info!("setup_admin completed");
+
+    Ok(LoginResponse {
+        token,
+        user_id: user.id,
+        username: user.username,
+        identity: user.identity,
+    })
+}
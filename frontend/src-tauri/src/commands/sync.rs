@@ -0,0 +1,96 @@
+//! 同步命令：立即同步、查询状态、列出与解决冲突（除查询状态外均需要管理员权限）
+
+use crate::auth::require_admin;
+use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
+use crate::models::sync::SyncConflict;
+use crate::repositories::audit::record;
+use crate::repositories::sync as store;
+use crate::sync::{self, SyncHandle, SyncReport, SyncStatus};
+use anyhow::Context;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, State};
+
+/// 立即同步，状态变化同时通过 `sync_status` 事件通知
+#[tauri::command]
+pub async fn sync_now(
+    token: String,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<SyncReport> {
+    log::info!("attempt to sync_now");
+    require_admin(pool.inner(), &config, &token, "manage sync").await?;
+    Ok(sync::sync_now(&app).await.context("failed to sync")?)
+}
+
+/// 当前同步状态
+#[tauri::command]
+pub async fn get_sync_status(handle: State<'_, SyncHandle>) -> CommandResult<SyncStatus> {
+    Ok(handle.status())
+}
+
+/// 未解决的正文冲突
+#[tauri::command]
+pub async fn list_sync_conflicts(
+    token: String,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<Vec<SyncConflict>> {
+    require_admin(pool.inner(), &config, &token, "manage sync").await?;
+    Ok(store::list_conflicts(pool.inner()).await?)
+}
+
+/// 解决冲突
+///
+/// `resolution` 为 `local`（保留本地正文）、`remote`（采用服务端正文）或 `custom`（使用 `content`，如手动合并的结果），
+/// 选定的正文在下次同步时写回服务端
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn resolve_sync_conflict(
+    token: String,
+    article_id: String,
+    resolution: String,
+    content: Option<String>,
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    config: State<'_, Config>,
+) -> CommandResult<SyncStatus> {
+    log::info!("attempt to resolve_sync_conflict: {}", article_id);
+    let user_id = require_admin(pool.inner(), &config, &token, "manage sync")
+        .await?
+        .user_id;
+
+    let conflict = store::find_conflict(pool.inner(), &article_id)
+        .await?
+        .ok_or_else(|| CommandError::NotFound(format!("conflict {}", article_id)))?;
+    let chosen = match resolution.as_str() {
+        "local" => conflict.local_content.clone(),
+        "remote" => conflict.remote_content.clone(),
+        "custom" => content,
+        _ => {
+            return Err(CommandError::BadRequest(format!(
+                "unknown resolution: {}",
+                resolution
+            )))
+        }
+    }
+    .unwrap_or_default();
+
+    store::resolve_conflict(pool.inner(), &conflict, &chosen).await?;
+    record(
+        pool.inner(),
+        Some(&user_id),
+        "sync.resolve",
+        "article",
+        Some(&article_id),
+        None,
+        Some(serde_json::json!({ "resolution": resolution }).to_string()),
+    )
+    .await?;
+
+    log::info!("success resolve_sync_conflict: {}", article_id);
+    Ok(sync::refresh_status(&app)
+        .await
+        .context("failed to refresh sync status")?)
+}
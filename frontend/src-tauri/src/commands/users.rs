@@ -1,13 +1,18 @@
 //! 用户管理相关命令
 
-use crate::auth::{decode_token, hash_password};
+use crate::auth::{decode_token, hash_password, require_admin};
 use crate::config::Config;
+use crate::error::{CommandError, CommandResult};
 use crate::models::user::UserPublic;
 use crate::models::ResponseMessage;
+use crate::repositories::audit::{record, snapshot};
+use crate::repositories::identity::delete_identities_by_user;
 use crate::repositories::user::{
-    delete_user_by_id, edit_user_account, get_ident_by_id, list_users, AdminEditAccountPayload,
+    delete_user_by_id, edit_user_account, find_user_by_id, get_ident_by_id, list_users,
+    AdminEditAccountPayload,
 };
 use crate::tray::update_system_tray_icon;
+use crate::validate::validated;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use std::fs;
@@ -26,23 +31,12 @@ pub async fn get_users(
     limit: Option<i32>,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<ListUsersResponse, String> {
-    // 验证 token
-    let claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
-
-    // 检查用户身份
-    let identity = get_ident_by_id(pool.inner(), &claims.user_id)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
-
-    if identity != "admin" {
-        return Err("Only admin can view users".to_string());
-    }
+) -> CommandResult<ListUsersResponse> {
+    // 验证 token 与用户身份
+    require_admin(pool.inner(), &config, &token, "view users").await?;
 
     let default_limit = limit.unwrap_or(10);
-    let users = list_users(pool.inner(), default_limit)
-        .await
-        .map_err(|e| format!("Failed to fetch users: {}", e))?;
+    let users = list_users(pool.inner(), default_limit).await?;
 
     Ok(ListUsersResponse { users })
 }
@@ -54,22 +48,31 @@ pub async fn delete_user(
     user_id: String,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<ResponseMessage, String> {
-    // 验证 token
-    let claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
-
-    // 检查用户身份
-    let identity = get_ident_by_id(pool.inner(), &claims.user_id)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
-
-    if identity != "admin" {
-        return Err("Only admin can delete users".to_string());
+) -> CommandResult<ResponseMessage> {
+    // 验证 token 与用户身份
+    let claims = require_admin(pool.inner(), &config, &token, "delete users").await?;
+
+    // 超管不可被删除
+    let target = find_user_by_id(pool.inner(), user_id.clone()).await?;
+    if target.as_ref().is_some_and(|u| u.is_super_admin) {
+        return Err(CommandError::Forbidden(
+            "cannot delete superadmin".to_string(),
+        ));
     }
 
-    delete_user_by_id(pool.inner(), &user_id)
-        .await
-        .map_err(|e| format!("Failed to delete user: {}", e))?;
+    delete_user_by_id(pool.inner(), &user_id).await?;
+    delete_identities_by_user(pool.inner(), &user_id).await?;
+
+    record(
+        pool.inner(),
+        Some(&claims.user_id),
+        "user.delete",
+        "user",
+        Some(&user_id),
+        target.map(UserPublic::from).as_ref().and_then(snapshot),
+        None,
+    )
+    .await?;
 
     Ok(ResponseMessage {
         message: "done".to_string(),
@@ -83,30 +86,29 @@ pub async fn edit_account(
     payload: AdminEditAccountPayload,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<ResponseMessage, String> {
-    // 验证 token
-    let claims = decode_token(&config, &token).map_err(|e| format!("Invalid token: {}", e))?;
-
-    // 检查用户身份
-    let identity = get_ident_by_id(pool.inner(), &claims.user_id)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
-
-    if identity != "admin" {
-        return Err("Only admin can edit user accounts".to_string());
-    }
-
-    // 防止更改超管的权限
-    if payload.edited_identity.as_ref().map(|s| s.as_str()) == Some("admin")
-        && payload.edited_id == "1"
-    {
-        return Err("Cannot change superadmin identity".to_string());
+) -> CommandResult<ResponseMessage> {
+    // 验证 token 与用户身份
+    let claims = require_admin(pool.inner(), &config, &token, "edit user accounts").await?;
+    let payload = validated(payload)?;
+
+    // 防止更改超管的权限：只有超管本人能编辑自己的账号，且身份不可降级
+    let before = find_user_by_id(pool.inner(), payload.edited_id.clone()).await?;
+    if let Some(target) = &before {
+        let demoted = payload
+            .edited_identity
+            .as_deref()
+            .is_some_and(|ident| ident != "admin");
+        if target.is_super_admin && (target.id != claims.user_id || demoted) {
+            return Err(CommandError::Forbidden(
+                "cannot change superadmin account".to_string(),
+            ));
+        }
     }
 
     // 将传递的密码转为 hash
     let new_password = if let Some(ref password) = payload.edited_password {
         if !password.is_empty() {
-            Some(hash_password(password).map_err(|e| format!("Failed to hash password: {}", e))?)
+            Some(hash_password(password)?)
         } else {
             None
         }
@@ -114,6 +116,8 @@ pub async fn edit_account(
         None
     };
 
+    let password_changed = new_password.is_some();
+    let edited_id = payload.edited_id.clone();
     let updated_payload = AdminEditAccountPayload {
         edited_id: payload.edited_id,
         edited_username: payload.edited_username,
@@ -121,9 +125,27 @@ pub async fn edit_account(
         edited_identity: payload.edited_identity,
     };
 
-    edit_user_account(pool.inner(), updated_payload)
-        .await
-        .map_err(|e| format!("Failed to edit account: {}", e))?;
+    edit_user_account(pool.inner(), updated_payload).await?;
+
+    // 快照不含密码哈希，仅标记密码是否被修改
+    let after = find_user_by_id(pool.inner(), edited_id.clone())
+        .await?
+        .map(UserPublic::from)
+        .and_then(|u| serde_json::to_value(u).ok())
+        .map(|mut v| {
+            v["password_changed"] = password_changed.into();
+            v.to_string()
+        });
+    record(
+        pool.inner(),
+        Some(&claims.user_id),
+        "user.update",
+        "user",
+        Some(&edited_id),
+        before.map(UserPublic::from).as_ref().and_then(snapshot),
+        after,
+    )
+    .await?;
 
     Ok(ResponseMessage {
         message: "done".to_string(),
@@ -150,20 +172,22 @@ pub async fn save_avatar(
     payload: SaveAvatarPayload,
     pool: State<'_, SqlitePool>,
     config: State<'_, Config>,
-) -> Result<SaveAvatarResponse, String> {
+) -> CommandResult<SaveAvatarResponse> {
     // 验证 token
-    let claims =
-        decode_token(&config, &payload.token).map_err(|e| format!("Invalid token: {}", e))?;
+    let claims = decode_token(&config, &payload.token)?;
 
     // 验证用户是否存在
     get_ident_by_id(pool.inner(), &claims.user_id)
         .await
-        .map_err(|e| format!("User not found: {}", e))?;
+        .map_err(|_| CommandError::NotFound(format!("user {}", claims.user_id)))?;
 
     // 验证源文件是否存在
     let source_path = PathBuf::from(&payload.source_path);
     if !source_path.exists() {
-        return Err("Source file does not exist".to_string());
+        return Err(CommandError::NotFound(format!(
+            "source file {}",
+            payload.source_path
+        )));
     }
 
     // 验证文件类型（通过扩展名）
@@ -171,10 +195,12 @@ pub async fn save_avatar(
         .extension()
         .and_then(|e| e.to_str())
         .map(|e| e.to_lowercase())
-        .ok_or("File has no extension")?;
+        .ok_or_else(|| CommandError::BadRequest("file has no extension".to_string()))?;
 
     if extension != "png" && extension != "jpg" && extension != "jpeg" {
-        return Err("Only PNG and JPG files are allowed".to_string());
+        return Err(CommandError::BadRequest(
+            "only PNG and JPG files are allowed".to_string(),
+        ));
     }
 
     // 统一扩展名
@@ -188,11 +214,11 @@ pub async fn save_avatar(
     let app_data = app
         .path()
         .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        .map_err(|e| CommandError::Internal(format!("failed to get app data dir: {}", e)))?;
 
     let icons_dir = app_data.join("icons");
     fs::create_dir_all(&icons_dir)
-        .map_err(|e| format!("Failed to create icons directory: {}", e))?;
+        .map_err(|e| CommandError::Internal(format!("failed to create icons directory: {}", e)))?;
 
     // 生成文件名：user_id + 时间戳
     let timestamp = chrono::Utc::now().timestamp();
@@ -200,19 +226,21 @@ pub async fn save_avatar(
     let dest_path = icons_dir.join(&filename);
 
     // 复制文件（而不是移动，保留用户的原始文件）
-    fs::copy(&source_path, &dest_path).map_err(|e| format!("Failed to copy avatar: {}", e))?;
+    fs::copy(&source_path, &dest_path)
+        .map_err(|e| CommandError::Internal(format!("failed to copy avatar: {}", e)))?;
 
     if extension == "png" {
         log::info!("将png文件更新为系统托盘: {:?}", dest_path);
         // 将头像应用至系统托盘(仅限.png)
-        update_system_tray_icon(&app, dest_path.to_str().unwrap_or(""))?;
+        update_system_tray_icon(&app, dest_path.to_str().unwrap_or(""))
+            .map_err(CommandError::Internal)?;
     }
 
     log::info!("Avatar saved: {:?}", dest_path);
 
     // 清理旧头像（保留最新 10 个）
     cleanup_old_avatars(&icons_dir, &claims.user_id, 10)
-        .map_err(|e| format!("Failed to cleanup old avatars: {}", e))?;
+        .map_err(|e| CommandError::Internal(format!("failed to cleanup old avatars: {}", e)))?;
 
     // 返回相对路径
     let relative_path = format!("icons/{}", filename);
@@ -1,25 +1,137 @@
 //! 统一配置中心：统一使用应用数据目录存储配置和数据
+//! 读取 `config.json` 后会校验，错误一次性列出，避免带着无效配置启动
 
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use tauri::Manager;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
+    #[serde(default = "default_host")]
     pub host: String,
+    #[serde(default = "default_port")]
     pub port: u16,
+    /// 服务端地址（如 `https://blog.example.com`），配置后 HTTP 代理使用它代替 `host` / `port`
+    #[serde(default)]
+    pub server_url: Option<String>,
+    /// 额外信任的 CA 证书（PEM），相对路径基于应用数据目录，用于自签名的 HTTPS 服务端
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+    /// HTTP 代理的请求超时（秒）
+    #[serde(default = "default_http_timeout_secs")]
+    pub http_timeout_secs: u64,
+    /// HTTP 代理允许访问的路径前缀
+    #[serde(default = "default_api_prefixes")]
+    pub api_prefixes: Vec<String>,
     pub jwt_secret: String,
     /// JWT 过期秒数
+    #[serde(default = "default_jwt_ttl")]
     pub jwt_ttl: i64,
+    /// OIDC 登录提供方（桌面端使用 loopback 回调，无需配置 redirect_uri）
+    #[serde(default)]
+    pub oidc_providers: Vec<OidcProviderConfig>,
+    /// 保留的备份份数，备份位于应用数据目录下的 `backups`
+    #[serde(default = "default_backup_keep")]
+    pub backup_keep: u32,
+    /// 定时备份间隔（小时），0 为关闭
+    #[serde(default = "default_backup_interval_hours")]
+    pub backup_interval_hours: u32,
+    /// 与服务端同步，未配置时不同步
+    #[serde(default)]
+    pub sync: Option<SyncConfig>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SyncConfig {
+    /// 服务端地址，如 `https://blog.example.com`
+    pub server_url: String,
+    /// 服务端管理员创建的个人访问令牌，需要 `sync` scope
+    pub token: String,
+    /// 自动同步间隔（秒），0 为只手动同步
+    #[serde(default = "default_sync_interval_secs")]
+    pub interval_secs: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OidcProviderConfig {
+    pub name: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    pub issuer: String,
+    pub client_id: String,
+    /// 公共客户端（仅 PKCE）可以不配置
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: String,
+}
+
+fn default_oidc_scopes() -> String {
+    "openid profile email".into()
+}
+
+fn default_host() -> String {
+    "127.0.0.1".into()
+}
+
+fn default_port() -> u16 {
+    3000
+}
+
+fn default_http_timeout_secs() -> u64 {
+    30
+}
+
+fn default_api_prefixes() -> Vec<String> {
+    [
+        "/api/",
+        "/articles",
+        "/article/",
+        "/comments/",
+        "/comment/",
+        "/suggestions/",
+        "/setup",
+        "/health",
+        "/.well-known/",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_jwt_ttl() -> i64 {
+    7 * 24 * 3600
+}
+
+fn default_backup_keep() -> u32 {
+    7
+}
+
+fn default_backup_interval_hours() -> u32 {
+    24
+}
+
+fn default_sync_interval_secs() -> u64 {
+    300
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            host: "127.0.0.1".into(),
-            port: 3000,
+            host: default_host(),
+            port: default_port(),
+            server_url: None,
+            ca_cert: None,
+            http_timeout_secs: default_http_timeout_secs(),
+            api_prefixes: default_api_prefixes(),
             jwt_secret: Self::generate_random_secret(),
-            jwt_ttl: 7 * 24 * 3600,
+            jwt_ttl: default_jwt_ttl(),
+            oidc_providers: Vec::new(),
+            backup_keep: default_backup_keep(),
+            backup_interval_hours: default_backup_interval_hours(),
+            sync: None,
         }
     }
 }
@@ -39,7 +151,12 @@ impl Config {
         if config_path.exists() {
             // 读取现有配置
             let config_str = std::fs::read_to_string(&config_path)?;
-            Ok(serde_json::from_str(&config_str)?)
+            let config: Self = serde_json::from_str(&config_str)
+                .map_err(|e| format!("{} 解析失败: {}", config_path.display(), e))?;
+            config
+                .validate()
+                .map_err(|e| format!("{} 无效:\n{}", config_path.display(), e))?;
+            Ok(config)
         } else {
             // 创建默认配置
             let config = Self::default();
@@ -52,6 +169,81 @@ impl Config {
         }
     }
 
+    /// 校验配置，返回全部问题（每行一条）
+    pub fn validate(&self) -> Result<(), String> {
+        let mut issues = Vec::new();
+
+        if self.host.parse::<std::net::IpAddr>().is_err() {
+            issues.push(format!("host: {:?} 不是合法的 IP 地址", self.host));
+        }
+        if self.port == 0 {
+            issues.push("port: 不能为 0".to_string());
+        }
+        if let Some(url) = &self.server_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                issues.push(format!("server_url: {:?} 不是合法的 URL", url));
+            }
+        }
+        if self.http_timeout_secs == 0 {
+            issues.push("http_timeout_secs: 必须大于 0".to_string());
+        }
+        for (i, prefix) in self.api_prefixes.iter().enumerate() {
+            if !prefix.starts_with('/') {
+                issues.push(format!("api_prefixes[{}]: {:?} 必须以 / 开头", i, prefix));
+            }
+        }
+        if self.jwt_secret.len() < 16 {
+            issues.push("jwt_secret: 长度至少 16 个字符".to_string());
+        }
+        if self.jwt_ttl <= 0 {
+            issues.push("jwt_ttl: 必须大于 0".to_string());
+        }
+        if self.backup_keep == 0 {
+            issues.push("backup_keep: 必须大于 0".to_string());
+        }
+        if let Some(sync) = &self.sync {
+            if !sync.server_url.starts_with("http://") && !sync.server_url.starts_with("https://") {
+                issues.push(format!(
+                    "sync.server_url: {:?} 不是合法的 URL",
+                    sync.server_url
+                ));
+            }
+            if sync.token.is_empty() {
+                issues.push("sync.token: 不能为空".to_string());
+            }
+        }
+        for (i, p) in self.oidc_providers.iter().enumerate() {
+            if p.name.is_empty() {
+                issues.push(format!("oidc_providers[{}].name: 不能为空", i));
+            }
+            if self.oidc_providers[..i].iter().any(|q| q.name == p.name) {
+                issues.push(format!(
+                    "oidc_providers[{}].name: 提供方 {:?} 重复",
+                    i, p.name
+                ));
+            }
+            if !p.issuer.starts_with("http://") && !p.issuer.starts_with("https://") {
+                issues.push(format!(
+                    "oidc_providers[{}].issuer: {:?} 不是合法的 URL",
+                    i, p.issuer
+                ));
+            }
+            if p.client_id.is_empty() {
+                issues.push(format!("oidc_providers[{}].client_id: 不能为空", i));
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues
+                .iter()
+                .map(|issue| format!("  - {}", issue))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+    }
+
     /// 生成随机 JWT secret
     fn generate_random_secret() -> String {
         use rand::Rng;
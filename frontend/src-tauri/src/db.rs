@@ -1,4 +1,4 @@
-use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use sqlx::{migrate::Migrator, SqlitePool, sqlite::SqlitePoolOptions};
 use std::path::PathBuf;
 
 /// 创建新的数据库连接池
@@ -39,11 +39,44 @@ pub async fn new_pool(db_url: &str) -> Result<SqlitePool, sqlx::Error> {
         .await
 }
 
+/// 迁移脚本（编译期打包），恢复备份前也用它检查备份的迁移版本
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// 编号有变化的迁移：(旧版本, 旧描述)。迁移编号与服务端共用，同一编号只对应同一个迁移
+pub const RENUMBERED: &[(i64, &str)] = &[
+    // 0011 在服务端是用户名唯一索引，桌面端改为 0017
+    (11, "add http offline"),
+];
+
 /// 运行数据库迁移
 pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::migrate::MigrateError> {
-    sqlx::migrate!("./migrations")
-        .run(pool)
-        .await
+    forget_renumbered(pool).await?;
+    MIGRATOR.run(pool).await
+}
+
+/// 删除按旧编号执行过的迁移记录；这些迁移都可重复执行（`IF NOT EXISTS`），会按新编号重新记录
+async fn forget_renumbered(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let (tables,): (i64,) = sqlx::query_as(
+        r#"SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'"#,
+    )
+    .fetch_one(pool)
+    .await?;
+    if tables == 0 {
+        return Ok(());
+    }
+
+    for (version, description) in RENUMBERED {
+        let res =
+            sqlx::query(r#"DELETE FROM _sqlx_migrations WHERE version = ? AND description = ?"#)
+                .bind(version)
+                .bind(description)
+                .execute(pool)
+                .await?;
+        if res.rows_affected() > 0 {
+            log::info!("迁移 {} ({}) 已改用新编号", version, description);
+        }
+    }
+    Ok(())
 }
 
 /// 运行数据库种子数据
@@ -0,0 +1,98 @@
+//! 命令统一错误类型：与服务端 `AppError` 的变体对应，
+//! 序列化为 `{ code, message, details }` 返回给前端，便于按 `code` 区分错误，命令中可直接使用 `?`。
+
+use crate::oidc::OidcError;
+use crate::proxy::ProxyError;
+use crate::validate::FieldError;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("bad request: {0}")]
+    BadRequest(String),
+    #[error("insufficient privileges: {0}")]
+    Forbidden(String),
+    /// 参数校验失败，字段错误放在 `details` 中
+    #[error("validation failed")]
+    Validation(Vec<FieldError>),
+    #[error("database error")]
+    Sqlx(#[from] sqlx::Error),
+    #[error("invalid token")]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+    #[error("password hash error")]
+    PasswordHash(#[from] argon2::password_hash::Error),
+    #[error("{0:#}")]
+    Other(#[from] anyhow::Error),
+    #[error("{0}")]
+    Internal(String),
+    /// HTTP 代理的错误，原样放在 `details` 中
+    #[error(transparent)]
+    Proxy(#[from] ProxyError),
+}
+
+impl CommandError {
+    /// 稳定的错误码，前端据此区分错误
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::NotFound(_) => "NOT_FOUND",
+            CommandError::Unauthorized(_) | CommandError::Jwt(_) => "UNAUTHORIZED",
+            CommandError::BadRequest(_) => "BAD_REQUEST",
+            CommandError::Forbidden(_) => "FORBIDDEN",
+            CommandError::Validation(_) => "VALIDATION_FAILED",
+            CommandError::Sqlx(_) => "DATABASE_ERROR",
+            CommandError::PasswordHash(_) => "PASSWORD_HASH_ERROR",
+            CommandError::Other(_) | CommandError::Internal(_) => "INTERNAL_ERROR",
+            CommandError::Proxy(_) => "PROXY_ERROR",
+        }
+    }
+
+    /// 底层错误的详细信息，不包含在 `message` 中
+    fn details(&self) -> Option<Value> {
+        match self {
+            CommandError::Sqlx(e) => Some(e.to_string().into()),
+            CommandError::Jwt(e) => Some(e.to_string().into()),
+            CommandError::PasswordHash(e) => Some(e.to_string().into()),
+            CommandError::Proxy(e) => serde_json::to_value(e).ok(),
+            CommandError::Validation(fields) => serde_json::to_value(fields).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct ErrorBody {
+            code: &'static str,
+            message: String,
+            details: Option<Value>,
+        }
+
+        ErrorBody {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<OidcError> for CommandError {
+    fn from(e: OidcError) -> Self {
+        match e {
+            OidcError::Jwt(_) | OidcError::Key(_) | OidcError::Protocol(_) => {
+                CommandError::Unauthorized(e.to_string())
+            }
+            OidcError::Http(_) | OidcError::Io(_) => CommandError::Internal(e.to_string()),
+        }
+    }
+}
+
+/// 命令的统一结果类型
+pub type CommandResult<T> = Result<T, CommandError>;
@@ -0,0 +1,225 @@
+//! 服务端实时事件的转发，文章事件与站内通知各自维持一条连接：
+//! - 文章事件：连接 `/api/v1/events`（SSE），收到的事件以 `blog_event` 事件原样发给前端，数据为服务端的 JSON（带 `type`）
+//! - 站内通知：连接 `/api/v1/notifications/stream`，新通知以 `notification` 事件发给前端，同时弹出系统通知
+//! - 服务端积压丢弃事件时发送 `{ "type": "lagged", "skipped": n }`，断线重连成功后发送 `{ "type": "reconnected" }`，前端收到后应重新拉取数据
+//! - 连接断开后指数退避重连；token 失效（401）时停止，等待前端用新 token 重新订阅
+//! - 每种连接同一时间只保留一个，重新订阅会取消之前的连接
+
+use crate::proxy::HttpProxy;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// 首次重连的等待时间，之后每次翻倍
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+/// 最长等待时间
+const BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// 应用状态中的事件订阅
+#[derive(Default)]
+pub struct EventRelay {
+    articles: Mutex<Option<JoinHandle<()>>>,
+    notifications: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// 一路事件流：请求路径、查询参数与收到事件后的处理
+struct Source {
+    path: &'static str,
+    query: Vec<(&'static str, String)>,
+    on_event: fn(&AppHandle, Value),
+}
+
+impl EventRelay {
+    /// 订阅 `article_ids` 的事件，为空时订阅全部文章
+    pub fn subscribe(&self, app: AppHandle, token: String, article_ids: Vec<String>) {
+        let mut query = Vec::new();
+        if !article_ids.is_empty() {
+            query.push(("articles", article_ids.join(",")));
+        }
+        let source = Source {
+            path: "/api/v1/events",
+            query,
+            on_event: emit_blog_event,
+        };
+        replace(&self.articles, Some(spawn(app, token, source)));
+    }
+
+    pub fn unsubscribe(&self) {
+        replace(&self.articles, None);
+    }
+
+    /// 接收当前用户的站内通知
+    pub fn watch_notifications(&self, app: AppHandle, token: String) {
+        let source = Source {
+            path: "/api/v1/notifications/stream",
+            query: Vec::new(),
+            on_event: show_notification,
+        };
+        replace(&self.notifications, Some(spawn(app, token, source)));
+    }
+
+    pub fn unwatch_notifications(&self) {
+        replace(&self.notifications, None);
+    }
+}
+
+fn spawn(app: AppHandle, token: String, source: Source) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(run(app, token, source))
+}
+
+/// 换上新的连接任务，取消之前的
+fn replace(slot: &Mutex<Option<JoinHandle<()>>>, task: Option<JoinHandle<()>>) {
+    let previous = std::mem::replace(&mut *slot.lock().unwrap(), task);
+    if let Some(previous) = previous {
+        previous.abort();
+    }
+}
+
+/// 一次连接的结果
+enum Outcome {
+    /// 连接曾经建立，之后断开
+    Disconnected,
+    /// 未能建立连接
+    Failed,
+    /// token 无效，不再重试
+    Unauthorized,
+}
+
+async fn run(app: AppHandle, token: String, source: Source) {
+    let mut backoff = BACKOFF_BASE;
+    let mut connected_before = false;
+
+    loop {
+        match connect(&app, &token, &source, connected_before).await {
+            Outcome::Unauthorized => {
+                log::warn!("{} token 无效，停止订阅", source.path);
+                return;
+            }
+            Outcome::Disconnected => {
+                connected_before = true;
+                backoff = BACKOFF_BASE;
+            }
+            Outcome::Failed => {}
+        }
+
+        log::info!("{} 断开，{} 秒后重连", source.path, backoff.as_secs());
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(BACKOFF_MAX);
+    }
+}
+
+async fn connect(app: &AppHandle, token: &str, source: &Source, reconnect: bool) -> Outcome {
+    let proxy = app.state::<HttpProxy>();
+    let request = proxy
+        .stream_client()
+        .get(format!("{}{}", proxy.base_url(), source.path))
+        .query(&source.query)
+        .bearer_auth(token)
+        .header("Accept", "text/event-stream");
+
+    let mut resp = match request.send().await {
+        Ok(resp) if resp.status() == reqwest::StatusCode::UNAUTHORIZED => {
+            return Outcome::Unauthorized
+        }
+        Ok(resp) if resp.status().is_success() => resp,
+        Ok(resp) => {
+            log::warn!("订阅 {} 失败: HTTP {}", source.path, resp.status());
+            return Outcome::Failed;
+        }
+        Err(e) => {
+            log::warn!("订阅 {} 失败: {}", source.path, e);
+            return Outcome::Failed;
+        }
+    };
+
+    log::info!("已连接 {}", source.path);
+    if reconnect {
+        (source.on_event)(app, json!({ "type": "reconnected" }));
+    }
+
+    let mut buffer: Vec<u8> = Vec::new();
+    loop {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => {
+                buffer.extend(chunk.iter().filter(|b| **b != b'\r'));
+                while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                    let frame: Vec<u8> = buffer.drain(..end + 2).collect();
+                    if let Some(payload) = parse_frame(&String::from_utf8_lossy(&frame)) {
+                        (source.on_event)(app, payload);
+                    }
+                }
+            }
+            Ok(None) => return Outcome::Disconnected,
+            Err(e) => {
+                log::warn!("读取 {} 失败: {}", source.path, e);
+                return Outcome::Disconnected;
+            }
+        }
+    }
+}
+
+/// 解析一帧 SSE，注释（保活）与空帧返回 `None`
+fn parse_frame(frame: &str) -> Option<Value> {
+    let mut event = None;
+    let mut data = Vec::new();
+    for line in frame.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = Some(value.trim());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    if data.is_empty() {
+        return None;
+    }
+    let data = data.join("\n");
+
+    match event {
+        Some("lagged") => Some(json!({
+            "type": "lagged",
+            "skipped": data.parse::<u64>().unwrap_or_default(),
+        })),
+        _ => match serde_json::from_str(&data) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                log::warn!("无法解析事件 {:?}: {}", event, e);
+                None
+            }
+        },
+    }
+}
+
+fn emit_blog_event(app: &AppHandle, payload: Value) {
+    if let Err(e) = app.emit("blog_event", payload) {
+        log::warn!("发送实时事件失败: {}", e);
+    }
+}
+
+/// 转发给前端，新通知（带 `kind`）同时弹出系统通知
+fn show_notification(app: &AppHandle, payload: Value) {
+    if let Some(kind) = payload["kind"].as_str() {
+        let actor = payload["actor_name"].as_str().unwrap_or("有人");
+        let title = match kind {
+            "reply" => format!("{} 回复了你的评论", actor),
+            "like" => format!("{} 赞了你的评论", actor),
+            "moderation" => "你的评论已被管理员删除".to_string(),
+            _ => "新通知".to_string(),
+        };
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title(title)
+            .body(payload["message"].as_str().unwrap_or_default())
+            .show()
+        {
+            log::warn!("弹出系统通知失败: {}", e);
+        }
+    }
+
+    if let Err(e) = app.emit("notification", payload) {
+        log::warn!("发送通知事件失败: {}", e);
+    }
+}
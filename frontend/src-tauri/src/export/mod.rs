@@ -0,0 +1,51 @@
+//! 导出整站内容：归档与静态站点的生成由 `blog_shared::export` 完成（与服务端共用），
+//! 这里负责从数据库读出内容并转换为共用的数据结构
+
+use crate::models::article::ArticleModel;
+use crate::models::comment::Comment;
+use crate::repositories::article::list_articles_for_export;
+use crate::repositories::comment::list_comments_for_export;
+use blog_shared::export::{self as shared, Article};
+use sqlx::SqlitePool;
+
+pub use blog_shared::export::{archive, site, Snapshot};
+
+/// 读取全部文章（按发布日期倒序）与评论
+pub async fn snapshot(pool: &SqlitePool) -> Result<Snapshot, sqlx::Error> {
+    let articles = list_articles_for_export(pool).await?;
+    let comments = list_comments_for_export(pool).await?;
+    Ok(Snapshot::new(
+        articles.into_iter().map(Article::from).collect(),
+        comments.into_iter().map(shared::Comment::from).collect(),
+    ))
+}
+
+impl From<ArticleModel> for Article {
+    fn from(a: ArticleModel) -> Self {
+        Self {
+            id: a.id,
+            title: a.title,
+            content: a.content,
+            summary: a.summary,
+            created_at: a.created_at,
+            update_at: a.update_at,
+            status: a.status,
+            views: a.views,
+            tags: a.tags,
+        }
+    }
+}
+
+impl From<Comment> for shared::Comment {
+    fn from(c: Comment) -> Self {
+        Self {
+            comment_id: c.comment_id,
+            article_id: c.article_id,
+            user: c.user,
+            content: c.content,
+            created_at: c.created_at,
+            parent_id: c.parent_id,
+            like_count: c.like_count,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! 从其他博客迁移文章：解析由 `blog_shared::import` 完成（与服务端共用），
+//! 这里由 `run` 把 `ImportedPost` 写入数据库：保留原发布日期，
+//! 与已有文章或本批次中同名的文章记为冲突并跳过；`dry_run` 时只生成报告不写库
+
+use crate::repositories::article::{
+    find_article_id_by_title, insert_imported_article, ImportedArticle,
+};
+use crate::repositories::comment::{insert_imported_comment, ImportedComment as CommentRow};
+use chrono::Local;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub use blog_shared::import::{load, ImportSkipped, ImportedPost};
+
+/// 与 `models::article` 一致的日期存储格式
+const DATE_FORMAT: &str = "%Y::%m::%d";
+
+#[derive(Debug, Default, Clone)]
+pub struct ImportOptions {
+    pub dry_run: bool,
+    /// 全部导入为草稿，忽略原文的发布状态
+    pub draft: bool,
+    /// 附加到每篇文章的标签
+    pub extra_tags: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub imported: Vec<ImportedEntry>,
+    pub conflicts: Vec<ImportConflict>,
+    pub skipped: Vec<ImportSkipped>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportedEntry {
+    pub source: String,
+    pub title: String,
+    /// dry-run 时为空
+    pub id: Option<String>,
+    pub status: String,
+    pub comments: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportConflict {
+    pub source: String,
+    pub title: String,
+    /// 已存在的文章 id，或本批次中先出现的同名来源
+    pub existing: String,
+}
+
+/// 写入解析结果，整批在一个事务中完成
+pub async fn run(
+    pool: &SqlitePool,
+    posts: Vec<ImportedPost>,
+    skipped: Vec<ImportSkipped>,
+    opts: &ImportOptions,
+) -> anyhow::Result<ImportReport> {
+    let mut report = ImportReport {
+        dry_run: opts.dry_run,
+        skipped,
+        ..Default::default()
+    };
+    let mut seen: HashMap<String, String> = HashMap::new();
+    let mut tx = pool.begin().await?;
+
+    for mut post in posts {
+        let existing = match seen.get(&post.title) {
+            Some(source) => Some(source.clone()),
+            None => find_article_id_by_title(&mut tx, &post.title).await?,
+        };
+        if let Some(existing) = existing {
+            report.conflicts.push(ImportConflict {
+                source: post.source,
+                title: post.title,
+                existing,
+            });
+            continue;
+        }
+        seen.insert(post.title.clone(), post.source.clone());
+
+        for tag in &opts.extra_tags {
+            if !post.tags.contains(tag) {
+                post.tags.push(tag.clone());
+            }
+        }
+        let status = if post.draft || opts.draft {
+            "draft"
+        } else {
+            "published"
+        };
+
+        let id = if opts.dry_run {
+            None
+        } else {
+            Some(save(&mut tx, &post, status).await?)
+        };
+        report.imported.push(ImportedEntry {
+            source: post.source,
+            title: post.title,
+            id,
+            status: status.into(),
+            comments: post.comments.len(),
+        });
+    }
+
+    if !opts.dry_run {
+        tx.commit().await?;
+    }
+    Ok(report)
+}
+
+async fn save(
+    conn: &mut sqlx::SqliteConnection,
+    post: &ImportedPost,
+    status: &str,
+) -> anyhow::Result<String> {
+    let created_at = post
+        .created_at
+        .unwrap_or_else(|| Local::now().naive_local())
+        .format(DATE_FORMAT)
+        .to_string();
+    let update_at = post.updated_at.map(|d| d.format(DATE_FORMAT).to_string());
+    let tags = (!post.tags.is_empty()).then(|| post.tags.join(","));
+
+    let article_id = insert_imported_article(
+        conn,
+        &ImportedArticle {
+            title: &post.title,
+            content: &post.content,
+            summary: &post.summary,
+            tags: tags.as_deref(),
+            status,
+            created_at: &created_at,
+            update_at: update_at.as_deref(),
+        },
+    )
+    .await?;
+
+    // 先为所有评论分配新 id，回复可能早于被回复的评论出现
+    let ids: HashMap<&str, String> = post
+        .comments
+        .iter()
+        .map(|c| (c.id.as_str(), Uuid::now_v7().to_string()))
+        .collect();
+    for c in &post.comments {
+        let created_at = c
+            .created_at
+            .map(|d| d.format(DATE_FORMAT).to_string())
+            .unwrap_or_else(|| created_at.clone());
+        insert_imported_comment(
+            conn,
+            &CommentRow {
+                comment_id: &ids[c.id.as_str()],
+                article_id: &article_id,
+                user: &c.author,
+                content: &c.content,
+                created_at: &created_at,
+                parent_id: c
+                    .parent
+                    .as_deref()
+                    .and_then(|p| ids.get(p))
+                    .map(String::as_str),
+            },
+        )
+        .await?;
+    }
+    Ok(article_id)
+}
@@ -1,12 +1,22 @@
 //! Tauri 应用库入口
 
 pub mod auth;
+pub mod backup;
 pub mod commands;
 pub mod config;
 pub mod db;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod import;
 pub mod models;
+pub mod offline;
+pub mod oidc;
+pub mod proxy;
 pub mod repositories;
+pub mod sync;
 pub mod tray;
+pub mod validate;
 
 use crate::tray::load_system_tray;
 use config::Config;
@@ -18,6 +28,8 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(
             tauri_plugin_log::Builder::default()
                 .level(log::LevelFilter::Info)
@@ -47,8 +59,13 @@ pub fn run() {
             log::info!("Database path: {}", database_url);
 
             let pool = match tauri::async_runtime::block_on(async {
+                // 先应用暂存的恢复，再连接数据库
+                let restored = backup::apply_pending(&backup::database_file(app.handle())?).await?;
                 let pool = new_pool(&database_url).await?;
                 run_migrations(&pool).await?;
+                if restored {
+                    backup::record_applied(&pool).await?;
+                }
 
                 // 运行种子数据（仅在开发模式或首次运行时）
                 if let Err(e) = run_seeds(&pool).await {
@@ -70,20 +87,52 @@ pub fn run() {
 
             log::info!("Database initialized successfully");
 
+            // HTTP 代理共享的客户端
+            let app_data_dir = app.path().app_data_dir()?;
+            let proxy = proxy::HttpProxy::new(&config, &app_data_dir).map_err(|e| {
+                log::error!("HTTP proxy initialization failed: {}", e);
+                std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    format!("Configuration error: {}", e),
+                )
+            })?;
+
             // 将配置和连接池存储到状态中
+            let backup_interval_hours = config.backup_interval_hours;
+            let sync_interval_secs = config.sync.as_ref().map(|s| s.interval_secs);
+            app.manage(sync::SyncHandle::new(sync_interval_secs.is_some()));
+            app.manage(offline::NetworkMonitor::default());
+            app.manage(events::EventRelay::default());
+            app.manage(proxy);
             app.manage(config);
             app.manage(pool);
+            backup::spawn_scheduler(app.handle().clone(), backup_interval_hours);
+            offline::spawn_replayer(app.handle().clone());
+            if let Some(secs) = sync_interval_secs {
+                sync::spawn_scheduler(app.handle().clone(), secs);
+            }
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // HTTP 请求代理
             commands::http_request,
+            commands::get_network_status,
+            commands::list_outbox,
+            commands::retry_outbox,
+            commands::discard_outbox,
             // 认证
             commands::login,
+            commands::logout,
             commands::register,
             commands::verify_token,
             commands::get_current_user,
+            // OIDC 登录
+            commands::get_oidc_providers,
+            commands::oidc_login,
+            // 首次设置
+            commands::get_setup_status,
+            commands::setup_admin,
             // 用户管理
             commands::get_users,
             commands::delete_user,
@@ -95,11 +144,27 @@ pub fn run() {
             commands::update_article,
             commands::delete_article,
             commands::toggle_article_status,
+            commands::import_articles,
+            commands::export_blog,
+            // 备份
+            commands::backup_now,
+            commands::list_backups,
+            commands::restore_backup,
+            // 同步
+            commands::sync_now,
+            commands::get_sync_status,
+            commands::list_sync_conflicts,
+            commands::resolve_sync_conflict,
             // 评论
             commands::get_comments,
             commands::post_comment,
             commands::delete_comment,
             commands::like_comment,
+            // 实时事件
+            commands::subscribe_events,
+            commands::unsubscribe_events,
+            commands::watch_notifications,
+            commands::unwatch_notifications,
             // 搜索
             commands::get_suggestions,
             // 用户操作
@@ -4,7 +4,10 @@ use serde::Serialize;
 
 pub mod article;
 pub mod comment;
+pub mod identity;
+pub mod offline;
 pub mod search;
+pub mod sync;
 pub mod user;
 
 
@@ -0,0 +1,23 @@
+//! 离线代理模型定义
+
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// 离线时排队的写请求
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OutboxEntry {
+    pub id: String,
+    pub method: String,
+    pub url: String,
+    /// JSON 对象
+    pub params: Option<String>,
+    /// JSON 请求体
+    pub data: Option<String>,
+    /// 发起请求的用户（见 `offline::token_user`），重放时使用该用户当前的 token
+    pub user_id: Option<String>,
+    pub attempts: i64,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub failed: bool,
+    pub created_at: String,
+}
@@ -0,0 +1,16 @@
+//! 同步模型定义
+
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// 同步时发现的正文冲突，用户选择保留哪一方后删除
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SyncConflict {
+    pub article_id: String,
+    pub title: Option<String>,
+    pub local_content: Option<String>,
+    pub remote_content: Option<String>,
+    #[serde(skip)]
+    pub remote_clocks: String,
+    pub detected_at: String,
+}
@@ -9,13 +9,16 @@ pub struct User {
     pub username: String,
     pub password: String,
     pub identity: String,
+    /// 超级管理员标记
+    pub is_super_admin: bool,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, FromRow, Serialize)]
 pub struct UserPublic {
     pub id: String,
     pub username: String,
     pub identity: String,
+    pub is_super_admin: bool,
 }
 
 impl From<User> for UserPublic {
@@ -24,6 +27,7 @@ impl From<User> for UserPublic {
             id: u.id,
             username: u.username,
             identity: u.identity,
+            is_super_admin: u.is_super_admin,
         }
     }
 }
@@ -0,0 +1,615 @@
+//! HTTP 代理的离线支持：
+//! - 连接服务端失败（连接错误或超时）时标记为离线，GET 请求返回上次成功的缓存
+//! - 离线时的写请求（multipart 除外）保存到 `http_outbox`，返回 `{ "queued": true, "id": ... }`；队列非空时新的写请求也排队，保证顺序
+//! - 缓存与队列按用户区分：缓存键包含用户，队列只记录用户，重放时使用该用户当前的 token（见 `NetworkMonitor`）；
+//!   登录时清空缓存，退出登录时清空缓存，并把该用户尚未重放的请求标记为失败
+//! - `spawn_replayer`：按入队顺序重放队列，失败时指数退避；离线时定期探测 `/health`，恢复后立即重放
+//! - 在线状态或队列变化时发送 `network_status` 事件
+
+use crate::proxy::{is_network_error, HttpProxy, HttpRequest, ProxyError, ResponseType};
+use crate::repositories::offline as store;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::{Response, StatusCode};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::Notify;
+
+/// 离线时探测服务端的间隔
+const PROBE_INTERVAL: Duration = Duration::from_secs(15);
+/// 首次重试的等待时间，之后每次翻倍
+const BACKOFF_BASE_MS: i64 = 2_000;
+/// 最长等待时间
+const BACKOFF_MAX_MS: i64 = 5 * 60 * 1000;
+/// 服务端持续出错（5xx / 429）时的最大重试次数，之后标记为失败
+const MAX_ATTEMPTS: i64 = 8;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkStatus {
+    pub online: bool,
+    /// 待重放的写请求数
+    pub pending: i64,
+    /// 已失败、需要手动处理的写请求数
+    pub failed: i64,
+}
+
+/// 应用状态中的网络监视器
+pub struct NetworkMonitor {
+    online: AtomicBool,
+    wake: Notify,
+    /// 用户 → 前端最近一次使用的 token，重放队列时使用；只保存在内存中
+    tokens: Mutex<HashMap<String, String>>,
+}
+
+impl Default for NetworkMonitor {
+    fn default() -> Self {
+        Self {
+            online: AtomicBool::new(true),
+            wake: Notify::new(),
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl NetworkMonitor {
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::SeqCst)
+    }
+
+    /// 唤醒重放任务
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// 记录用户当前的 token；该用户之前没有 token 时唤醒重放任务，让等待中的请求继续
+    pub fn remember_token(&self, user: &str, token: &str) {
+        let previous = self
+            .tokens
+            .lock()
+            .unwrap()
+            .insert(user.to_string(), token.to_string());
+        if previous.is_none() {
+            self.wake();
+        }
+    }
+
+    pub fn token_for(&self, user: &str) -> Option<String> {
+        self.tokens.lock().unwrap().get(user).cloned()
+    }
+
+    pub fn forget_token(&self, user: &str) {
+        self.tokens.lock().unwrap().remove(user);
+    }
+}
+
+/// token 对应的用户：登录 token 取 `user_id`；个人访问令牌不含用户信息，以令牌摘要代替；没有 token 时为 `None`
+pub fn token_user(token: Option<&str>) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct Claims {
+        user_id: String,
+    }
+
+    let token = token.filter(|t| !t.is_empty())?;
+    // 只用于区分缓存与队列，不校验签名，服务端仍会校验 token
+    let claims = token
+        .split('.')
+        .nth(1)
+        .and_then(|payload| URL_SAFE_NO_PAD.decode(payload).ok())
+        .and_then(|payload| serde_json::from_slice::<Claims>(&payload).ok());
+    Some(match claims {
+        Some(claims) => claims.user_id,
+        None => {
+            let digest = Sha256::digest(token.as_bytes());
+            let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+            format!("token:{}", hex)
+        }
+    })
+}
+
+/// GET 缓存的键：用户 + 路径 + 排序后的查询参数
+pub fn cache_key(
+    user: Option<&str>,
+    url: &str,
+    params: Option<&HashMap<String, String>>,
+) -> String {
+    let user = user.unwrap_or_default();
+    match params {
+        Some(params) if !params.is_empty() => {
+            let sorted: BTreeMap<_, _> = params.iter().collect();
+            let query = sorted
+                .into_iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join("&");
+            format!("{} {}?{}", user, url, query)
+        }
+        _ => format!("{} {}", user, url),
+    }
+}
+
+/// 登录后清空缓存，避免看到上一个用户的数据
+pub async fn start_session(pool: &SqlitePool) {
+    if let Err(e) = store::cache_clear(pool).await {
+        log::warn!("清空响应缓存失败: {}", e);
+    }
+}
+
+/// 退出登录：清空缓存，忘记 token，该用户尚未重放的请求标记为失败（可在重新登录后重试）
+pub async fn end_session<R: Runtime>(
+    app: &AppHandle<R>,
+    token: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let pool = app.state::<SqlitePool>();
+    store::cache_clear(pool.inner()).await?;
+    if let Some(user) = token_user(token) {
+        app.state::<NetworkMonitor>().forget_token(&user);
+        store::outbox_fail_user(pool.inner(), &user, "logged out before replay").await?;
+    }
+    emit_status(app).await;
+    Ok(())
+}
+
+/// 更新在线状态，变化时发送事件；恢复联网后立即重放队列
+pub async fn set_online<R: Runtime>(app: &AppHandle<R>, online: bool) {
+    let monitor = app.state::<NetworkMonitor>();
+    if monitor.online.swap(online, Ordering::SeqCst) == online {
+        return;
+    }
+
+    if online {
+        log::info!("服务端已恢复连接");
+        if let Err(e) = store::outbox_wake(app.state::<SqlitePool>().inner()).await {
+            log::warn!("重置重放队列失败: {}", e);
+        }
+        monitor.wake();
+    } else {
+        log::warn!("无法连接服务端，进入离线模式");
+    }
+    emit_status(app).await;
+}
+
+/// 当前网络状态
+pub async fn status<R: Runtime>(app: &AppHandle<R>) -> Result<NetworkStatus, sqlx::Error> {
+    let (pending, failed) = store::outbox_counts(app.state::<SqlitePool>().inner()).await?;
+    Ok(NetworkStatus {
+        online: app.state::<NetworkMonitor>().is_online(),
+        pending,
+        failed,
+    })
+}
+
+/// 发送 `network_status` 事件
+pub async fn emit_status<R: Runtime>(app: &AppHandle<R>) {
+    match status(app).await {
+        Ok(status) => {
+            if let Err(e) = app.emit("network_status", status) {
+                log::warn!("发送网络状态事件失败: {}", e);
+            }
+        }
+        Err(e) => log::warn!("读取重放队列失败: {}", e),
+    }
+}
+
+/// 第 `attempts` 次失败后的等待时间
+fn backoff_ms(attempts: i64) -> i64 {
+    let exp = attempts.clamp(0, 16) as u32;
+    BACKOFF_BASE_MS.saturating_mul(1 << exp).min(BACKOFF_MAX_MS)
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// 启动重放任务
+pub fn spawn_replayer(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // 启动时先重放上次遗留的请求
+        let mut wait = Duration::ZERO;
+        loop {
+            let monitor = app.state::<NetworkMonitor>();
+            tokio::select! {
+                _ = monitor.wake.notified() => {}
+                _ = tokio::time::sleep(wait) => {}
+            }
+
+            if !monitor.is_online() {
+                probe(&app).await;
+            }
+            wait = if monitor.is_online() {
+                replay(&app).await
+            } else {
+                PROBE_INTERVAL
+            };
+        }
+    });
+}
+
+/// 离线时探测服务端，只要有响应就视为恢复
+async fn probe<R: Runtime>(app: &AppHandle<R>) {
+    let proxy = app.state::<HttpProxy>();
+    let url = format!("{}/health", proxy.base_url());
+    if proxy.client().get(url).send().await.is_ok() {
+        set_online(app, true).await;
+    }
+}
+
+/// 按顺序重放，返回下次需要唤醒的时间
+async fn replay<R: Runtime>(app: &AppHandle<R>) -> Duration {
+    let pool = app.state::<SqlitePool>();
+    let proxy = app.state::<HttpProxy>();
+
+    loop {
+        let entry = match store::outbox_next(pool.inner()).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return PROBE_INTERVAL,
+            Err(e) => {
+                log::warn!("读取重放队列失败: {}", e);
+                return PROBE_INTERVAL;
+            }
+        };
+        let delay = entry.next_attempt_at - now_ms();
+        if delay > 0 {
+            return Duration::from_millis(delay as u64);
+        }
+
+        // 该用户本次启动后还没有发过请求（如刚启动、尚未加载页面），等前端带上 token 后再重放
+        let token = match &entry.user_id {
+            Some(user) => match app.state::<NetworkMonitor>().token_for(user) {
+                Some(token) => Some(token),
+                None => return PROBE_INTERVAL,
+            },
+            None => None,
+        };
+        let request = HttpRequest {
+            method: entry.method.clone(),
+            url: entry.url.clone(),
+            token,
+            params: entry
+                .params
+                .as_deref()
+                .and_then(|s| serde_json::from_str(s).ok()),
+            data: entry
+                .data
+                .as_deref()
+                .and_then(|s| serde_json::from_str(s).ok()),
+            multipart: None,
+            response_type: ResponseType::Json,
+        };
+
+        let outcome = match proxy.execute(app, &request).await {
+            Err(e) => Outcome::Fail(e.to_string()),
+            Ok(Err(e)) if is_network_error(&e) => {
+                set_online(app, false).await;
+                Outcome::Retry(format!("Request failed: {}", e))
+            }
+            Ok(Err(e)) => Outcome::Retry(format!("Request failed: {}", e)),
+            Ok(Ok(resp)) => classify(resp).await,
+        };
+
+        let result = match &outcome {
+            Outcome::Done => store::outbox_delete(pool.inner(), &entry.id)
+                .await
+                .map(|_| ()),
+            Outcome::Retry(error) if entry.attempts + 1 < MAX_ATTEMPTS || !is_online(app) => {
+                let next = now_ms() + backoff_ms(entry.attempts);
+                store::outbox_retry_later(pool.inner(), &entry.id, next, error).await
+            }
+            Outcome::Retry(error) | Outcome::Fail(error) => {
+                log::warn!("离线请求 {} {} 失败: {}", entry.method, entry.url, error);
+                store::outbox_fail(pool.inner(), &entry.id, error).await
+            }
+        };
+        if let Err(e) = result {
+            log::warn!("更新重放队列失败: {}", e);
+            return PROBE_INTERVAL;
+        }
+        emit_status(app).await;
+
+        if let Outcome::Done = outcome {
+            log::info!("已重放离线请求 {} {}", entry.method, entry.url);
+        } else if !is_online(app) {
+            return PROBE_INTERVAL;
+        }
+    }
+}
+
+fn is_online<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.state::<NetworkMonitor>().is_online()
+}
+
+enum Outcome {
+    Done,
+    /// 暂时失败，稍后重试
+    Retry(String),
+    /// 服务端拒绝，不再重试
+    Fail(String),
+}
+
+async fn classify(resp: Response) -> Outcome {
+    let status = resp.status();
+    if status.is_success() {
+        return Outcome::Done;
+    }
+    let error = ProxyError::from_response(resp).await.to_string();
+    if status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+    {
+        Outcome::Retry(error)
+    } else {
+        Outcome::Fail(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::MIGRATOR;
+    use serde_json::json;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use std::sync::Arc;
+    use tauri::test::{mock_app, MockRuntime};
+    use tauri::App;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// 本地服务端收到的请求
+    #[derive(Debug, Clone)]
+    struct Received {
+        method: String,
+        path: String,
+        authorization: Option<String>,
+        body: String,
+    }
+
+    /// 本地服务端：依次返回 `statuses` 中的状态码（用完后返回 200），记录收到的请求
+    async fn server(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        tokio::spawn(async move {
+            let mut statuses = statuses.into_iter();
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut socket).await;
+                log.lock().unwrap().push(request);
+                let status = statuses.next().unwrap_or(200);
+                let response = format!(
+                    "HTTP/1.1 {} Test\r\ncontent-type: application/json\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{{}}",
+                    status
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, received)
+    }
+
+    async fn read_request(socket: &mut TcpStream) -> Received {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let head_end = loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "连接提前关闭");
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+        let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+        let header = |name: &str| {
+            head.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.eq_ignore_ascii_case(name)
+                    .then(|| value.trim().to_string())
+            })
+        };
+        let length: usize = header("content-length").map_or(0, |v| v.parse().unwrap());
+        while buf.len() < head_end + length {
+            let n = socket.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "连接提前关闭");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+
+        let mut start = head.split(' ');
+        Received {
+            method: start.next().unwrap().to_string(),
+            path: start.next().unwrap().to_string(),
+            authorization: header("authorization"),
+            body: String::from_utf8_lossy(&buf[head_end..head_end + length]).into_owned(),
+        }
+    }
+
+    async fn memory_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        pool
+    }
+
+    /// 按正式启动时的方式管理状态，代理指向 `server_url`
+    async fn manage_state(app: &App<MockRuntime>, server_url: &str) -> SqlitePool {
+        let pool = memory_pool().await;
+        let config: Config =
+            serde_json::from_value(json!({ "jwt_secret": "test", "server_url": server_url }))
+                .unwrap();
+        app.manage(pool.clone());
+        app.manage(NetworkMonitor::default());
+        app.manage(HttpProxy::new(&config, &std::env::temp_dir()).unwrap());
+        pool
+    }
+
+    /// 形如登录 JWT 的 token，只有 payload 中的 `user_id` 有意义
+    fn login_token(user: &str, serial: u32) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(json!({ "user_id": user }).to_string());
+        format!("header.{}.signature{}", payload, serial)
+    }
+
+    #[test]
+    fn cache_is_never_shared_between_users() {
+        tauri::async_runtime::block_on(async {
+            let pool = memory_pool().await;
+            let alice = token_user(Some(&login_token("alice", 1)));
+            let bob = token_user(Some(&login_token("bob", 1)));
+            assert_eq!(alice.as_deref(), Some("alice"));
+            assert_eq!(bob.as_deref(), Some("bob"));
+            // 刷新后的 token 仍属于同一用户
+            assert_eq!(token_user(Some(&login_token("alice", 2))), alice);
+            // 个人访问令牌按令牌区分
+            assert_ne!(token_user(Some("pat_one")), token_user(Some("pat_two")));
+            assert_eq!(token_user(Some("")), None);
+
+            let params = HashMap::from([
+                ("page".to_string(), "1".to_string()),
+                ("size".to_string(), "10".to_string()),
+            ]);
+            let key = |user: &Option<String>| {
+                cache_key(user.as_deref(), "/api/v1/articles", Some(&params))
+            };
+            store::cache_put(&pool, &key(&alice), r#"{"owner":"alice"}"#)
+                .await
+                .unwrap();
+
+            let cases = [
+                ("其他用户", &bob, None),
+                ("未登录", &None, None),
+                ("本人", &alice, Some(r#"{"owner":"alice"}"#)),
+            ];
+            for (desc, user, expected) in cases {
+                let cached = store::cache_get(&pool, &key(user)).await.unwrap();
+                assert_eq!(cached.as_deref(), expected, "{}", desc);
+            }
+
+            // 登录新会话时清空
+            start_session(&pool).await;
+            assert_eq!(store::cache_get(&pool, &key(&alice)).await.unwrap(), None);
+        });
+    }
+
+    #[test]
+    fn queued_writes_replay_in_order_with_current_token() {
+        let app = mock_app();
+        tauri::async_runtime::block_on(async {
+            let (url, received) = server(Vec::new()).await;
+            let pool = manage_state(&app, &url).await;
+            let requests = [
+                ("POST", "/api/v1/articles", 1),
+                ("PUT", "/api/v1/articles/1", 2),
+                ("DELETE", "/api/v1/articles/1", 3),
+            ];
+            for (method, path, n) in requests {
+                let data = json!({ "n": n }).to_string();
+                store::outbox_push(&pool, method, path, None, Some(data), Some("alice"))
+                    .await
+                    .unwrap();
+            }
+
+            // 本次启动后 alice 还没有发过请求：不能匿名重放，等待该用户的 token
+            replay(app.handle()).await;
+            assert!(received.lock().unwrap().is_empty());
+            assert_eq!(store::outbox_counts(&pool).await.unwrap(), (3, 0));
+
+            // 入队后 token 已更换，重放使用最新的 token
+            let monitor = app.state::<NetworkMonitor>();
+            monitor.remember_token("alice", &login_token("alice", 1));
+            let current = login_token("alice", 2);
+            monitor.remember_token("alice", &current);
+            replay(app.handle()).await;
+
+            let received = received.lock().unwrap().clone();
+            let sent: Vec<_> = received
+                .iter()
+                .map(|r| (r.method.as_str(), r.path.as_str(), r.body.as_str()))
+                .collect();
+            assert_eq!(
+                sent,
+                [
+                    ("POST", "/api/v1/articles", r#"{"n":1}"#),
+                    ("PUT", "/api/v1/articles/1", r#"{"n":2}"#),
+                    ("DELETE", "/api/v1/articles/1", r#"{"n":3}"#),
+                ]
+            );
+            let bearer = format!("Bearer {}", current);
+            for r in &received {
+                assert_eq!(r.authorization.as_deref(), Some(bearer.as_str()));
+            }
+            assert_eq!(store::outbox_counts(&pool).await.unwrap(), (0, 0));
+        });
+    }
+
+    #[test]
+    fn client_errors_leave_the_queue_without_retry() {
+        let app = mock_app();
+        tauri::async_runtime::block_on(async {
+            let (url, received) = server(vec![422]).await;
+            let pool = manage_state(&app, &url).await;
+            app.state::<NetworkMonitor>()
+                .remember_token("alice", &login_token("alice", 1));
+            let rejected =
+                store::outbox_push(&pool, "POST", "/api/v1/articles", None, None, Some("alice"))
+                    .await
+                    .unwrap();
+            store::outbox_push(
+                &pool,
+                "POST",
+                "/api/v1/comments/1",
+                None,
+                None,
+                Some("alice"),
+            )
+            .await
+            .unwrap();
+
+            // 被拒绝的请求不阻塞后面的请求
+            replay(app.handle()).await;
+            assert_eq!(received.lock().unwrap().len(), 2);
+            let entries = store::outbox_list(&pool).await.unwrap();
+            assert_eq!(entries.len(), 1, "成功的请求已删除");
+            let entry = &entries[0];
+            assert_eq!(entry.id, rejected);
+            assert!(entry.failed);
+            assert_eq!(entry.attempts, 1);
+            assert!(entry.last_error.as_deref().unwrap().contains("HTTP 422"));
+
+            // 不再自动重放
+            replay(app.handle()).await;
+            assert_eq!(received.lock().unwrap().len(), 2);
+            assert_eq!(store::outbox_counts(&pool).await.unwrap(), (0, 1));
+        });
+    }
+
+    #[test]
+    fn server_errors_are_retried_later() {
+        let app = mock_app();
+        tauri::async_runtime::block_on(async {
+            let (url, received) = server(vec![503]).await;
+            let pool = manage_state(&app, &url).await;
+            app.state::<NetworkMonitor>()
+                .remember_token("alice", &login_token("alice", 1));
+            store::outbox_push(&pool, "POST", "/api/v1/articles", None, None, Some("alice"))
+                .await
+                .unwrap();
+
+            let wait = replay(app.handle()).await;
+            assert!(wait <= Duration::from_millis(BACKOFF_BASE_MS as u64));
+            let entry = store::outbox_next(&pool).await.unwrap().unwrap();
+            assert!(!entry.failed);
+            assert_eq!(entry.attempts, 1);
+            assert!(entry.next_attempt_at > now_ms());
+
+            // 退避期间不重发
+            replay(app.handle()).await;
+            assert_eq!(received.lock().unwrap().len(), 1);
+        });
+    }
+}
@@ -0,0 +1,263 @@
+//! 桌面端 OIDC 登录（授权码 + PKCE + loopback 回调）：
+//! - 在 127.0.0.1 的随机端口上监听一次回调
+//! - 由系统浏览器完成提供方登录，回跳到 loopback 地址
+//! - 用授权码换取 id_token，并基于提供方的 JWKS 校验签名、iss、aud、nonce；
+//!   只接受非对称签名算法，且算法必须与 JWKS 中的密钥相符
+
+use crate::config::OidcProviderConfig;
+use blog_shared::oidc::{allowed_algorithms, decoding_key, pkce_challenge, KeyError};
+use blog_shared::random_string;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::Validation;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+/// 等待浏览器回调的最长时间
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum OidcError {
+    #[error("OIDC request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Invalid id_token: {0}")]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+    #[error("Invalid id_token: {0}")]
+    Key(#[from] KeyError),
+    #[error("Loopback listener error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Protocol(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    #[serde(default)]
+    id_token_signing_alg_values_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// id_token 中用到的声明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+    pub nonce: Option<String>,
+}
+
+/// 一次进行中的 loopback 登录
+pub struct LoopbackLogin {
+    listener: TcpListener,
+    redirect_uri: String,
+    state: String,
+    nonce: String,
+    code_verifier: String,
+    metadata: ProviderMetadata,
+    http: reqwest::Client,
+}
+
+impl LoopbackLogin {
+    /// 启动 loopback 监听并生成授权地址，返回 (登录会话, authorization_url)
+    pub async fn start(provider: &OidcProviderConfig) -> Result<(Self, String), OidcError> {
+        let http = reqwest::Client::new();
+        let metadata = discover(&http, provider).await?;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let redirect_uri = format!(
+            "http://127.0.0.1:{}/callback",
+            listener.local_addr()?.port()
+        );
+
+        let state = random_string(32);
+        let nonce = random_string(32);
+        let code_verifier = random_string(64);
+
+        let mut url = Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| OidcError::Protocol(format!("Invalid authorization endpoint: {}", e)))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &provider.client_id)
+            .append_pair("redirect_uri", &redirect_uri)
+            .append_pair("scope", &provider.scopes)
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &pkce_challenge(&code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok((
+            Self {
+                listener,
+                redirect_uri,
+                state,
+                nonce,
+                code_verifier,
+                metadata,
+                http,
+            },
+            url.to_string(),
+        ))
+    }
+
+    /// 等待浏览器回调，换取并校验 id_token
+    pub async fn finish(self, provider: &OidcProviderConfig) -> Result<IdTokenClaims, OidcError> {
+        let (code, state) = tokio::time::timeout(CALLBACK_TIMEOUT, self.accept_callback())
+            .await
+            .map_err(|_| OidcError::Protocol("Timed out waiting for OIDC callback".into()))??;
+
+        if state != self.state {
+            return Err(OidcError::Protocol("OIDC state mismatch".into()));
+        }
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("code_verifier", self.code_verifier.as_str()),
+        ];
+        if let Some(secret) = &provider.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let token: TokenResponse = self
+            .http
+            .post(&self.metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let claims = self.verify_id_token(provider, &token.id_token).await?;
+        if claims.nonce.as_deref() != Some(self.nonce.as_str()) {
+            return Err(OidcError::Protocol("OIDC nonce mismatch".into()));
+        }
+
+        Ok(claims)
+    }
+
+    /// 接收一次回调请求，返回 (code, state)
+    async fn accept_callback(&self) -> Result<(String, String), OidcError> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+
+            let mut buf = vec![0u8; 8192];
+            let n = stream.read(&mut buf).await?;
+            let request = String::from_utf8_lossy(&buf[..n]);
+
+            // 请求行形如：GET /callback?code=...&state=... HTTP/1.1
+            let Some(target) = request
+                .lines()
+                .next()
+                .and_then(|line| line.split_whitespace().nth(1))
+            else {
+                continue;
+            };
+            let Ok(url) = Url::parse(&format!("http://127.0.0.1{}", target)) else {
+                continue;
+            };
+            if url.path() != "/callback" {
+                // 浏览器可能会顺带请求 favicon 等资源
+                let _ = stream
+                    .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                    .await;
+                continue;
+            }
+
+            let param = |key: &str| {
+                url.query_pairs()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v.into_owned())
+            };
+
+            let body = if param("error").is_some() {
+                "登录失败，请返回应用重试。"
+            } else {
+                "登录完成，可以关闭此页面并返回应用。"
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+
+            if let Some(error) = param("error") {
+                return Err(OidcError::Protocol(format!(
+                    "OIDC provider error: {}",
+                    error
+                )));
+            }
+
+            return match (param("code"), param("state")) {
+                (Some(code), Some(state)) => Ok((code, state)),
+                _ => Err(OidcError::Protocol(
+                    "Missing code or state in callback".into(),
+                )),
+            };
+        }
+    }
+
+    /// 基于 JWKS 校验 id_token 的签名与 iss / aud
+    async fn verify_id_token(
+        &self,
+        provider: &OidcProviderConfig,
+        id_token: &str,
+    ) -> Result<IdTokenClaims, OidcError> {
+        let header = jsonwebtoken::decode_header(id_token)?;
+
+        let jwks: JwkSet = self
+            .http
+            .get(&self.metadata.jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let allowed = allowed_algorithms(&self.metadata.id_token_signing_alg_values_supported);
+        let (key, alg) = decoding_key(&header, &jwks, &allowed)?;
+
+        let mut validation = Validation::new(alg);
+        validation.set_issuer(&[&self.metadata.issuer]);
+        validation.set_audience(&[&provider.client_id]);
+
+        let data = jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation)?;
+        Ok(data.claims)
+    }
+}
+
+/// 获取提供方元数据
+async fn discover(
+    http: &reqwest::Client,
+    provider: &OidcProviderConfig,
+) -> Result<ProviderMetadata, OidcError> {
+    let issuer = provider.issuer.trim_end_matches('/');
+    let metadata: ProviderMetadata = http
+        .get(format!("{}/.well-known/openid-configuration", issuer))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if metadata.issuer.trim_end_matches('/') != issuer {
+        return Err(OidcError::Protocol(format!(
+            "OIDC issuer mismatch: expected {}, got {}",
+            issuer, metadata.issuer
+        )));
+    }
+    Ok(metadata)
+}
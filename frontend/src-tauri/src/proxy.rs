@@ -0,0 +1,574 @@
+//! HTTP 代理：把前端请求转发到服务端
+//! - 全局共享一个 `reqwest::Client`（连接池、连接与请求超时），另有一个无总超时的客户端用于事件流；支持 HTTPS 与自定义 CA（`ca_cert`）
+//! - 只允许访问 `api_prefixes` 中的路径
+//! - 请求体支持 JSON 与 multipart，响应支持 JSON、文本与二进制（base64）；代理不写本地文件，保存由前端经对话框完成
+//! - 登录 token 返回 401 时自动调用 `/api/v1/auth/refresh` 刷新并重试一次，新 token 通过 `token_refreshed` 事件通知前端
+//! - 失败时返回结构化的 `ProxyError`，服务端错误取自响应体 `{ code, message }`
+
+use crate::config::Config;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use percent_encoding::percent_decode_str;
+use reqwest::multipart::{Form, Part};
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// 建立连接的超时，超过即视为离线
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// 空闲连接保留时间
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// 长连接两次读取之间的最长间隔，服务端每 15 秒发送保活
+const STREAM_READ_TIMEOUT: Duration = Duration::from_secs(45);
+/// 个人访问令牌前缀，与服务端一致；这类令牌不刷新
+const API_TOKEN_PREFIX: &str = "pat_";
+
+/// 代理支持的请求方法
+pub const METHODS: [&str; 5] = ["GET", "POST", "PUT", "DELETE", "PATCH"];
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(rename = "__token")]
+    pub token: Option<String>,
+    pub params: Option<HashMap<String, String>>,
+    /// JSON 请求体
+    pub data: Option<Value>,
+    /// multipart 请求体，与 `data` 二选一
+    #[serde(default)]
+    pub multipart: Option<Vec<MultipartField>>,
+    #[serde(default)]
+    pub response_type: ResponseType,
+}
+
+/// multipart 字段：`value` 为文本，`base64` 为文件内容；
+/// 文件由前端通过对话框选择并读取，代理不读取任意本地路径
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultipartField {
+    pub name: String,
+    pub value: Option<String>,
+    pub base64: Option<String>,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseType {
+    #[default]
+    Json,
+    Text,
+    Binary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyErrorKind {
+    /// 请求本身无效（方法、路径、请求体）
+    InvalidRequest,
+    /// 路径不在 `api_prefixes` 中
+    Forbidden,
+    /// 无法连接服务端
+    Network,
+    /// 离线且没有可用的缓存
+    Offline,
+    /// 服务端返回了错误状态码
+    Http,
+    /// 响应无法读取或解析
+    Response,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyError {
+    pub kind: ProxyErrorKind,
+    /// HTTP 状态码
+    pub status: Option<u16>,
+    /// 服务端错误码，如 `ARTICLE_NOT_FOUND`
+    pub code: Option<String>,
+    pub message: String,
+    /// 服务端返回的 request id，便于对照服务端日志
+    pub request_id: Option<String>,
+}
+
+impl ProxyError {
+    pub fn new(kind: ProxyErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            status: None,
+            code: None,
+            message: message.into(),
+            request_id: None,
+        }
+    }
+
+    /// 由服务端的错误响应生成，响应体不是 `{ code, message }` 时使用原文
+    pub async fn from_response(resp: Response) -> Self {
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            code: String,
+            message: String,
+            request_id: Option<String>,
+        }
+
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        let (code, message, request_id) = match serde_json::from_str::<ErrorBody>(&text) {
+            Ok(body) => (Some(body.code), body.message, body.request_id),
+            Err(_) if text.is_empty() => (None, status.to_string(), None),
+            Err(_) => (None, text, None),
+        };
+        Self {
+            kind: ProxyErrorKind::Http,
+            status: Some(status.as_u16()),
+            code,
+            message,
+            request_id,
+        }
+    }
+}
+
+impl std::fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            Some(status) => write!(f, "HTTP {} - {}", status, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for ProxyError {}
+
+#[derive(Clone, Serialize)]
+struct TokenRefreshedPayload {
+    token: String,
+    user: Value,
+}
+
+/// 应用状态中的代理
+pub struct HttpProxy {
+    client: reqwest::Client,
+    /// 事件流等长连接使用的客户端
+    stream_client: reqwest::Client,
+    base_url: String,
+    prefixes: Vec<String>,
+    /// 最近一次刷新：旧 token → 新 token，离线队列中的旧请求据此换用新 token
+    refreshed: Mutex<Option<(String, String)>>,
+}
+
+impl HttpProxy {
+    pub fn new(config: &Config, app_data_dir: &Path) -> Result<Self, String> {
+        let ca_cert = match &config.ca_cert {
+            Some(ca_cert) => {
+                let path = app_data_dir.join(ca_cert);
+                let pem = std::fs::read(&path)
+                    .map_err(|e| format!("读取 CA 证书 {} 失败: {}", path.display(), e))?;
+                Some(
+                    reqwest::Certificate::from_pem(&pem)
+                        .map_err(|e| format!("CA 证书 {} 无效: {}", path.display(), e))?,
+                )
+            }
+            None => None,
+        };
+        let builder = || {
+            let builder = reqwest::Client::builder()
+                .connect_timeout(CONNECT_TIMEOUT)
+                .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+                .user_agent(concat!("blog-desktop/", env!("CARGO_PKG_VERSION")));
+            match &ca_cert {
+                Some(cert) => builder.add_root_certificate(cert.clone()),
+                None => builder,
+            }
+        };
+
+        let base_url = match &config.server_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://{}:{}", config.host, config.port),
+        };
+
+        Ok(Self {
+            client: builder()
+                .timeout(Duration::from_secs(config.http_timeout_secs))
+                .build()
+                .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?,
+            // 长连接没有总超时，只限制两次读取的间隔
+            stream_client: builder()
+                .read_timeout(STREAM_READ_TIMEOUT)
+                .build()
+                .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?,
+            base_url,
+            prefixes: config.api_prefixes.clone(),
+            refreshed: Mutex::new(None),
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn stream_client(&self) -> &reqwest::Client {
+        &self.stream_client
+    }
+
+    /// 校验方法与路径，返回规范化的路径（以 `/` 开头）
+    pub fn check(&self, request: &HttpRequest) -> Result<String, ProxyError> {
+        if !METHODS.contains(&request.method.to_uppercase().as_str()) {
+            return Err(ProxyError::new(
+                ProxyErrorKind::InvalidRequest,
+                format!("Unsupported HTTP method: {}", request.method),
+            ));
+        }
+        if request.data.is_some() && request.multipart.is_some() {
+            return Err(ProxyError::new(
+                ProxyErrorKind::InvalidRequest,
+                "data and multipart cannot be used together",
+            ));
+        }
+
+        let path = if request.url.starts_with('/') {
+            request.url.clone()
+        } else {
+            format!("/{}", request.url)
+        };
+        let forbidden = || {
+            ProxyError::new(
+                ProxyErrorKind::Forbidden,
+                format!("Path not allowed: {}", request.url),
+            )
+        };
+        // 先解码再检查，`%2e%2e` 等编码后的路径同样会被拒绝
+        let bare = path.split(['?', '#']).next().unwrap_or_default();
+        let bare = percent_decode_str(bare)
+            .decode_utf8()
+            .map_err(|_| forbidden())?;
+        let allowed = !bare.contains("..")
+            && !bare.contains("//")
+            && !bare.contains('\\')
+            && self.prefixes.iter().any(|p| bare.starts_with(p.as_str()));
+        if !allowed {
+            return Err(forbidden());
+        }
+        Ok(path)
+    }
+
+    /// 发送请求；登录 token 返回 401 时刷新并重试一次
+    pub async fn execute<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        request: &HttpRequest,
+    ) -> Result<Result<Response, reqwest::Error>, ProxyError> {
+        let path = self.check(request)?;
+        let token = self.current_token(request.token.as_deref());
+
+        let resp = self.send(&path, request, token.as_deref()).await?;
+        let Ok(first) = &resp else { return Ok(resp) };
+        if first.status() != StatusCode::UNAUTHORIZED {
+            return Ok(resp);
+        }
+        let Some(token) = token.filter(|t| !t.is_empty() && !t.starts_with(API_TOKEN_PREFIX))
+        else {
+            return Ok(resp);
+        };
+
+        match self.refresh(app, &token).await {
+            Some(new_token) => self.send(&path, request, Some(&new_token)).await,
+            None => Ok(resp),
+        }
+    }
+
+    fn current_token(&self, token: Option<&str>) -> Option<String> {
+        let token = token?;
+        match &*self.refreshed.lock().unwrap() {
+            Some((old, new)) if old == token => Some(new.clone()),
+            _ => Some(token.to_string()),
+        }
+    }
+
+    async fn send(
+        &self,
+        path: &str,
+        request: &HttpRequest,
+        token: Option<&str>,
+    ) -> Result<Result<Response, reqwest::Error>, ProxyError> {
+        let url = format!("{}{}", self.base_url, path);
+        let method = request.method.to_uppercase();
+        let mut req_builder = match method.as_str() {
+            "GET" => self.client.get(&url),
+            "POST" => self.client.post(&url),
+            "PUT" => self.client.put(&url),
+            "DELETE" => self.client.delete(&url),
+            _ => self.client.patch(&url),
+        };
+
+        if let Some(token) = token {
+            if !token.is_empty() {
+                req_builder = req_builder.bearer_auth(token);
+            }
+        }
+        if let Some(params) = &request.params {
+            req_builder = req_builder.query(params);
+        }
+        if let Some(data) = &request.data {
+            req_builder = req_builder.json(data);
+        }
+        if let Some(fields) = &request.multipart {
+            req_builder = req_builder.multipart(build_form(fields)?);
+        }
+
+        Ok(req_builder.send().await)
+    }
+
+    /// 用旧 token 换新 token，成功时通知前端
+    async fn refresh<R: Runtime>(&self, app: &AppHandle<R>, token: &str) -> Option<String> {
+        let resp = self
+            .client
+            .post(format!("{}/api/v1/auth/refresh", self.base_url))
+            .bearer_auth(token)
+            .send()
+            .await
+            .ok()?;
+        if !resp.status().is_success() {
+            log::warn!("刷新 token 失败: HTTP {}", resp.status());
+            return None;
+        }
+        let body: Value = resp.json().await.ok()?;
+        let new_token = body.get("token")?.as_str()?.to_string();
+
+        *self.refreshed.lock().unwrap() = Some((token.to_string(), new_token.clone()));
+        log::info!("token 已刷新");
+        let payload = TokenRefreshedPayload {
+            token: new_token.clone(),
+            user: body.get("user").cloned().unwrap_or(Value::Null),
+        };
+        if let Err(e) = app.emit("token_refreshed", payload) {
+            log::warn!("发送 token 刷新事件失败: {}", e);
+        }
+        Some(new_token)
+    }
+}
+
+fn build_form(fields: &[MultipartField]) -> Result<Form, ProxyError> {
+    let invalid = |msg: String| ProxyError::new(ProxyErrorKind::InvalidRequest, msg);
+
+    let mut form = Form::new();
+    for field in fields {
+        let bytes = match (&field.value, &field.base64) {
+            (Some(value), None) => {
+                form = form.text(field.name.clone(), value.clone());
+                continue;
+            }
+            (None, Some(data)) => STANDARD
+                .decode(data)
+                .map_err(|e| invalid(format!("Invalid base64 in {}: {}", field.name, e)))?,
+            _ => {
+                return Err(invalid(format!(
+                    "multipart field {} needs exactly one of value / base64",
+                    field.name
+                )))
+            }
+        };
+
+        let mut part = Part::bytes(bytes);
+        if let Some(filename) = &field.filename {
+            part = part.file_name(filename.clone());
+        }
+        if let Some(content_type) = &field.content_type {
+            part = part
+                .mime_str(content_type)
+                .map_err(|e| invalid(format!("Invalid content type {}: {}", content_type, e)))?;
+        }
+        form = form.part(field.name.clone(), part);
+    }
+    Ok(form)
+}
+
+/// 是否为网络不可达（而不是服务端返回了错误）
+pub fn is_network_error(e: &reqwest::Error) -> bool {
+    e.is_connect() || e.is_timeout()
+}
+
+/// 按 `response_type` 读取成功的响应
+pub async fn read_response(resp: Response, request: &HttpRequest) -> Result<Value, ProxyError> {
+    let failed = |e: String| ProxyError::new(ProxyErrorKind::Response, e);
+
+    match request.response_type {
+        ResponseType::Json => {
+            let bytes = resp
+                .bytes()
+                .await
+                .map_err(|e| failed(format!("Failed to read response: {}", e)))?;
+            // 204 等空响应返回 null
+            if bytes.is_empty() {
+                return Ok(Value::Null);
+            }
+            serde_json::from_slice(&bytes)
+                .map_err(|e| failed(format!("Failed to parse response: {}", e)))
+        }
+        ResponseType::Text => resp
+            .text()
+            .await
+            .map(Value::String)
+            .map_err(|e| failed(format!("Failed to read response: {}", e))),
+        ResponseType::Binary => {
+            let content_type = resp
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from);
+            let bytes = resp
+                .bytes()
+                .await
+                .map_err(|e| failed(format!("Failed to read response: {}", e)))?;
+            Ok(json!({
+                "content_type": content_type,
+                "size": bytes.len(),
+                "base64": STANDARD.encode(&bytes),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy() -> HttpProxy {
+        let config: Config = serde_json::from_value(json!({
+            "jwt_secret": "test",
+            "server_url": "http://127.0.0.1:3000/",
+            "api_prefixes": ["/api/", "/articles"],
+        }))
+        .unwrap();
+        HttpProxy::new(&config, &std::env::temp_dir()).unwrap()
+    }
+
+    fn request(method: &str, url: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.into(),
+            url: url.into(),
+            token: None,
+            params: None,
+            data: None,
+            multipart: None,
+            response_type: ResponseType::Json,
+        }
+    }
+
+    #[test]
+    fn check_allows_only_listed_paths() {
+        use ProxyErrorKind::{Forbidden, InvalidRequest};
+
+        let cases = [
+            (
+                "普通路径",
+                "GET",
+                "/api/v1/articles",
+                Ok("/api/v1/articles"),
+            ),
+            (
+                "补全开头的 /",
+                "GET",
+                "api/v1/articles",
+                Ok("/api/v1/articles"),
+            ),
+            ("方法不区分大小写", "post", "/articles", Ok("/articles")),
+            (
+                "查询参数不参与检查",
+                "GET",
+                "/api/v1/articles?next=//evil.example.com/..",
+                Ok("/api/v1/articles?next=//evil.example.com/.."),
+            ),
+            (
+                "编码后的普通字符",
+                "GET",
+                "/api/v1/tags/%E4%B8%AD",
+                Ok("/api/v1/tags/%E4%B8%AD"),
+            ),
+            ("不在允许列表", "GET", "/admin", Err(Forbidden)),
+            ("前缀不完整", "GET", "/api", Err(Forbidden)),
+            ("上级目录", "GET", "/api/../admin", Err(Forbidden)),
+            ("编码的 ..", "GET", "/api/%2e%2e/admin", Err(Forbidden)),
+            ("大写编码的 ..", "GET", "/api/%2E%2E/admin", Err(Forbidden)),
+            (
+                "编码的 / 与 ..",
+                "GET",
+                "/api%2f%2e%2e%2fadmin",
+                Err(Forbidden),
+            ),
+            ("编码的 /", "GET", "%2fapi/v1/articles", Err(Forbidden)),
+            ("编码的反斜杠", "GET", "/api/v1%5carticles", Err(Forbidden)),
+            ("重复的 /", "GET", "/api//v1/articles", Err(Forbidden)),
+            (
+                "协议相对地址",
+                "GET",
+                "//evil.example.com/api/v1",
+                Err(Forbidden),
+            ),
+            (
+                "完整 URL",
+                "GET",
+                "http://evil.example.com/api/v1",
+                Err(Forbidden),
+            ),
+            (
+                "编码的完整 URL",
+                "GET",
+                "http:%2f%2fevil.example.com/api/",
+                Err(Forbidden),
+            ),
+            (
+                "userinfo",
+                "GET",
+                "@evil.example.com/api/v1",
+                Err(Forbidden),
+            ),
+            ("非法 UTF-8", "GET", "/api/v1/%ff", Err(Forbidden)),
+            (
+                "不支持的方法",
+                "TRACE",
+                "/api/v1/articles",
+                Err(InvalidRequest),
+            ),
+            ("空方法", "", "/api/v1/articles", Err(InvalidRequest)),
+            ("空路径", "GET", "", Err(Forbidden)),
+        ];
+
+        let proxy = proxy();
+        for (desc, method, url, expected) in cases {
+            let result = proxy.check(&request(method, url));
+            assert_eq!(
+                result.as_deref().map_err(|e| e.kind),
+                expected,
+                "{}: {} {}",
+                desc,
+                method,
+                url
+            );
+
+            // 通过检查的路径拼接后仍指向配置的服务端
+            if let Ok(path) = result {
+                let full = url::Url::parse(&format!("{}{}", proxy.base_url(), path)).unwrap();
+                assert_eq!(full.host_str(), Some("127.0.0.1"), "{}", desc);
+                assert_eq!(full.port(), Some(3000), "{}", desc);
+            }
+        }
+    }
+
+    #[test]
+    fn check_rejects_data_with_multipart() {
+        let mut request = request("POST", "/api/v1/media");
+        request.data = Some(json!({}));
+        request.multipart = Some(Vec::new());
+        let err = proxy().check(&request).unwrap_err();
+        assert_eq!(err.kind, ProxyErrorKind::InvalidRequest);
+    }
+}
@@ -2,10 +2,14 @@
 
 use chrono::Local;
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
 use crate::models::article::{ArticleModel, PubArticles};
+use crate::validate::{
+    self, Format, Validate, Validator, ARTICLE_STATUSES, CONTENT_MAX, SUMMARY_MAX, TAGS_MAX,
+    TITLE_MAX,
+};
 
 #[derive(Deserialize, Debug)]
 pub struct GetArticlesParams {
@@ -23,11 +27,52 @@ pub struct NewArticle {
     pub tags: Option<String>,
 }
 
+impl Validate for NewArticle {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim_opt(&mut self.id);
+        validate::trim_opt(&mut self.status);
+        for value in [&mut self.title, &mut self.summary, &mut self.tags]
+            .into_iter()
+            .flatten()
+        {
+            validate::trim(value);
+        }
+
+        if let Some(id) = &self.id {
+            v.format("id", id, Format::Slug);
+        }
+        v.length(
+            "title",
+            self.title.as_deref().unwrap_or_default(),
+            1,
+            TITLE_MAX,
+        )
+        .length(
+            "content",
+            self.content.as_deref().unwrap_or_default().trim(),
+            1,
+            CONTENT_MAX,
+        )
+        .max_length("summary", self.summary.as_deref(), SUMMARY_MAX)
+        .max_length("tags", self.tags.as_deref(), TAGS_MAX);
+        if let Some(status) = &self.status {
+            v.one_of("status", status, ARTICLE_STATUSES);
+        }
+    }
+}
+
 #[derive(Deserialize, Clone, Serialize, Debug)]
 pub struct NewStatus {
     pub toggle: String,
 }
 
+impl Validate for NewStatus {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.toggle);
+        v.one_of("toggle", &self.toggle, ARTICLE_STATUSES);
+    }
+}
+
 /// 获取文章列表
 pub async fn get_articles(
     pool: &SqlitePool,
@@ -180,3 +225,59 @@ pub async fn patch_article_by_id(
     article.message = "done".to_string();
     Ok(article)
 }
+
+/// 导入的文章，日期沿用原博客中的日期
+pub struct ImportedArticle<'a> {
+    pub title: &'a str,
+    pub content: &'a str,
+    pub summary: &'a str,
+    pub tags: Option<&'a str>,
+    pub status: &'a str,
+    pub created_at: &'a str,
+    pub update_at: Option<&'a str>,
+}
+
+/// 写入导入的文章，返回新文章 id
+pub async fn insert_imported_article(
+    conn: &mut SqliteConnection,
+    new: &ImportedArticle<'_>,
+) -> Result<String, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO articles (id, title, content, summary, created_at, update_at, status, tags)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(new.title)
+    .bind(new.content)
+    .bind(new.summary)
+    .bind(new.created_at)
+    .bind(new.update_at)
+    .bind(new.status)
+    .bind(new.tags)
+    .execute(conn)
+    .await?;
+    Ok(id)
+}
+
+/// 按标题查找已有文章，用于导入时检测冲突
+pub async fn find_article_id_by_title(
+    conn: &mut SqliteConnection,
+    title: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(r#"SELECT id FROM articles WHERE title = ? LIMIT 1"#)
+        .bind(title)
+        .fetch_optional(conn)
+        .await
+}
+
+/// 按发布日期倒序列出全部文章（含正文），用于导出
+pub async fn list_articles_for_export(pool: &SqlitePool) -> Result<Vec<ArticleModel>, sqlx::Error> {
+    sqlx::query_as::<_, ArticleModel>(
+        r#"SELECT *, '' as message FROM articles ORDER BY created_at DESC, id DESC"#,
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,57 @@
+//! Audit Repository - 审计日志数据访问层
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::repositories::user::find_user_by_id;
+
+/// 将对象转为 JSON 快照，序列化失败时不记录快照
+pub fn snapshot<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_string(value).ok()
+}
+
+/// 写入一条审计记录，执行者用户名在写入时解析
+pub async fn record(
+    pool: &SqlitePool,
+    actor_id: Option<&str>,
+    action: &str,
+    target_type: &str,
+    target_id: Option<&str>,
+    before: Option<String>,
+    after: Option<String>,
+) -> Result<(), sqlx::Error> {
+    let actor_name = match actor_id {
+        Some(id) => find_user_by_id(pool, id.to_string())
+            .await?
+            .map(|u| u.username),
+        None => None,
+    };
+
+    let id = Uuid::now_v7().to_string();
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log (id, actor_id, actor_name, action, target_type, target_id, before, after)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&id)
+    .bind(actor_id)
+    .bind(&actor_name)
+    .bind(action)
+    .bind(target_type)
+    .bind(target_id)
+    .bind(before)
+    .bind(after)
+    .execute(pool)
+    .await?;
+
+    log::info!(
+        "audit: {} {} {:?} by {:?}",
+        action,
+        target_type,
+        target_id,
+        actor_id
+    );
+    Ok(())
+}
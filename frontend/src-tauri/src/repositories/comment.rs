@@ -2,10 +2,11 @@
 
 use chrono::Local;
 use serde::Deserialize;
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 use uuid::Uuid;
 
 use crate::models::comment::{Comment, CommentWithLike};
+use crate::validate::{self, Format, Validate, Validator, COMMENT_MAX};
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct CommentIncome {
@@ -14,11 +15,31 @@ pub struct CommentIncome {
     pub parent_id: Option<String>,
 }
 
+impl Validate for CommentIncome {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.article_id);
+        validate::trim(&mut self.content);
+        validate::trim_opt(&mut self.parent_id);
+        v.format("article_id", &self.article_id, Format::Slug)
+            .length("content", &self.content, 1, COMMENT_MAX);
+        if let Some(parent_id) = &self.parent_id {
+            v.format("parent_id", parent_id, Format::Slug);
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct LikeCommentPayload {
     pub comment_id: String,
 }
 
+impl Validate for LikeCommentPayload {
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.comment_id);
+        v.format("comment_id", &self.comment_id, Format::Slug);
+    }
+}
+
 /// 获取文章的评论列表
 pub async fn fetch_comments_by_article_id(
     pool: &SqlitePool,
@@ -78,6 +99,17 @@ pub async fn post_comment_by_article_id(
     .await
 }
 
+/// 查找单条评论
+pub async fn find_comment_by_id(
+    pool: &SqlitePool,
+    comment_id: &str,
+) -> Result<Option<Comment>, sqlx::Error> {
+    sqlx::query_as::<_, Comment>(r#"SELECT * FROM comments WHERE comment_id = ?"#)
+        .bind(comment_id)
+        .fetch_optional(pool)
+        .await
+}
+
 /// 删除评论
 pub async fn delete_comment_by_comment_id(
     pool: &SqlitePool,
@@ -137,3 +169,41 @@ pub async fn like_comment_db(
 
     Ok("liked".to_string())
 }
+
+/// 导入的评论，`user` 为原评论者的显示名
+pub struct ImportedComment<'a> {
+    pub comment_id: &'a str,
+    pub article_id: &'a str,
+    pub user: &'a str,
+    pub content: &'a str,
+    pub created_at: &'a str,
+    pub parent_id: Option<&'a str>,
+}
+
+/// 写入导入的评论
+pub async fn insert_imported_comment(
+    conn: &mut SqliteConnection,
+    new: &ImportedComment<'_>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO comments
+        (comment_id, article_id, user, content, created_at, parent_id)
+        VALUES (?,?,?,?,?,?)"#,
+    )
+    .bind(new.comment_id)
+    .bind(new.article_id)
+    .bind(new.user)
+    .bind(new.content)
+    .bind(new.created_at)
+    .bind(new.parent_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 列出全部评论，用于导出
+pub async fn list_comments_for_export(pool: &SqlitePool) -> Result<Vec<Comment>, sqlx::Error> {
+    sqlx::query_as::<_, Comment>(r#"SELECT * FROM comments ORDER BY created_at, comment_id"#)
+        .fetch_all(pool)
+        .await
+}
@@ -0,0 +1,59 @@
+//! Identity Repository - 外部身份（OIDC）数据访问层
+
+use sqlx::{SqliteExecutor, SqlitePool};
+use uuid::Uuid;
+
+use crate::models::identity::UserIdentity;
+
+/// 通过提供方与 subject 查找已关联的身份
+pub async fn find_identity(
+    pool: &SqlitePool,
+    provider: &str,
+    subject: &str,
+) -> Result<Option<UserIdentity>, sqlx::Error> {
+    sqlx::query_as::<_, UserIdentity>(
+        r#"SELECT * FROM user_identities WHERE provider = ? AND subject = ? LIMIT 1"#,
+    )
+    .bind(provider)
+    .bind(subject)
+    .fetch_optional(pool)
+    .await
+}
+
+/// 将外部身份关联到本地用户，可在事务中使用
+pub async fn insert_identity(
+    executor: impl SqliteExecutor<'_>,
+    user_id: &str,
+    provider: &str,
+    subject: &str,
+    email: Option<&str>,
+) -> Result<UserIdentity, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+
+    sqlx::query_as::<_, UserIdentity>(
+        r#"
+        INSERT INTO user_identities (id, user_id, provider, subject, email)
+        VALUES (?, ?, ?, ?, ?)
+        RETURNING *
+        "#,
+    )
+    .bind(&id)
+    .bind(user_id)
+    .bind(provider)
+    .bind(subject)
+    .bind(email)
+    .fetch_one(executor)
+    .await
+}
+
+/// 删除用户时一并清理其外部身份
+pub async fn delete_identities_by_user(
+    pool: &SqlitePool,
+    user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM user_identities WHERE user_id = ?"#)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
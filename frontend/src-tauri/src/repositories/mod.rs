@@ -2,6 +2,10 @@
 //! 数据访问层，封装所有数据库操作
 
 pub mod article;
+pub mod audit;
 pub mod comment;
+pub mod identity;
+pub mod offline;
 pub mod search;
+pub mod sync;
 pub mod user;
@@ -0,0 +1,168 @@
+//! Offline Repository - HTTP 响应缓存与离线写请求队列
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::offline::OutboxEntry;
+
+/// 最多缓存的 GET 响应数，超出时删除最旧的
+const CACHE_MAX_ENTRIES: i64 = 500;
+
+pub async fn cache_get(pool: &SqlitePool, key: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(r#"SELECT body FROM http_cache WHERE key = ?"#)
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn cache_clear(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM http_cache"#)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn cache_put(pool: &SqlitePool, key: &str, body: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT OR REPLACE INTO http_cache (key, body, fetched_at) VALUES (?, ?, datetime('now'))"#,
+    )
+    .bind(key)
+    .bind(body)
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"
+        DELETE FROM http_cache WHERE key NOT IN (
+            SELECT key FROM http_cache ORDER BY fetched_at DESC LIMIT ?
+        )
+        "#,
+    )
+    .bind(CACHE_MAX_ENTRIES)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 写请求入队，返回 id
+pub async fn outbox_push(
+    pool: &SqlitePool,
+    method: &str,
+    url: &str,
+    params: Option<String>,
+    data: Option<String>,
+    user_id: Option<&str>,
+) -> Result<String, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+    sqlx::query(
+        r#"INSERT INTO http_outbox (id, method, url, params, data, user_id) VALUES (?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(&id)
+    .bind(method)
+    .bind(url)
+    .bind(params)
+    .bind(data)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+/// 队首（最早入队且未失败）的请求
+pub async fn outbox_next(pool: &SqlitePool) -> Result<Option<OutboxEntry>, sqlx::Error> {
+    sqlx::query_as::<_, OutboxEntry>(
+        r#"SELECT * FROM http_outbox WHERE failed = 0 ORDER BY id LIMIT 1"#,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn outbox_list(pool: &SqlitePool) -> Result<Vec<OutboxEntry>, sqlx::Error> {
+    sqlx::query_as::<_, OutboxEntry>(r#"SELECT * FROM http_outbox ORDER BY id"#)
+        .fetch_all(pool)
+        .await
+}
+
+/// 待重放与已失败的请求数
+pub async fn outbox_counts(pool: &SqlitePool) -> Result<(i64, i64), sqlx::Error> {
+    sqlx::query_as(
+        r#"SELECT COALESCE(SUM(failed = 0), 0), COALESCE(SUM(failed = 1), 0) FROM http_outbox"#,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn outbox_delete(pool: &SqlitePool, id: &str) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(r#"DELETE FROM http_outbox WHERE id = ?"#)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+/// 记录失败并安排下次重试
+pub async fn outbox_retry_later(
+    pool: &SqlitePool,
+    id: &str,
+    next_attempt_at: i64,
+    error: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE http_outbox SET attempts = attempts + 1, next_attempt_at = ?, last_error = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(next_attempt_at)
+    .bind(error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 标记为失败，不再自动重放
+pub async fn outbox_fail(pool: &SqlitePool, id: &str, error: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE http_outbox SET attempts = attempts + 1, failed = 1, last_error = ? WHERE id = ?"#,
+    )
+    .bind(error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 把用户尚未重放的请求标记为失败
+pub async fn outbox_fail_user(
+    pool: &SqlitePool,
+    user_id: &str,
+    error: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE http_outbox SET failed = 1, last_error = ? WHERE user_id = ? AND failed = 0"#,
+    )
+    .bind(error)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 立即重试：`id` 为空时作用于全部请求（包括已失败的）
+pub async fn outbox_reset(pool: &SqlitePool, id: Option<&str>) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query(
+        r#"UPDATE http_outbox SET failed = 0, next_attempt_at = 0 WHERE ?1 IS NULL OR id = ?1"#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// 恢复联网后取消退避等待，失败的请求不受影响
+pub async fn outbox_wake(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"UPDATE http_outbox SET next_attempt_at = 0 WHERE failed = 0"#)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
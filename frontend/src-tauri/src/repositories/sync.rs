@@ -0,0 +1,365 @@
+//! Sync Repository - 同步数据访问层
+//! 本地修改由触发器记录（`dirty`、字段时钟、`sync_tombstones`）；写入远端数据前调用 `begin_apply`，
+//! 触发器在同一事务内跳过，写入的数据不会被当成本地修改再推送回去
+
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::models::sync::SyncConflict;
+use crate::sync::protocol::{
+    Clocks, PushArticle, PushTombstone, SyncArticle, SyncComment, Tombstone,
+};
+
+/// 当前毫秒时间戳，与触发器一致
+const NOW_MS: &str = "CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)";
+
+#[derive(sqlx::FromRow)]
+struct DirtyArticle {
+    #[sqlx(flatten)]
+    article: SyncArticle,
+    base_clocks: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TombstoneRow {
+    #[sqlx(flatten)]
+    tombstone: Tombstone,
+    base_clocks: Option<String>,
+}
+
+pub async fn get_state(pool: &SqlitePool, key: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(r#"SELECT value FROM sync_state WHERE key = ?"#)
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn set_state(
+    conn: &mut SqliteConnection,
+    key: &str,
+    value: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"INSERT OR REPLACE INTO sync_state (key, value) VALUES (?, ?)"#)
+        .bind(key)
+        .bind(value)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// 开始写入远端数据，须在事务内调用，提交前调用 `end_apply`
+pub async fn begin_apply(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    set_state(conn, "applying", "1").await
+}
+
+pub async fn end_apply(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM sync_state WHERE key = 'applying'"#)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// 换了服务端：清空游标与冲突，本地数据全部重新推送
+pub async fn reset_for_server(pool: &SqlitePool, server_url: &str) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(r#"DELETE FROM sync_state WHERE key IN ('cursor', 'last_synced_at')"#)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(r#"DELETE FROM sync_conflicts"#)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(r#"UPDATE articles SET base_clocks = NULL, dirty = 1"#)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(r#"UPDATE comments SET dirty = 1"#)
+        .execute(&mut *tx)
+        .await?;
+    set_state(&mut tx, "server_url", server_url).await?;
+    tx.commit().await
+}
+
+/// 按 id 顺序取 `after` 之后待推送的文章，跳过有未解决冲突的
+pub async fn dirty_articles(
+    pool: &SqlitePool,
+    after: &str,
+    limit: i64,
+) -> Result<Vec<PushArticle>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, DirtyArticle>(
+        r#"
+        SELECT id, title, content, summary, tags, status, created_at, update_at, clocks, base_clocks
+        FROM articles
+        WHERE dirty = 1 AND id > ? AND id NOT IN (SELECT article_id FROM sync_conflicts)
+        ORDER BY id LIMIT ?
+        "#,
+    )
+    .bind(after)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| PushArticle {
+            article: row.article,
+            base_clocks: row.base_clocks.and_then(|s| serde_json::from_str(&s).ok()),
+        })
+        .collect())
+}
+
+/// 按 id 顺序取 `after` 之后待推送的评论
+pub async fn dirty_comments(
+    pool: &SqlitePool,
+    after: &str,
+    limit: i64,
+) -> Result<Vec<SyncComment>, sqlx::Error> {
+    sqlx::query_as::<_, SyncComment>(
+        r#"
+        SELECT comment_id, article_id, user, content, created_at, parent_id
+        FROM comments WHERE dirty = 1 AND comment_id > ? ORDER BY comment_id LIMIT ?
+        "#,
+    )
+    .bind(after)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn list_tombstones(pool: &SqlitePool) -> Result<Vec<PushTombstone>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, TombstoneRow>(
+        r#"SELECT entity, id, base_clocks FROM sync_tombstones ORDER BY deleted_at"#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| PushTombstone {
+            tombstone: row.tombstone,
+            base_clocks: row.base_clocks.and_then(|s| serde_json::from_str(&s).ok()),
+        })
+        .collect())
+}
+
+pub async fn clear_tombstone(
+    conn: &mut SqliteConnection,
+    entity: &str,
+    id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM sync_tombstones WHERE entity = ? AND id = ?"#)
+        .bind(entity)
+        .bind(id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn find_clocks(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<Option<Clocks>, sqlx::Error> {
+    let clocks: Option<String> = sqlx::query_scalar(r#"SELECT clocks FROM articles WHERE id = ?"#)
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+    Ok(clocks.and_then(|s| serde_json::from_str(&s).ok()))
+}
+
+/// 本地文章有未推送的修改或已在本地删除，拉取时不覆盖
+pub async fn is_article_pending(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<bool, sqlx::Error> {
+    let found: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT 1 FROM articles WHERE id = ?1 AND dirty = 1
+        UNION ALL SELECT 1 FROM sync_tombstones WHERE entity = 'article' AND id = ?1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await?;
+    Ok(found.is_some())
+}
+
+pub async fn is_comment_deleted(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<bool, sqlx::Error> {
+    let found: Option<i64> =
+        sqlx::query_scalar(r#"SELECT 1 FROM sync_tombstones WHERE entity = 'comment' AND id = ?"#)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?;
+    Ok(found.is_some())
+}
+
+/// 写入服务端版本，并记为已同步
+pub async fn save_article(
+    conn: &mut SqliteConnection,
+    article: &SyncArticle,
+) -> Result<(), sqlx::Error> {
+    let clocks = serde_json::to_string(&article.clocks).unwrap_or_else(|_| "{}".into());
+    sqlx::query(
+        r#"
+        INSERT INTO articles (id, title, content, summary, tags, status, created_at, update_at, views, clocks, base_clocks, dirty)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9, ?9, 0)
+        ON CONFLICT (id) DO UPDATE SET
+            title = excluded.title, content = excluded.content, summary = excluded.summary,
+            tags = excluded.tags, status = excluded.status, update_at = excluded.update_at,
+            clocks = excluded.clocks, base_clocks = excluded.base_clocks, dirty = 0
+        "#,
+    )
+    .bind(&article.id)
+    .bind(&article.title)
+    .bind(&article.content)
+    .bind(&article.summary)
+    .bind(&article.tags)
+    .bind(&article.status)
+    .bind(&article.created_at)
+    .bind(&article.update_at)
+    .bind(clocks)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// 推送期间本地又有修改：只记录服务端时钟，保留本地修改等待下次推送
+pub async fn set_base_clocks(
+    conn: &mut SqliteConnection,
+    id: &str,
+    clocks: &Clocks,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"UPDATE articles SET base_clocks = ? WHERE id = ?"#)
+        .bind(serde_json::to_string(clocks).unwrap_or_else(|_| "{}".into()))
+        .bind(id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// 写入服务端的评论，本地已有时忽略
+pub async fn save_comment(
+    conn: &mut SqliteConnection,
+    comment: &SyncComment,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO comments (comment_id, article_id, user, content, created_at, parent_id, like_count, dirty)
+        VALUES (?, ?, ?, ?, ?, ?, 0, 0)
+        "#,
+    )
+    .bind(&comment.comment_id)
+    .bind(&comment.article_id)
+    .bind(&comment.user)
+    .bind(&comment.content)
+    .bind(&comment.created_at)
+    .bind(&comment.parent_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+pub async fn mark_comment_synced(conn: &mut SqliteConnection, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"UPDATE comments SET dirty = 0 WHERE comment_id = ?"#)
+        .bind(id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// 删除文章及其评论、冲突记录
+pub async fn remove_article(conn: &mut SqliteConnection, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM comments WHERE article_id = ?"#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(r#"DELETE FROM sync_conflicts WHERE article_id = ?"#)
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(r#"DELETE FROM articles WHERE id = ?"#)
+        .bind(id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn remove_comment(conn: &mut SqliteConnection, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM comments WHERE comment_id = ?"#)
+        .bind(id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// 记录冲突；同一文章再次冲突时更新为最新的双方正文
+pub async fn save_conflict(
+    conn: &mut SqliteConnection,
+    local: &SyncArticle,
+    remote: &SyncArticle,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO sync_conflicts (article_id, title, local_content, remote_content, remote_clocks)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(&local.id)
+    .bind(&local.title)
+    .bind(&local.content)
+    .bind(&remote.content)
+    .bind(serde_json::to_string(&remote.clocks).unwrap_or_else(|_| "{}".into()))
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+pub async fn list_conflicts(pool: &SqlitePool) -> Result<Vec<SyncConflict>, sqlx::Error> {
+    sqlx::query_as::<_, SyncConflict>(r#"SELECT * FROM sync_conflicts ORDER BY detected_at"#)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn count_conflicts(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(r#"SELECT COUNT(*) FROM sync_conflicts"#)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn find_conflict(
+    pool: &SqlitePool,
+    article_id: &str,
+) -> Result<Option<SyncConflict>, sqlx::Error> {
+    sqlx::query_as::<_, SyncConflict>(r#"SELECT * FROM sync_conflicts WHERE article_id = ?"#)
+        .bind(article_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// 以 `content` 解决冲突：正文时钟记为当前时间、基准时钟取冲突时的服务端时钟，下次同步时覆盖服务端正文
+pub async fn resolve_conflict(
+    pool: &SqlitePool,
+    conflict: &SyncConflict,
+    content: &str,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(r#"UPDATE articles SET content = ? WHERE id = ?"#)
+        .bind(content)
+        .bind(&conflict.article_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(&format!(
+        r#"
+        UPDATE articles SET clocks = json_set(clocks, '$.content', {NOW_MS}), base_clocks = ?, dirty = 1
+        WHERE id = ?
+        "#
+    ))
+    .bind(&conflict.remote_clocks)
+    .bind(&conflict.article_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(r#"DELETE FROM sync_conflicts WHERE article_id = ?"#)
+        .bind(&conflict.article_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
@@ -1,10 +1,11 @@
 //! User Repository - 用户数据访问层
 
 use serde::Deserialize;
-use sqlx::SqlitePool;
+use sqlx::{SqliteExecutor, SqlitePool};
 use uuid::Uuid;
 
 use crate::models::user::{NewUser, User, UserPublic};
+use crate::validate::{self, Format, Validate, Validator, IDENTITIES};
 
 #[derive(Deserialize, Debug)]
 pub struct AdminEditAccountPayload {
@@ -14,42 +15,98 @@ pub struct AdminEditAccountPayload {
     pub edited_identity: Option<String>,
 }
 
-/// 新增用户
-pub async fn insert_common_user(pool: &SqlitePool, new: &NewUser) -> Result<User, sqlx::Error> {
+impl Validate for AdminEditAccountPayload {
+    /// 空的用户名、密码与身份视为不修改
+    fn validate(&mut self, v: &mut Validator) {
+        validate::trim(&mut self.edited_id);
+        validate::trim_opt(&mut self.edited_username);
+        validate::trim_opt(&mut self.edited_identity);
+        if self.edited_password.as_deref() == Some("") {
+            self.edited_password = None;
+        }
+
+        v.format("edited_id", &self.edited_id, Format::Slug);
+        if let Some(username) = &self.edited_username {
+            v.username("edited_username", username);
+        }
+        if let Some(password) = &self.edited_password {
+            v.password("edited_password", password, 3);
+        }
+        if let Some(identity) = &self.edited_identity {
+            v.one_of("edited_identity", identity, IDENTITIES);
+        }
+    }
+}
+
+/// 新增用户，可在事务中使用
+pub async fn insert_common_user(
+    executor: impl SqliteExecutor<'_>,
+    new: &NewUser,
+) -> Result<User, sqlx::Error> {
     let id = Uuid::now_v7().to_string();
     
     sqlx::query_as::<_, User>(
         r#"
         INSERT INTO users (id, username, password, identity)
         VALUES (?, ?, ?, ?)
-        RETURNING id, username, password, identity
+        RETURNING id, username, password, identity, is_super_admin
         "#,
     )
     .bind(&id)
     .bind(&new.username)
     .bind(&new.password)
     .bind(&new.identity)
+    .fetch_one(executor)
+    .await
+}
+
+/// 新增超级管理员（仅首次设置时使用）
+pub async fn insert_super_admin(
+    pool: &SqlitePool,
+    username: &str,
+    password_hash: &str,
+) -> Result<User, sqlx::Error> {
+    let id = Uuid::now_v7().to_string();
+
+    sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (id, username, password, identity, is_super_admin)
+        VALUES (?, ?, ?, 'admin', 1)
+        RETURNING id, username, password, identity, is_super_admin
+        "#,
+    )
+    .bind(&id)
+    .bind(username)
+    .bind(password_hash)
     .fetch_one(pool)
     .await
 }
 
+/// 是否已经存在超级管理员，没有时需要首次设置
+pub async fn has_super_admin(pool: &SqlitePool) -> Result<bool, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(r#"SELECT COUNT(*) FROM users WHERE is_super_admin = 1"#)
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
+
 /// 通过用户名查找用户
 pub async fn find_user_by_username(
-    pool: &SqlitePool,
+    executor: impl SqliteExecutor<'_>,
     username: &str,
 ) -> Result<Option<User>, sqlx::Error> {
     sqlx::query_as::<_, User>(
-        r#"SELECT id, username, password, identity FROM users WHERE username = ? LIMIT 1"#,
+        r#"SELECT id, username, password, identity, is_super_admin FROM users WHERE username = ? LIMIT 1"#,
     )
     .bind(username)
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await
 }
 
 /// 通过id查找用户
 pub async fn find_user_by_id(pool: &SqlitePool, id: String) -> Result<Option<User>, sqlx::Error> {
     sqlx::query_as::<_, User>(
-        r#"SELECT id, username, password, identity FROM users WHERE id = ? LIMIT 1"#,
+        r#"SELECT id, username, password, identity, is_super_admin FROM users WHERE id = ? LIMIT 1"#,
     )
     .bind(id)
     .fetch_optional(pool)
@@ -58,23 +115,12 @@ pub async fn find_user_by_id(pool: &SqlitePool, id: String) -> Result<Option<Use
 
 /// 用户列表
 pub async fn list_users(pool: &SqlitePool, limit: i32) -> Result<Vec<UserPublic>, sqlx::Error> {
-    let rows = sqlx::query!(
-        r#"SELECT id, username, identity FROM users ORDER BY id LIMIT ?"#,
-        limit
+    sqlx::query_as::<_, UserPublic>(
+        r#"SELECT id, username, identity, is_super_admin FROM users ORDER BY id LIMIT ?"#,
     )
+    .bind(limit)
     .fetch_all(pool)
-    .await?;
-
-    let users = rows
-        .into_iter()
-        .map(|row| UserPublic {
-            id: row.id.unwrap_or_default(),
-            username: row.username.unwrap_or_default(),
-            identity: row.identity.unwrap_or_default(),
-        })
-        .collect::<Vec<UserPublic>>();
-
-    Ok(users)
+    .await
 }
 
 /// 通过id删除用户
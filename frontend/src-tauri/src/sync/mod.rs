@@ -0,0 +1,415 @@
+//! 与服务端同步（配置 `sync` 后启用）：
+//! - 每次同步先推送本地修改（文章、评论、删除），再从上次的游标开始拉取服务端变更
+//! - 合并在服务端按字段进行；双方都改了正文时记入 `sync_conflicts`，由用户选择保留哪一方
+//! - 同步状态保存在 `SyncHandle`，变化时发送 `sync_status` 事件并更新托盘提示
+//! - `spawn_scheduler`：启动时同步一次，之后每隔 `interval_secs` 秒同步
+
+pub mod protocol;
+
+use crate::config::{Config, SyncConfig};
+use crate::repositories::sync as store;
+use anyhow::{bail, Context};
+use chrono::Local;
+use protocol::{Changes, PushRequest, PushResponse, PushStatus};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 每次推送的文章 / 评论条数
+const PUSH_BATCH: i64 = 50;
+/// 每次拉取的条数
+const PULL_LIMIT: i64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    /// 未配置同步
+    Disabled,
+    Idle,
+    Syncing,
+    /// 有未解决的冲突
+    Conflict,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub state: SyncState,
+    pub message: Option<String>,
+    pub last_synced_at: Option<String>,
+    pub conflicts: i64,
+}
+
+/// 一次同步推送与拉取的条数
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub conflicts: usize,
+}
+
+/// 应用状态中的同步句柄：保证同一时间只有一次同步，并保存最新状态
+pub struct SyncHandle {
+    running: tokio::sync::Mutex<()>,
+    status: Mutex<SyncStatus>,
+}
+
+impl SyncHandle {
+    pub fn new(enabled: bool) -> Self {
+        let state = if enabled {
+            SyncState::Idle
+        } else {
+            SyncState::Disabled
+        };
+        Self {
+            running: tokio::sync::Mutex::new(()),
+            status: Mutex::new(SyncStatus {
+                state,
+                message: None,
+                last_synced_at: None,
+                conflicts: 0,
+            }),
+        }
+    }
+
+    pub fn status(&self) -> SyncStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// 更新状态，发送 `sync_status` 事件并把托盘提示改为当前状态
+fn set_status(app: &AppHandle, status: SyncStatus) {
+    if let Some(handle) = app.try_state::<SyncHandle>() {
+        *handle.status.lock().unwrap() = status.clone();
+    }
+
+    let tooltip = match status.state {
+        SyncState::Disabled => "未配置同步".to_string(),
+        SyncState::Idle => match &status.last_synced_at {
+            Some(at) => format!("已同步 {}", at),
+            None => "等待同步".to_string(),
+        },
+        SyncState::Syncing => "正在同步…".to_string(),
+        SyncState::Conflict => format!("{} 篇文章有冲突待处理", status.conflicts),
+        SyncState::Error => format!("同步失败: {}", status.message.as_deref().unwrap_or("")),
+    };
+    if let Some(tray) = app.tray_by_id("main") {
+        if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+            log::warn!("更新托盘提示失败: {}", e);
+        }
+    }
+    if let Err(e) = app.emit("sync_status", status) {
+        log::warn!("发送同步事件失败: {}", e);
+    }
+}
+
+/// 重新统计冲突并刷新状态，解决冲突后调用
+pub async fn refresh_status(app: &AppHandle) -> anyhow::Result<SyncStatus> {
+    let pool = app.state::<SqlitePool>();
+    let handle = app.state::<SyncHandle>();
+    let mut status = handle.status();
+    status.conflicts = store::count_conflicts(pool.inner()).await?;
+    status.last_synced_at = store::get_state(pool.inner(), "last_synced_at").await?;
+    if matches!(status.state, SyncState::Idle | SyncState::Conflict) {
+        status.state = if status.conflicts > 0 {
+            SyncState::Conflict
+        } else {
+            SyncState::Idle
+        };
+    }
+    set_status(app, status.clone());
+    Ok(status)
+}
+
+/// 立即同步一次；已有同步在进行时返回错误
+pub async fn sync_now(app: &AppHandle) -> anyhow::Result<SyncReport> {
+    let config = app.state::<Config>();
+    let Some(sync) = config.sync.clone() else {
+        bail!("未配置同步");
+    };
+    let pool = app.state::<SqlitePool>().inner().clone();
+    let handle = app.state::<SyncHandle>();
+    let Ok(_running) = handle.running.try_lock() else {
+        bail!("同步正在进行");
+    };
+
+    let mut status = handle.status();
+    status.state = SyncState::Syncing;
+    status.message = None;
+    set_status(app, status.clone());
+
+    let result = run(&pool, &sync).await;
+
+    status.conflicts = store::count_conflicts(&pool)
+        .await
+        .unwrap_or(status.conflicts);
+    status.last_synced_at = store::get_state(&pool, "last_synced_at")
+        .await
+        .unwrap_or(None);
+    match &result {
+        Ok(report) => {
+            status.state = if status.conflicts > 0 {
+                SyncState::Conflict
+            } else {
+                SyncState::Idle
+            };
+            status.message = Some(format!(
+                "推送 {} 条，拉取 {} 条",
+                report.pushed, report.pulled
+            ));
+        }
+        Err(e) => {
+            status.state = SyncState::Error;
+            status.message = Some(format!("{:#}", e));
+        }
+    }
+    set_status(app, status);
+    result
+}
+
+async fn run(pool: &SqlitePool, sync: &SyncConfig) -> anyhow::Result<SyncReport> {
+    let server_url = sync.server_url.trim_end_matches('/');
+    if store::get_state(pool, "server_url").await?.as_deref() != Some(server_url) {
+        log::info!("同步服务端变更为 {}，将重新推送全部数据", server_url);
+        store::reset_for_server(pool, server_url).await?;
+    }
+
+    let client = Client {
+        http: reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()?,
+        base: server_url.to_string(),
+        token: sync.token.clone(),
+    };
+
+    let mut report = SyncReport::default();
+    push(pool, &client, &mut report).await?;
+    pull(pool, &client, &mut report).await?;
+
+    let mut conn = pool.acquire().await?;
+    store::set_state(&mut conn, "last_synced_at", &Local::now().to_rfc3339()).await?;
+    log::info!(
+        "同步完成: 推送 {} 条，拉取 {} 条，冲突 {} 条",
+        report.pushed,
+        report.pulled,
+        report.conflicts
+    );
+    Ok(report)
+}
+
+struct Client {
+    http: reqwest::Client,
+    base: String,
+    token: String,
+}
+
+impl Client {
+    async fn changes(&self, since: i64) -> anyhow::Result<Changes> {
+        let resp = self
+            .http
+            .get(format!("{}/api/v1/sync/changes", self.base))
+            .bearer_auth(&self.token)
+            .query(&[("since", since), ("limit", PULL_LIMIT)])
+            .send()
+            .await
+            .context("连接服务端失败")?;
+        Ok(check(resp).await?.json().await?)
+    }
+
+    async fn push(&self, req: &PushRequest) -> anyhow::Result<PushResponse> {
+        let resp = self
+            .http
+            .post(format!("{}/api/v1/sync/push", self.base))
+            .bearer_auth(&self.token)
+            .json(req)
+            .send()
+            .await
+            .context("连接服务端失败")?;
+        Ok(check(resp).await?.json().await?)
+    }
+}
+
+async fn check(resp: reqwest::Response) -> anyhow::Result<reqwest::Response> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let body = resp.text().await.unwrap_or_default();
+    match status.as_u16() {
+        401 => bail!("同步令牌无效或已过期"),
+        403 => bail!("同步令牌没有 sync 权限或不属于管理员"),
+        _ => bail!("服务端返回 {}: {}", status, body),
+    }
+}
+
+/// 推送本地修改：文章、评论分批推送，删除记录最后一次推送
+async fn push(pool: &SqlitePool, client: &Client, report: &mut SyncReport) -> anyhow::Result<()> {
+    let mut after = String::new();
+    loop {
+        let articles = store::dirty_articles(pool, &after, PUSH_BATCH).await?;
+        let Some(last) = articles.last() else { break };
+        after = last.article.id.clone();
+
+        let req = PushRequest {
+            articles,
+            ..Default::default()
+        };
+        let resp = client.push(&req).await?;
+        apply_pushed_articles(pool, &req, resp, report).await?;
+    }
+
+    let mut after = String::new();
+    loop {
+        let comments = store::dirty_comments(pool, &after, PUSH_BATCH).await?;
+        let Some(last) = comments.last() else { break };
+        after = last.comment_id.clone();
+
+        let resp = client
+            .push(&PushRequest {
+                comments,
+                ..Default::default()
+            })
+            .await?;
+        let mut tx = pool.begin().await?;
+        store::begin_apply(&mut tx).await?;
+        for result in resp.comments {
+            match result.status {
+                PushStatus::Deleted => store::remove_comment(&mut tx, &result.id).await?,
+                _ => store::mark_comment_synced(&mut tx, &result.id).await?,
+            }
+            report.pushed += 1;
+        }
+        store::end_apply(&mut tx).await?;
+        tx.commit().await?;
+    }
+
+    let deleted = store::list_tombstones(pool).await?;
+    if !deleted.is_empty() {
+        let resp = client
+            .push(&PushRequest {
+                deleted: deleted.clone(),
+                ..Default::default()
+            })
+            .await?;
+        let mut tx = pool.begin().await?;
+        store::begin_apply(&mut tx).await?;
+        for (sent, result) in deleted.iter().zip(resp.deleted) {
+            let entity = &sent.tombstone.entity;
+            match (result.status, result.article) {
+                // 服务端在上次同步后又修改过，删除被拒绝：恢复服务端版本
+                (PushStatus::Conflict, Some(remote)) => {
+                    log::warn!("文章 {} 已在服务端修改，恢复而不删除", result.id);
+                    store::save_article(&mut tx, &remote).await?;
+                    report.pulled += 1;
+                }
+                _ => report.pushed += 1,
+            }
+            store::clear_tombstone(&mut tx, entity, &result.id).await?;
+        }
+        store::end_apply(&mut tx).await?;
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+/// 处理文章推送结果
+async fn apply_pushed_articles(
+    pool: &SqlitePool,
+    req: &PushRequest,
+    resp: PushResponse,
+    report: &mut SyncReport,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    store::begin_apply(&mut tx).await?;
+    for (sent, result) in req.articles.iter().zip(resp.articles) {
+        let local = &sent.article;
+        match (result.status, result.article) {
+            (PushStatus::Applied, Some(remote)) => {
+                // 推送期间本地又有修改时保留本地数据，只记录服务端时钟
+                if store::find_clocks(&mut tx, &local.id).await?.as_ref() == Some(&local.clocks) {
+                    store::save_article(&mut tx, &remote).await?;
+                } else {
+                    store::set_base_clocks(&mut tx, &local.id, &remote.clocks).await?;
+                }
+                report.pushed += 1;
+            }
+            (PushStatus::Conflict, Some(remote)) => {
+                log::warn!("文章 {} 正文冲突", local.id);
+                store::save_conflict(&mut tx, local, &remote).await?;
+                report.conflicts += 1;
+            }
+            (PushStatus::Deleted, _) => store::remove_article(&mut tx, &local.id).await?,
+            (status, _) => log::warn!("文章 {} 推送结果为 {:?}", local.id, status),
+        }
+    }
+    store::end_apply(&mut tx).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 从游标开始拉取服务端变更，直到没有更多
+async fn pull(pool: &SqlitePool, client: &Client, report: &mut SyncReport) -> anyhow::Result<()> {
+    let mut cursor: i64 = store::get_state(pool, "cursor")
+        .await?
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    loop {
+        let changes = client.changes(cursor).await?;
+
+        let mut tx = pool.begin().await?;
+        store::begin_apply(&mut tx).await?;
+        for article in &changes.articles {
+            // 本地有未推送的修改（如冲突）时跳过，由下次推送合并
+            if !store::is_article_pending(&mut tx, &article.id).await? {
+                store::save_article(&mut tx, article).await?;
+                report.pulled += 1;
+            }
+        }
+        for comment in &changes.comments {
+            if !store::is_comment_deleted(&mut tx, &comment.comment_id).await? {
+                store::save_comment(&mut tx, comment).await?;
+                report.pulled += 1;
+            }
+        }
+        for tombstone in &changes.deleted {
+            match tombstone.entity.as_str() {
+                "article" => store::remove_article(&mut tx, &tombstone.id).await?,
+                "comment" => store::remove_comment(&mut tx, &tombstone.id).await?,
+                _ => continue,
+            }
+            report.pulled += 1;
+        }
+        cursor = changes.cursor;
+        store::set_state(&mut tx, "cursor", &cursor.to_string()).await?;
+        store::end_apply(&mut tx).await?;
+        tx.commit().await?;
+
+        if !changes.more {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 启动时同步一次，之后每隔 `secs` 秒同步；0 为只手动同步
+pub fn spawn_scheduler(app: AppHandle, secs: u64) {
+    if secs == 0 {
+        return;
+    }
+    log::info!("自动同步已启用，每 {} 秒一次", secs);
+
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(secs));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            if let Err(e) = sync_now(&app).await {
+                log::warn!("自动同步失败: {:#}", e);
+            }
+        }
+    });
+}
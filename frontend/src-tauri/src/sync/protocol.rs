@@ -0,0 +1,97 @@
+//! 同步协议的数据结构，与服务端 `backend/src/sync` 保持一致
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+
+/// 字段名 → 最后修改的毫秒时间戳
+pub type Clocks = BTreeMap<String, i64>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct SyncArticle {
+    pub id: String,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub summary: Option<String>,
+    pub tags: Option<String>,
+    pub status: Option<String>,
+    pub created_at: Option<String>,
+    pub update_at: Option<String>,
+    #[sqlx(json)]
+    pub clocks: Clocks,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SyncComment {
+    pub comment_id: String,
+    pub article_id: Option<String>,
+    pub user: Option<String>,
+    pub content: Option<String>,
+    pub created_at: Option<String>,
+    pub parent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Tombstone {
+    /// `article` / `comment`
+    pub entity: String,
+    pub id: String,
+}
+
+/// `GET /api/v1/sync/changes` 的结果
+#[derive(Debug, Deserialize)]
+pub struct Changes {
+    pub cursor: i64,
+    pub more: bool,
+    pub articles: Vec<SyncArticle>,
+    pub comments: Vec<SyncComment>,
+    pub deleted: Vec<Tombstone>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PushArticle {
+    #[serde(flatten)]
+    pub article: SyncArticle,
+    /// 上次同步时服务端的字段时钟，从未同步过的文章为空
+    pub base_clocks: Option<Clocks>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PushTombstone {
+    #[serde(flatten)]
+    pub tombstone: Tombstone,
+    /// 删除前上次同步时服务端的字段时钟，服务端此后又有修改时拒绝删除
+    pub base_clocks: Option<Clocks>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PushRequest {
+    pub articles: Vec<PushArticle>,
+    pub comments: Vec<SyncComment>,
+    pub deleted: Vec<PushTombstone>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushStatus {
+    Applied,
+    Conflict,
+    Deleted,
+    Skipped,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushResult {
+    pub id: String,
+    pub status: PushStatus,
+    /// 文章合并后的服务端版本
+    #[serde(default)]
+    pub article: Option<SyncArticle>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushResponse {
+    pub articles: Vec<PushResult>,
+    pub comments: Vec<PushResult>,
+    pub deleted: Vec<PushResult>,
+}
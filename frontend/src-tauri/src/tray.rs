@@ -1,5 +1,9 @@
+use crate::backup;
+use crate::repositories::audit::record;
+use crate::sync;
 use log::info;
 use serde::Serialize;
+use sqlx::SqlitePool;
 use tauri::image::Image;
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{TrayIconBuilder, TrayIconEvent};
@@ -13,13 +17,24 @@ struct UpdateStatusPayload {
     message: Option<String>,
 }
 
+#[derive(Clone, Serialize)]
+struct BackupStatusPayload {
+    status: String,
+    message: String,
+}
+
 pub fn load_system_tray(app_handle: &App) -> tauri::Result<()> {
     let check_version =
         MenuItem::with_id(app_handle, "check_version", "检查更新", true, None::<&str>)?;
+    let backup_now = MenuItem::with_id(app_handle, "backup_now", "立即备份", true, None::<&str>)?;
+    let sync_now = MenuItem::with_id(app_handle, "sync_now", "立即同步", true, None::<&str>)?;
     let quit = MenuItem::with_id(app_handle, "quit", "退出", true, None::<&str>)?;
     let hide = MenuItem::with_id(app_handle, "hide", "隐藏", true, None::<&str>)?;
     let show = MenuItem::with_id(app_handle, "show", "显示", true, None::<&str>)?;
-    let menu = Menu::with_items(app_handle, &[&check_version, &hide, &show, &quit])?;
+    let menu = Menu::with_items(
+        app_handle,
+        &[&check_version, &backup_now, &sync_now, &hide, &show, &quit],
+    )?;
     TrayIconBuilder::with_id("main")
         // 把图标打进二进制，发布时不用带外部文件
         .icon(Image::from_bytes(include_bytes!("../icons/cjc.png"))?)
@@ -84,10 +99,61 @@ fn handle_menu_event(app: &AppHandle, event_id: &str) {
                 }
             });
         }
+        "backup_now" => {
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move { backup_from_tray(&app_handle).await });
+        }
+        "sync_now" => {
+            // 结果通过 `sync_status` 事件通知前端，并显示在托盘提示中
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = sync::sync_now(&app_handle).await {
+                    log::warn!("托盘同步失败: {:#}", e);
+                }
+            });
+        }
         _ => {}
     }
 }
 
+/// 托盘菜单“立即备份”，结果通过 `backup_status` 事件通知前端
+async fn backup_from_tray(app: &AppHandle) {
+    let payload = match backup::backup_now(app).await {
+        Ok(info) => {
+            if let Some(pool) = app.try_state::<SqlitePool>() {
+                let after = serde_json::to_string(&info).ok();
+                let audit = record(
+                    pool.inner(),
+                    None,
+                    "db.backup",
+                    "database",
+                    Some(&info.name),
+                    None,
+                    after,
+                );
+                if let Err(e) = audit.await {
+                    log::warn!("写入审计日志失败: {}", e);
+                }
+            }
+            info!("托盘备份完成: {}", info.name);
+            BackupStatusPayload {
+                status: "done".into(),
+                message: format!("已备份到 {}", info.name),
+            }
+        }
+        Err(e) => {
+            log::warn!("托盘备份失败: {:#}", e);
+            BackupStatusPayload {
+                status: "error".into(),
+                message: format!("备份失败: {:#}", e),
+            }
+        }
+    };
+    if let Err(e) = app.emit("backup_status", payload) {
+        log::warn!("发送备份事件失败: {}", e);
+    }
+}
+
 /// 双击托盘图标显示主窗口
 fn handle_tray_event(app: &AppHandle, event: TrayIconEvent) {
     if let TrayIconEvent::DoubleClick { .. } = event {
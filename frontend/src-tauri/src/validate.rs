@@ -0,0 +1,15 @@
+//! 命令参数校验：规则在 `blog_shared::validate` 中与服务端共用，这里只负责转换为 `CommandError`
+//! - 参数实现 `Validate`，先规整输入（去除首尾空白等），再把问题逐项交给 `Validator`
+//! - 命令中调用 `validated(payload)?`，校验失败返回 `VALIDATION_FAILED`，`details` 为全部字段错误
+
+use crate::error::{CommandError, CommandResult};
+
+pub use blog_shared::validate::{
+    trim, trim_opt, FieldError, Format, Validate, Validator, ARTICLE_STATUSES, COMMENT_MAX,
+    CONTENT_MAX, IDENTITIES, SUMMARY_MAX, TAGS_MAX, TITLE_MAX,
+};
+
+/// 规整并校验命令参数
+pub fn validated<T: Validate>(value: T) -> CommandResult<T> {
+    blog_shared::validate::validated(value).map_err(CommandError::Validation)
+}
@@ -16,7 +16,7 @@ export const registerAccount = async (args: {
         return { data };
     }
 
-    return client.post("/api/register", args);
+    return client.post("/api/v1/auth/register", args);
 };
 
 export const loginAccount = async (data: {
@@ -30,7 +30,110 @@ export const loginAccount = async (data: {
         return { data: result };
     }
 
-    return client.post("/api/login", data);
+    return client.post("/api/v1/auth/login", data);
+};
+
+// 查询是否需要首次设置（没有任何管理员时）
+export const fetchSetupStatus = async () => {
+    const app = useAppStore();
+
+    if (app.isTauri) {
+        const data = await invoke<{ needs_setup: boolean }>("get_setup_status");
+        return { data };
+    }
+
+    return client.get<{ needs_setup: boolean }>("/api/v1/setup");
+};
+
+// 首次设置：创建超级管理员
+// Web 端需要填写后端启动日志中打印的一次性 setup token
+export const setupAdmin = async (args: {
+    username: string;
+    password: string;
+    token?: string;
+}) => {
+    const app = useAppStore();
+
+    if (app.isTauri) {
+        const data = await invoke("setup_admin", {
+            payload: { username: args.username, password: args.password },
+        });
+        return { data };
+    }
+
+    const res = await client.post("/api/v1/setup", args);
+    // 与 Tauri 返回格式保持一致
+    return {
+        data: {
+            token: res.data.token,
+            user_id: res.data.user.id,
+            username: res.data.user.username,
+            identity: res.data.user.identity,
+        },
+    };
+};
+
+export interface OidcProvider {
+    name: string;
+    display_name: string;
+}
+
+// 已配置的 OIDC 登录提供方
+export const fetchOidcProviders = async () => {
+    const app = useAppStore();
+
+    if (app.isTauri) {
+        const providers = await invoke<OidcProvider[]>("get_oidc_providers");
+        return { data: { providers } };
+    }
+
+    return client.get<{ providers: OidcProvider[] }>("/api/v1/oidc/providers");
+};
+
+// Web 端：获取授权地址后跳转到提供方
+// Tauri 端：由后端打开系统浏览器并等待 loopback 回调，直接返回登录结果
+export const oidcLogin = async (provider: string) => {
+    const app = useAppStore();
+    const user = useUserStore();
+
+    if (app.isTauri) {
+        const data = await invoke("oidc_login", {
+            provider,
+            token: user.token || null,
+        });
+        return { data };
+    }
+
+    // 已登录时服务端把 state 与当前用户绑定，回调时据此关联外部身份
+    const res = await client.get<{ authorization_url: string; state: string }>(
+        `/api/v1/oidc/${encodeURIComponent(provider)}/authorize`
+    );
+    sessionStorage.setItem("oidc_provider", provider);
+    // 回调页比对 state，拒绝不是由本页面发起的回跳
+    sessionStorage.setItem("oidc_state", res.data.state);
+    window.location.href = res.data.authorization_url;
+    return { data: null };
+};
+
+// Web 端回调：提交授权码换取本站 token
+export const oidcCallback = async (args: {
+    provider: string;
+    code: string;
+    state: string;
+}) => {
+    const res = await client.post(
+        `/api/v1/oidc/${encodeURIComponent(args.provider)}/callback`,
+        { code: args.code, state: args.state }
+    );
+    // 与 Tauri 返回格式保持一致
+    return {
+        data: {
+            token: res.data.token,
+            user_id: res.data.user.id,
+            username: res.data.user.username,
+            identity: res.data.user.identity,
+        },
+    };
 };
 
 export const fetchUsers = async (limit: number) => {
@@ -46,7 +149,7 @@ export const fetchUsers = async (limit: number) => {
     }
 
     // 使用已配置的 `client`（包含 baseURL 与自动注入的 Authorization header）
-    return client.get("/api/users", { params: { limit } });
+    return client.get("/api/v1/users", { params: { limit } });
 };
 
 export const deleteUser = async (userId: string) => {
@@ -62,7 +165,7 @@ export const deleteUser = async (userId: string) => {
     }
 
     // client 会自动注入 Authorization header
-    return client.delete(`/api/users/${userId}`);
+    return client.delete(`/api/v1/users/${userId}`);
 };
 
 export const EditAccount = async (payload: EditUserData) => {
@@ -77,7 +180,11 @@ export const EditAccount = async (payload: EditUserData) => {
         return { data };
     }
 
-    return client.put("/api/editAccount", payload);
+    return client.patch(`/api/v1/users/${encodeURIComponent(payload.edited_id)}`, {
+        username: payload.edited_username,
+        password: payload.edited_password,
+        identity: payload.edited_identity,
+    });
 };
 
 // 上传头像到本地
@@ -19,7 +19,7 @@ export const fetchArticles = async (identity: string, condition?: string) => {
     }
     
     // GET请求中，第二个参数需要写在params里，params 是专门用来指定 URL 查询参数的字段，它的值必须是一个对象
-    return client.get("/articles", { params: { identity, condition } });
+    return client.get("/api/v1/articles", { params: { identity, condition } });
 };
 
 // 获取文章详情
@@ -33,7 +33,7 @@ export const fetchArticleById = async (id: Article["id"]) => {
         return { data };
     }
     
-    return client.get(`/article/${id}`);
+    return client.get(`/api/v1/articles/${id}`);
 };
 
 // 新建文章
@@ -49,7 +49,7 @@ export const publishArticle = async (arg: Article) => {
         return { data };
     }
     
-    return client.post("/api/article", arg);
+    return client.post("/api/v1/articles", arg);
 };
 
 // 修改文章
@@ -66,7 +66,7 @@ export const updateArticle = async (id: Article["id"], data: Article) => {
         return { data: result };
     }
     
-    return client.put(`/api/article/${id}`, data);
+    return client.put(`/api/v1/articles/${id}`, data);
 };
 
 // 删除文章
@@ -82,7 +82,7 @@ export const deleteArticle = async (id: Article["id"]) => {
         return { data };
     }
     
-    return client.delete(`/api/article/${id}`);
+    return client.delete(`/api/v1/articles/${id}`);
 };
 
 // 转换文章状态
@@ -99,7 +99,41 @@ export const toggleStatus = async (id: Article["id"], toggle: string) => {
         return { data };
     }
     
-    return client.patch(`/api/article/${id}`, { toggle });
+    return client.patch(`/api/v1/articles/${id}`, { toggle });
+};
+
+// 导入文章（仅桌面端）：kind 为 "markdown" 时选择目录，"wxr" 时选择 WordPress 导出文件
+// 传入上次返回的 path 可跳过选择对话框；用户取消选择时返回 null
+export const importArticles = async (
+    kind: "markdown" | "wxr",
+    dryRun: boolean,
+    path?: string,
+) => {
+    const user = useUserStore();
+    const data = await invoke("import_articles", {
+        token: user.token,
+        kind,
+        path,
+        dryRun,
+    });
+    return { data };
+};
+
+// 导出整站（仅桌面端）：kind 为 "archive" 时生成 zip 归档，"site" 时生成静态站点
+// 会弹出对话框选择导出目录；用户取消选择时返回 null
+export const exportBlog = async (
+    kind: "archive" | "site",
+    siteTitle?: string,
+    baseUrl?: string,
+) => {
+    const user = useUserStore();
+    const data = await invoke("export_blog", {
+        token: user.token,
+        kind,
+        siteTitle,
+        baseUrl,
+    });
+    return { data };
 };
 
 // 获取建议
@@ -111,7 +145,7 @@ export const fetchSuggestions = async (keyword: string) => {
         return { data };
     }
     
-    return client.get(`/suggestions/${keyword}`);
+    return client.get("/api/v1/search/suggestions", { params: { q: keyword } });
 };
 
 // 根据标签获取文章
@@ -126,5 +160,5 @@ export const fetchArticleByConditions = async (condition: string) => {
         return { data };
     }
     
-    return client.get(`/article/${condition}`);
+    return client.get(`/api/v1/articles/${condition}`);
 };
@@ -0,0 +1,52 @@
+import client from "./client";
+import { useAppStore } from "@/stores/app";
+import { useUserStore } from "@/stores/user";
+import { invoke } from "@tauri-apps/api/core";
+
+// 数据库备份，均需要管理员身份
+
+export interface BackupInfo {
+    name: string;
+    size: number;
+    created_at: string;
+}
+
+// 立即备份，超出保留份数的旧备份会被删除
+export const backupNow = async () => {
+    const app = useAppStore();
+
+    if (app.isTauri) {
+        const user = useUserStore();
+        const data = await invoke<BackupInfo>("backup_now", { token: user.token });
+        return { data };
+    }
+
+    return client.post<BackupInfo>("/api/v1/backups");
+};
+
+// 备份列表，最新的在前
+export const fetchBackups = async () => {
+    const app = useAppStore();
+
+    if (app.isTauri) {
+        const user = useUserStore();
+        const data = await invoke<BackupInfo[]>("list_backups", { token: user.token });
+        return { data };
+    }
+
+    return client.get<BackupInfo[]>("/api/v1/backups");
+};
+
+// 从备份恢复：校验通过后先备份当前数据库再暂存所选备份
+// 桌面端会自动重启完成恢复（不传 name 时弹出对话框选择文件）；服务端需要重启服务后生效
+export const restoreBackup = async (name?: string) => {
+    const app = useAppStore();
+
+    if (app.isTauri) {
+        const user = useUserStore();
+        const data = await invoke("restore_backup", { token: user.token, name });
+        return { data };
+    }
+
+    return client.post(`/api/v1/backups/${encodeURIComponent(name ?? "")}/restore`);
+};
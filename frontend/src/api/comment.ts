@@ -25,7 +25,7 @@ export const postComment = async (
         return { data };
     }
     
-    return client.post("/api/comment", { article_id, user_id, content, parent_id });
+    return client.post(`/api/v1/articles/${article_id}/comments`, { content, parent_id });
 };
 
 // 删除评论
@@ -41,7 +41,7 @@ export const DeleteComment = async (CommentId: string) => {
         return { data };
     }
     
-    return client.delete(`/comment/${CommentId}`);
+    return client.delete(`/api/v1/comments/${CommentId}`);
 };
 
 // 获取评论
@@ -57,7 +57,7 @@ export const fetchComments = async (articleId: string) => {
         return { data };
     }
     
-    return client.get(`/comments/${articleId}`);
+    return client.get(`/api/v1/articles/${articleId}/comments`);
 };
 
 // 获取评论点赞情况
@@ -74,8 +74,8 @@ export const fetchCommentsLike = async (articleId: string) => {
     });
 };
 
-// 更新评论点赞数
-export const updateCommentLike = async (commentId: string, userToken: string) => {
+// 更新评论点赞数，liked 为期望的点赞状态（桌面端按当前状态切换）
+export const updateCommentLike = async (commentId: string, userToken: string, liked: boolean) => {
     const app = useAppStore();
     const user = useUserStore();
     
@@ -89,8 +89,7 @@ export const updateCommentLike = async (commentId: string, userToken: string) =>
         return { data };
     }
     
-    return client.put("/api/comment/like", {
-        comment_id: commentId,
-    });
+    const url = `/api/v1/comments/${commentId}/likes`;
+    return liked ? client.put(url) : client.delete(url);
 };
 ``
\ No newline at end of file
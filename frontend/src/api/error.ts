@@ -0,0 +1,42 @@
+// 桌面端命令失败时返回的错误（与 Tauri 端 `CommandError` 对应）
+// code 用于区分错误类型，details 为底层错误信息（如数据库错误原文、代理错误）
+
+export type CommandErrorCode =
+    | "NOT_FOUND"
+    | "UNAUTHORIZED"
+    | "BAD_REQUEST"
+    | "FORBIDDEN"
+    | "VALIDATION_FAILED"
+    | "DATABASE_ERROR"
+    | "PASSWORD_HASH_ERROR"
+    | "INTERNAL_ERROR"
+    | "PROXY_ERROR";
+
+export interface CommandError {
+    code: CommandErrorCode;
+    message: string;
+    details: unknown;
+}
+
+// 参数校验失败（VALIDATION_FAILED）时 details 中的单个字段错误，与服务端格式一致
+export interface FieldError {
+    field: string;
+    code: "REQUIRED" | "LENGTH" | "RANGE" | "ONE_OF" | "FORMAT";
+    message: string;
+    [param: string]: unknown;
+}
+
+export const isCommandError = (err: unknown): err is CommandError =>
+    typeof err === "object" &&
+    err !== null &&
+    "code" in err &&
+    "message" in err &&
+    typeof (err as CommandError).message === "string";
+
+// 取出可展示的错误信息，兼容命令错误、Error 与字符串
+export const errorMessage = (err: unknown): string => {
+    if (isCommandError(err) || err instanceof Error) {
+        return err.message;
+    }
+    return String(err);
+};
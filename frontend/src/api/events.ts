@@ -0,0 +1,101 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { API_BASE_URL } from "@/config";
+import { useAppStore } from "@/stores/app";
+import { useUserStore } from "@/stores/user";
+
+// 服务端实时事件（需要登录），桌面端由后端转发为 `blog_event` 事件，Web 端直接读取 SSE
+// lagged / reconnected 表示可能漏掉了事件，收到后应重新拉取数据
+
+export type BlogEvent =
+    | { type: "comment_created"; article_id: string; comment: any }
+    | { type: "comment_deleted"; article_id: string; comment_id: string }
+    | { type: "comment_liked"; article_id: string; comment_id: string; like_count: number }
+    | { type: "article_status_changed"; article_id: string; status: string }
+    | { type: "lagged"; skipped: number }
+    | { type: "reconnected" };
+
+const BACKOFF_BASE_MS = 1000;
+const BACKOFF_MAX_MS = 60 * 1000;
+
+// 订阅 articleIds 的事件（为空时订阅全部文章），返回取消订阅的函数
+export const subscribeEvents = async (
+    articleIds: string[],
+    onEvent: (event: BlogEvent) => void
+) => {
+    const app = useAppStore();
+    const user = useUserStore();
+
+    if (app.isTauri) {
+        const unlisten = await listen<BlogEvent>("blog_event", (e) => onEvent(e.payload));
+        await invoke("subscribe_events", { token: user.token, articleIds });
+        return () => {
+            unlisten();
+            invoke("unsubscribe_events");
+        };
+    }
+
+    const url = new URL("api/v1/events", API_BASE_URL);
+    if (articleIds.length) url.searchParams.set("articles", articleIds.join(","));
+    const controller = new AbortController();
+    streamEvents(url, user.token, onEvent, controller.signal);
+    return () => controller.abort();
+};
+
+// Web 端：fetch 读取 SSE（EventSource 无法携带 Authorization），断线后指数退避重连，401 时停止
+export const streamEvents = async (
+    url: URL,
+    token: string,
+    onEvent: (event: any) => void,
+    signal: AbortSignal
+) => {
+    let backoff = BACKOFF_BASE_MS;
+    let connectedBefore = false;
+    while (!signal.aborted) {
+        try {
+            const res = await fetch(url, {
+                headers: { Authorization: `Bearer ${token}`, Accept: "text/event-stream" },
+                signal,
+            });
+            if (res.status === 401) return;
+            if (res.ok && res.body) {
+                if (connectedBefore) onEvent({ type: "reconnected" });
+                connectedBefore = true;
+                backoff = BACKOFF_BASE_MS;
+                await readFrames(res.body, onEvent);
+            }
+        } catch (e) {
+            if (signal.aborted) return;
+            console.warn("事件流断开:", e);
+        }
+        await new Promise((resolve) => setTimeout(resolve, backoff));
+        backoff = Math.min(backoff * 2, BACKOFF_MAX_MS);
+    }
+};
+
+const readFrames = async (body: ReadableStream<Uint8Array>, onEvent: (event: any) => void) => {
+    const reader = body.pipeThrough(new TextDecoderStream()).getReader();
+    let buffer = "";
+    for (;;) {
+        const { value, done } = await reader.read();
+        if (done) return;
+        buffer += value.replace(/\r/g, "");
+
+        let end: number;
+        while ((end = buffer.indexOf("\n\n")) >= 0) {
+            const frame = buffer.slice(0, end);
+            buffer = buffer.slice(end + 2);
+
+            let name = "";
+            const data: string[] = [];
+            for (const line of frame.split("\n")) {
+                if (line.startsWith("event:")) name = line.slice(6).trim();
+                else if (line.startsWith("data:")) data.push(line.slice(5).replace(/^ /, ""));
+            }
+            if (!data.length) continue; // 保活注释
+
+            if (name === "lagged") onEvent({ type: "lagged", skipped: Number(data[0]) });
+            else onEvent(JSON.parse(data.join("\n")));
+        }
+    }
+};
@@ -0,0 +1,44 @@
+import { invoke } from "@tauri-apps/api/core";
+
+// 桌面端 HTTP 代理的离线状态（仅桌面端），状态变化时同时发送 `network_status` 事件
+// 离线时 GET 返回缓存的响应，写请求排队并返回 { queued: true, id }，联网后按顺序自动重放
+
+export interface NetworkStatus {
+    online: boolean;
+    pending: number;
+    failed: number;
+}
+
+export interface OutboxEntry {
+    id: string;
+    method: string;
+    url: string;
+    params: string | null;
+    data: string | null;
+    attempts: number;
+    next_attempt_at: number;
+    last_error: string | null;
+    failed: boolean;
+    created_at: string;
+}
+
+export const fetchNetworkStatus = async () => {
+    const data = await invoke<NetworkStatus>("get_network_status");
+    return { data };
+};
+
+// 排队中的写请求（含已失败的），按入队顺序
+export const fetchOutbox = async () => {
+    const data = await invoke<OutboxEntry[]>("list_outbox");
+    return { data };
+};
+
+// 立即重试，不传 id 时重试全部
+export const retryOutbox = async (id?: string) => {
+    const data = await invoke<number>("retry_outbox", { id });
+    return { data };
+};
+
+export const discardOutbox = async (id: string) => {
+    await invoke("discard_outbox", { id });
+};
@@ -0,0 +1,93 @@
+import client from "./client";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { API_BASE_URL } from "@/config";
+import { useAppStore } from "@/stores/app";
+import { useUserStore } from "@/stores/user";
+import { streamEvents } from "./events";
+
+// 站内通知：评论被回复、被点赞或被管理员删除，只能访问自己的通知
+// 桌面端请求经 HTTP 代理转发，新通知由后端转发为 `notification` 事件并弹出系统通知
+
+export interface Notification {
+    id: string;
+    user_id: string;
+    kind: "reply" | "like" | "moderation";
+    actor_name: string | null;
+    article_id: string | null;
+    comment_id: string | null;
+    message: string;
+    read_at: string | null;
+    created_at: string;
+}
+
+export interface NotificationsPage {
+    notifications: Notification[];
+    total: number;
+    unread: number;
+    page: number;
+    per_page: number;
+}
+
+export interface NotificationPreferences {
+    reply: boolean;
+    like: boolean;
+    moderation: boolean;
+}
+
+// 桌面端经代理请求服务端，web 端直接请求
+const request = async <T>(method: string, url: string, params?: any, data?: any) => {
+    const app = useAppStore();
+
+    if (app.isTauri) {
+        const user = useUserStore();
+        const res = await invoke<T>("http_request", {
+            request: { method, url, __token: user.token, params, data },
+        });
+        return { data: res };
+    }
+
+    return client.request<T>({ method, url, params, data });
+};
+
+export const fetchNotifications = (unread = false, page = 1) =>
+    request<NotificationsPage>("GET", "/api/v1/notifications", {
+        unread: String(unread),
+        page: String(page),
+    });
+
+export const fetchUnreadCount = () =>
+    request<{ unread: number }>("GET", "/api/v1/notifications/unread-count");
+
+export const markNotificationRead = (id: string) =>
+    request("PUT", `/api/v1/notifications/${id}/read`);
+
+export const markAllNotificationsRead = () =>
+    request<{ updated: number }>("POST", "/api/v1/notifications/read-all");
+
+export const fetchNotificationPreferences = () =>
+    request<NotificationPreferences>("GET", "/api/v1/notifications/preferences");
+
+// 省略的类型不修改
+export const updateNotificationPreferences = (prefs: Partial<NotificationPreferences>) =>
+    request<NotificationPreferences>("PUT", "/api/v1/notifications/preferences", undefined, prefs);
+
+// 接收新通知，返回停止接收的函数；payload 没有 kind 时（lagged / reconnected）应重新拉取未读数
+export const watchNotifications = async (onNotification: (payload: any) => void) => {
+    const app = useAppStore();
+    const user = useUserStore();
+
+    if (app.isTauri) {
+        const unlisten = await listen("notification", (e) => onNotification(e.payload));
+        await invoke("watch_notifications", { token: user.token });
+        return () => {
+            unlisten();
+            invoke("unwatch_notifications");
+        };
+    }
+
+    const controller = new AbortController();
+    const url = new URL("api/v1/notifications/stream", API_BASE_URL);
+    streamEvents(url, user.token, onNotification, controller.signal);
+    return () => controller.abort();
+};
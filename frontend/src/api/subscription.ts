@@ -0,0 +1,34 @@
+import client from "./client";
+import { invoke } from "@tauri-apps/api/core";
+import { useAppStore } from "@/stores/app";
+import { useUserStore } from "@/stores/user";
+
+// 邮件订阅：提交后服务端发送确认邮件，点击邮件中的链接后生效
+// 回复提醒需要登录，桌面端请求经 HTTP 代理转发
+
+export interface SubscribeOptions {
+    // 接收文章摘要，默认开启
+    digest?: boolean;
+    // 评论被回复时接收邮件
+    replies?: boolean;
+}
+
+export const subscribe = async (email: string, options: SubscribeOptions = {}) => {
+    const app = useAppStore();
+    const data = { email, ...options };
+
+    if (app.isTauri) {
+        const user = useUserStore();
+        const res = await invoke("http_request", {
+            request: {
+                method: "POST",
+                url: "/api/v1/subscribers",
+                __token: user.token,
+                data,
+            },
+        });
+        return { data: res };
+    }
+
+    return client.post("/api/v1/subscribers", data);
+};
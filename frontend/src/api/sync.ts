@@ -0,0 +1,62 @@
+import { useUserStore } from "@/stores/user";
+import { invoke } from "@tauri-apps/api/core";
+
+// 桌面端与服务端同步（仅桌面端），状态变化时同时发送 `sync_status` 事件
+
+export type SyncState = "disabled" | "idle" | "syncing" | "conflict" | "error";
+
+export interface SyncStatus {
+    state: SyncState;
+    message: string | null;
+    last_synced_at: string | null;
+    conflicts: number;
+}
+
+export interface SyncReport {
+    pushed: number;
+    pulled: number;
+    conflicts: number;
+}
+
+export interface SyncConflict {
+    article_id: string;
+    title: string | null;
+    local_content: string | null;
+    remote_content: string | null;
+    detected_at: string;
+}
+
+// 立即同步，需要管理员身份
+export const syncNow = async () => {
+    const user = useUserStore();
+    const data = await invoke<SyncReport>("sync_now", { token: user.token });
+    return { data };
+};
+
+export const fetchSyncStatus = async () => {
+    const data = await invoke<SyncStatus>("get_sync_status");
+    return { data };
+};
+
+// 未解决的正文冲突
+export const fetchSyncConflicts = async () => {
+    const user = useUserStore();
+    const data = await invoke<SyncConflict[]>("list_sync_conflicts", { token: user.token });
+    return { data };
+};
+
+// 解决冲突：保留本地、采用服务端，或使用手动合并的正文（custom），下次同步时写回服务端
+export const resolveSyncConflict = async (
+    articleId: string,
+    resolution: "local" | "remote" | "custom",
+    content?: string,
+) => {
+    const user = useUserStore();
+    const data = await invoke<SyncStatus>("resolve_sync_conflict", {
+        token: user.token,
+        articleId,
+        resolution,
+        content,
+    });
+    return { data };
+};
@@ -1,2 +1,3 @@
 // Common/shared components
 export { default as MarkdownEditor } from './MarkdownEditor.vue';
+export { default as SubscribeForm } from './SubscribeForm.vue';
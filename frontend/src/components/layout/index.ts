@@ -1,4 +1,5 @@
 // Layout components
 export { default as NavBar } from './NavBar.vue';
 export { default as NavSearch } from './NavSearch.vue';
+export { default as NotificationBell } from './NotificationBell.vue';
 export { default as Sider } from './Sider.vue';
@@ -3,6 +3,7 @@ import { useMessage } from "naive-ui";
 import { useUserStore } from "@/stores/user";
 import { useAppStore } from "@/stores/app";
 import { fetchComments, updateCommentLike, DeleteComment } from "@/api/comment";
+import type { BlogEvent } from "@/api/events";
 
 export function useComments() {
     const comments = ref<any[]>([]);
@@ -102,12 +103,12 @@ export function useComments() {
         }
 
         try {
-            const res = await updateCommentLike(commentId, utoken);
+            const res = await updateCommentLike(commentId, utoken, previousLikedState === 0);
             let responseData: any;
             if (AppStore.isTauri) {
                 responseData = res.data;
             } else {
-                responseData = (res as any).data.like_or_unlike;
+                responseData = (res as any).data.liked ? "liked" : "unliked";
             }
 
             const isLiked = responseData === "liked";
@@ -136,6 +137,25 @@ export function useComments() {
         }
     };
 
+    // 实时事件：点赞数直接更新，其余变化重新拉取评论
+    const applyEvent = async (event: BlogEvent, articleId: string) => {
+        switch (event.type) {
+            case "comment_liked": {
+                const target = comments.value
+                    .flatMap((c) => [c, ...c.children])
+                    .find((c: any) => c.comment_id === event.comment_id);
+                if (target) target.like_count = event.like_count;
+                break;
+            }
+            case "comment_created":
+            case "comment_deleted":
+            case "lagged":
+            case "reconnected":
+                await loadComments(articleId);
+                break;
+        }
+    };
+
     return {
         comments,
         ifComment,
@@ -143,5 +163,6 @@ export function useComments() {
         postedComment,
         handlerDeleteComment,
         likeComment,
+        applyEvent,
     };
 }
@@ -1,4 +1,5 @@
 import { useUserStore } from "@/stores/user";
+import { fetchSetupStatus } from "@/api/account";
 import { createRouter, createWebHistory } from "vue-router";
 
 const router = createRouter({
@@ -24,6 +25,16 @@ const router = createRouter({
       name: "Login",
       component: () => import("@/views/Login.vue"),
     },
+    {
+      path: "/oidc/callback",
+      name: "OidcCallback",
+      component: () => import("@/views/OidcCallback.vue"),
+    },
+    {
+      path: "/setup",
+      name: "Setup",
+      component: () => import("@/views/Setup.vue"),
+    },
     {
       path: "/admin",
       name: "Admin",
@@ -49,7 +60,29 @@ const router = createRouter({
 // to：即将进入的目标路由对象
 // from：当前导航正要离开的路由对象
 // next()：必须调用，用来决定导航行为
-router.beforeEach((to, _, next) => {
+// 首次设置状态只需查询一次，完成设置后由 Setup 页面重置
+let setupChecked = false;
+export const resetSetupCheck = () => {
+  setupChecked = false;
+};
+
+router.beforeEach(async (to, _, next) => {
+  // 没有管理员时先进入首次设置页面
+  if (!setupChecked && to.name !== "Setup") {
+    try {
+      const res = await fetchSetupStatus();
+      setupChecked = true;
+      if (res.data.needs_setup) {
+        next("/setup");
+        return;
+      }
+    } catch (err) {
+      // 后端不可用或未实现时不阻塞导航
+      setupChecked = true;
+      console.warn("检查首次设置状态失败", err);
+    }
+  }
+
   // 如果不加()，则user本身是一个函数引用，并不会拿到useUserStore中的值
   const user = useUserStore();
 
@@ -1,4 +1,5 @@
 import type { User } from "@/types/user";
+import { invoke } from "@tauri-apps/api/core";
 import { defineStore } from "pinia";
 import { uuidv7 } from "uuidv7";
 
@@ -35,6 +36,10 @@ export const useUserStore = defineStore("user", {
         },
 
         logout() {
+            // 清空离线缓存，未重放的离线请求标记为失败
+            invoke("logout", { token: this.token || null }).catch((e) =>
+                console.warn("logout cleanup failed", e)
+            );
             this.token = "";
             this.username = "";
             this.identity = "";
@@ -0,0 +1,95 @@
+//! zip 归档：
+//! ```text
+//! manifest.json
+//! articles/<id>.md          带 front matter 的 Markdown
+//! comments/<id>.json        该文章的全部评论
+//! media/...                 上传的媒体文件（服务端为 storage.media_dir，桌面端为应用数据目录）
+//! ```
+
+use super::{to_markdown, Snapshot};
+use anyhow::Context;
+use chrono::Local;
+use serde::Serialize;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// 归档格式版本，格式变化时递增
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveSummary {
+    pub format_version: u32,
+    pub exported_at: String,
+    pub articles: usize,
+    pub comments: usize,
+    pub media: usize,
+}
+
+/// 写入 zip 归档；`media_dir` 不存在时跳过媒体文件
+pub fn write(snapshot: &Snapshot, out: &Path, media_dir: &Path) -> anyhow::Result<ArchiveSummary> {
+    if let Some(dir) = out.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("创建目录 {} 失败", dir.display()))?;
+    }
+    let file = File::create_new(out).with_context(|| format!("创建 {} 失败", out.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let now = Local::now();
+    let options = SimpleFileOptions::default()
+        .last_modified_time(zip::DateTime::try_from(now.naive_local()).unwrap_or_default());
+
+    let mut comments = 0;
+    for article in &snapshot.articles {
+        zip.start_file(format!("articles/{}.md", article.id), options)?;
+        zip.write_all(to_markdown(article).as_bytes())?;
+
+        if let Some(list) = snapshot.comments.get(&article.id) {
+            zip.start_file(format!("comments/{}.json", article.id), options)?;
+            zip.write_all(&serde_json::to_vec_pretty(list)?)?;
+            comments += list.len();
+        }
+    }
+
+    let mut media = Vec::new();
+    if media_dir.is_dir() {
+        collect(media_dir, &mut media)?;
+        media.sort();
+    }
+    for path in &media {
+        let name = path
+            .strip_prefix(media_dir)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        zip.start_file(format!("media/{name}"), options)?;
+        let mut src = File::open(path).with_context(|| format!("读取 {} 失败", path.display()))?;
+        std::io::copy(&mut src, &mut zip)?;
+    }
+
+    let summary = ArchiveSummary {
+        format_version: FORMAT_VERSION,
+        exported_at: now.to_rfc3339(),
+        articles: snapshot.articles.len(),
+        comments,
+        media: media.len(),
+    };
+    zip.start_file("manifest.json", options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&summary)?)?;
+    zip.finish()?;
+    Ok(summary)
+}
+
+fn collect(dir: &Path, out: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect(&path, out)?;
+        } else {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! 导出整站内容：
+//! - `archive`：zip 归档，包含带 front matter 的 Markdown、评论 JSON 与上传的媒体文件，可再用 `import` 导入
+//! - `site`：静态站点，渲染已发布的文章、标签页、RSS / Atom 订阅与 sitemap
+//!
+//! 服务端与桌面端各自从数据库读出内容并转换为这里的 `Article` / `Comment`
+
+pub mod archive;
+pub mod render;
+pub mod site;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 导出用的文章，字段与两端的 `ArticleModel` 一致
+#[derive(Debug, Clone, Default)]
+pub struct Article {
+    pub id: String,
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub summary: Option<String>,
+    pub created_at: Option<String>,
+    pub update_at: Option<String>,
+    pub status: Option<String>,
+    pub views: Option<i32>,
+    pub tags: Option<String>,
+}
+
+impl Article {
+    pub fn tags(&self) -> Vec<String> {
+        split_tags(self.tags.as_deref())
+    }
+
+    pub fn is_published(&self) -> bool {
+        is_published(self.status.as_deref())
+    }
+}
+
+/// 导出用的评论，序列化结果即归档中的 `comments/<id>.json`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Comment {
+    pub comment_id: String,
+    pub article_id: Option<String>,
+    pub user: Option<String>,
+    pub content: Option<String>,
+    pub created_at: Option<String>,
+    pub parent_id: Option<String>,
+    pub like_count: Option<i64>,
+}
+
+/// 导出时读取的全部内容
+pub struct Snapshot {
+    /// 按发布日期倒序
+    pub articles: Vec<Article>,
+    /// 文章 id → 评论
+    pub comments: HashMap<String, Vec<Comment>>,
+}
+
+impl Snapshot {
+    /// 评论按所属文章分组，没有文章 id 的评论被丢弃
+    pub fn new(articles: Vec<Article>, comments: Vec<Comment>) -> Self {
+        let mut grouped: HashMap<String, Vec<Comment>> = HashMap::new();
+        for c in comments {
+            if let Some(article_id) = c.article_id.clone() {
+                grouped.entry(article_id).or_default().push(c);
+            }
+        }
+        Self {
+            articles,
+            comments: grouped,
+        }
+    }
+}
+
+/// 标签在库中以逗号分隔存储
+pub fn split_tags(tags: Option<&str>) -> Vec<String> {
+    tags.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn is_published(status: Option<&str>) -> bool {
+    status == Some("published")
+}
+
+/// 解析库中 `%Y::%m::%d` 格式的日期
+pub fn parse_date(s: Option<&str>) -> Option<NaiveDateTime> {
+    NaiveDate::parse_from_str(s?, "%Y::%m::%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)
+}
+
+/// 带 YAML front matter 的 Markdown，字段与 `import` 读取的一致
+pub fn to_markdown(article: &Article) -> String {
+    let quote = |s: &str| serde_json::Value::from(s).to_string();
+    let mut out = String::from("---\n");
+    out.push_str(&format!("id: {}\n", article.id));
+    out.push_str(&format!(
+        "title: {}\n",
+        quote(article.title.as_deref().unwrap_or_default())
+    ));
+    if let Some(date) = parse_date(article.created_at.as_deref()) {
+        out.push_str(&format!("date: {}\n", date.format("%Y-%m-%d")));
+    }
+    if let Some(date) = parse_date(article.update_at.as_deref()) {
+        out.push_str(&format!("updated: {}\n", date.format("%Y-%m-%d")));
+    }
+    let tags: Vec<String> = article.tags().iter().map(|t| quote(t)).collect();
+    out.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+    out.push_str(&format!("draft: {}\n", !article.is_published()));
+    out.push_str(&format!(
+        "summary: {}\n",
+        quote(article.summary.as_deref().unwrap_or_default())
+    ));
+    out.push_str(&format!("views: {}\n", article.views.unwrap_or(0)));
+    out.push_str("---\n\n");
+    out.push_str(article.content.as_deref().unwrap_or_default());
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import;
+
+    #[test]
+    fn markdown_round_trips_through_import() {
+        let article = Article {
+            id: "a1".into(),
+            title: Some("标题: \"引号\"".into()),
+            content: Some("正文".into()),
+            summary: Some("摘要".into()),
+            created_at: Some("2020::01::02".into()),
+            status: Some("draft".into()),
+            tags: Some("rust, web dev,".into()),
+            ..Default::default()
+        };
+        let dir = std::env::temp_dir().join(format!("blog-export-{}", crate::random_string(8)));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a1.md");
+        std::fs::write(&path, to_markdown(&article)).unwrap();
+
+        let (posts, _) = import::load(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let post = &posts[0];
+        assert_eq!(post.title, "标题: \"引号\"");
+        assert_eq!(post.content, "正文");
+        assert_eq!(post.summary, "摘要");
+        assert_eq!(post.tags, ["rust", "web dev"]);
+        assert!(post.draft);
+        assert_eq!(
+            post.created_at.map(|d| d.date().to_string()).as_deref(),
+            Some("2020-01-02")
+        );
+    }
+
+    #[test]
+    fn snapshot_groups_comments_by_article() {
+        let comment = |id: &str, article: Option<&str>| Comment {
+            comment_id: id.into(),
+            article_id: article.map(str::to_string),
+            ..Default::default()
+        };
+        let snapshot = Snapshot::new(
+            Vec::new(),
+            vec![
+                comment("c1", Some("a")),
+                comment("c2", None),
+                comment("c3", Some("a")),
+            ],
+        );
+        assert_eq!(snapshot.comments.len(), 1);
+        assert_eq!(snapshot.comments["a"].len(), 2);
+    }
+}
@@ -0,0 +1,112 @@
+//! 静态站点与订阅源用的 Markdown 渲染：按 CommonMark（含表格、删除线、任务列表）渲染后再做白名单过滤。
+//! 正文中的 HTML（导入的 WordPress 文章即为 HTML）同样经过过滤，
+//! `<script>`、事件属性与 `javascript:` 等链接会被去掉
+
+use pulldown_cmark::{html, Options, Parser};
+
+/// 转义 HTML 特殊字符
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+pub fn markdown(src: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut out = String::with_capacity(src.len() * 3 / 2);
+    html::push_html(&mut out, Parser::new_ext(src, options));
+    sanitize(&out)
+}
+
+/// 只保留安全的标签与属性；代码块保留 `class` 以便按语言高亮
+pub fn sanitize(html: &str) -> String {
+    ammonia::Builder::default()
+        .add_tag_attributes("code", &["class"])
+        .clean(html)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_html_special_characters() {
+        assert_eq!(
+            escape("<a href=\"x\">'&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn renders_common_markdown() {
+        let html = markdown("# 标题\n\n段落 **粗体** *斜体* `code`\n\n- 一\n- 二\n\n> 引用\n");
+        assert!(html.contains("<h1>标题</h1>"));
+        assert!(html.contains("<strong>粗体</strong>"));
+        assert!(html.contains("<em>斜体</em>"));
+        assert!(html.contains("<code>code</code>"));
+        assert!(html.contains("<ul>\n<li>一</li>\n<li>二</li>\n</ul>"));
+        assert!(html.contains("<blockquote>"));
+    }
+
+    #[test]
+    fn keeps_code_language_and_escapes_code() {
+        let html = markdown("```rust\nlet a = \"<b>\";\n```\n");
+        assert!(html.contains("<code class=\"language-rust\">"));
+        assert!(html.contains("&lt;b&gt;"));
+    }
+
+    #[test]
+    fn renders_tables_and_links() {
+        let html =
+            markdown("| a | b |\n|---|---|\n| 1 | 2 |\n\n[链接](https://example.com) ![图](a.png)");
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<td>1</td>"));
+        assert!(html.contains("href=\"https://example.com\""));
+        assert!(html.contains("<img src=\"a.png\" alt=\"图\">"));
+    }
+
+    #[test]
+    fn strips_scripts_and_event_handlers() {
+        let html = markdown("<script>alert(1)</script>\n\n<img src=\"x\" onerror=\"alert(1)\">");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert(1)"));
+        assert!(!html.contains("onerror"));
+
+        let html = markdown("段落 <iframe src=\"https://evil\"></iframe> <b onclick=\"x()\">b</b>");
+        assert!(!html.contains("iframe"));
+        assert!(!html.contains("onclick"));
+        assert!(html.contains("<b>b</b>"));
+    }
+
+    #[test]
+    fn strips_script_urls() {
+        for src in [
+            "[x](javascript:alert(1))",
+            "<a href=\"JavaScript:alert(1)\">x</a>",
+            "<a href=\"vbscript:x\">x</a>",
+            "![x](data:text/html;base64,PHNjcmlwdD4=)",
+        ] {
+            let html = markdown(src);
+            assert!(!html.to_ascii_lowercase().contains("script:"), "{html}");
+            assert!(!html.contains("data:text"), "{html}");
+        }
+    }
+
+    #[test]
+    fn keeps_safe_html_from_imported_posts() {
+        let html = markdown("<p>WordPress <strong>正文</strong></p>\n<ul><li>项</li></ul>");
+        assert!(html.contains("<p>WordPress <strong>正文</strong></p>"));
+        assert!(html.contains("<li>项</li>"));
+    }
+}
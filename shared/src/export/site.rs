@@ -0,0 +1,334 @@
+//! 静态站点：只包含已发布的文章
+//! ```text
+//! index.html                文章列表
+//! posts/<id>.html           文章页（含评论）
+//! tags/index.html           标签列表
+//! tags/<标签>.html          标签下的文章
+//! feed.xml / atom.xml       RSS 2.0 / Atom 订阅
+//! sitemap.xml
+//! style.css
+//! ```
+//! 页面之间使用相对链接，可直接用浏览器打开；订阅与 sitemap 需要绝对地址，使用 `base_url`
+
+use super::render::{escape, markdown};
+use super::{parse_date, Article, Snapshot};
+use anyhow::Context;
+use chrono::{Local, NaiveDateTime};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const STYLE: &str = "\
+body{max-width:46rem;margin:2rem auto;padding:0 1rem;font:16px/1.7 system-ui,sans-serif;color:#222}
+header,footer{color:#666}header a{color:inherit;text-decoration:none;font-weight:600}
+a{color:#2563eb}pre{background:#f5f5f5;padding:1rem;overflow:auto}code{font-size:.9em}
+blockquote{margin:0;padding-left:1rem;border-left:3px solid #ddd;color:#555}
+img{max-width:100%}.meta{color:#888;font-size:.9em}.tag{margin-right:.5rem}
+ul.posts{list-style:none;padding:0}ul.posts li{margin:1.2rem 0}
+.comment{border-top:1px solid #eee;padding:.5rem 0}
+";
+
+pub struct SiteOptions {
+    pub title: String,
+    /// 站点对外地址，不带结尾的 `/`
+    pub base_url: String,
+    /// 订阅源中的文章条数
+    pub feed_items: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SiteSummary {
+    pub articles: usize,
+    pub tags: usize,
+    pub files: usize,
+}
+
+/// 渲染到 `out` 目录，已存在的同名文件会被覆盖
+pub fn write(snapshot: &Snapshot, out: &Path, opts: &SiteOptions) -> anyhow::Result<SiteSummary> {
+    let posts: Vec<&Article> = snapshot
+        .articles
+        .iter()
+        .filter(|a| a.is_published())
+        .collect();
+    let mut by_tag: BTreeMap<String, Vec<&Article>> = BTreeMap::new();
+    for post in &posts {
+        for tag in post.tags() {
+            by_tag.entry(tag).or_default().push(post);
+        }
+    }
+
+    let mut files = Files::new(out)?;
+    files.write("style.css", STYLE)?;
+    files.write(
+        "index.html",
+        &page(opts, &opts.title, "", &post_list(&posts, "")),
+    )?;
+
+    for post in &posts {
+        let body = post_page(post, snapshot, "../");
+        files.write(
+            &format!("posts/{}.html", post.id),
+            &page(opts, title(post), "../", &body),
+        )?;
+    }
+
+    let mut tag_index = String::from("<h1>标签</h1>\n<ul class=\"posts\">\n");
+    for (tag, list) in &by_tag {
+        tag_index.push_str(&format!(
+            "<li><a href=\"{}.html\">{}</a> <span class=\"meta\">{} 篇</span></li>\n",
+            encode(tag),
+            escape(tag),
+            list.len()
+        ));
+        let body = format!("<h1>{}</h1>\n{}", escape(tag), post_list(list, "../"));
+        files.write(
+            &format!("tags/{}.html", file_name(tag)),
+            &page(opts, tag, "../", &body),
+        )?;
+    }
+    tag_index.push_str("</ul>\n");
+    files.write("tags/index.html", &page(opts, "标签", "../", &tag_index))?;
+
+    let feed_posts = &posts[..posts.len().min(opts.feed_items)];
+    files.write("feed.xml", &rss(opts, feed_posts))?;
+    files.write("atom.xml", &atom(opts, feed_posts))?;
+    files.write("sitemap.xml", &sitemap(opts, &posts, by_tag.keys()))?;
+
+    Ok(SiteSummary {
+        articles: posts.len(),
+        tags: by_tag.len(),
+        files: files.count,
+    })
+}
+
+struct Files<'a> {
+    root: &'a Path,
+    count: usize,
+}
+
+impl<'a> Files<'a> {
+    fn new(root: &'a Path) -> anyhow::Result<Self> {
+        for dir in ["", "posts", "tags"] {
+            let dir = root.join(dir);
+            std::fs::create_dir_all(&dir)
+                .with_context(|| format!("创建目录 {} 失败", dir.display()))?;
+        }
+        Ok(Self { root, count: 0 })
+    }
+
+    fn write(&mut self, name: &str, content: &str) -> anyhow::Result<()> {
+        let path = self.root.join(name);
+        std::fs::write(&path, content).with_context(|| format!("写入 {} 失败", path.display()))?;
+        self.count += 1;
+        Ok(())
+    }
+}
+
+fn title(post: &Article) -> &str {
+    post.title.as_deref().unwrap_or("无标题")
+}
+
+fn date(post: &Article) -> NaiveDateTime {
+    parse_date(post.created_at.as_deref()).unwrap_or_default()
+}
+
+fn updated(post: &Article) -> NaiveDateTime {
+    parse_date(post.update_at.as_deref()).unwrap_or_else(|| date(post))
+}
+
+fn post_url(opts: &SiteOptions, post: &Article) -> String {
+    format!("{}/posts/{}.html", opts.base_url, post.id)
+}
+
+/// 页面骨架；`root` 为到站点根目录的相对路径
+fn page(opts: &SiteOptions, title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"zh-CN\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{title} - {site}</title>
+<link rel=\"stylesheet\" href=\"{root}style.css\">
+<link rel=\"alternate\" type=\"application/rss+xml\" title=\"{site}\" href=\"{root}feed.xml\">
+<link rel=\"alternate\" type=\"application/atom+xml\" title=\"{site}\" href=\"{root}atom.xml\">
+</head>
+<body>
+<header><a href=\"{root}index.html\">{site}</a> · <a href=\"{root}tags/index.html\">标签</a></header>
+<main>
+{body}</main>
+<footer><p><a href=\"{root}feed.xml\">RSS</a> · <a href=\"{root}atom.xml\">Atom</a></p></footer>
+</body>
+</html>
+",
+        title = escape(title),
+        site = escape(&opts.title),
+    )
+}
+
+fn post_list(posts: &[&Article], root: &str) -> String {
+    let mut html = String::from("<ul class=\"posts\">\n");
+    for post in posts {
+        html.push_str(&format!(
+            "<li><a href=\"{root}posts/{id}.html\">{title}</a> <span class=\"meta\">{date}</span><br>{summary}</li>\n",
+            id = post.id,
+            title = escape(title(post)),
+            date = date(post).format("%Y-%m-%d"),
+            summary = escape(post.summary.as_deref().unwrap_or_default()),
+        ));
+    }
+    html.push_str("</ul>\n");
+    html
+}
+
+fn post_page(post: &Article, snapshot: &Snapshot, root: &str) -> String {
+    let tag_links: String = post
+        .tags()
+        .iter()
+        .map(|t| {
+            format!(
+                "<a class=\"tag\" href=\"{root}tags/{}.html\">#{}</a>",
+                encode(t),
+                escape(t)
+            )
+        })
+        .collect();
+    let mut html = format!(
+        "<article>\n<h1>{}</h1>\n<p class=\"meta\">{} {}</p>\n{}</article>\n",
+        escape(title(post)),
+        date(post).format("%Y-%m-%d"),
+        tag_links,
+        markdown(post.content.as_deref().unwrap_or_default()),
+    );
+
+    if let Some(comments) = snapshot.comments.get(&post.id) {
+        html.push_str(&format!("<section>\n<h2>评论（{}）</h2>\n", comments.len()));
+        for c in comments {
+            html.push_str(&format!(
+                "<div class=\"comment\"><p class=\"meta\">{} · {}</p><p>{}</p></div>\n",
+                escape(c.user.as_deref().unwrap_or("匿名")),
+                parse_date(c.created_at.as_deref())
+                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default(),
+                escape(c.content.as_deref().unwrap_or_default()).replace('\n', "<br>"),
+            ));
+        }
+        html.push_str("</section>\n");
+    }
+    html
+}
+
+fn rss(opts: &SiteOptions, posts: &[&Article]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">
+<channel>
+<title>{title}</title>
+<link>{base}/</link>
+<description>{title}</description>
+<atom:link href=\"{base}/feed.xml\" rel=\"self\" type=\"application/rss+xml\"/>
+<lastBuildDate>{now}</lastBuildDate>
+",
+        title = escape(&opts.title),
+        base = escape(&opts.base_url),
+        now = Local::now().to_rfc2822(),
+    );
+    for post in posts {
+        let url = escape(&post_url(opts, post));
+        xml.push_str(&format!(
+            "<item><title>{}</title><link>{url}</link><guid>{url}</guid><pubDate>{}</pubDate><description>{}</description></item>\n",
+            escape(title(post)),
+            date(post).and_utc().to_rfc2822(),
+            escape(post.summary.as_deref().unwrap_or_default()),
+        ));
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+fn atom(opts: &SiteOptions, posts: &[&Article]) -> String {
+    let updated_at = posts
+        .iter()
+        .map(|p| updated(p))
+        .max()
+        .unwrap_or_else(|| Local::now().naive_utc());
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<feed xmlns=\"http://www.w3.org/2005/Atom\">
+<title>{title}</title>
+<id>{base}/</id>
+<link href=\"{base}/\"/>
+<link href=\"{base}/atom.xml\" rel=\"self\"/>
+<updated>{updated}</updated>
+",
+        title = escape(&opts.title),
+        base = escape(&opts.base_url),
+        updated = updated_at.and_utc().to_rfc3339(),
+    );
+    for post in posts {
+        let url = escape(&post_url(opts, post));
+        xml.push_str(&format!(
+            "<entry><title>{}</title><id>{url}</id><link href=\"{url}\"/><published>{}</published><updated>{}</updated><author><name>{}</name></author><summary>{}</summary><content type=\"html\">{}</content></entry>\n",
+            escape(title(post)),
+            date(post).and_utc().to_rfc3339(),
+            updated(post).and_utc().to_rfc3339(),
+            escape(&opts.title),
+            escape(post.summary.as_deref().unwrap_or_default()),
+            escape(&markdown(post.content.as_deref().unwrap_or_default())),
+        ));
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn sitemap<'a>(
+    opts: &SiteOptions,
+    posts: &[&Article],
+    tags: impl Iterator<Item = &'a String>,
+) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    let mut url = |loc: String, lastmod: Option<NaiveDateTime>| {
+        xml.push_str(&format!("<url><loc>{}</loc>", escape(&loc)));
+        if let Some(d) = lastmod {
+            xml.push_str(&format!("<lastmod>{}</lastmod>", d.format("%Y-%m-%d")));
+        }
+        xml.push_str("</url>\n");
+    };
+
+    url(format!("{}/index.html", opts.base_url), None);
+    for post in posts {
+        url(post_url(opts, post), Some(updated(post)));
+    }
+    url(format!("{}/tags/index.html", opts.base_url), None);
+    for tag in tags {
+        url(format!("{}/tags/{}.html", opts.base_url, encode(tag)), None);
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+/// 标签页文件名：去掉路径分隔符等不适合出现在文件名中的字符
+fn file_name(tag: &str) -> String {
+    tag.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '%' => '-',
+            c if c.is_whitespace() => '-',
+            c => c,
+        })
+        .collect()
+}
+
+/// 标签页链接：对文件名做百分号编码
+fn encode(tag: &str) -> String {
+    let mut out = String::new();
+    for b in file_name(tag).bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
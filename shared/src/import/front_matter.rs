@@ -0,0 +1,332 @@
+//! Markdown front matter：`---` 包裹的 YAML 与 `+++` 包裹的 TOML
+//!
+//! YAML 只支持博客 front matter 常见的写法：`key: value`、`[a, b]` 行内列表、
+//! `- item` 块列表与 `|` / `>` 多行文本，嵌套对象会被忽略
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Bool(bool),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Default)]
+pub struct FrontMatter(BTreeMap<String, Value>);
+
+impl FrontMatter {
+    /// 按顺序取第一个存在的字符串字段
+    pub fn str(&self, keys: &[&str]) -> Option<String> {
+        keys.iter().find_map(|k| match self.0.get(*k) {
+            Some(Value::Str(s)) if !s.is_empty() => Some(s.clone()),
+            _ => None,
+        })
+    }
+
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        match self.0.get(key)? {
+            Value::Bool(b) => Some(*b),
+            Value::Str(s) => s.parse().ok(),
+            Value::List(_) => None,
+        }
+    }
+
+    /// 合并多个列表字段，单个字符串按逗号或空格拆分
+    pub fn list(&self, keys: &[&str]) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for key in keys {
+            let items = match self.0.get(*key) {
+                Some(Value::List(items)) => items.clone(),
+                Some(Value::Str(s)) => s
+                    .split([',', ' '])
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                _ => continue,
+            };
+            for item in items {
+                if !out.contains(&item) {
+                    out.push(item);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// 拆分 front matter 与正文；没有 front matter 时返回空表和原文
+pub fn split(text: &str) -> Result<(FrontMatter, &str), String> {
+    for (fence, yaml) in [("---", true), ("+++", false)] {
+        let Some(rest) = text.strip_prefix(fence) else {
+            continue;
+        };
+        let Some(rest) = rest
+            .strip_prefix("\r\n")
+            .or_else(|| rest.strip_prefix('\n'))
+        else {
+            continue;
+        };
+
+        let (head, body) = match find_fence(rest, fence) {
+            Some(pos) => pos,
+            None => return Err(format!("front matter 缺少结束的 {fence}")),
+        };
+        let matter = if yaml {
+            parse_yaml(head)
+        } else {
+            parse_toml(head)?
+        };
+        return Ok((matter, body));
+    }
+    Ok((FrontMatter::default(), text))
+}
+
+/// 找到单独成行的结束标记，返回（front matter 内容，正文）
+fn find_fence<'a>(text: &'a str, fence: &str) -> Option<(&'a str, &'a str)> {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim_end() == fence {
+            return Some((&text[..offset], &text[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn parse_toml(text: &str) -> Result<FrontMatter, String> {
+    let table: toml::Table = text
+        .parse()
+        .map_err(|e: toml::de::Error| format!("TOML front matter 解析失败: {}", e.message()))?;
+
+    let mut map = BTreeMap::new();
+    for (key, value) in table {
+        let value = match value {
+            toml::Value::String(s) => Value::Str(s),
+            toml::Value::Boolean(b) => Value::Bool(b),
+            toml::Value::Datetime(d) => Value::Str(d.to_string()),
+            toml::Value::Array(items) => Value::List(
+                items
+                    .into_iter()
+                    .map(|v| match v {
+                        toml::Value::String(s) => s,
+                        other => other.to_string(),
+                    })
+                    .collect(),
+            ),
+            toml::Value::Table(_) => continue,
+            other => Value::Str(other.to_string()),
+        };
+        map.insert(key, value);
+    }
+    Ok(FrontMatter(map))
+}
+
+fn parse_yaml(text: &str) -> FrontMatter {
+    let mut map = BTreeMap::new();
+    let lines: Vec<&str> = text.lines().collect();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        // 缩进行属于嵌套对象或已处理的列表
+        if line.trim().is_empty() || line.starts_with([' ', '\t', '#', '-']) {
+            continue;
+        }
+        let Some((key, raw)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_string();
+        let raw = strip_comment(raw).trim();
+
+        let value = match raw {
+            "" => {
+                // 下面的 `- item` 行组成列表
+                let mut items = Vec::new();
+                while let Some(item) = lines.get(i).and_then(|l| l.trim_start().strip_prefix('-')) {
+                    items.push(unquote(item.trim()));
+                    i += 1;
+                }
+                if items.is_empty() {
+                    continue;
+                }
+                Value::List(items)
+            }
+            "|" | "|-" | ">" | ">-" => {
+                let mut block = Vec::new();
+                while let Some(l) = lines
+                    .get(i)
+                    .filter(|l| l.trim().is_empty() || l.starts_with([' ', '\t']))
+                {
+                    block.push(l.trim());
+                    i += 1;
+                }
+                let sep = if raw.starts_with('|') { "\n" } else { " " };
+                Value::Str(block.join(sep).trim().to_string())
+            }
+            "true" | "yes" => Value::Bool(true),
+            "false" | "no" => Value::Bool(false),
+            _ if raw.starts_with('[') && raw.ends_with(']') => Value::List(
+                raw[1..raw.len() - 1]
+                    .split(',')
+                    .map(|s| unquote(s.trim()))
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+            ),
+            _ => Value::Str(unquote(raw)),
+        };
+        map.insert(key, value);
+    }
+    FrontMatter(map)
+}
+
+/// 去掉行尾的 `# 注释`，引号内的 `#` 保留
+fn strip_comment(raw: &str) -> &str {
+    let raw = raw.trim_start();
+    let Some(quote) = raw.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+        return match raw.find(" #") {
+            Some(pos) => &raw[..pos],
+            None => raw,
+        };
+    };
+    // 双引号内允许 `\"` 转义，单引号内 `''` 表示一个单引号
+    let mut escaped = false;
+    let mut chars = raw.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            '\'' if quote == '\'' && chars.peek().is_some_and(|(_, n)| *n == '\'') => {
+                chars.next();
+            }
+            c if c == quote && !escaped => return &raw[..=i],
+            _ => escaped = false,
+        }
+    }
+    raw
+}
+
+/// 去掉引号；双引号字符串按 JSON 规则处理转义
+fn unquote(s: &str) -> String {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        return serde_json::from_str(s).unwrap_or_else(|_| s[1..s.len() - 1].to_string());
+    }
+    if s.len() >= 2 && s.starts_with('\'') && s.ends_with('\'') {
+        return s[1..s.len() - 1].replace("''", "'");
+    }
+    s.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(head: &str) -> FrontMatter {
+        let text = format!("---\n{head}---\nbody");
+        let (matter, body) = split(&text).unwrap();
+        assert_eq!(body, "body");
+        matter
+    }
+
+    #[test]
+    fn yaml_quoted_strings() {
+        let m = yaml(concat!(
+            "title: \"Hello: \\\"world\\\" # not a comment\"\n",
+            "subtitle: 'It''s here' # comment\n",
+            "plain: value # comment\n",
+            "hash: a#b\n",
+        ));
+        assert_eq!(
+            m.str(&["title"]).as_deref(),
+            Some("Hello: \"world\" # not a comment")
+        );
+        assert_eq!(m.str(&["subtitle"]).as_deref(), Some("It's here"));
+        assert_eq!(m.str(&["plain"]).as_deref(), Some("value"));
+        assert_eq!(m.str(&["hash"]).as_deref(), Some("a#b"));
+    }
+
+    #[test]
+    fn yaml_lists() {
+        let m = yaml(concat!(
+            "tags: [rust, \"web dev\", 'blog']\n",
+            "categories:\n",
+            "  - notes\n",
+            "  - \"rust\"\n",
+            "keywords: a, b c\n",
+            "empty: []\n",
+        ));
+        assert_eq!(m.list(&["tags"]), ["rust", "web dev", "blog"]);
+        // 多个字段合并去重
+        assert_eq!(
+            m.list(&["tags", "categories"]),
+            ["rust", "web dev", "blog", "notes"]
+        );
+        assert_eq!(m.list(&["keywords"]), ["a", "b", "c"]);
+        assert!(m.list(&["empty", "missing"]).is_empty());
+    }
+
+    #[test]
+    fn yaml_multiline_values() {
+        let m = yaml(concat!(
+            "literal: |\n",
+            "  line one\n",
+            "  line two\n",
+            "folded: >-\n",
+            "  folded\n",
+            "  text\n",
+            "draft: yes\n",
+        ));
+        assert_eq!(m.str(&["literal"]).as_deref(), Some("line one\nline two"));
+        assert_eq!(m.str(&["folded"]).as_deref(), Some("folded text"));
+        assert_eq!(m.bool("draft"), Some(true));
+    }
+
+    #[test]
+    fn yaml_ignores_nested_objects() {
+        let m = yaml("author:\n  name: someone\ntitle: t\n");
+        assert_eq!(m.str(&["name", "author"]), None);
+        assert_eq!(m.str(&["title"]).as_deref(), Some("t"));
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let text = "---\r\ntitle: \"CRLF\"\r\ntags:\r\n  - a\r\n  - b\r\n---\r\nbody\r\n";
+        let (m, body) = split(text).unwrap();
+        assert_eq!(m.str(&["title"]).as_deref(), Some("CRLF"));
+        assert_eq!(m.list(&["tags"]), ["a", "b"]);
+        assert_eq!(body, "body\r\n");
+    }
+
+    #[test]
+    fn toml_front_matter() {
+        let text = concat!(
+            "+++\n",
+            "title = \"TOML\"\n",
+            "draft = true\n",
+            "date = 2020-01-02T03:04:05Z\n",
+            "tags = [\"a\", \"b\"]\n",
+            "[params]\n",
+            "x = 1\n",
+            "+++\n",
+            "body",
+        );
+        let (m, body) = split(text).unwrap();
+        assert_eq!(m.str(&["title"]).as_deref(), Some("TOML"));
+        assert_eq!(m.bool("draft"), Some(true));
+        assert_eq!(m.str(&["date"]).as_deref(), Some("2020-01-02T03:04:05Z"));
+        assert_eq!(m.list(&["tags"]), ["a", "b"]);
+        assert_eq!(body, "body");
+        assert!(split("+++\ntitle = \n+++\n").is_err());
+    }
+
+    #[test]
+    fn missing_or_unterminated_front_matter() {
+        let (m, body) = split("# 标题\n正文").unwrap();
+        assert_eq!(m.str(&["title"]), None);
+        assert_eq!(body, "# 标题\n正文");
+        assert!(split("---\ntitle: t\n").is_err());
+        // 分隔线必须单独成行
+        let (_, body) = split("----\ntext").unwrap();
+        assert_eq!(body, "----\ntext");
+    }
+}
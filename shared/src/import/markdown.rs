@@ -0,0 +1,224 @@
+//! Markdown 目录导入：兼容 Hexo（`source/_posts`）、Hugo（`content/`，含 page bundle）与 Jekyll（`_posts`、`_drafts`）；
+//! 也可以导入本项目 `export archive` 解压后的目录，评论从同级的 `comments/<id>.json` 读取
+
+use super::front_matter;
+use super::{parse_date, summary, ImportSkipped, ImportedComment, ImportedPost};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Hexo 的摘要分隔符
+const MORE_MARKER: &str = "<!-- more -->";
+
+/// 递归读取目录下的 `.md` / `.markdown` 文件，解析失败的文件记入跳过列表
+pub fn read_dir(root: &Path) -> anyhow::Result<(Vec<ImportedPost>, Vec<ImportSkipped>)> {
+    let mut files = Vec::new();
+    collect(root, &mut files)?;
+    files.sort();
+
+    let mut posts = Vec::new();
+    let mut skipped = Vec::new();
+    for path in files {
+        let source = path.display().to_string();
+        // Hugo 的 _index.md 是栏目页，不是文章
+        if path.file_stem().is_some_and(|s| s == "_index") {
+            skipped.push(ImportSkipped::new(source, "栏目页"));
+            continue;
+        }
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| parse(&text, &path))
+        {
+            Ok(post) => posts.push(post),
+            Err(e) => skipped.push(ImportSkipped::new(source, e)),
+        }
+    }
+    Ok((posts, skipped))
+}
+
+fn collect(dir: &Path, out: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            collect(&path, out)?;
+        } else if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"))
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 解析单个 Markdown 文件
+pub fn parse(text: &str, path: &Path) -> Result<ImportedPost, String> {
+    let text = text.trim_start_matches('\u{feff}');
+    let (matter, body) = front_matter::split(text)?;
+    let (title, content) = match matter.str(&["title"]) {
+        Some(title) => (Some(title), body.trim().to_string()),
+        None => split_heading(body),
+    };
+    let (date_prefix, name) = file_name(path);
+    let title = title.or(name).ok_or("无法确定标题")?;
+    let summary = matter
+        .str(&["summary", "description", "excerpt"])
+        .or_else(|| {
+            content
+                .split_once(MORE_MARKER)
+                .map(|(head, _)| super::strip_tags(head.trim()))
+        })
+        .unwrap_or_else(|| summary(&content));
+    // Jekyll 的 _drafts 目录与 `published: false` 都表示草稿
+    let in_drafts = path.components().any(|c| c.as_os_str() == "_drafts");
+    let draft = matter.bool("draft").unwrap_or(false)
+        || matter.bool("published") == Some(false)
+        || in_drafts;
+
+    Ok(ImportedPost {
+        source: path.display().to_string(),
+        title,
+        summary,
+        tags: matter.list(&["tags", "categories"]),
+        draft,
+        created_at: matter
+            .str(&["date"])
+            .and_then(|d| parse_date(&d))
+            .or(date_prefix),
+        updated_at: matter
+            .str(&["updated", "lastmod", "last_modified_at"])
+            .and_then(|d| parse_date(&d)),
+        comments: matter
+            .str(&["id"])
+            .map(|id| archived_comments(path, &id))
+            .transpose()?
+            .unwrap_or_default(),
+        content,
+    })
+}
+
+/// 没有 front matter 标题时，以首行 `# 标题` 作为标题
+fn split_heading(body: &str) -> (Option<String>, String) {
+    let body = body.trim_start();
+    let mut lines = body.lines();
+    match lines.next().and_then(|l| l.strip_prefix("# ")) {
+        Some(title) => (
+            Some(title.trim().to_string()),
+            lines.collect::<Vec<_>>().join("\n").trim().to_string(),
+        ),
+        None => (None, body.trim().to_string()),
+    }
+}
+
+/// 从文件名取日期与名称：Jekyll 的 `2020-01-02-slug.md`，Hugo page bundle 的 `slug/index.md`
+fn file_name(path: &Path) -> (Option<chrono::NaiveDateTime>, Option<String>) {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+    let stem = match stem.as_deref() {
+        Some("index") => path
+            .parent()
+            .and_then(Path::file_name)
+            .map(|s| s.to_string_lossy().into_owned()),
+        _ => stem,
+    };
+    let Some(stem) = stem else {
+        return (None, None);
+    };
+
+    let date = stem.get(..10).and_then(parse_date);
+    match (date, stem.get(11..)) {
+        (Some(date), Some(rest)) if stem.as_bytes()[10] == b'-' => {
+            (Some(date), Some(rest.replace('-', " ")))
+        }
+        _ => (None, Some(stem)),
+    }
+}
+
+/// `export archive` 中的评论
+#[derive(Deserialize)]
+struct ArchivedComment {
+    comment_id: String,
+    parent_id: Option<String>,
+    user: Option<String>,
+    content: Option<String>,
+    created_at: Option<String>,
+}
+
+/// 读取归档中 `articles/<id>.md` 对应的 `comments/<id>.json`，不存在时没有评论
+fn archived_comments(path: &Path, id: &str) -> Result<Vec<ImportedComment>, String> {
+    let Some(file) = path
+        .parent()
+        .and_then(Path::parent)
+        .map(|root| root.join("comments").join(format!("{id}.json")))
+        .filter(|f| f.is_file())
+    else {
+        return Ok(Vec::new());
+    };
+
+    let text = std::fs::read_to_string(&file).map_err(|e| e.to_string())?;
+    let list: Vec<ArchivedComment> =
+        serde_json::from_str(&text).map_err(|e| format!("{} 解析失败: {e}", file.display()))?;
+    Ok(list
+        .into_iter()
+        .map(|c| ImportedComment {
+            id: c.comment_id,
+            parent: c.parent_id.filter(|p| !p.is_empty()),
+            author: c.user.unwrap_or_default(),
+            content: c.content.unwrap_or_default(),
+            created_at: c.created_at.as_deref().and_then(parse_date),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_front_matter_with_bom_and_crlf() {
+        let text = "\u{feff}---\r\ntitle: 标题\r\ndate: 2020-01-02 03:04:05\r\ntags: [a, b]\r\ndraft: true\r\n---\r\n\r\n第一段\r\n";
+        let post = parse(text, Path::new("posts/hello.md")).unwrap();
+        assert_eq!(post.title, "标题");
+        assert_eq!(post.tags, ["a", "b"]);
+        assert!(post.draft);
+        assert_eq!(
+            post.created_at.map(|d| d.to_string()).as_deref(),
+            Some("2020-01-02 03:04:05")
+        );
+        assert_eq!(post.content, "第一段");
+        assert_eq!(post.summary, "第一段");
+    }
+
+    #[test]
+    fn falls_back_to_heading_and_file_name() {
+        let post = parse("# 标题\n\n正文", Path::new("a.md")).unwrap();
+        assert_eq!(post.title, "标题");
+        assert_eq!(post.content, "正文");
+
+        let post = parse("正文", Path::new("_drafts/2020-01-02-hello-world.md")).unwrap();
+        assert_eq!(post.title, "hello world");
+        assert!(post.draft);
+        assert_eq!(
+            post.created_at.map(|d| d.date().to_string()).as_deref(),
+            Some("2020-01-02")
+        );
+
+        let post = parse("正文", Path::new("content/bundle/index.md")).unwrap();
+        assert_eq!(post.title, "bundle");
+    }
+
+    #[test]
+    fn uses_more_marker_as_summary() {
+        let text = "---\ntitle: t\n---\n<p>引言</p>\n<!-- more -->\n正文";
+        let post = parse(text, Path::new("t.md")).unwrap();
+        assert_eq!(post.summary, "引言");
+        let text = "---\ntitle: t\npublished: false\ndescription: 描述\n---\n正文";
+        let post = parse(text, Path::new("t.md")).unwrap();
+        assert_eq!(post.summary, "描述");
+        assert!(post.draft);
+    }
+}
@@ -0,0 +1,166 @@
+//! 解析其他博客的导出内容：
+//! - Markdown 目录（Hexo / Hugo / Jekyll），支持 YAML（`---`）与 TOML（`+++`）front matter
+//! - WordPress 导出的 WXR（XML），包括标签与已审核的评论
+//!
+//! 解析结果统一为 `ImportedPost`，写库、冲突检测与报告由服务端和桌面端各自完成
+
+mod front_matter;
+mod markdown;
+mod wxr;
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use std::path::Path;
+
+/// 摘要截取的字符数
+const SUMMARY_CHARS: usize = 120;
+
+#[derive(Debug, Default)]
+pub struct ImportedPost {
+    /// 来源：文件路径或 WXR 中的文章链接
+    pub source: String,
+    pub title: String,
+    pub content: String,
+    pub summary: String,
+    pub tags: Vec<String>,
+    pub draft: bool,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub comments: Vec<ImportedComment>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportedComment {
+    /// 原评论 id，仅用于还原回复关系
+    pub id: String,
+    pub parent: Option<String>,
+    pub author: String,
+    pub content: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportSkipped {
+    pub source: String,
+    pub reason: String,
+}
+
+impl ImportSkipped {
+    fn new(source: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// 按路径识别来源：目录按 Markdown 导入，`.xml` 文件按 WXR 导入，其余按单个 Markdown 文件导入
+pub fn load(path: &Path) -> anyhow::Result<(Vec<ImportedPost>, Vec<ImportSkipped>)> {
+    if path.is_dir() {
+        return markdown::read_dir(path);
+    }
+
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("读取 {} 失败", path.display()))?;
+    let is_xml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"));
+    if is_xml {
+        wxr::parse(&text).with_context(|| format!("解析 {} 失败", path.display()))
+    } else {
+        let post = markdown::parse(&text, path)
+            .map_err(anyhow::Error::msg)
+            .with_context(|| format!("解析 {} 失败", path.display()))?;
+        Ok((vec![post], Vec::new()))
+    }
+}
+
+/// 解析常见的日期写法，带时区的按原时区取本地时间
+fn parse_date(s: &str) -> Option<NaiveDateTime> {
+    let s = s.trim().trim_matches(['"', '\'']);
+    if let Ok(d) = DateTime::parse_from_rfc3339(s) {
+        return Some(d.naive_local());
+    }
+    for fmt in [
+        "%Y-%m-%d %H:%M:%S %z",
+        "%Y-%m-%d %H:%M %z",
+        "%a, %d %b %Y %H:%M:%S %z",
+    ] {
+        if let Ok(d) = DateTime::parse_from_str(s, fmt) {
+            return Some(d.naive_local());
+        }
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(d) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Some(d);
+        }
+    }
+    // `%Y::%m::%d` 为本项目导出的评论日期
+    ["%Y-%m-%d", "%Y/%m/%d", "%Y::%m::%d"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(s, fmt).ok())
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+}
+
+/// 取第一个非标题段落作为摘要，去掉其中的 HTML 标签
+fn summary(content: &str) -> String {
+    let paragraph = content
+        .split("\n\n")
+        .map(str::trim)
+        .find(|p| !p.is_empty() && !p.starts_with('#'))
+        .unwrap_or_default();
+    strip_tags(paragraph).chars().take(SUMMARY_CHARS).collect()
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(ch),
+            _ => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn parses_common_date_formats() {
+        assert_eq!(
+            parse_date("2020-01-02T03:04:05+08:00"),
+            Some(date("2020-01-02 03:04:05"))
+        );
+        assert_eq!(
+            parse_date("'2020-01-02 03:04:05'"),
+            Some(date("2020-01-02 03:04:05"))
+        );
+        assert_eq!(
+            parse_date("Thu, 02 Jan 2020 03:04:05 +0000"),
+            Some(date("2020-01-02 03:04:05"))
+        );
+        assert_eq!(parse_date("2020/01/02"), Some(date("2020-01-02 00:00:00")));
+        assert_eq!(
+            parse_date("2020::01::02"),
+            Some(date("2020-01-02 00:00:00"))
+        );
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn summary_skips_headings_and_strips_tags() {
+        let content = "# 标题\n\n第一段 <b>加粗</b>\n\n第二段";
+        assert_eq!(summary(content), "第一段 加粗");
+        assert_eq!(summary(&"字".repeat(200)).chars().count(), SUMMARY_CHARS);
+    }
+}
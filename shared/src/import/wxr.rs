@@ -0,0 +1,252 @@
+//! WordPress 导出文件（WXR）：只导入 `post` 类型的条目，页面、附件等记入跳过列表；
+//! 评论只保留已审核的
+
+use super::{parse_date, strip_tags, summary, ImportSkipped, ImportedComment, ImportedPost};
+use anyhow::anyhow;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+#[derive(Default)]
+struct Item {
+    title: String,
+    link: String,
+    content: String,
+    excerpt: String,
+    post_date: String,
+    modified: String,
+    status: String,
+    post_type: String,
+    /// (domain, 名称)
+    categories: Vec<(String, String)>,
+    comments: Vec<ImportedComment>,
+}
+
+#[derive(Default)]
+struct Comment {
+    id: String,
+    parent: String,
+    author: String,
+    date: String,
+    content: String,
+    approved: String,
+}
+
+pub fn parse(xml: &str) -> anyhow::Result<(Vec<ImportedPost>, Vec<ImportSkipped>)> {
+    let mut reader = Reader::from_str(xml);
+    let mut posts = Vec::new();
+    let mut skipped = Vec::new();
+
+    let mut item: Option<Item> = None;
+    let mut comment: Option<Comment> = None;
+    let mut domain = String::new();
+    let mut text = String::new();
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| anyhow!("XML 格式错误（位置 {}）: {e}", reader.error_position()))?;
+        match event {
+            Event::Start(e) => {
+                text.clear();
+                match e.name().as_ref() {
+                    b"item" => item = Some(Item::default()),
+                    b"wp:comment" if item.is_some() => comment = Some(Comment::default()),
+                    b"category" => domain = attribute(&e, "domain")?,
+                    _ => {}
+                }
+            }
+            Event::Text(t) => text.push_str(&t.unescape()?),
+            Event::CData(c) => text.push_str(&String::from_utf8_lossy(&c)),
+            Event::End(e) => {
+                let value = std::mem::take(&mut text).trim().to_string();
+                let name = e.name();
+                let name = name.as_ref();
+
+                if let Some(c) = comment.as_mut() {
+                    match name {
+                        b"wp:comment_id" => c.id = value,
+                        b"wp:comment_parent" => c.parent = value,
+                        b"wp:comment_author" => c.author = value,
+                        b"wp:comment_date" => c.date = value,
+                        b"wp:comment_content" => c.content = value,
+                        b"wp:comment_approved" => c.approved = value,
+                        b"wp:comment" => {
+                            let c = comment.take().unwrap_or_default();
+                            if let (Some(it), "1") = (item.as_mut(), c.approved.as_str()) {
+                                it.comments.push(ImportedComment {
+                                    parent: (c.parent != "0" && !c.parent.is_empty())
+                                        .then_some(c.parent),
+                                    id: c.id,
+                                    author: c.author,
+                                    content: c.content,
+                                    created_at: parse_date(&c.date),
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                let Some(it) = item.as_mut() else {
+                    continue;
+                };
+                match name {
+                    b"title" => it.title = value,
+                    b"link" => it.link = value,
+                    b"content:encoded" => it.content = value,
+                    b"excerpt:encoded" => it.excerpt = value,
+                    b"wp:post_date" => it.post_date = value,
+                    b"wp:post_modified" => it.modified = value,
+                    b"wp:status" => it.status = value,
+                    b"wp:post_type" => it.post_type = value,
+                    b"category" => it.categories.push((std::mem::take(&mut domain), value)),
+                    b"item" => {
+                        if let Some(it) = item.take() {
+                            match into_post(it) {
+                                Ok(post) => posts.push(post),
+                                Err(skip) => skipped.push(skip),
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok((posts, skipped))
+}
+
+fn attribute(e: &BytesStart, name: &str) -> anyhow::Result<String> {
+    Ok(match e.try_get_attribute(name)? {
+        Some(attr) => attr.unescape_value()?.into_owned(),
+        None => String::new(),
+    })
+}
+
+fn into_post(it: Item) -> Result<ImportedPost, ImportSkipped> {
+    let source = if it.link.is_empty() {
+        it.title.clone()
+    } else {
+        it.link
+    };
+    if it.post_type != "post" {
+        return Err(ImportSkipped::new(
+            source,
+            format!("类型为 {}", it.post_type),
+        ));
+    }
+    if it.status == "trash" || it.status == "auto-draft" {
+        return Err(ImportSkipped::new(source, format!("状态为 {}", it.status)));
+    }
+    if it.title.is_empty() {
+        return Err(ImportSkipped::new(source, "缺少标题"));
+    }
+
+    // 优先使用标签，没有标签时退回到分类（忽略默认的“未分类”）
+    let pick = |domain: &str| -> Vec<String> {
+        it.categories
+            .iter()
+            .filter(|(d, name)| d == domain && !name.eq_ignore_ascii_case("uncategorized"))
+            .map(|(_, name)| name.clone())
+            .collect()
+    };
+    let mut tags = pick("post_tag");
+    if tags.is_empty() {
+        tags = pick("category");
+    }
+
+    Ok(ImportedPost {
+        source,
+        summary: if it.excerpt.is_empty() {
+            summary(&it.content)
+        } else {
+            strip_tags(&it.excerpt)
+        },
+        title: it.title,
+        tags,
+        draft: it.status != "publish",
+        created_at: parse_date(&it.post_date),
+        updated_at: parse_date(&it.modified),
+        content: it.content,
+        comments: it.comments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WXR: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss xmlns:content="http://purl.org/rss/1.0/modules/content/"
+     xmlns:excerpt="http://wordpress.org/export/1.2/excerpt/"
+     xmlns:wp="http://wordpress.org/export/1.2/">
+<channel>
+  <item>
+    <title>Hello &amp; welcome</title>
+    <link>https://example.com/hello</link>
+    <content:encoded><![CDATA[<p>First</p>]]></content:encoded>
+    <excerpt:encoded><![CDATA[]]></excerpt:encoded>
+    <wp:post_date>2020-01-02 03:04:05</wp:post_date>
+    <wp:status>publish</wp:status>
+    <wp:post_type>post</wp:post_type>
+    <category domain="category" nicename="uncategorized"><![CDATA[Uncategorized]]></category>
+    <category domain="category" nicename="notes"><![CDATA[Notes]]></category>
+    <wp:comment>
+      <wp:comment_id>2</wp:comment_id>
+      <wp:comment_author>bob</wp:comment_author>
+      <wp:comment_date>2020-01-03 00:00:00</wp:comment_date>
+      <wp:comment_content>reply</wp:comment_content>
+      <wp:comment_approved>1</wp:comment_approved>
+      <wp:comment_parent>1</wp:comment_parent>
+    </wp:comment>
+    <wp:comment>
+      <wp:comment_id>3</wp:comment_id>
+      <wp:comment_content>spam</wp:comment_content>
+      <wp:comment_approved>spam</wp:comment_approved>
+      <wp:comment_parent>0</wp:comment_parent>
+    </wp:comment>
+  </item>
+  <item>
+    <title>About</title>
+    <wp:post_type>page</wp:post_type>
+  </item>
+  <item>
+    <title>Draft</title>
+    <wp:status>draft</wp:status>
+    <wp:post_type>post</wp:post_type>
+    <category domain="post_tag"><![CDATA[rust]]></category>
+    <category domain="category"><![CDATA[Notes]]></category>
+  </item>
+</channel>
+</rss>"#;
+
+    #[test]
+    fn parses_posts_comments_and_skips_pages() {
+        let (posts, skipped) = parse(WXR).unwrap();
+        assert_eq!(posts.len(), 2);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].source, "About");
+
+        let hello = &posts[0];
+        assert_eq!(hello.title, "Hello & welcome");
+        assert_eq!(hello.source, "https://example.com/hello");
+        assert_eq!(hello.summary, "First");
+        assert_eq!(hello.tags, ["Notes"]);
+        assert!(!hello.draft);
+        // 只保留已审核的评论，父评论 0 表示顶层
+        assert_eq!(hello.comments.len(), 1);
+        assert_eq!(hello.comments[0].parent.as_deref(), Some("1"));
+
+        // 有标签时不使用分类
+        assert_eq!(posts[1].tags, ["rust"]);
+        assert!(posts[1].draft);
+    }
+
+    #[test]
+    fn reports_malformed_xml() {
+        assert!(parse("<rss><item><title>x</item></rss>").is_err());
+    }
+}
@@ -0,0 +1,30 @@
+//! 服务端与桌面端共用的逻辑，两端各自只保留错误类型等薄适配层
+
+pub mod export;
+pub mod import;
+pub mod oidc;
+pub mod validate;
+
+use rand::{distributions::Alphanumeric, Rng};
+
+/// 生成由字母和数字组成的随机字符串，用于令牌、state、随机密码等
+pub fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_string_has_requested_length_and_charset() {
+        let s = random_string(48);
+        assert_eq!(s.len(), 48);
+        assert!(s.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(s, random_string(48));
+    }
+}
@@ -0,0 +1,249 @@
+//! OIDC 客户端的公共部分：
+//! - PKCE S256 挑战值
+//! - 按 id_token 头部从 JWKS 中选出校验密钥：只接受非对称签名算法，
+//!   拒绝对称密钥（kty=oct），并要求头部的 alg 与密钥的类型 / alg 一致
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm, PublicKeyUse,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, Header};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+/// 可用于校验 id_token 的签名算法（均为非对称算法）
+pub const SIGNING_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeyError {
+    #[error("id_token signing algorithm {0:?} is not allowed")]
+    Algorithm(Algorithm),
+    #[error("no matching oidc signing key")]
+    NotFound,
+    #[error("oidc signing key does not match algorithm {0:?}")]
+    Mismatch(Algorithm),
+    #[error("invalid oidc signing key: {0}")]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+}
+
+/// PKCE S256：BASE64URL(SHA256(code_verifier))
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// 允许的签名算法：提供方声明的 `id_token_signing_alg_values_supported`
+/// 与 [`SIGNING_ALGORITHMS`] 的交集；未声明时使用全部非对称算法
+pub fn allowed_algorithms(supported: &[String]) -> Vec<Algorithm> {
+    if supported.is_empty() {
+        return SIGNING_ALGORITHMS.to_vec();
+    }
+    supported
+        .iter()
+        .filter_map(|s| Algorithm::from_str(s).ok())
+        .filter(|alg| SIGNING_ALGORITHMS.contains(alg))
+        .collect()
+}
+
+/// 按 id_token 头部选出校验密钥，返回密钥与应使用的算法
+///
+/// 算法取自头部，但必须在 `allowed` 中，且与密钥匹配，
+/// 因此无法通过改写头部把公钥当作 HMAC 密钥使用
+pub fn decoding_key(
+    header: &Header,
+    jwks: &JwkSet,
+    allowed: &[Algorithm],
+) -> Result<(DecodingKey, Algorithm), KeyError> {
+    let alg = header.alg;
+    if !allowed.contains(&alg) || !SIGNING_ALGORITHMS.contains(&alg) {
+        return Err(KeyError::Algorithm(alg));
+    }
+
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        // 没有 kid 时取第一个与算法匹配的密钥
+        None => jwks.keys.iter().find(|jwk| key_matches(jwk, alg)),
+    }
+    .ok_or(KeyError::NotFound)?;
+    if !key_matches(jwk, alg) {
+        return Err(KeyError::Mismatch(alg));
+    }
+
+    Ok((DecodingKey::from_jwk(jwk)?, alg))
+}
+
+/// 密钥用途、类型与声明的 alg 是否都与算法相符
+fn key_matches(jwk: &Jwk, alg: Algorithm) -> bool {
+    if matches!(&jwk.common.public_key_use, Some(u) if *u != PublicKeyUse::Signature) {
+        return false;
+    }
+    if let Some(key_alg) = jwk.common.key_algorithm {
+        if key_algorithm(alg) != Some(key_alg) {
+            return false;
+        }
+    }
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => matches!(
+            alg,
+            Algorithm::RS256
+                | Algorithm::RS384
+                | Algorithm::RS512
+                | Algorithm::PS256
+                | Algorithm::PS384
+                | Algorithm::PS512
+        ),
+        AlgorithmParameters::EllipticCurve(params) => matches!(
+            (alg, &params.curve),
+            (Algorithm::ES256, EllipticCurve::P256) | (Algorithm::ES384, EllipticCurve::P384)
+        ),
+        AlgorithmParameters::OctetKeyPair(params) => {
+            alg == Algorithm::EdDSA && params.curve == EllipticCurve::Ed25519
+        }
+        // 对称密钥：公开的 JWKS 中不应出现，一律拒绝
+        AlgorithmParameters::OctetKey(_) => false,
+    }
+}
+
+fn key_algorithm(alg: Algorithm) -> Option<KeyAlgorithm> {
+    Some(match alg {
+        Algorithm::RS256 => KeyAlgorithm::RS256,
+        Algorithm::RS384 => KeyAlgorithm::RS384,
+        Algorithm::RS512 => KeyAlgorithm::RS512,
+        Algorithm::PS256 => KeyAlgorithm::PS256,
+        Algorithm::PS384 => KeyAlgorithm::PS384,
+        Algorithm::PS512 => KeyAlgorithm::PS512,
+        Algorithm::ES256 => KeyAlgorithm::ES256,
+        Algorithm::ES384 => KeyAlgorithm::ES384,
+        Algorithm::EdDSA => KeyAlgorithm::EdDSA,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn jwks() -> JwkSet {
+        serde_json::from_value(json!({
+            "keys": [
+                { "kty": "oct", "kid": "hmac", "alg": "HS256", "k": "c2VjcmV0" },
+                { "kty": "RSA", "kid": "rsa", "use": "sig", "alg": "RS256", "n": "sXch", "e": "AQAB" },
+                { "kty": "EC", "kid": "ec", "crv": "P-256",
+                  "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+                  "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0" },
+                { "kty": "OKP", "kid": "ed", "crv": "Ed25519",
+                  "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo" },
+                { "kty": "RSA", "kid": "enc", "use": "enc", "n": "sXch", "e": "AQAB" },
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn header(alg: Algorithm, kid: Option<&str>) -> Header {
+        Header {
+            alg,
+            kid: kid.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn select(alg: Algorithm, kid: Option<&str>) -> Result<Algorithm, KeyError> {
+        decoding_key(&header(alg, kid), &jwks(), SIGNING_ALGORITHMS).map(|(_, alg)| alg)
+    }
+
+    #[test]
+    fn pkce_challenge_matches_rfc7636_example() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn selects_asymmetric_keys_matching_the_header() {
+        assert_eq!(
+            select(Algorithm::RS256, Some("rsa")).unwrap(),
+            Algorithm::RS256
+        );
+        assert_eq!(
+            select(Algorithm::ES256, Some("ec")).unwrap(),
+            Algorithm::ES256
+        );
+        assert_eq!(
+            select(Algorithm::EdDSA, Some("ed")).unwrap(),
+            Algorithm::EdDSA
+        );
+        // 没有 kid 时跳过 oct 密钥，取第一个匹配的密钥
+        assert_eq!(select(Algorithm::EdDSA, None).unwrap(), Algorithm::EdDSA);
+    }
+
+    #[test]
+    fn rejects_symmetric_algorithms_and_keys() {
+        assert!(matches!(
+            select(Algorithm::HS256, Some("hmac")),
+            Err(KeyError::Algorithm(Algorithm::HS256))
+        ));
+        // 即使 HMAC 算法被误加入允许列表，oct 密钥仍被拒绝
+        let allowed = [Algorithm::HS256, Algorithm::RS256];
+        assert!(decoding_key(&header(Algorithm::HS256, Some("hmac")), &jwks(), &allowed).is_err());
+        assert!(matches!(
+            select(Algorithm::RS256, Some("hmac")),
+            Err(KeyError::Mismatch(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_header_alg_that_does_not_match_the_key() {
+        // 密钥声明 alg = RS256
+        assert!(matches!(
+            select(Algorithm::PS256, Some("rsa")),
+            Err(KeyError::Mismatch(_))
+        ));
+        // 类型不符
+        assert!(matches!(
+            select(Algorithm::ES256, Some("rsa")),
+            Err(KeyError::Mismatch(_))
+        ));
+        // 曲线不符
+        assert!(matches!(
+            select(Algorithm::ES384, Some("ec")),
+            Err(KeyError::Mismatch(_))
+        ));
+        // 加密用途的密钥
+        assert!(matches!(
+            select(Algorithm::RS256, Some("enc")),
+            Err(KeyError::Mismatch(_))
+        ));
+        assert!(matches!(
+            select(Algorithm::RS256, Some("missing")),
+            Err(KeyError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn allowed_algorithms_follow_provider_metadata() {
+        assert_eq!(allowed_algorithms(&[]), SIGNING_ALGORITHMS);
+        let supported = ["HS256", "RS256", "none", "EdDSA"].map(String::from);
+        assert_eq!(
+            allowed_algorithms(&supported),
+            vec![Algorithm::RS256, Algorithm::EdDSA]
+        );
+        let err = decoding_key(
+            &header(Algorithm::ES256, Some("ec")),
+            &jwks(),
+            &allowed_algorithms(&supported),
+        );
+        assert!(matches!(err, Err(KeyError::Algorithm(Algorithm::ES256))));
+    }
+}
@@ -0,0 +1,531 @@
+//! 输入校验规则：取值范围、字符串格式与字段错误
+//! - 参数实现 `Validate`，先规整输入（去除首尾空白等），再把问题逐项交给 `Validator`
+//! - 两端各自把 `Vec<FieldError>` 转换为自己的错误类型：服务端返回 422 与本地化提示，桌面端返回 `VALIDATION_FAILED`
+
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// 用户身份
+pub const IDENTITIES: &[&str] = &["admin", "user", "visitor"];
+/// 文章状态
+pub const ARTICLE_STATUSES: &[&str] = &["draft", "published", "archived"];
+
+/// 用户名长度
+pub const USERNAME_LEN: (usize, usize) = (2, 32);
+/// 密码长度上限，避免超长输入拖慢哈希
+pub const PASSWORD_MAX: usize = 128;
+pub const TITLE_MAX: usize = 200;
+pub const SUMMARY_MAX: usize = 500;
+pub const TAGS_MAX: usize = 200;
+pub const CONTENT_MAX: usize = 200_000;
+pub const COMMENT_MAX: usize = 2000;
+
+/// 单个字段的校验错误，序列化为 `{ field, code, ...参数, message }`，`message` 为英文提示
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub violation: Violation,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, violation: Violation) -> Self {
+        Self {
+            field: field.into(),
+            violation,
+        }
+    }
+}
+
+impl Serialize for FieldError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Detail<'a> {
+            field: &'a str,
+            #[serde(flatten)]
+            violation: &'a Violation,
+            message: String,
+        }
+
+        Detail {
+            field: &self.field,
+            violation: &self.violation,
+            message: self.violation.to_string(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// 校验规则，序列化后作为字段错误的 `code` 与参数
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "code", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Violation {
+    Required,
+    Length {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    Range {
+        min: i64,
+        max: i64,
+    },
+    OneOf {
+        allowed: Vec<String>,
+    },
+    Format {
+        format: Format,
+    },
+}
+
+/// 英文提示，服务端按 `Accept-Language` 另行本地化
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Required => f.write_str("is required"),
+            Violation::Length { min, max } => match (min, max) {
+                (Some(min), Some(max)) => write!(f, "length must be between {min} and {max}"),
+                (Some(min), None) => write!(f, "must be at least {min} characters"),
+                (None, Some(max)) => write!(f, "must be at most {max} characters"),
+                (None, None) => f.write_str("has an invalid length"),
+            },
+            Violation::Range { min, max } => write!(f, "must be between {min} and {max}"),
+            Violation::OneOf { allowed } => write!(f, "must be one of: {}", allowed.join(", ")),
+            Violation::Format { format } => f.write_str(match format {
+                Format::Username => {
+                    "may only contain letters, digits, underscores, hyphens and dots"
+                }
+                Format::Slug => "may only contain lowercase letters, digits and hyphens",
+                Format::Url => "must be an http or https URL",
+                Format::Email => "must be a valid email address",
+            }),
+        }
+    }
+}
+
+/// 字符串格式规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Format {
+    Username,
+    Slug,
+    /// http(s) 地址
+    Url,
+    /// 邮箱地址
+    Email,
+}
+
+impl Format {
+    pub fn matches(self, value: &str) -> bool {
+        match self {
+            // 字母（含中文）、数字、下划线、连字符与点
+            Format::Username => value.chars().all(username_char),
+            // 小写字母、数字，以连字符分隔；文章、评论、用户 id（UUID）均符合
+            Format::Slug => value.split('-').all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            }),
+            Format::Url => url::Url::parse(value)
+                .is_ok_and(|u| matches!(u.scheme(), "http" | "https") && u.host().is_some()),
+            Format::Email => value.parse::<lettre::Address>().is_ok(),
+        }
+    }
+}
+
+pub trait Validate {
+    /// 规整并校验自身，问题写入 `v`
+    fn validate(&mut self, v: &mut Validator);
+}
+
+/// 收集字段错误，长度按字符计算
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    /// 条件不成立时记录一条错误
+    pub fn check(&mut self, field: impl Into<String>, ok: bool, violation: Violation) -> &mut Self {
+        if !ok {
+            self.errors.push(FieldError::new(field, violation));
+        }
+        self
+    }
+
+    pub fn required(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(field, !value.is_empty(), Violation::Required)
+    }
+
+    /// 为空时只报 `REQUIRED`，否则检查长度
+    pub fn length(&mut self, field: &str, value: &str, min: usize, max: usize) -> &mut Self {
+        if min > 0 && value.is_empty() {
+            return self.required(field, value);
+        }
+        let len = value.chars().count();
+        self.check(
+            field,
+            (min..=max).contains(&len),
+            Violation::Length {
+                min: (min > 0).then_some(min),
+                max: Some(max),
+            },
+        )
+    }
+
+    pub fn max_length(&mut self, field: &str, value: Option<&str>, max: usize) -> &mut Self {
+        match value {
+            Some(value) => self.length(field, value, 0, max),
+            None => self,
+        }
+    }
+
+    pub fn one_of(&mut self, field: impl Into<String>, value: &str, allowed: &[&str]) -> &mut Self {
+        self.check(
+            field,
+            allowed.contains(&value),
+            Violation::OneOf {
+                allowed: allowed.iter().map(|s| s.to_string()).collect(),
+            },
+        )
+    }
+
+    pub fn range(&mut self, field: &str, value: i64, min: i64, max: i64) -> &mut Self {
+        self.check(
+            field,
+            (min..=max).contains(&value),
+            Violation::Range { min, max },
+        )
+    }
+
+    /// 为空时只报 `REQUIRED`，否则检查格式
+    pub fn format(&mut self, field: &str, value: &str, format: Format) -> &mut Self {
+        if value.is_empty() {
+            return self.required(field, value);
+        }
+        self.check(field, format.matches(value), Violation::Format { format })
+    }
+
+    /// 用户名：格式与长度
+    pub fn username(&mut self, field: &str, value: &str) -> &mut Self {
+        let before = self.errors.len();
+        self.format(field, value, Format::Username);
+        if self.errors.len() == before {
+            self.length(field, value, USERNAME_LEN.0, USERNAME_LEN.1);
+        }
+        self
+    }
+
+    pub fn password(&mut self, field: &str, value: &str, min: usize) -> &mut Self {
+        self.length(field, value, min, PASSWORD_MAX)
+    }
+
+    /// 没有错误时返回 `Ok`，否则返回全部字段错误
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// 规整并校验输入
+pub fn validated<T: Validate>(mut value: T) -> Result<T, Vec<FieldError>> {
+    let mut v = Validator::default();
+    value.validate(&mut v);
+    v.finish()?;
+    Ok(value)
+}
+
+/// 去除首尾空白
+pub fn trim(value: &mut String) {
+    let trimmed = value.trim();
+    if trimmed.len() != value.len() {
+        *value = trimmed.to_string();
+    }
+}
+
+/// 去除首尾空白，空字符串视为未提供
+pub fn trim_opt(value: &mut Option<String>) {
+    if let Some(v) = value {
+        trim(v);
+        if v.is_empty() {
+            *value = None;
+        }
+    }
+}
+
+/// 用户名允许的字符：字母（含中文）、数字、下划线、连字符与点
+fn username_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// 是否符合注册时的用户名规则
+pub fn is_valid_username(value: &str) -> bool {
+    let mut v = Validator::default();
+    v.username("username", value);
+    v.finish().is_ok()
+}
+
+/// 为外部身份推导用户名：取第一个符合用户名规则的候选；都不符合时只保留 `fallback` 中允许的字符，
+/// 仍不合法时为 `user`
+pub fn derive_username<'a>(
+    candidates: impl IntoIterator<Item = Option<&'a str>>,
+    fallback: &str,
+) -> String {
+    if let Some(name) = candidates
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|s| is_valid_username(s))
+    {
+        return name.to_string();
+    }
+    let cleaned: String = fallback
+        .chars()
+        .filter(|c| username_char(*c))
+        .take(USERNAME_LEN.1)
+        .collect();
+    if is_valid_username(&cleaned) {
+        cleaned
+    } else {
+        "user".to_string()
+    }
+}
+
+/// 用户名冲突时追加 `_后缀`，超出长度上限时截短原用户名
+pub fn username_with_suffix(base: &str, suffix: &str) -> String {
+    let keep = USERNAME_LEN.1.saturating_sub(suffix.chars().count() + 1);
+    format!("{}_{}", base.chars().take(keep).collect::<String>(), suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(f: impl FnOnce(&mut Validator)) -> Vec<Violation> {
+        let mut v = Validator::default();
+        f(&mut v);
+        v.finish()
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| e.violation)
+            .collect()
+    }
+
+    #[test]
+    fn username_rules() {
+        let format = Violation::Format {
+            format: Format::Username,
+        };
+        let length = Violation::Length {
+            min: Some(USERNAME_LEN.0),
+            max: Some(USERNAME_LEN.1),
+        };
+        let cases = [
+            ("alice".to_string(), vec![]),
+            ("张三".to_string(), vec![]),
+            ("a.b-c_d1".to_string(), vec![]),
+            (String::new(), vec![Violation::Required]),
+            ("a".to_string(), vec![length.clone()]),
+            ("x".repeat(33), vec![length]),
+            ("a b".to_string(), vec![format.clone()]),
+            ("<script>".to_string(), vec![format]),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(
+                errors(|v| {
+                    v.username("u", &value);
+                }),
+                expected,
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn slug_format() {
+        for ok in [
+            "hello",
+            "hello-world-2",
+            "0190b8a4-7c2e-7000-8000-000000000000",
+        ] {
+            assert!(Format::Slug.matches(ok), "{ok}");
+        }
+        for bad in [
+            "Hello",
+            "hello--world",
+            "-hello",
+            "hello-",
+            "hello_world",
+            "../a",
+            "",
+        ] {
+            assert!(!Format::Slug.matches(bad), "{bad}");
+        }
+    }
+
+    #[test]
+    fn email_and_url_formats() {
+        assert!(Format::Email.matches("me@example.com"));
+        for bad in [
+            "me",
+            "me@",
+            "@example.com",
+            "me@@example.com",
+            "me @example.com",
+        ] {
+            assert!(!Format::Email.matches(bad), "{bad}");
+        }
+        assert!(Format::Url.matches("https://example.com/hook"));
+        for bad in [
+            "ftp://example.com",
+            "javascript:alert(1)",
+            "example.com",
+            "http://",
+        ] {
+            assert!(!Format::Url.matches(bad), "{bad}");
+        }
+    }
+
+    #[test]
+    fn empty_values_only_report_required() {
+        assert_eq!(
+            errors(|v| {
+                v.format("email", "", Format::Email)
+                    .length("title", "", 1, TITLE_MAX);
+            }),
+            [Violation::Required, Violation::Required]
+        );
+        assert!(errors(|v| {
+            v.max_length("summary", None, SUMMARY_MAX);
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn length_counts_characters() {
+        assert!(errors(|v| {
+            v.length("title", &"中".repeat(TITLE_MAX), 1, TITLE_MAX);
+        })
+        .is_empty());
+        assert_eq!(
+            errors(|v| {
+                v.max_length("tags", Some(&"中".repeat(TAGS_MAX + 1)), TAGS_MAX);
+            }),
+            [Violation::Length {
+                min: None,
+                max: Some(TAGS_MAX)
+            }]
+        );
+    }
+
+    #[test]
+    fn trims_input() {
+        let mut s = "  alice \n".to_string();
+        trim(&mut s);
+        assert_eq!(s, "alice");
+
+        let mut blank = Some("   ".to_string());
+        trim_opt(&mut blank);
+        assert_eq!(blank, None);
+
+        let mut some = Some(" draft ".to_string());
+        trim_opt(&mut some);
+        assert_eq!(some.as_deref(), Some("draft"));
+    }
+
+    #[test]
+    fn validated_trims_before_checking() {
+        struct Login {
+            username: String,
+        }
+        impl Validate for Login {
+            fn validate(&mut self, v: &mut Validator) {
+                trim(&mut self.username);
+                v.username("username", &self.username);
+            }
+        }
+
+        let login = validated(Login {
+            username: " alice ".into(),
+        })
+        .unwrap();
+        assert_eq!(login.username, "alice");
+
+        let errors = validated(Login {
+            username: "  ".into(),
+        })
+        .err()
+        .unwrap();
+        assert_eq!(errors, [FieldError::new("username", Violation::Required)]);
+    }
+
+    #[test]
+    fn derived_usernames_follow_the_rules() {
+        let long = "a".repeat(40);
+        let cases = [
+            (
+                "首个合法候选",
+                [Some("alice"), Some("bob"), None],
+                "github_123",
+                "alice",
+            ),
+            (
+                "跳过含空格、过短与为空的候选",
+                [Some("Alice Smith"), Some("a"), Some("  ")],
+                "github_123",
+                "github_123",
+            ),
+            (
+                "邮箱前缀",
+                [None, Some("alice.smith"), Some("Alice Smith")],
+                "github_123",
+                "alice.smith",
+            ),
+            (
+                "过长的候选不使用，回退值去掉不允许的字符",
+                [Some(long.as_str()), None, None],
+                "my idp|ab/cd",
+                "myidpabcd",
+            ),
+            ("回退值也不合法", [None, None, None], "+", "user"),
+        ];
+        for (case, candidates, fallback, expected) in cases {
+            let name = derive_username(candidates, fallback);
+            assert_eq!(name, expected, "{case}");
+            assert!(is_valid_username(&name), "{case}");
+        }
+    }
+
+    #[test]
+    fn suffixed_usernames_stay_within_the_length_limit() {
+        assert_eq!(username_with_suffix("alice", "x1y2z3"), "alice_x1y2z3");
+
+        let long = "张".repeat(USERNAME_LEN.1);
+        let name = username_with_suffix(&long, "x1y2z3");
+        assert_eq!(name.chars().count(), USERNAME_LEN.1);
+        assert!(name.ends_with("_x1y2z3"));
+        assert!(is_valid_username(&name));
+    }
+
+    #[test]
+    fn field_errors_serialize_with_code_and_message() {
+        let error = FieldError::new(
+            "identity",
+            Violation::OneOf {
+                allowed: vec!["admin".into(), "user".into()],
+            },
+        );
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "field": "identity",
+                "code": "ONE_OF",
+                "allowed": ["admin", "user"],
+                "message": "must be one of: admin, user",
+            })
+        );
+    }
+}